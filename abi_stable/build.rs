@@ -4,5 +4,16 @@
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
 
-    let _channel = rustc_version::version_meta().unwrap().channel;
+    let version_meta = rustc_version::version_meta().unwrap();
+    let _channel = version_meta.channel;
+
+    // Used by `library::BuildMetadata::new`
+    println!(
+        "cargo:rustc-env=ABI_STABLE_RUSTC_VERSION={}",
+        version_meta.short_version_string
+    );
+    println!(
+        "cargo:rustc-env=ABI_STABLE_TARGET={}",
+        std::env::var("TARGET").unwrap()
+    );
 }
@@ -5,12 +5,18 @@ pub mod abi_checking;
 mod const_generics;
 pub mod extra_checks;
 pub mod get_static_equivalent;
+pub mod semver;
+pub mod snapshot;
 pub mod stable_abi_trait;
 
 pub use self::{
-    abi_checking::exported_check_layout_compatibility as check_layout_compatibility,
+    abi_checking::{
+        exported_check_layout_compatibility as check_layout_compatibility, AbiMismatch,
+        AbiMismatchReport, MismatchSeverity, ReportedType,
+    },
     const_generics::ConstGeneric,
     get_static_equivalent::{GetStaticEquivalent, GetStaticEquivalent_},
+    snapshot::{check_layout_against_snapshot, AbiSnapshot},
     stable_abi_trait::{AbiConsts, PrefixStableAbi, StableAbi},
 };
 
@@ -1,16 +1,18 @@
 //! Functions and types related to the layout checking.
 
-use std::{cmp::Ordering, fmt, mem};
+use std::{borrow::Cow, cmp::Ordering, fmt, mem, ops::Deref};
 
 #[allow(unused_imports)]
 use core_extensions::{matches, SelfOps};
 
 use std::{
-    borrow::Borrow,
     cell::Cell,
     collections::hash_map::{Entry, HashMap},
 };
 
+#[cfg(test)]
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
 use crate::{
     abi_stability::{
         extra_checks::{
@@ -23,19 +25,28 @@ use crate::{
     std_types::{RArc, RBox, RBoxError, RErr, RNone, ROk, RResult, RSome, RStr, RVec, UTypeId},
     traits::IntoReprC,
     type_layout::{
-        tagging::TagErrors, FmtFullType, IncompatibleWithNonExhaustive, IsExhaustive, ReprAttr,
-        TLData, TLDataDiscriminant, TLDiscriminant, TLEnum, TLField, TLFieldOrFunction, TLFunction,
-        TLNonExhaustive, TLPrimitive, TypeLayout,
+        identical_layouts, tagging::TagErrors, FmtFullType, IncompatibleWithNonExhaustive,
+        IsExhaustive, ReprAttr, TLData, TLDataDiscriminant, TLDiscriminant, TLEnum, TLField,
+        TLFieldOrFunction, TLFieldsIterator, TLFunction, TLFunctionIter, TLNonExhaustive,
+        TLPrefixType, TLPrimitive, TypeLayout,
     },
     type_level::downcasting::TD_Opaque,
     utils::{max_by, min_max_by},
 };
 
+mod checkable_layout;
 mod errors;
+mod report;
+
+pub(crate) use self::checkable_layout::{CheckableLayout, LayoutData, Src};
 
-pub use self::errors::{
-    AbiInstability, AbiInstability as AI, AbiInstabilityError, AbiInstabilityErrors,
-    ExtraCheckError,
+pub use self::{
+    checkable_layout::LayoutSource,
+    errors::{
+        AbiInstability, AbiInstability as AI, AbiInstabilityError, AbiInstabilityErrors,
+        ExtraCheckError,
+    },
+    report::{AbiMismatch, AbiMismatchReport, MismatchSeverity, ReportedType},
 };
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -51,22 +62,6 @@ enum FieldContext {
 
 //////
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash)]
-#[repr(C)]
-struct CheckingUTypeId {
-    type_id: UTypeId,
-}
-
-impl CheckingUTypeId {
-    fn new(this: &'static TypeLayout) -> Self {
-        Self {
-            type_id: this.get_utypeid(),
-        }
-    }
-}
-
-//////
-
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
 pub enum CheckingState {
     Checking { layer: u32 },
@@ -164,15 +159,15 @@ pub struct CheckedNonExhaustiveEnums {
 
 ///////////////////////////////////////////////
 
-struct AbiChecker {
-    stack_trace: RVec<ExpectedFound<TLFieldOrFunction>>,
+pub(crate) struct AbiChecker<L: CheckableLayout> {
+    stack_trace: RVec<ExpectedFound<<Src<L> as LayoutSource>::FieldOrFunction>>,
     checked_prefix_types: RVec<CheckedPrefixTypes>,
     checked_nonexhaustive_enums: RVec<CheckedNonExhaustiveEnums>,
     checked_extra_checks: RVec<ExtraChecksBoxWithContext>,
 
-    visited: HashMap<(CheckingUTypeId, CheckingUTypeId), CheckingState>,
+    visited: HashMap<(L::Id, L::Id), CheckingState>,
 
-    errors: RVec<AbiInstabilityError>,
+    errors: RVec<AbiInstabilityError<Src<L>>>,
 
     /// Layer 0 is checking a type layout,
     ///
@@ -188,12 +183,18 @@ struct AbiChecker {
     current_layer: u32,
 
     error_index: usize,
+
+    /// Whether the layouts are already known to be compatible,
+    /// in which case `check_inner` only pairs up the types that are recorded
+    /// for the checks done with the `CheckingGlobals`,
+    /// skipping the comparisons of the types themselves.
+    trusted: bool,
 }
 
 ///////////////////////////////////////////////
 
-impl AbiChecker {
-    fn new() -> Self {
+impl<L: CheckableLayout> AbiChecker<L> {
+    pub(crate) fn new() -> Self {
         Self {
             stack_trace: RVec::new(),
             checked_prefix_types: RVec::new(),
@@ -204,21 +205,21 @@ impl AbiChecker {
             errors: RVec::new(),
             current_layer: 0,
             error_index: 0,
+            trusted: false,
         }
     }
 
     #[inline]
-    fn check_fields<I, F>(
+    fn check_fields<I>(
         &mut self,
-        errs: &mut RVec<AbiInstability>,
-        t_lay: &'static TypeLayout,
-        o_lay: &'static TypeLayout,
+        errs: &mut RVec<AbiInstability<Src<L>>>,
+        t_lay: L,
+        o_lay: L,
         ctx: FieldContext,
         t_fields: I,
         o_fields: I,
     ) where
-        I: ExactSizeIterator<Item = F>,
-        F: Borrow<TLField>,
+        I: ExactSizeIterator<Item = L::Field>,
     {
         if t_fields.len() == 0 && o_fields.len() == 0 {
             return;
@@ -227,8 +228,8 @@ impl AbiChecker {
         let t_data = t_lay.data();
 
         let is_prefix = match &t_data {
-            TLData::PrefixType { .. } => true,
-            TLData::Enum(enum_) => !enum_.exhaustiveness.is_exhaustive(),
+            LayoutData::PrefixType { .. } => true,
+            LayoutData::Enum(enum_) => !L::is_exhaustive(enum_),
             _ => false,
         };
         match (t_fields.len().cmp(&o_fields.len()), is_prefix) {
@@ -246,22 +247,27 @@ impl AbiChecker {
 
         let acc_fields: Option<(FieldAccessibility, FieldAccessibility)> =
             match (&t_data, &o_lay.data()) {
-                (TLData::PrefixType(t_prefix), TLData::PrefixType(o_prefix)) => {
-                    Some((t_prefix.accessible_fields, o_prefix.accessible_fields))
-                }
+                (LayoutData::PrefixType(t_prefix), LayoutData::PrefixType(o_prefix)) => Some((
+                    L::accessible_fields(t_prefix),
+                    L::accessible_fields(o_prefix),
+                )),
                 _ => None,
             };
 
         for (field_i, (this_f, other_f)) in t_fields.zip(o_fields).enumerate() {
-            let this_f = this_f.borrow();
-            let other_f = other_f.borrow();
-            if this_f.name() != other_f.name() {
-                push_err(errs, this_f, other_f, |x| *x, AI::UnexpectedField);
+            if !L::is_same_field(&this_f, &other_f) {
+                push_err(
+                    errs,
+                    (t_lay, this_f),
+                    (o_lay, other_f),
+                    |(lay, f)| lay.field_payload(f),
+                    AI::UnexpectedField,
+                );
                 continue;
             }
 
-            let t_field_abi = this_f.layout();
-            let o_field_abi = other_f.layout();
+            let t_field_abi = t_lay.field_layout(this_f);
+            let o_field_abi = o_lay.field_layout(other_f);
 
             let is_accessible = match (ctx, acc_fields) {
                 (FieldContext::Fields, Some((l, r))) => {
@@ -271,35 +277,55 @@ impl AbiChecker {
             };
 
             if is_accessible {
-                if this_f.lifetime_indices() != other_f.lifetime_indices() {
-                    push_err(errs, this_f, other_f, |x| *x, AI::FieldLifetimeMismatch);
+                if !L::same_field_lifetimes(&this_f, &other_f) {
+                    push_err(
+                        errs,
+                        (t_lay, this_f),
+                        (o_lay, other_f),
+                        |(lay, f)| lay.field_payload(f),
+                        AI::FieldLifetimeMismatch,
+                    );
                 }
 
                 self.stack_trace.push(ExpectedFound {
-                    expected: (*this_f).into(),
-                    found: (*other_f).into(),
+                    expected: t_lay.field_payload(this_f).into(),
+                    found: o_lay.field_payload(other_f).into(),
                 });
 
                 let sf_ctx = FieldContext::Subfields;
 
-                let func_ranges = this_f.function_range().iter().zip(other_f.function_range());
+                let func_ranges = t_lay
+                    .field_functions(this_f)
+                    .zip(o_lay.field_functions(other_f));
                 for (t_func, o_func) in func_ranges {
                     self.error_index += 1;
                     let errs_index = self.error_index;
-                    let mut errs_ = RVec::<AbiInstability>::new();
+                    let mut errs_ = RVec::<AbiInstability<Src<L>>>::new();
                     let errs = &mut errs_;
 
                     self.stack_trace.push(ExpectedFound {
-                        expected: t_func.into(),
-                        found: o_func.into(),
+                        expected: t_lay.function_payload(t_func).into(),
+                        found: o_lay.function_payload(o_func).into(),
                     });
 
-                    if t_func.paramret_lifetime_indices != o_func.paramret_lifetime_indices {
-                        push_err(errs, t_func, o_func, |x| x, AI::FnLifetimeMismatch);
+                    if !L::same_function_lifetimes(&t_func, &o_func) {
+                        push_err(
+                            errs,
+                            (t_lay, t_func),
+                            (o_lay, o_func),
+                            |(lay, f)| lay.function_payload(f),
+                            AI::FnLifetimeMismatch,
+                        );
                     }
 
-                    if t_func.qualifiers() != o_func.qualifiers() {
-                        push_err(errs, t_func, o_func, |x| x, AI::FnQualifierMismatch);
+                    if !L::same_function_qualifiers(&t_func, &o_func) {
+                        push_err(
+                            errs,
+                            (t_lay, t_func),
+                            (o_lay, o_func),
+                            |(lay, f)| lay.function_payload(f),
+                            AI::FnQualifierMismatch,
+                        );
                     }
 
                     self.check_fields(
@@ -307,8 +333,8 @@ impl AbiChecker {
                         t_lay,
                         o_lay,
                         sf_ctx,
-                        t_func.get_params_ret_iter(),
-                        o_func.get_params_ret_iter(),
+                        t_lay.function_params_ret(t_func),
+                        o_lay.function_params_ret(o_func),
                     );
 
                     if !errs_.is_empty() {
@@ -326,20 +352,18 @@ impl AbiChecker {
                 self.stack_trace.pop();
             } else {
                 self.stack_trace.push(ExpectedFound {
-                    expected: (*this_f).into(),
-                    found: (*other_f).into(),
+                    expected: t_lay.field_payload(this_f).into(),
+                    found: o_lay.field_payload(other_f).into(),
                 });
 
-                let t_field_layout = &t_field_abi;
-                let o_field_layout = &o_field_abi;
-                if t_field_layout.size() != o_field_layout.size() {
-                    push_err(errs, t_field_layout, o_field_layout, |x| x.size(), AI::Size);
+                if t_field_abi.size() != o_field_abi.size() {
+                    push_err(errs, t_field_abi, o_field_abi, |x| x.size(), AI::Size);
                 }
-                if t_field_layout.alignment() != o_field_layout.alignment() {
+                if t_field_abi.alignment() != o_field_abi.alignment() {
                     push_err(
                         errs,
-                        t_field_layout,
-                        o_field_layout,
+                        t_field_abi,
+                        o_field_abi,
                         |x| x.alignment(),
                         AI::Alignment,
                     );
@@ -349,21 +373,15 @@ impl AbiChecker {
         }
     }
 
-    fn check_inner(
-        &mut self,
-        this: &'static TypeLayout,
-        other: &'static TypeLayout,
-    ) -> Result<(), ()> {
-        let t_cuti = CheckingUTypeId::new(this);
-        let o_cuti = CheckingUTypeId::new(other);
-        let cuti_pair = (t_cuti, o_cuti);
+    pub(crate) fn check_inner(&mut self, this: L, other: L) -> Result<(), ()> {
+        let cuti_pair = (this.id(), other.id());
 
         self.error_index += 1;
         let errs_index = self.error_index;
-        let mut errs_ = RVec::<AbiInstability>::new();
-        let mut top_level_errs_ = RVec::<AbiInstabilityError>::new();
-        let t_lay = &this;
-        let o_lay = &other;
+        let mut errs_ = RVec::<AbiInstability<Src<L>>>::new();
+        let mut top_level_errs_ = RVec::<AbiInstabilityError<Src<L>>>::new();
+        let t_lay = this;
+        let o_lay = other;
 
         let start_errors = self.errors.len();
 
@@ -375,8 +393,8 @@ impl AbiChecker {
                     self.errors.push(AbiInstabilityError {
                         stack_trace: self.stack_trace.clone(),
                         errs: rvec![AbiInstability::CyclicTypeChecking {
-                            interface: this,
-                            implementation: other,
+                            interface: this.to_type(),
+                            implementation: other.to_type(),
                         }],
                         index: errs_index,
                     });
@@ -399,69 +417,66 @@ impl AbiChecker {
         (|| {
             let errs = &mut errs_;
             let top_level_errs = &mut top_level_errs_;
-            if t_lay.name() != o_lay.name() {
-                push_err(errs, t_lay, o_lay, |x| x.full_type(), AI::Name);
-                return;
-            }
-            let (t_package, t_ver_str) = t_lay.package_and_version();
-            let (o_package, o_ver_str) = o_lay.package_and_version();
-            if t_package != o_package {
-                push_err(errs, t_lay, o_lay, |x| x.package(), AI::Package);
-                return;
-            }
+            let t_package = t_lay.package();
+            let o_package = o_lay.package();
+            let is_moved = || t_lay.is_moved(o_lay);
+            let trusted = self.trusted;
+
+            // Layouts found to be compatible by a previous check only need to
+            // be traversed to record the types that the final checks use.
+            if !trusted {
+                if t_lay.name() != o_lay.name() && !is_moved() {
+                    push_err(errs, t_lay, o_lay, |x| x.full_type(), AI::Name);
+                    return;
+                }
+                if t_package != o_package && !is_moved() {
+                    errs.push(AI::Package(ExpectedFound {
+                        expected: t_package,
+                        found: o_package,
+                    }));
+                    return;
+                }
 
-            if this.is_nonzero() != other.is_nonzero() {
-                push_err(errs, this, other, |x| x.is_nonzero(), AI::NonZeroness);
-            }
+                if this.is_nonzero() != other.is_nonzero() {
+                    push_err(errs, this, other, |x| x.is_nonzero(), AI::NonZeroness);
+                }
 
-            if t_lay.repr_attr() != o_lay.repr_attr() {
-                push_err(errs, t_lay, o_lay, |x| x.repr_attr(), AI::ReprAttr);
-            }
+                if t_lay.repr_attr() != o_lay.repr_attr() {
+                    push_err(errs, t_lay, o_lay, |x| x.repr_attr(), AI::ReprAttr);
+                }
 
-            {
-                let x = (|| {
-                    let l = t_ver_str.parsed()?;
-                    let r = o_ver_str.parsed()?;
-                    Ok(l.is_loosely_compatible(r))
-                })();
-                match x {
-                    Ok(false) => {
-                        push_err(
-                            errs,
-                            t_lay,
-                            o_lay,
-                            |x| x.package_version(),
-                            AI::PackageVersion,
-                        );
-                    }
-                    Ok(true) => {}
-                    Err(parse_error) => {
-                        errs.push(AI::PackageVersionParseError(parse_error));
-                        return;
+                // The versions of types moved to another package aren't comparable.
+                if t_package == o_package {
+                    let x = (|| {
+                        let l = t_lay.parsed_version()?;
+                        let r = o_lay.parsed_version()?;
+                        Ok(l.is_loosely_compatible(r))
+                    })();
+                    match x {
+                        Ok(false) => {
+                            push_err(
+                                errs,
+                                t_lay,
+                                o_lay,
+                                |x| x.package_version(),
+                                AI::PackageVersion,
+                            );
+                        }
+                        Ok(true) => {}
+                        Err(parse_error) => {
+                            errs.push(AI::PackageVersionParseError(parse_error));
+                            return;
+                        }
                     }
                 }
-            }
-            {
-                let t_gens = t_lay.generics();
-                let o_gens = o_lay.generics();
-
-                let t_consts = t_gens.const_params();
-                let o_consts = o_gens.const_params();
-                if t_gens.lifetime_count() != o_gens.lifetime_count()
-                    || t_gens.const_params().len() != o_gens.const_params().len()
+
+                if t_lay.lifetime_count() != o_lay.lifetime_count()
+                    || t_lay.const_param_count() != o_lay.const_param_count()
                 {
                     push_err(errs, t_lay, o_lay, |x| x.full_type(), AI::GenericParamCount);
                 }
 
-                let mut ty_checker = TypeCheckerMut::from_ptr(&mut *self, TD_Opaque);
-                for (l, r) in t_consts.iter().zip(o_consts.iter()) {
-                    match l.is_equal(r, ty_checker.sabi_reborrow_mut()) {
-                        Ok(false) | Err(_) => {
-                            push_err(errs, l, r, |x| *x, AI::MismatchedConstParam);
-                        }
-                        Ok(true) => {}
-                    }
-                }
+                L::check_const_params(self, errs, this, other);
             }
 
             // Checking phantom fields
@@ -470,76 +485,55 @@ impl AbiChecker {
                 this,
                 other,
                 FieldContext::PhantomFields,
-                this.phantom_fields().iter(),
-                other.phantom_fields().iter(),
+                this.phantom_fields(),
+                other.phantom_fields(),
             );
 
-            match (t_lay.size().cmp(&o_lay.size()), this.is_prefix_kind()) {
-                (Ordering::Greater, _) | (Ordering::Less, false) => {
-                    push_err(errs, t_lay, o_lay, |x| x.size(), AI::Size);
+            if !trusted {
+                let t_discr = t_lay.data_discriminant();
+                let o_discr = o_lay.data_discriminant();
+
+                let is_prefix_kind = t_discr == TLDataDiscriminant::PrefixType;
+                match (t_lay.size().cmp(&o_lay.size()), is_prefix_kind) {
+                    (Ordering::Greater, _) | (Ordering::Less, false) => {
+                        push_err(errs, t_lay, o_lay, |x| x.size(), AI::Size);
+                    }
+                    (Ordering::Equal, _) | (Ordering::Less, true) => {}
+                }
+                if t_lay.alignment() != o_lay.alignment() {
+                    push_err(errs, t_lay, o_lay, |x| x.alignment(), AI::Alignment);
                 }
-                (Ordering::Equal, _) | (Ordering::Less, true) => {}
-            }
-            if t_lay.alignment() != o_lay.alignment() {
-                push_err(errs, t_lay, o_lay, |x| x.alignment(), AI::Alignment);
-            }
 
-            let t_discr = t_lay.data_discriminant();
-            let o_discr = o_lay.data_discriminant();
-            if t_discr != o_discr {
-                errs.push(AI::TLDataDiscriminant(ExpectedFound {
-                    expected: t_discr,
-                    found: o_discr,
-                }));
-            }
+                if t_discr != o_discr {
+                    errs.push(AI::TLDataDiscriminant(ExpectedFound {
+                        expected: t_discr,
+                        found: o_discr,
+                    }));
+                }
 
-            let t_tag = t_lay.tag().to_checkable();
-            let o_tag = o_lay.tag().to_checkable();
-            if let Err(tag_err) = t_tag.check_compatible(&o_tag) {
-                errs.push(AI::TagError { err: tag_err });
+                let t_tag = t_lay.tag();
+                let o_tag = o_lay.tag();
+                if let Err(tag_err) = t_tag.check_compatible(&o_tag) {
+                    errs.push(AI::TagError { err: tag_err });
+                }
             }
 
-            match (t_lay.extra_checks(), o_lay.extra_checks()) {
-                (None, _) => {}
-                (Some(_), None) => {
+            match (t_lay.has_extra_checks(), o_lay.has_extra_checks()) {
+                (false, _) => {}
+                (true, false) => {
                     errs.push(AI::NoneExtraChecks);
                 }
-                (Some(t_extra_checks), Some(o_extra_checks)) => {
-                    let mut ty_checker = TypeCheckerMut::from_ptr(&mut *self, TD_Opaque);
-
-                    let res = handle_extra_checks_ret(
-                        t_extra_checks.clone(),
-                        o_extra_checks.clone(),
-                        errs,
-                        top_level_errs,
-                        move || {
-                            let ty_checker_ = ty_checker.sabi_reborrow_mut();
-                            rtry!(t_extra_checks.check_compatibility(t_lay, o_lay, ty_checker_));
-
-                            let ty_checker_ = ty_checker.sabi_reborrow_mut();
-                            let opt = rtry!(t_extra_checks.combine(o_extra_checks, ty_checker_));
-
-                            opt.map(|combined| ExtraChecksBoxWithContext {
-                                t_lay,
-                                o_lay,
-                                extra_checks: combined,
-                            })
-                            .piped(ROk)
-                        },
-                    );
-
-                    if let Ok(RSome(x)) = res {
-                        self.checked_extra_checks.push(x);
-                    }
+                (true, true) => {
+                    L::check_extra_checks(self, errs, top_level_errs, this, other);
                 }
             }
 
             match (t_lay.data(), o_lay.data()) {
-                (TLData::Opaque { .. }, _) => {
+                (LayoutData::Opaque { .. }, _) => {
                     // No checks are necessary
                 }
 
-                (TLData::Primitive(t_prim), TLData::Primitive(o_prim)) => {
+                (LayoutData::Primitive(t_prim), LayoutData::Primitive(o_prim)) => {
                     if t_prim != o_prim {
                         errs.push(AI::MismatchedPrimitive(ExpectedFound {
                             expected: t_prim,
@@ -547,68 +541,29 @@ impl AbiChecker {
                         }));
                     }
                 }
-                (TLData::Primitive { .. }, _) => {}
+                (LayoutData::Primitive { .. }, _) => {}
 
-                (TLData::Struct { fields: t_fields }, TLData::Struct { fields: o_fields }) => {
-                    self.check_fields(
-                        errs,
-                        this,
-                        other,
-                        FieldContext::Fields,
-                        t_fields.iter(),
-                        o_fields.iter(),
-                    );
+                (LayoutData::Struct(t_fields), LayoutData::Struct(o_fields)) => {
+                    self.check_fields(errs, this, other, FieldContext::Fields, t_fields, o_fields);
                 }
-                (TLData::Struct { .. }, _) => {}
+                (LayoutData::Struct { .. }, _) => {}
 
-                (TLData::Union { fields: t_fields }, TLData::Union { fields: o_fields }) => {
-                    self.check_fields(
-                        errs,
-                        this,
-                        other,
-                        FieldContext::Fields,
-                        t_fields.iter(),
-                        o_fields.iter(),
-                    );
+                (LayoutData::Union(t_fields), LayoutData::Union(o_fields)) => {
+                    self.check_fields(errs, this, other, FieldContext::Fields, t_fields, o_fields);
                 }
-                (TLData::Union { .. }, _) => {}
+                (LayoutData::Union { .. }, _) => {}
 
-                (TLData::Enum(t_enum), TLData::Enum(o_enum)) => {
+                (LayoutData::Enum(t_enum), LayoutData::Enum(o_enum)) => {
                     self.check_enum(errs, this, other, t_enum, o_enum);
-                    let t_as_ne = t_enum.exhaustiveness.as_nonexhaustive();
-                    let o_as_ne = o_enum.exhaustiveness.as_nonexhaustive();
-                    if let (Some(this_ne), Some(other_ne)) = (t_as_ne, o_as_ne) {
-                        self.checked_nonexhaustive_enums
-                            .push(CheckedNonExhaustiveEnums {
-                                this: NonExhaustiveEnumWithContext {
-                                    layout: this,
-                                    enum_: t_enum,
-                                    nonexhaustive: this_ne,
-                                },
-                                other: NonExhaustiveEnumWithContext {
-                                    layout: other,
-                                    enum_: o_enum,
-                                    nonexhaustive: other_ne,
-                                },
-                            });
-                    }
+                    L::record_nonexhaustive_enums(self, (this, t_enum), (other, o_enum));
                 }
-                (TLData::Enum { .. }, _) => {}
-
-                (TLData::PrefixType(t_prefix), TLData::PrefixType(o_prefix)) => {
-                    let this_prefix = __PrefixTypeMetadata::with_prefix_layout(t_prefix, t_lay);
-                    let other_prefix = __PrefixTypeMetadata::with_prefix_layout(o_prefix, o_lay);
+                (LayoutData::Enum { .. }, _) => {}
 
-                    self.check_prefix_types(errs, &this_prefix, &other_prefix);
-
-                    self.checked_prefix_types.push(CheckedPrefixTypes {
-                        this,
-                        this_prefix,
-                        other,
-                        other_prefix,
-                    })
+                (LayoutData::PrefixType(t_prefix), LayoutData::PrefixType(o_prefix)) => {
+                    self.check_prefix_types(errs, this, other, t_prefix, o_prefix);
+                    L::record_prefix_types(self, (this, t_prefix), (other, o_prefix));
                 }
-                (TLData::PrefixType { .. }, _) => {}
+                (LayoutData::PrefixType { .. }, _) => {}
             }
         })();
 
@@ -636,62 +591,60 @@ impl AbiChecker {
 
     fn check_enum(
         &mut self,
-        errs: &mut RVec<AbiInstability>,
-        this: &'static TypeLayout,
-        other: &'static TypeLayout,
-        t_enum: TLEnum,
-        o_enum: TLEnum,
+        errs: &mut RVec<AbiInstability<Src<L>>>,
+        this: L,
+        other: L,
+        t_enum: L::Enum,
+        o_enum: L::Enum,
     ) {
-        let TLEnum {
-            fields: t_fields, ..
-        } = t_enum;
-        let TLEnum {
-            fields: o_fields, ..
-        } = o_enum;
-
-        let t_fcount = t_enum.field_count.as_slice();
-        let o_fcount = o_enum.field_count.as_slice();
-
-        let t_exhaus = t_enum.exhaustiveness;
-        let o_exhaus = o_enum.exhaustiveness;
-
-        match (t_exhaus.as_nonexhaustive(), o_exhaus.as_nonexhaustive()) {
-            (Some(this_ne), Some(other_ne)) => {
-                if let Err(e) = this_ne.check_compatible(this) {
+        let t_fcount = L::variant_field_counts(&t_enum);
+        let o_fcount = L::variant_field_counts(&o_enum);
+
+        let t_exhaustive = L::is_exhaustive(&t_enum);
+        let o_exhaustive = L::is_exhaustive(&o_enum);
+
+        match (t_exhaustive, o_exhaustive) {
+            (false, false) => {
+                if let Err(e) = this.check_nonexhaustive(&t_enum) {
                     errs.push(AI::IncompatibleWithNonExhaustive(e))
                 }
-                if let Err(e) = other_ne.check_compatible(other) {
+                if let Err(e) = other.check_nonexhaustive(&o_enum) {
                     errs.push(AI::IncompatibleWithNonExhaustive(e))
                 }
             }
-            (Some(_), None) | (None, Some(_)) => {
+            (false, true) | (true, false) => {
                 push_err(
                     errs,
-                    t_enum,
-                    o_enum,
-                    |x| x.exhaustiveness,
+                    &t_enum,
+                    &o_enum,
+                    |x| L::exhaustiveness(x),
                     AI::MismatchedExhaustiveness,
                 );
             }
-            (None, None) => {}
+            (true, true) => {}
         }
 
-        if t_exhaus.is_exhaustive() && t_fcount.len() != o_fcount.len()
-            || t_exhaus.is_nonexhaustive() && t_fcount.len() > o_fcount.len()
+        if t_exhaustive && t_fcount.len() != o_fcount.len()
+            || !t_exhaustive && t_fcount.len() > o_fcount.len()
         {
             push_err(errs, t_fcount, o_fcount, |x| x.len(), AI::TooManyVariants);
         }
 
-        if let Err(d_errs) = t_enum.discriminants.compare(&o_enum.discriminants) {
+        if let Err(d_errs) = L::compare_discriminants(&t_enum, &o_enum) {
             errs.extend(d_errs);
         }
 
-        let mut t_names = t_enum.variant_names.as_str().split(';');
-        let mut o_names = o_enum.variant_names.as_str().split(';');
+        let mut t_names = this.variant_names(t_enum);
+        let mut o_names = other.variant_names(o_enum);
         let mut total_field_count = 0;
-        for (t_field_count, o_field_count) in t_fcount.iter().zip(o_fcount) {
-            let t_name = t_names.next().unwrap_or("<this unavailable>");
-            let o_name = o_names.next().unwrap_or("<other unavailable>");
+        for (variant_i, (t_field_count, o_field_count)) in t_fcount.iter().zip(o_fcount).enumerate()
+        {
+            let t_name = t_names
+                .next()
+                .unwrap_or_else(|| "<this unavailable>".into());
+            let o_name = o_names
+                .next()
+                .unwrap_or_else(|| "<other unavailable>".into());
 
             total_field_count += usize::from(*t_field_count);
 
@@ -705,12 +658,20 @@ impl AbiChecker {
                 );
             }
 
-            if t_name != o_name {
-                push_err(errs, t_name, o_name, RStr::from_str, AI::UnexpectedVariant);
+            let is_same_variant = *t_name == *o_name
+                || this.is_variant_renamed_from(&t_enum, variant_i, &t_name, &o_name)
+                || other.is_variant_renamed_from(&o_enum, variant_i, &o_name, &t_name);
+            if !is_same_variant {
+                errs.push(AI::UnexpectedVariant(ExpectedFound {
+                    expected: t_name,
+                    found: o_name,
+                }));
                 continue;
             }
         }
 
+        let t_fields = this.enum_fields(t_enum);
+        let o_fields = other.enum_fields(o_enum);
         let min_field_count = t_fields.len().min(o_fields.len());
         if total_field_count != min_field_count {
             push_err(
@@ -722,17 +683,109 @@ impl AbiChecker {
             );
         }
 
+        self.check_fields(errs, this, other, FieldContext::Fields, t_fields, o_fields);
+    }
+
+    fn check_prefix_types(
+        &mut self,
+        errs: &mut RVec<AbiInstability<Src<L>>>,
+        this: L,
+        other: L,
+        t_prefix: L::Prefix,
+        o_prefix: L::Prefix,
+    ) {
+        if L::prefix_field_count(&t_prefix) != L::prefix_field_count(&o_prefix) {
+            push_err(
+                errs,
+                &t_prefix,
+                &o_prefix,
+                |x| L::prefix_field_count(x),
+                AI::MismatchedPrefixSize,
+            );
+        }
+
+        if L::conditional_prefix_fields(&t_prefix) != L::conditional_prefix_fields(&o_prefix) {
+            push_err(
+                errs,
+                &t_prefix,
+                &o_prefix,
+                |x| L::conditional_prefix_fields(x),
+                AI::MismatchedPrefixConditionality,
+            );
+        }
+
         self.check_fields(
             errs,
             this,
             other,
             FieldContext::Fields,
-            t_fields.iter(),
-            o_fields.iter(),
+            this.prefix_fields(t_prefix),
+            other.prefix_fields(o_prefix),
         );
     }
+}
 
-    fn check_prefix_types(
+impl AbiChecker<&'static TypeLayout> {
+    /// Checks layouts that are structurally identical (as determined by `identical_layouts`),
+    /// only recording the prefix types and nonexhaustive enums they reference,
+    /// for the checks done with the `CheckingGlobals`.
+    ///
+    /// Returns false if the layouts aren't identical,
+    /// or if they fail a check that doesn't involve comparing them,
+    /// in which case they must be checked with `check_inner`.
+    fn check_identical(
+        &mut self,
+        interface: &'static TypeLayout,
+        implementation: &'static TypeLayout,
+    ) -> bool {
+        // Cheaper checks, to avoid encoding types that are obviously different.
+        if interface.name() != implementation.name()
+            || interface.size() != implementation.size()
+            || interface.alignment() != implementation.alignment()
+            || interface.data_discriminant() != implementation.data_discriminant()
+        {
+            return false;
+        }
+
+        let pairs = match identical_layouts(interface, implementation) {
+            Some(x) => x,
+            None => return false,
+        };
+
+        for (this, other) in pairs {
+            if this.package_and_version().1.parsed().is_err() {
+                return false;
+            }
+
+            match (this.data(), other.data()) {
+                (TLData::Enum(t_enum), TLData::Enum(o_enum)) => {
+                    let t_as_ne = t_enum.exhaustiveness.as_nonexhaustive();
+                    let o_as_ne = o_enum.exhaustiveness.as_nonexhaustive();
+                    if let (Some(this_ne), Some(other_ne)) = (t_as_ne, o_as_ne) {
+                        if this_ne.check_compatible(this).is_err()
+                            || other_ne.check_compatible(other).is_err()
+                        {
+                            return false;
+                        }
+                    }
+                    CheckableLayout::record_nonexhaustive_enums(
+                        self,
+                        (this, t_enum),
+                        (other, o_enum),
+                    );
+                }
+                (TLData::PrefixType(t_prefix), TLData::PrefixType(o_prefix)) => {
+                    CheckableLayout::record_prefix_types(self, (this, t_prefix), (other, o_prefix));
+                }
+                _ => {}
+            }
+        }
+
+        true
+    }
+
+    /// Checks prefix types that were combined with the ones in the `CheckingGlobals`.
+    fn check_prefix_metadata(
         &mut self,
         errs: &mut RVec<AbiInstability>,
         this: &__PrefixTypeMetadata,
@@ -763,8 +816,8 @@ impl AbiChecker {
             this.layout,
             other.layout,
             FieldContext::Fields,
-            this.fields.iter(),
-            other.fields.iter(),
+            this.fields.iter().map(Cow::into_owned),
+            other.fields.iter().map(Cow::into_owned),
         );
     }
 
@@ -815,7 +868,7 @@ impl AbiChecker {
                     let (min_prefix, max_prefix) =
                         min_max_by(im_prefix, &mut max_prefix, |x| x.fields.len());
 
-                    self.check_prefix_types(errs, min_prefix, max_prefix);
+                    self.check_prefix_metadata(errs, min_prefix, max_prefix);
                     if !errs.is_empty() || errors_before != self.errors.len() {
                         break;
                     }
@@ -843,7 +896,7 @@ impl AbiChecker {
 
                     let (min_prefix, max_prefix) =
                         min_max_by(l_prefix, r_prefix, |x| x.fields.len());
-                    self.check_prefix_types(errs, min_prefix, max_prefix);
+                    self.check_prefix_metadata(errs, min_prefix, max_prefix);
                     if !errs.is_empty() || errors_before != self.errors.len() {
                         break;
                     }
@@ -1089,9 +1142,92 @@ pub fn check_layout_compatibility_with_globals(
     implementation: &'static TypeLayout,
     globals: &CheckingGlobals,
 ) -> Result<(), AbiInstabilityErrors> {
-    let mut errors: RVec<AbiInstabilityError>;
+    check_with_globals(interface, implementation, globals, false)
+}
+
+/// Records the prefix types, nonexhaustive enums, and extra checks
+/// referenced by `implementation` in `globals`,
+/// checking them against the ones recorded by previous layout checks.
+///
+/// This is for layouts that were already found to be compatible,
+/// (eg: by a previous run of the program),
+/// skipping the comparisons that `check_layout_compatibility_with_globals` does,
+/// other than the ones needed to pair up the types that the layouts reference.
+pub(crate) fn record_compatible_layout(
+    interface: &'static TypeLayout,
+    implementation: &'static TypeLayout,
+    globals: &CheckingGlobals,
+) -> Result<(), AbiInstabilityErrors> {
+    check_with_globals(interface, implementation, globals, true)
+}
 
-    if interface.is_prefix_kind() || implementation.is_prefix_kind() {
+fn check_with_globals(
+    interface: &'static TypeLayout,
+    implementation: &'static TypeLayout,
+    globals: &CheckingGlobals,
+    trusted: bool,
+) -> Result<(), AbiInstabilityErrors> {
+    check_layouts(interface, implementation, || {
+        let mut checker = AbiChecker::new();
+        if checker.check_identical(interface, implementation) {
+            #[cfg(test)]
+            IDENTICAL_LAYOUT_CHECKS.fetch_add(1, AtomicOrdering::Relaxed);
+        } else {
+            checker = AbiChecker::new();
+            checker.trusted = trusted;
+            let _ = checker.check_inner(interface, implementation);
+        }
+        if checker.errors.is_empty() {
+            if let Err(e) = checker.final_prefix_type_checks(globals) {
+                checker.errors.push(e);
+            }
+            if let Err(e) = checker.final_non_exhaustive_enum_checks(globals) {
+                checker.errors.push(e);
+            }
+            if let Err(e) = checker.final_extra_checks(globals) {
+                checker.errors.extend(e);
+            }
+        }
+        checker.errors
+    })
+}
+
+/// Checks that the layout of `interface` is compatible with `implementation`,
+/// without recording the prefix types, nonexhaustive enums, and extra checks
+/// that they reference in the `CheckingGlobals`.
+///
+/// This is used to check layouts that aren't `&'static TypeLayout`s,
+/// (eg: the layouts stored in an `AbiSnapshot`),
+/// which can't be stored in the `CheckingGlobals`.
+pub(crate) fn check_unrecorded_compatibility<L>(
+    interface: L,
+    implementation: L,
+) -> Result<(), AbiInstabilityErrors<Src<L>>>
+where
+    L: CheckableLayout,
+{
+    check_layouts(interface, implementation, || {
+        let mut checker = AbiChecker::new();
+        let _ = checker.check_inner(interface, implementation);
+        checker.errors
+    })
+}
+
+/// Rejects prefix types at the top level,
+/// calling `check` to check the layouts otherwise.
+fn check_layouts<L, F>(
+    interface: L,
+    implementation: L,
+    check: F,
+) -> Result<(), AbiInstabilityErrors<Src<L>>>
+where
+    L: CheckableLayout,
+    F: FnOnce() -> RVec<AbiInstabilityError<Src<L>>>,
+{
+    let mut errors: RVec<AbiInstabilityError<Src<L>>>;
+
+    let is_prefix_kind = |x: L| x.data_discriminant() == TLDataDiscriminant::PrefixType;
+    if is_prefix_kind(interface) || is_prefix_kind(implementation) {
         let mut errs = RVec::with_capacity(1);
         push_err(
             &mut errs,
@@ -1107,20 +1243,7 @@ pub fn check_layout_compatibility_with_globals(
         }]
         .into();
     } else {
-        let mut checker = AbiChecker::new();
-        let _ = checker.check_inner(interface, implementation);
-        if checker.errors.is_empty() {
-            if let Err(e) = checker.final_prefix_type_checks(globals) {
-                checker.errors.push(e);
-            }
-            if let Err(e) = checker.final_non_exhaustive_enum_checks(globals) {
-                checker.errors.push(e);
-            }
-            if let Err(e) = checker.final_extra_checks(globals) {
-                checker.errors.extend(e);
-            }
-        }
-        errors = checker.errors;
+        errors = check();
     }
 
     if errors.is_empty() {
@@ -1128,13 +1251,62 @@ pub fn check_layout_compatibility_with_globals(
     } else {
         errors.sort_by_key(|x| x.index);
         Err(AbiInstabilityErrors {
-            interface,
-            implementation,
+            interface: interface.to_type(),
+            implementation: implementation.to_type(),
             errors,
         })
     }
 }
 
+/// The amount of layout checks where the layouts were structurally identical,
+/// which skip comparing the layouts field by field.
+#[cfg(test)]
+static IDENTICAL_LAYOUT_CHECKS: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether `this` and `other` are the same field,
+/// either because they have the same name,
+/// or because one of them was renamed from the name of the other.
+pub(crate) fn is_same_field(this: &TLField, other: &TLField) -> bool {
+    this.name() == other.name()
+        || this.renamed_from().any(|x| x == other.name())
+        || other.renamed_from().any(|x| x == this.name())
+}
+
+/// Whether the `this_name` variant of the `this` enum is the same as
+/// the `other_name` variant of the `other` enum,
+/// either because they have the same name,
+/// or because one of them was renamed from the name of the other.
+pub(crate) fn is_same_variant(
+    this: &'static TypeLayout,
+    this_name: &str,
+    other: &'static TypeLayout,
+    other_name: &str,
+) -> bool {
+    this_name == other_name
+        || this
+            .item_info()
+            .variant_renamed_from(this_name)
+            .any(|x| x == other_name)
+        || other
+            .item_info()
+            .variant_renamed_from(other_name)
+            .any(|x| x == this_name)
+}
+
+/// Whether `this` and `other` are the same type,
+/// because one of them was moved from the package and name of the other,
+/// declared with the `#[sabi(moved_from( .. ))]` attribute.
+pub(crate) fn is_moved_type(this: &'static TypeLayout, other: &'static TypeLayout) -> bool {
+    let was_moved_from = |moved: &'static TypeLayout, from: &'static TypeLayout| {
+        let package = from.item_info().package();
+        moved
+            .item_info()
+            .moved_from()
+            .any(|x| x == (package, from.name()))
+    };
+    was_moved_from(this, other) || was_moved_from(other, this)
+}
+
 /// Checks that the layout of `interface` is compatible with `implementation`,
 pub(crate) extern "C" fn check_layout_compatibility_for_ffi(
     interface: &'static TypeLayout,
@@ -1190,7 +1362,7 @@ pub extern "C" fn exported_check_layout_compatibility(
     }
 }
 
-impl AbiChecker {
+impl AbiChecker<&'static TypeLayout> {
     fn check_compatibility_inner(
         &mut self,
         interface: &'static TypeLayout,
@@ -1212,7 +1384,7 @@ impl AbiChecker {
     }
 }
 
-unsafe impl TypeChecker for AbiChecker {
+unsafe impl TypeChecker for AbiChecker<&'static TypeLayout> {
     fn check_compatibility(
         &mut self,
         interface: &'static TypeLayout,
@@ -1300,15 +1472,16 @@ pub fn get_checking_globals() -> &'static CheckingGlobals {
 
 ///////////////////////////////////////////////
 
-pub(crate) fn push_err<O, U, FG, VC>(
-    errs: &mut RVec<AbiInstability>,
+pub(crate) fn push_err<L, O, U, FG, VC>(
+    errs: &mut RVec<AbiInstability<L>>,
     this: O,
     other: O,
     field_getter: FG,
     mut variant_constructor: VC,
 ) where
     FG: FnMut(O) -> U,
-    VC: FnMut(ExpectedFound<U>) -> AbiInstability,
+    L: LayoutSource,
+    VC: FnMut(ExpectedFound<U>) -> AbiInstability<L>,
 {
     let x = ExpectedFound::new(this, other, field_getter);
     let x = variant_constructor(x);
@@ -1389,3 +1562,52 @@ fn combine_extra_checks(
         }
     }
 }
+
+//////////////////////////////////////////////////////////////////////
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests {
+    use super::*;
+
+    use crate::{
+        external_types::RMutex,
+        std_types::{RString, RVec},
+        StableAbi,
+    };
+
+    #[test]
+    fn identical_layouts_skip_field_checks() {
+        let globals = CheckingGlobals::new();
+        let identical_checks = || IDENTICAL_LAYOUT_CHECKS.load(AtomicOrdering::Relaxed);
+
+        let before = identical_checks();
+        check_layout_compatibility_with_globals(
+            <RVec<RString>>::LAYOUT,
+            <RVec<RString>>::LAYOUT,
+            &globals,
+        )
+        .unwrap();
+        assert!(identical_checks() > before);
+
+        check_layout_compatibility_with_globals(
+            <RVec<RString>>::LAYOUT,
+            <RVec<u8>>::LAYOUT,
+            &globals,
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn trusted_layouts_record_prefix_types() {
+        let layout = <RMutex<u32>>::LAYOUT;
+
+        let mut checker = AbiChecker::new();
+        checker.trusted = true;
+        checker.check_inner(layout, layout).unwrap();
+        assert_ne!(checker.checked_prefix_types.len(), 0);
+
+        let globals = CheckingGlobals::new();
+        record_compatible_layout(layout, layout, &globals).unwrap();
+        assert_ne!(globals.prefix_type_map.lock().unwrap().value_len(), 0);
+    }
+}
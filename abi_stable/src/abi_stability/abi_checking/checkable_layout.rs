@@ -0,0 +1,462 @@
+use super::*;
+
+use std::hash::Hash;
+
+use crate::{sabi_types::VersionNumber, type_layout::tagging::CheckableTag};
+
+/// The types used to describe the layouts in the errors of the layout checker.
+///
+/// This is implemented for `&'static TypeLayout`,
+/// the layouts of the types in the current process,
+/// and for [`AbiSnapshot`], the layouts stored in a snapshot.
+///
+/// [`AbiSnapshot`]: ../snapshot/struct.AbiSnapshot.html
+pub trait LayoutSource: fmt::Debug + Clone + PartialEq + 'static {
+    /// A checked type, as a whole.
+    type Type: fmt::Display + fmt::Debug + Clone + PartialEq;
+    /// The name of a type, including its generic parameters.
+    type FullType: fmt::Display + fmt::Debug + Clone + PartialEq;
+    /// The name of a package, or of an enum variant.
+    type Str: fmt::Display
+        + fmt::Debug
+        + Clone
+        + PartialEq
+        + Deref<Target = str>
+        + From<&'static str>;
+    /// The version of a package.
+    type Version: fmt::Display + fmt::Debug + Clone + PartialEq;
+    /// The error from parsing the version of a package.
+    type VersionError: fmt::Debug + Clone + PartialEq;
+    /// A field.
+    type Field: fmt::Display + fmt::Debug + Clone + PartialEq;
+    /// A function pointer in a field.
+    type Function: fmt::Display + fmt::Debug + Clone + PartialEq;
+    /// Either a `Field` or a `Function`, for the stack trace of an error.
+    type FieldOrFunction: fmt::Display
+        + fmt::Debug
+        + Clone
+        + PartialEq
+        + From<Self::Field>
+        + From<Self::Function>;
+    /// A const parameter.
+    type ConstParam: fmt::Debug + Clone + PartialEq;
+    /// Whether an enum is exhaustive.
+    type Exhaustiveness: fmt::Debug + Clone + PartialEq;
+    /// The error from a nonexhaustive enum not fitting in its storage.
+    type NonExhaustiveError: fmt::Display + fmt::Debug + Clone + PartialEq;
+
+    /// The layout of the type of a field,
+    /// or of a function pointer, to show the last item in the stack trace of an error.
+    fn formatted_layout(item: &Self::FieldOrFunction) -> Option<String>;
+}
+
+impl LayoutSource for &'static TypeLayout {
+    type Type = &'static TypeLayout;
+    type FullType = FmtFullType;
+    type Str = RStr<'static>;
+    type Version = VersionStrings;
+    type VersionError = ParseVersionError;
+    type Field = TLField;
+    type Function = TLFunction;
+    type FieldOrFunction = TLFieldOrFunction;
+    type ConstParam = ConstGeneric;
+    type Exhaustiveness = IsExhaustive;
+    type NonExhaustiveError = IncompatibleWithNonExhaustive;
+
+    fn formatted_layout(item: &TLFieldOrFunction) -> Option<String> {
+        Some(item.formatted_layout())
+    }
+}
+
+///////////////////////////////////////////////
+
+/// The `LayoutSource` of a `CheckableLayout`.
+pub(crate) type Src<L> = <L as CheckableLayout>::Source;
+
+/// The data of a `CheckableLayout`, equivalent to `TLData`.
+pub(crate) enum LayoutData<L: CheckableLayout> {
+    Primitive(TLPrimitive),
+    Opaque,
+    Struct(L::Fields),
+    Union(L::Fields),
+    Enum(L::Enum),
+    PrefixType(L::Prefix),
+}
+
+/// A type layout that `AbiChecker` can traverse,
+/// the methods that take `self` along with a field/function/enum/prefix type
+/// take the layout that contains them.
+pub(crate) trait CheckableLayout: Copy {
+    type Source: LayoutSource;
+    /// Identifies the type, to avoid checking the same pair of types twice.
+    type Id: Copy + Eq + Hash;
+    type Field: Copy;
+    type Function: Copy;
+    type Enum: Copy;
+    type Prefix: Copy;
+    type Fields: ExactSizeIterator<Item = Self::Field>;
+    type Functions: Iterator<Item = Self::Function>;
+    type ParamsRet: ExactSizeIterator<Item = Self::Field>;
+    type VariantNames: Iterator<Item = <Self::Source as LayoutSource>::Str>;
+
+    fn id(self) -> Self::Id;
+    fn to_type(self) -> <Self::Source as LayoutSource>::Type;
+    fn name(&self) -> &str;
+    fn full_type(self) -> <Self::Source as LayoutSource>::FullType;
+    fn package(self) -> <Self::Source as LayoutSource>::Str;
+    fn package_version(self) -> <Self::Source as LayoutSource>::Version;
+    fn parsed_version(self) -> Result<VersionNumber, <Self::Source as LayoutSource>::VersionError>;
+    /// Whether `self` and `other` are the same type,
+    /// because one of them was moved from the package and name of the other.
+    fn is_moved(self, other: Self) -> bool;
+    fn is_nonzero(self) -> bool;
+    fn repr_attr(self) -> ReprAttr;
+    fn lifetime_count(self) -> usize;
+    fn const_param_count(self) -> usize;
+    fn size(self) -> usize;
+    fn alignment(self) -> usize;
+    fn data_discriminant(self) -> TLDataDiscriminant;
+    fn tag(self) -> CheckableTag;
+    fn has_extra_checks(self) -> bool;
+    fn phantom_fields(self) -> Self::Fields;
+    fn data(self) -> LayoutData<Self>;
+
+    fn is_same_field(this: &Self::Field, other: &Self::Field) -> bool;
+    fn same_field_lifetimes(this: &Self::Field, other: &Self::Field) -> bool;
+    fn field_payload(self, field: Self::Field) -> <Self::Source as LayoutSource>::Field;
+    fn field_layout(self, field: Self::Field) -> Self;
+    fn field_functions(self, field: Self::Field) -> Self::Functions;
+
+    fn same_function_lifetimes(this: &Self::Function, other: &Self::Function) -> bool;
+    fn same_function_qualifiers(this: &Self::Function, other: &Self::Function) -> bool;
+    fn function_payload(self, func: Self::Function) -> <Self::Source as LayoutSource>::Function;
+    fn function_params_ret(self, func: Self::Function) -> Self::ParamsRet;
+
+    fn is_exhaustive(enum_: &Self::Enum) -> bool;
+    fn exhaustiveness(enum_: &Self::Enum) -> <Self::Source as LayoutSource>::Exhaustiveness;
+    /// Checks that this nonexhaustive enum fits in its storage.
+    fn check_nonexhaustive(
+        self,
+        enum_: &Self::Enum,
+    ) -> Result<(), <Self::Source as LayoutSource>::NonExhaustiveError>;
+    fn variant_field_counts(enum_: &Self::Enum) -> &[u8];
+    fn compare_discriminants(
+        this: &Self::Enum,
+        other: &Self::Enum,
+    ) -> Result<(), RVec<AbiInstability<Self::Source>>>;
+    fn variant_names(self, enum_: Self::Enum) -> Self::VariantNames;
+    /// Whether the `variant_index`th variant (named `name`) was renamed from `old_name`.
+    fn is_variant_renamed_from(
+        self,
+        enum_: &Self::Enum,
+        variant_index: usize,
+        name: &str,
+        old_name: &str,
+    ) -> bool;
+    fn enum_fields(self, enum_: Self::Enum) -> Self::Fields;
+
+    fn prefix_field_count(prefix: &Self::Prefix) -> u8;
+    fn conditional_prefix_fields(prefix: &Self::Prefix) -> FieldConditionality;
+    fn accessible_fields(prefix: &Self::Prefix) -> FieldAccessibility;
+    fn prefix_fields(self, prefix: Self::Prefix) -> Self::Fields;
+
+    /// Checks that the const parameters of `this` and `other` are equal,
+    /// after it was checked that they have the same amount of them.
+    fn check_const_params(
+        checker: &mut AbiChecker<Self>,
+        errs: &mut RVec<AbiInstability<Self::Source>>,
+        this: Self,
+        other: Self,
+    );
+
+    /// Checks the extra checks of `this` and `other`,
+    /// after it was checked that both have them.
+    fn check_extra_checks(
+        checker: &mut AbiChecker<Self>,
+        errs: &mut RVec<AbiInstability<Self::Source>>,
+        top_level_errs: &mut RVec<AbiInstabilityError<Self::Source>>,
+        this: Self,
+        other: Self,
+    );
+
+    /// Records a pair of nonexhaustive enums, for the checks done with the `CheckingGlobals`.
+    fn record_nonexhaustive_enums(
+        _checker: &mut AbiChecker<Self>,
+        _this: (Self, Self::Enum),
+        _other: (Self, Self::Enum),
+    ) {
+    }
+
+    /// Records a pair of prefix types, for the checks done with the `CheckingGlobals`.
+    fn record_prefix_types(
+        _checker: &mut AbiChecker<Self>,
+        _this: (Self, Self::Prefix),
+        _other: (Self, Self::Prefix),
+    ) {
+    }
+}
+
+///////////////////////////////////////////////
+
+impl CheckableLayout for &'static TypeLayout {
+    type Source = &'static TypeLayout;
+    type Id = UTypeId;
+    type Field = TLField;
+    type Function = TLFunction;
+    type Enum = TLEnum;
+    type Prefix = TLPrefixType;
+    type Fields = TLFieldsIterator;
+    type Functions = TLFunctionIter;
+    type ParamsRet = std::vec::IntoIter<TLField>;
+    type VariantNames = std::iter::Map<std::str::Split<'static, char>, StrToRStr>;
+
+    fn id(self) -> UTypeId {
+        self.get_utypeid()
+    }
+    fn to_type(self) -> &'static TypeLayout {
+        self
+    }
+    fn name(&self) -> &str {
+        TypeLayout::name(self)
+    }
+    fn full_type(self) -> FmtFullType {
+        TypeLayout::full_type(self)
+    }
+    fn package(self) -> RStr<'static> {
+        TypeLayout::package(self)
+    }
+    fn package_version(self) -> VersionStrings {
+        TypeLayout::package_version(self)
+    }
+    fn parsed_version(self) -> Result<VersionNumber, ParseVersionError> {
+        TypeLayout::package_version(self).parsed()
+    }
+    fn is_moved(self, other: Self) -> bool {
+        is_moved_type(self, other)
+    }
+    fn is_nonzero(self) -> bool {
+        TypeLayout::is_nonzero(self)
+    }
+    fn repr_attr(self) -> ReprAttr {
+        TypeLayout::repr_attr(self)
+    }
+    fn lifetime_count(self) -> usize {
+        self.generics().lifetime_count()
+    }
+    fn const_param_count(self) -> usize {
+        self.generics().const_params().len()
+    }
+    fn size(self) -> usize {
+        TypeLayout::size(self)
+    }
+    fn alignment(self) -> usize {
+        TypeLayout::alignment(self)
+    }
+    fn data_discriminant(self) -> TLDataDiscriminant {
+        TypeLayout::data_discriminant(self)
+    }
+    fn tag(self) -> CheckableTag {
+        TypeLayout::tag(self).to_checkable()
+    }
+    fn has_extra_checks(self) -> bool {
+        TypeLayout::extra_checks(self).is_some()
+    }
+    fn phantom_fields(self) -> TLFieldsIterator {
+        TypeLayout::phantom_fields(self).iter()
+    }
+    fn data(self) -> LayoutData<Self> {
+        match TypeLayout::data(self) {
+            TLData::Primitive(x) => LayoutData::Primitive(x),
+            TLData::Opaque => LayoutData::Opaque,
+            TLData::Struct { fields } => LayoutData::Struct(fields.iter()),
+            TLData::Union { fields } => LayoutData::Union(fields.iter()),
+            TLData::Enum(x) => LayoutData::Enum(x),
+            TLData::PrefixType(x) => LayoutData::PrefixType(x),
+        }
+    }
+
+    fn is_same_field(this: &TLField, other: &TLField) -> bool {
+        is_same_field(this, other)
+    }
+    fn same_field_lifetimes(this: &TLField, other: &TLField) -> bool {
+        this.lifetime_indices() == other.lifetime_indices()
+    }
+    fn field_payload(self, field: TLField) -> TLField {
+        field
+    }
+    fn field_layout(self, field: TLField) -> Self {
+        field.layout()
+    }
+    fn field_functions(self, field: TLField) -> TLFunctionIter {
+        field.function_range().iter()
+    }
+
+    fn same_function_lifetimes(this: &TLFunction, other: &TLFunction) -> bool {
+        this.paramret_lifetime_indices == other.paramret_lifetime_indices
+    }
+    fn same_function_qualifiers(this: &TLFunction, other: &TLFunction) -> bool {
+        this.qualifiers() == other.qualifiers()
+    }
+    fn function_payload(self, func: TLFunction) -> TLFunction {
+        func
+    }
+    fn function_params_ret(self, func: TLFunction) -> Self::ParamsRet {
+        func.get_params_ret_iter().collect::<Vec<_>>().into_iter()
+    }
+
+    fn is_exhaustive(enum_: &TLEnum) -> bool {
+        enum_.exhaustiveness.is_exhaustive()
+    }
+    fn exhaustiveness(enum_: &TLEnum) -> IsExhaustive {
+        enum_.exhaustiveness
+    }
+    fn check_nonexhaustive(self, enum_: &TLEnum) -> Result<(), IncompatibleWithNonExhaustive> {
+        match enum_.exhaustiveness.as_nonexhaustive() {
+            Some(nonexhaustive) => nonexhaustive.check_compatible(self),
+            None => Ok(()),
+        }
+    }
+    fn variant_field_counts(enum_: &TLEnum) -> &[u8] {
+        enum_.field_count.as_slice()
+    }
+    fn compare_discriminants(this: &TLEnum, other: &TLEnum) -> Result<(), RVec<AbiInstability>> {
+        this.discriminants.compare(&other.discriminants)
+    }
+    fn variant_names(self, enum_: TLEnum) -> Self::VariantNames {
+        enum_
+            .variant_names
+            .as_str()
+            .split(';')
+            .map(RStr::from_str as StrToRStr)
+    }
+    fn is_variant_renamed_from(
+        self,
+        _enum: &TLEnum,
+        _variant_index: usize,
+        name: &str,
+        old_name: &str,
+    ) -> bool {
+        self.item_info()
+            .variant_renamed_from(name)
+            .any(|x| x == old_name)
+    }
+    fn enum_fields(self, enum_: TLEnum) -> TLFieldsIterator {
+        enum_.fields.iter()
+    }
+
+    fn prefix_field_count(prefix: &TLPrefixType) -> u8 {
+        prefix.first_suffix_field
+    }
+    fn conditional_prefix_fields(prefix: &TLPrefixType) -> FieldConditionality {
+        prefix.conditional_prefix_fields
+    }
+    fn accessible_fields(prefix: &TLPrefixType) -> FieldAccessibility {
+        prefix.accessible_fields
+    }
+    fn prefix_fields(self, prefix: TLPrefixType) -> TLFieldsIterator {
+        prefix.fields.iter()
+    }
+
+    fn check_const_params(
+        checker: &mut AbiChecker<Self>,
+        errs: &mut RVec<AbiInstability>,
+        this: Self,
+        other: Self,
+    ) {
+        let t_consts = this.generics().const_params();
+        let o_consts = other.generics().const_params();
+
+        let mut ty_checker = TypeCheckerMut::from_ptr(checker, TD_Opaque);
+        for (l, r) in t_consts.iter().zip(o_consts.iter()) {
+            match l.is_equal(r, ty_checker.sabi_reborrow_mut()) {
+                Ok(false) | Err(_) => {
+                    push_err(errs, l, r, |x| *x, AI::MismatchedConstParam);
+                }
+                Ok(true) => {}
+            }
+        }
+    }
+
+    fn check_extra_checks(
+        checker: &mut AbiChecker<Self>,
+        errs: &mut RVec<AbiInstability>,
+        top_level_errs: &mut RVec<AbiInstabilityError>,
+        t_lay: Self,
+        o_lay: Self,
+    ) {
+        let (t_extra_checks, o_extra_checks) = match (t_lay.extra_checks(), o_lay.extra_checks()) {
+            (Some(t_extra_checks), Some(o_extra_checks)) => (t_extra_checks, o_extra_checks),
+            _ => return,
+        };
+        let trusted = checker.trusted;
+
+        let mut ty_checker = TypeCheckerMut::from_ptr(&mut *checker, TD_Opaque);
+
+        let res = handle_extra_checks_ret(
+            t_extra_checks.clone(),
+            o_extra_checks.clone(),
+            errs,
+            top_level_errs,
+            move || {
+                if !trusted {
+                    let ty_checker_ = ty_checker.sabi_reborrow_mut();
+                    rtry!(t_extra_checks.check_compatibility(t_lay, o_lay, ty_checker_));
+                }
+
+                let ty_checker_ = ty_checker.sabi_reborrow_mut();
+                let opt = rtry!(t_extra_checks.combine(o_extra_checks, ty_checker_));
+
+                opt.map(|combined| ExtraChecksBoxWithContext {
+                    t_lay,
+                    o_lay,
+                    extra_checks: combined,
+                })
+                .piped(ROk)
+            },
+        );
+
+        if let Ok(RSome(x)) = res {
+            checker.checked_extra_checks.push(x);
+        }
+    }
+
+    fn record_nonexhaustive_enums(
+        checker: &mut AbiChecker<Self>,
+        (this, t_enum): (Self, TLEnum),
+        (other, o_enum): (Self, TLEnum),
+    ) {
+        let t_as_ne = t_enum.exhaustiveness.as_nonexhaustive();
+        let o_as_ne = o_enum.exhaustiveness.as_nonexhaustive();
+        if let (Some(this_ne), Some(other_ne)) = (t_as_ne, o_as_ne) {
+            checker
+                .checked_nonexhaustive_enums
+                .push(CheckedNonExhaustiveEnums {
+                    this: NonExhaustiveEnumWithContext {
+                        layout: this,
+                        enum_: t_enum,
+                        nonexhaustive: this_ne,
+                    },
+                    other: NonExhaustiveEnumWithContext {
+                        layout: other,
+                        enum_: o_enum,
+                        nonexhaustive: other_ne,
+                    },
+                });
+        }
+    }
+
+    fn record_prefix_types(
+        checker: &mut AbiChecker<Self>,
+        (this, t_prefix): (Self, TLPrefixType),
+        (other, o_prefix): (Self, TLPrefixType),
+    ) {
+        checker.checked_prefix_types.push(CheckedPrefixTypes {
+            this,
+            this_prefix: __PrefixTypeMetadata::with_prefix_layout(t_prefix, this),
+            other,
+            other_prefix: __PrefixTypeMetadata::with_prefix_layout(o_prefix, other),
+        })
+    }
+}
+
+type StrToRStr = fn(&'static str) -> RStr<'static>;
@@ -3,37 +3,43 @@ use super::*;
 use core_extensions::StringExt;
 
 /// An individual error from checking the layout of some type.
+///
+/// `L` determines the types used to describe the checked layouts,
+/// it's `&'static TypeLayout` for the layouts of types in the current process,
+/// and [`AbiSnapshot`] for the layouts in a snapshot.
+///
+/// [`AbiSnapshot`]: ../snapshot/struct.AbiSnapshot.html
 #[derive(Debug, PartialEq, Clone)]
-pub enum AbiInstability {
+pub enum AbiInstability<L: LayoutSource = &'static TypeLayout> {
     ReentrantLayoutCheckingCall,
     CyclicTypeChecking {
-        interface: &'static TypeLayout,
-        implementation: &'static TypeLayout,
+        interface: L::Type,
+        implementation: L::Type,
     },
     NonZeroness(ExpectedFound<bool>),
-    Name(ExpectedFound<FmtFullType>),
-    Package(ExpectedFound<RStr<'static>>),
-    PackageVersionParseError(ParseVersionError),
-    PackageVersion(ExpectedFound<VersionStrings>),
+    Name(ExpectedFound<L::FullType>),
+    Package(ExpectedFound<L::Str>),
+    PackageVersionParseError(L::VersionError),
+    PackageVersion(ExpectedFound<L::Version>),
     MismatchedPrefixSize(ExpectedFound<u8>),
     Size(ExpectedFound<usize>),
     Alignment(ExpectedFound<usize>),
-    GenericParamCount(ExpectedFound<FmtFullType>),
+    GenericParamCount(ExpectedFound<L::FullType>),
     TLDataDiscriminant(ExpectedFound<TLDataDiscriminant>),
     MismatchedPrimitive(ExpectedFound<TLPrimitive>),
     FieldCountMismatch(ExpectedFound<usize>),
-    FieldLifetimeMismatch(ExpectedFound<TLField>),
-    FnLifetimeMismatch(ExpectedFound<TLFunction>),
-    FnQualifierMismatch(ExpectedFound<TLFunction>),
-    UnexpectedField(ExpectedFound<TLField>),
+    FieldLifetimeMismatch(ExpectedFound<L::Field>),
+    FnLifetimeMismatch(ExpectedFound<L::Function>),
+    FnQualifierMismatch(ExpectedFound<L::Function>),
+    UnexpectedField(ExpectedFound<L::Field>),
     TooManyVariants(ExpectedFound<usize>),
     MismatchedPrefixConditionality(ExpectedFound<FieldConditionality>),
-    MismatchedExhaustiveness(ExpectedFound<IsExhaustive>),
-    MismatchedConstParam(ExpectedFound<ConstGeneric>),
-    UnexpectedVariant(ExpectedFound<RStr<'static>>),
+    MismatchedExhaustiveness(ExpectedFound<L::Exhaustiveness>),
+    MismatchedConstParam(ExpectedFound<L::ConstParam>),
+    UnexpectedVariant(ExpectedFound<L::Str>),
     ReprAttr(ExpectedFound<ReprAttr>),
     EnumDiscriminant(ExpectedFound<TLDiscriminant>),
-    IncompatibleWithNonExhaustive(IncompatibleWithNonExhaustive),
+    IncompatibleWithNonExhaustive(L::NonExhaustiveError),
     NoneExtraChecks,
     ExtraCheckError(CmpIgnored<ExtraCheckError>),
     TagError {
@@ -50,26 +56,26 @@ pub struct ExtraCheckError {
 use self::AbiInstability as AI;
 
 #[allow(dead_code)]
-impl AbiInstabilityErrors {
+impl<L: LayoutSource> AbiInstabilityErrors<L> {
     #[cfg(feature = "testing")]
-    pub fn flatten_errors(&self) -> RVec<AbiInstability> {
-        self.flattened_errors().collect::<RVec<AbiInstability>>()
+    pub fn flatten_errors(&self) -> RVec<AbiInstability<L>> {
+        self.flattened_errors().collect::<RVec<AbiInstability<L>>>()
     }
 
     #[cfg(feature = "testing")]
-    pub fn flattened_errors(&self) -> impl Iterator<Item = AbiInstability> + '_ {
+    pub fn flattened_errors(&self) -> impl Iterator<Item = AbiInstability<L>> + '_ {
         self.errors.iter().flat_map(|x| &x.errs).cloned()
     }
 }
 
-impl std::error::Error for AbiInstabilityErrors {}
+impl<L: LayoutSource> std::error::Error for AbiInstabilityErrors<L> {}
 
-impl fmt::Debug for AbiInstabilityErrors {
+impl<L: LayoutSource> fmt::Debug for AbiInstabilityErrors<L> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt::Display::fmt(self, f)
     }
 }
-impl fmt::Display for AbiInstabilityErrors {
+impl<L: LayoutSource> fmt::Display for AbiInstabilityErrors<L> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(
             f,
@@ -84,7 +90,7 @@ impl fmt::Display for AbiInstabilityErrors {
     }
 }
 
-impl fmt::Display for AbiInstabilityError {
+impl<L: LayoutSource> fmt::Display for AbiInstabilityError<L> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut extra_err = None::<String>;
 
@@ -97,13 +103,19 @@ impl fmt::Display for AbiInstabilityError {
         for field in &self.stack_trace {
             writeln!(f, "{}\n", field.found.to_string().left_padder(4))?;
         }
-        if let Some(ExpectedFound { expected, found }) = self.stack_trace.last() {
+        let last_layouts = self.stack_trace.last().and_then(|x| {
+            Some((
+                L::formatted_layout(&x.expected)?,
+                L::formatted_layout(&x.found)?,
+            ))
+        });
+        if let Some((expected, found)) = last_layouts {
             writeln!(
                 f,
                 "Layout of expected type:\n{}\n\n\
                  Layout of found type:\n{}\n",
-                expected.formatted_layout().left_padder(4),
-                found.formatted_layout().left_padder(4),
+                expected.left_padder(4),
+                found.left_padder(4),
             )?;
         }
         writeln!(f)?;
@@ -220,10 +232,10 @@ impl fmt::Display for AbiInstabilityError {
 #[derive(Clone, PartialEq)]
 #[repr(C)]
 #[non_exhaustive]
-pub struct AbiInstabilityErrors {
-    pub interface: &'static TypeLayout,
-    pub implementation: &'static TypeLayout,
-    pub errors: RVec<AbiInstabilityError>,
+pub struct AbiInstabilityErrors<L: LayoutSource = &'static TypeLayout> {
+    pub interface: L::Type,
+    pub implementation: L::Type,
+    pub errors: RVec<AbiInstabilityError<L>>,
 }
 
 /// All the shallow errors from checking an individual type.
@@ -233,8 +245,8 @@ pub struct AbiInstabilityErrors {
 #[derive(Debug, Clone, PartialEq)]
 #[repr(C)]
 #[non_exhaustive]
-pub struct AbiInstabilityError {
-    pub stack_trace: RVec<ExpectedFound<TLFieldOrFunction>>,
-    pub errs: RVec<AbiInstability>,
+pub struct AbiInstabilityError<L: LayoutSource = &'static TypeLayout> {
+    pub stack_trace: RVec<ExpectedFound<L::FieldOrFunction>>,
+    pub errs: RVec<AbiInstability<L>>,
     pub index: usize,
 }
@@ -0,0 +1,384 @@
+//! A structured, serializable report of the errors from layout checking.
+
+use super::*;
+
+use core_extensions::StringExt;
+
+use serde::{Deserialize, Serialize};
+
+/// A structured report of the errors from checking the layout of a type,
+/// with one [`AbiMismatch`] per individual error.
+///
+/// This is constructed with [`AbiInstabilityErrors::report`],
+/// or [`LibraryError::abi_mismatch_report`] when loading a library.
+///
+/// Its `Display` impl outputs one line per mismatch,
+/// and it can be serialized with serde (or [`to_json`](#method.to_json))
+/// for tools that annotate source code with the errors.
+///
+/// # Example
+///
+/// ```rust
+/// use abi_stable::{
+///     abi_stability::{abi_checking::check_layout_compatibility, MismatchSeverity},
+///     StableAbi,
+/// };
+///
+/// mod v1 {
+///     #[repr(C)]
+///     #[derive(abi_stable::StableAbi)]
+///     pub struct Point {
+///         pub x: u32,
+///         pub y: u32,
+///     }
+/// }
+///
+/// mod v2 {
+///     #[repr(C)]
+///     #[derive(abi_stable::StableAbi)]
+///     pub struct Point {
+///         pub x: u32,
+///         pub y: u64,
+///     }
+/// }
+///
+/// let report = check_layout_compatibility(
+///     <v1::Point as StableAbi>::LAYOUT,
+///     <v2::Point as StableAbi>::LAYOUT,
+/// )
+/// .unwrap_err()
+/// .report();
+///
+/// let mismatch = report
+///     .mismatches
+///     .iter()
+///     .find(|m| m.kind == "Name")
+///     .unwrap();
+///
+/// assert_eq!(mismatch.path, "Point.y");
+/// assert_eq!(mismatch.severity, MismatchSeverity::Error);
+/// assert_eq!(mismatch.to_string(), "Point.y: Name expected u32 found u64");
+///
+/// ```
+///
+/// [`LibraryError::abi_mismatch_report`]:
+/// ../library/enum.LibraryError.html#method.abi_mismatch_report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct AbiMismatchReport {
+    /// The type that was expected.
+    pub interface: ReportedType,
+    /// The type that was found.
+    pub implementation: ReportedType,
+    /// The individual mismatches, in the order that they were found.
+    pub mismatches: Vec<AbiMismatch>,
+}
+
+/// An individual error from layout checking.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct AbiMismatch {
+    /// The path from the checked type to the type with the error,
+    /// eg: `RootMod.handlers.callback.param[1]`.
+    ///
+    /// The first component is the name of the checked type,
+    /// followed by the names of fields,
+    /// with `param[N]`/`return` for the parameters/return type of function pointers.
+    pub path: String,
+    /// The kind of error, which is the name of the [`AbiInstability`] variant,
+    /// eg: `"Size"`, `"UnexpectedField"`.
+    pub kind: String,
+    /// How severe the error is.
+    pub severity: MismatchSeverity,
+    /// The type at `path` that was expected.
+    pub interface: ReportedType,
+    /// The type at `path` that was found.
+    pub implementation: ReportedType,
+    /// A short description of the expected value, if there is one.
+    pub expected: Option<String>,
+    /// A short description of the found value, if there is one.
+    pub found: Option<String>,
+    /// Additional information about the error,
+    /// which can span multiple lines.
+    pub details: Option<String>,
+}
+
+/// A type in an [`AbiMismatchReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ReportedType {
+    /// The full type, including generic arguments.
+    pub type_name: String,
+    /// The package that the type is declared in.
+    pub package: String,
+    /// The version of the package that the type is declared in.
+    pub version: String,
+}
+
+/// How severe an [`AbiMismatch`] is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum MismatchSeverity {
+    /// The types have incompatible layouts.
+    Error,
+    /// Layout checking could not be completed,
+    /// caused by a bug in the checked types
+    /// (eg: an invalid package version, or `ExtraChecks` that check the layout of
+    /// the type they're attached to).
+    Bug,
+}
+
+impl AbiInstabilityErrors {
+    /// Constructs a structured report of these errors.
+    pub fn report(&self) -> AbiMismatchReport {
+        let root = self.interface.name();
+
+        let mismatches = self
+            .errors
+            .iter()
+            .flat_map(|error| {
+                let path = error_path(root, &error.stack_trace);
+                let types = error_types(self.interface, self.implementation, &error.stack_trace);
+                error
+                    .errs
+                    .iter()
+                    .map(move |err| AbiMismatch::new(path.clone(), &types, err))
+            })
+            .collect();
+
+        AbiMismatchReport {
+            interface: ReportedType::new(self.interface),
+            implementation: ReportedType::new(self.implementation),
+            mismatches,
+        }
+    }
+}
+
+impl AbiMismatchReport {
+    /// Serializes this report to pretty-printed json.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("AbiMismatchReport is always serializable")
+    }
+}
+
+impl ReportedType {
+    pub(crate) fn new(layout: &'static TypeLayout) -> Self {
+        Self {
+            type_name: layout.full_type().to_string(),
+            package: layout.package().to_string(),
+            version: layout.package_version().to_string(),
+        }
+    }
+}
+
+impl AbiMismatch {
+    fn new(path: String, types: &ExpectedFound<&'static TypeLayout>, err: &AbiInstability) -> Self {
+        fn display<T: fmt::Display>(v: &ExpectedFound<T>) -> Option<ExpectedFound<String>> {
+            Some(v.as_ref().map(|x| x.to_string()))
+        }
+        fn debug<T: fmt::Debug>(v: &ExpectedFound<T>) -> Option<ExpectedFound<String>> {
+            Some(v.as_ref().map(|x| format!("{:?}", x)))
+        }
+        fn field(v: &ExpectedFound<TLField>) -> Option<ExpectedFound<String>> {
+            Some(
+                v.as_ref()
+                    .map(|x| format!("{}: {}", x.name(), x.full_type())),
+            )
+        }
+
+        let mut severity = MismatchSeverity::Error;
+        let mut details = None::<String>;
+
+        let (kind, expected_found) = match err {
+            AI::ReentrantLayoutCheckingCall => {
+                severity = MismatchSeverity::Bug;
+                ("ReentrantLayoutCheckingCall", None)
+            }
+            AI::CyclicTypeChecking { interface, .. } => {
+                severity = MismatchSeverity::Bug;
+                details = Some(format!(
+                    "attempted to check the layout of `{}` while checking \
+                     the layout of one of its const parameters/extra_checks",
+                    interface.full_type(),
+                ));
+                ("CyclicTypeChecking", None)
+            }
+            AI::NonZeroness(v) => ("NonZeroness", display(v)),
+            AI::Name(v) => ("Name", display(v)),
+            AI::Package(v) => ("Package", display(v)),
+            AI::PackageVersionParseError(v) => {
+                severity = MismatchSeverity::Bug;
+                details = Some(v.to_string());
+                ("PackageVersionParseError", None)
+            }
+            AI::PackageVersion(v) => ("PackageVersion", display(v)),
+            AI::MismatchedPrefixSize(v) => ("MismatchedPrefixSize", display(v)),
+            AI::Size(v) => ("Size", display(v)),
+            AI::Alignment(v) => ("Alignment", display(v)),
+            AI::GenericParamCount(v) => ("GenericParamCount", display(v)),
+            AI::TLDataDiscriminant(v) => ("TLDataDiscriminant", debug(v)),
+            AI::MismatchedPrimitive(v) => ("MismatchedPrimitive", debug(v)),
+            AI::FieldCountMismatch(v) => ("FieldCountMismatch", display(v)),
+            AI::FieldLifetimeMismatch(v) => {
+                details = Some(format!("in field `{}`", v.expected.name()));
+                let lifetimes = v.as_ref().map(|x| format!("{:?}", x.lifetime_indices()));
+                ("FieldLifetimeMismatch", Some(lifetimes))
+            }
+            AI::FnLifetimeMismatch(v) => {
+                let lifetimes = v
+                    .as_ref()
+                    .map(|x| format!("{:?}", x.paramret_lifetime_indices));
+                ("FnLifetimeMismatch", Some(lifetimes))
+            }
+            AI::FnQualifierMismatch(v) => {
+                let signature = v.as_ref().map(|x| {
+                    let x = x.to_string();
+                    x.lines().next().unwrap_or_default().to_string()
+                });
+                ("FnQualifierMismatch", Some(signature))
+            }
+            AI::UnexpectedField(v) => ("UnexpectedField", field(v)),
+            AI::TooManyVariants(v) => ("TooManyVariants", display(v)),
+            AI::MismatchedPrefixConditionality(v) => ("MismatchedPrefixConditionality", debug(v)),
+            AI::MismatchedExhaustiveness(v) => ("MismatchedExhaustiveness", debug(v)),
+            AI::MismatchedConstParam(v) => ("MismatchedConstParam", debug(v)),
+            AI::UnexpectedVariant(v) => ("UnexpectedVariant", display(v)),
+            AI::ReprAttr(v) => ("ReprAttr", debug(v)),
+            AI::EnumDiscriminant(v) => ("EnumDiscriminant", debug(v)),
+            AI::IncompatibleWithNonExhaustive(e) => {
+                details = Some(e.to_string());
+                ("IncompatibleWithNonExhaustive", None)
+            }
+            AI::NoneExtraChecks => {
+                details = Some(
+                    "the interface has `extra_checks` while the implementation does not"
+                        .to_string(),
+                );
+                ("NoneExtraChecks", None)
+            }
+            AI::ExtraCheckError(ec_error) => {
+                let ExtraCheckError { err, expected_err } = &**ec_error;
+                details = Some((**err).to_string());
+                ("ExtraCheckError", display(expected_err))
+            }
+            AI::TagError { err } => {
+                details = Some(err.to_string());
+                ("TagError", None)
+            }
+        };
+
+        let (expected, found) = match expected_found {
+            Some(ExpectedFound { expected, found }) => (Some(expected), Some(found)),
+            None => (None, None),
+        };
+
+        Self {
+            path,
+            kind: kind.to_string(),
+            severity,
+            interface: ReportedType::new(types.expected),
+            implementation: ReportedType::new(types.found),
+            expected,
+            found,
+            details,
+        }
+    }
+}
+
+/// Gets the path to the type that was being checked
+/// when the error with this stack trace happened.
+fn error_path(root: &str, stack_trace: &[ExpectedFound<TLFieldOrFunction>]) -> String {
+    let mut path = root.to_string();
+    let mut function = None::<TLFunction>;
+
+    for elem in stack_trace {
+        match (&elem.expected, function) {
+            (TLFieldOrFunction::Function(func), _) => {
+                function = Some(*func);
+            }
+            (TLFieldOrFunction::Field(field), Some(func)) => {
+                if *field == func.get_return() {
+                    path.push_str(".return");
+                } else {
+                    let index = func.get_params().position(|param| param == *field);
+                    match index {
+                        Some(index) => path.push_str(&format!(".param[{}]", index)),
+                        None => path.push_str(&format!(".{}", field.name())),
+                    }
+                }
+                function = None;
+            }
+            (TLFieldOrFunction::Field(field), None) => {
+                path.push('.');
+                path.push_str(field.name());
+            }
+        }
+    }
+
+    path
+}
+
+/// Gets the types that were being checked
+/// when the error with this stack trace happened.
+fn error_types(
+    interface: &'static TypeLayout,
+    implementation: &'static TypeLayout,
+    stack_trace: &[ExpectedFound<TLFieldOrFunction>],
+) -> ExpectedFound<&'static TypeLayout> {
+    stack_trace
+        .iter()
+        .rev()
+        .find_map(|elem| match (&elem.expected, &elem.found) {
+            (TLFieldOrFunction::Field(expected), TLFieldOrFunction::Field(found)) => {
+                Some(ExpectedFound {
+                    expected: expected.layout(),
+                    found: found.layout(),
+                })
+            }
+            _ => None,
+        })
+        .unwrap_or(ExpectedFound {
+            expected: interface,
+            found: implementation,
+        })
+}
+
+impl fmt::Display for AbiMismatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} ABI mismatch(es) between interface {} and implementation {}:",
+            self.mismatches.len(),
+            self.interface,
+            self.implementation,
+        )?;
+        for mismatch in &self.mismatches {
+            writeln!(f, "{}", mismatch.to_string().left_padder(4))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for AbiMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.kind)?;
+        if let (Some(expected), Some(found)) = (&self.expected, &self.found) {
+            write!(f, " expected {} found {}", expected, found)?;
+        }
+        if let Some(details) = &self.details {
+            write!(f, "\n{}", details.left_padder(4))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ReportedType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` (package `{}` version `{}`)",
+            self.type_name, self.package, self.version,
+        )
+    }
+}
@@ -0,0 +1,512 @@
+//! Classifying the differences between two versions of a type layout
+//! by whether they're compatible in a minor release.
+//!
+//! The entry point is [`classify_changes`],
+//! which takes the layouts of the same type (usually a root module) from
+//! two versions of a library,
+//! and returns a [`SemverReport`] with every difference between them,
+//! and the minimum version bump required by those differences.
+//!
+//! Differences are classified with the same rules that the layout checker uses:
+//!
+//! - Any error from the layout checker is a breaking change.
+//!
+//! - Appending fields to a prefix type (eg: a root module),
+//!     or methods to the vtable of a `#[sabi_trait]` trait object,
+//!     is a minor-compatible change.
+//!
+//! - Adding variants to a nonexhaustive enum is a minor-compatible change.
+//!
+//! - Renaming a field or variant with the `#[sabi(renamed_from = "old_name")]` attribute
+//!     is a minor-compatible change.
+//!
+//! - Moving a type to another package with the `#[sabi(moved_from( .. ))]` attribute
+//!     is a minor-compatible change.
+//!
+//! The nested layouts of [`ExtraChecks`] are not inspected for minor-compatible changes.
+//!
+//! # Example
+//!
+//! ```rust
+//! use abi_stable::{
+//!     abi_stability::semver::{classify_changes, ChangeKind, VersionBump},
+//!     sabi_types::VersionNumber,
+//!     StableAbi,
+//! };
+//!
+//! mod v1 {
+//!     #[repr(C)]
+//!     #[derive(abi_stable::StableAbi)]
+//!     #[sabi(kind(Prefix))]
+//!     pub struct Module {
+//!         #[sabi(last_prefix_field)]
+//!         pub first: u32,
+//!     }
+//! }
+//!
+//! mod v2 {
+//!     #[repr(C)]
+//!     #[derive(abi_stable::StableAbi)]
+//!     #[sabi(kind(Prefix))]
+//!     pub struct Module {
+//!         #[sabi(last_prefix_field)]
+//!         pub first: u32,
+//!         pub second: u64,
+//!     }
+//! }
+//!
+//! let report = classify_changes(
+//!     <v1::Module_Ref as StableAbi>::LAYOUT,
+//!     <v2::Module_Ref as StableAbi>::LAYOUT,
+//! );
+//!
+//! assert!(matches!(
+//!     &report.changes[0].kind,
+//!     ChangeKind::PrefixFieldsAppended { fields } if fields == &["second"],
+//! ));
+//! assert_eq!(report.recommended_bump(), VersionBump::Minor);
+//!
+//! let next = report.recommended_bump().next_version(VersionNumber {
+//!     major: 1,
+//!     minor: 2,
+//!     patch: 3,
+//! });
+//! assert_eq!(next.to_string(), "1.3.0");
+//!
+//! ```
+//!
+//! [`ExtraChecks`]: ../extra_checks/trait.ExtraChecks.html
+
+use std::{collections::HashSet, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    abi_stability::abi_checking::{
+        check_layout_compatibility_with_globals, is_moved_type, is_same_field, is_same_variant,
+        AbiMismatch, CheckingGlobals, ReportedType,
+    },
+    sabi_types::VersionNumber,
+    std_types::UTypeId,
+    type_layout::{TLData, TLField, TLFields, TypeLayout},
+};
+
+/// Classifies every difference between the `old` and `new` layouts of a type,
+/// by the version bump that it requires.
+///
+/// `old` is treated as the interface that users were compiled against,
+/// and `new` as the implementation that they will load.
+///
+/// Like [`check_layout_compatibility`], this expects the layouts of the
+/// `*_Ref` pointer to prefix types, rather than of the prefix types themselves.
+///
+/// For an example, look at the [module-level documentation](./index.html).
+///
+/// [`check_layout_compatibility`]: ../fn.check_layout_compatibility.html
+pub fn classify_changes(old: &'static TypeLayout, new: &'static TypeLayout) -> SemverReport {
+    let mut changes = Vec::new();
+
+    let globals = CheckingGlobals::new();
+    if let Err(errors) = check_layout_compatibility_with_globals(old, new, &globals) {
+        changes.extend(errors.report().mismatches.into_iter().map(|mismatch| {
+            SemverChange::new(
+                mismatch.path.clone(),
+                ChangeKind::Incompatible(Box::new(mismatch)),
+            )
+        }));
+    }
+
+    let mut classifier = ChangeClassifier {
+        visited: HashSet::new(),
+        changes: &mut changes,
+    };
+    classifier.visit_type(old.name().to_string(), old, new);
+
+    changes.sort_by(|l, r| l.path.cmp(&r.path));
+
+    SemverReport {
+        old: ReportedType::new(old),
+        new: ReportedType::new(new),
+        changes,
+    }
+}
+
+/// Every difference between two versions of a type layout,
+/// returned by [`classify_changes`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct SemverReport {
+    /// The type in the old version.
+    pub old: ReportedType,
+    /// The type in the new version.
+    pub new: ReportedType,
+    /// The differences between the types, sorted by path.
+    pub changes: Vec<SemverChange>,
+}
+
+/// A difference between two versions of a type layout.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct SemverChange {
+    /// The path from the root type to the type that changed,
+    /// in the same format as [`AbiMismatch::path`].
+    ///
+    /// [`AbiMismatch::path`]: ../struct.AbiMismatch.html#structfield.path
+    pub path: String,
+    /// The version bump that this change requires.
+    pub bump: VersionBump,
+    /// What changed.
+    pub kind: ChangeKind,
+}
+
+/// What changed between two versions of a type layout.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum ChangeKind {
+    /// Fields were appended to a prefix type.
+    PrefixFieldsAppended {
+        /// The names of the new fields.
+        fields: Vec<String>,
+    },
+    /// Methods were appended to the vtable of a `#[sabi_trait]` trait object.
+    VTableMethodsAppended {
+        /// The names of the new methods.
+        methods: Vec<String>,
+    },
+    /// Variants were added to a nonexhaustive enum.
+    VariantsAdded {
+        /// The names of the new variants.
+        variants: Vec<String>,
+    },
+    /// A field or variant was renamed,
+    /// declaring its old name with the `#[sabi(renamed_from = "old_name")]` attribute.
+    Renamed {
+        /// The name in the old layout.
+        old_name: String,
+        /// The name in the new layout.
+        new_name: String,
+    },
+    /// A type was moved to another package or renamed,
+    /// declaring its old identity with the `#[sabi(moved_from( .. ))]` attribute.
+    Moved {
+        /// The package and name of the type in the old layout.
+        old: ReportedType,
+        /// The package and name of the type in the new layout.
+        new: ReportedType,
+    },
+    /// The layout checker found an incompatibility between the types.
+    Incompatible(Box<AbiMismatch>),
+}
+
+/// The kind of version bump that a change requires.
+///
+/// For `0.y.z` versions,
+/// `y` is interpreted as the major version and `z` as the minor version,
+/// like [`VersionNumber::is_compatible`] does.
+///
+/// [`VersionNumber::is_compatible`]:
+/// ../../sabi_types/struct.VersionNumber.html#method.is_compatible
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum VersionBump {
+    /// The layouts are identical.
+    Patch,
+    /// The new layout is a compatible extension of the old one.
+    Minor,
+    /// The new layout is incompatible with the old one.
+    Major,
+}
+
+impl SemverReport {
+    /// The minimum version bump required by all the changes.
+    ///
+    /// This is `VersionBump::Patch` if there are no changes.
+    pub fn recommended_bump(&self) -> VersionBump {
+        self.changes
+            .iter()
+            .map(|change| change.bump)
+            .max()
+            .unwrap_or(VersionBump::Patch)
+    }
+
+    /// Serializes this report to pretty-printed json.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("SemverReport is always serializable")
+    }
+}
+
+impl SemverChange {
+    fn new(path: String, kind: ChangeKind) -> Self {
+        Self {
+            path,
+            bump: kind.bump(),
+            kind,
+        }
+    }
+}
+
+impl ChangeKind {
+    /// The version bump that this change requires.
+    pub fn bump(&self) -> VersionBump {
+        match self {
+            ChangeKind::PrefixFieldsAppended { .. }
+            | ChangeKind::VTableMethodsAppended { .. }
+            | ChangeKind::VariantsAdded { .. }
+            | ChangeKind::Renamed { .. }
+            | ChangeKind::Moved { .. } => VersionBump::Minor,
+            ChangeKind::Incompatible(_) => VersionBump::Major,
+        }
+    }
+}
+
+impl VersionBump {
+    /// Bumps `version` by this amount.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use abi_stable::{abi_stability::semver::VersionBump, sabi_types::VersionNumber};
+    ///
+    /// let version = |major, minor, patch| VersionNumber {
+    ///     major,
+    ///     minor,
+    ///     patch,
+    /// };
+    ///
+    /// assert_eq!(VersionBump::Major.next_version(version(1, 2, 3)), version(2, 0, 0));
+    /// assert_eq!(VersionBump::Minor.next_version(version(1, 2, 3)), version(1, 3, 0));
+    /// assert_eq!(VersionBump::Patch.next_version(version(1, 2, 3)), version(1, 2, 4));
+    ///
+    /// assert_eq!(VersionBump::Major.next_version(version(0, 2, 3)), version(0, 3, 0));
+    /// assert_eq!(VersionBump::Minor.next_version(version(0, 2, 3)), version(0, 2, 4));
+    /// assert_eq!(VersionBump::Patch.next_version(version(0, 2, 3)), version(0, 2, 4));
+    ///
+    /// ```
+    pub const fn next_version(self, version: VersionNumber) -> VersionNumber {
+        let VersionNumber {
+            major,
+            minor,
+            patch,
+        } = version;
+
+        let (major, minor, patch) = match (self, major) {
+            (VersionBump::Major, 0) => (0, minor + 1, 0),
+            (VersionBump::Major, _) => (major + 1, 0, 0),
+            (VersionBump::Minor, 0) | (VersionBump::Patch, _) => (major, minor, patch + 1),
+            (VersionBump::Minor, _) => (major, minor + 1, 0),
+        };
+
+        VersionNumber {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Finds the minor-compatible changes, which the layout checker doesn't report.
+struct ChangeClassifier<'a> {
+    visited: HashSet<(UTypeId, UTypeId)>,
+    changes: &'a mut Vec<SemverChange>,
+}
+
+impl ChangeClassifier<'_> {
+    fn visit_type(&mut self, path: String, old: &'static TypeLayout, new: &'static TypeLayout) {
+        if !self.visited.insert((old.get_utypeid(), new.get_utypeid())) {
+            return;
+        }
+
+        let same_identity = old.name() == new.name() && old.package() == new.package();
+        if !same_identity && is_moved_type(old, new) {
+            let kind = ChangeKind::Moved {
+                old: ReportedType::new(old),
+                new: ReportedType::new(new),
+            };
+            self.push(&path, kind);
+        }
+
+        self.visit_fields(&path, old.phantom_fields(), new.phantom_fields());
+
+        match (old.data(), new.data()) {
+            (TLData::Struct { fields: o_fields }, TLData::Struct { fields: n_fields })
+            | (TLData::Union { fields: o_fields }, TLData::Union { fields: n_fields }) => {
+                self.visit_fields(&path, o_fields, n_fields);
+            }
+            (TLData::Enum(o_enum), TLData::Enum(n_enum)) => {
+                let both_nonexhaustive = o_enum.exhaustiveness.is_nonexhaustive()
+                    && n_enum.exhaustiveness.is_nonexhaustive();
+                let o_count = o_enum.variant_count();
+
+                if both_nonexhaustive && o_count < n_enum.variant_count() {
+                    let variants = n_enum
+                        .variant_names_iter()
+                        .skip(o_count)
+                        .map(String::from)
+                        .collect();
+                    self.push(&path, ChangeKind::VariantsAdded { variants });
+                }
+
+                let names = o_enum.variant_names_iter().zip(n_enum.variant_names_iter());
+                for (o_name, n_name) in names {
+                    if o_name != n_name && is_same_variant(old, o_name, new, n_name) {
+                        self.push_renamed(&path, o_name, n_name);
+                    }
+                }
+
+                self.visit_fields(&path, o_enum.fields, n_enum.fields);
+            }
+            (TLData::PrefixType(o_prefix), TLData::PrefixType(n_prefix)) => {
+                let o_len = o_prefix.fields.len();
+
+                if o_len < n_prefix.fields.len() {
+                    let names = n_prefix
+                        .fields
+                        .iter()
+                        .skip(o_len)
+                        .map(|field| field.name().to_string())
+                        .collect();
+
+                    let is_vtable = n_prefix
+                        .fields
+                        .iter()
+                        .any(|field| field.name() == "_sabi_vtable");
+
+                    let kind = if is_vtable {
+                        ChangeKind::VTableMethodsAppended { methods: names }
+                    } else {
+                        ChangeKind::PrefixFieldsAppended { fields: names }
+                    };
+                    self.push(&path, kind);
+                }
+
+                self.visit_fields(&path, o_prefix.fields, n_prefix.fields);
+            }
+            _ => {}
+        }
+    }
+
+    /// Visits the fields that both types have in common,
+    /// including the parameters and return types of function pointers.
+    fn visit_fields(&mut self, path: &str, old: TLFields, new: TLFields) {
+        for (o_field, n_field) in old.iter().zip(new.iter()) {
+            if !is_same_field(&o_field, &n_field) {
+                continue;
+            } else if o_field.name() != n_field.name() {
+                self.push_renamed(path, o_field.name(), n_field.name());
+            }
+            let field_path = format!("{}.{}", path, o_field.name());
+
+            let functions = o_field
+                .function_range()
+                .iter()
+                .zip(n_field.function_range().iter());
+            for (o_func, n_func) in functions {
+                let params = o_func.get_params().zip(n_func.get_params());
+                for (i, (o_param, n_param)) in params.enumerate() {
+                    let param_path = format!("{}.param[{}]", field_path, i);
+                    self.visit_field_type(param_path, &o_param, &n_param);
+                }
+
+                let return_path = format!("{}.return", field_path);
+                self.visit_field_type(return_path, &o_func.get_return(), &n_func.get_return());
+            }
+
+            self.visit_field_type(field_path, &o_field, &n_field);
+        }
+    }
+
+    fn visit_field_type(&mut self, path: String, old: &TLField, new: &TLField) {
+        self.visit_type(path, old.layout(), new.layout());
+    }
+
+    fn push(&mut self, path: &str, kind: ChangeKind) {
+        self.changes.push(SemverChange::new(path.to_string(), kind));
+    }
+
+    fn push_renamed(&mut self, path: &str, old_name: &str, new_name: &str) {
+        let kind = ChangeKind::Renamed {
+            old_name: old_name.to_string(),
+            new_name: new_name.to_string(),
+        };
+        self.push(path, kind);
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+impl fmt::Display for SemverReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} change(s) from {} to {}:",
+            self.changes.len(),
+            self.old,
+            self.new,
+        )?;
+        for change in &self.changes {
+            writeln!(f, "    {}", change)?;
+        }
+        write!(f, "recommended version bump: {}", self.recommended_bump())
+    }
+}
+
+impl fmt::Display for SemverChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let compatibility = match self.bump {
+            VersionBump::Patch => "patch-compatible",
+            VersionBump::Minor => "minor-compatible",
+            VersionBump::Major => "breaking",
+        };
+        write!(f, "{}: {}: {}", self.path, self.kind, compatibility)
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list(f: &mut fmt::Formatter<'_>, names: &[String]) -> fmt::Result {
+            for (i, name) in names.iter().enumerate() {
+                let sep = if i == 0 { "" } else { ", " };
+                write!(f, "{}`{}`", sep, name)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            ChangeKind::PrefixFieldsAppended { fields } => {
+                f.write_str("field(s) appended to prefix type: ")?;
+                list(f, fields)
+            }
+            ChangeKind::VTableMethodsAppended { methods } => {
+                f.write_str("method(s) appended to sabi_trait vtable: ")?;
+                list(f, methods)
+            }
+            ChangeKind::VariantsAdded { variants } => {
+                f.write_str("variant(s) added to nonexhaustive enum: ")?;
+                list(f, variants)
+            }
+            ChangeKind::Renamed { old_name, new_name } => {
+                write!(f, "`{}` renamed to `{}`", old_name, new_name)
+            }
+            ChangeKind::Moved { old, new } => write!(f, "moved from {} to {}", old, new),
+            ChangeKind::Incompatible(mismatch) => {
+                f.write_str(&mismatch.kind)?;
+                if let (Some(expected), Some(found)) = (&mismatch.expected, &mismatch.found) {
+                    write!(f, " expected {} found {}", expected, found)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for VersionBump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            VersionBump::Patch => "patch",
+            VersionBump::Minor => "minor",
+            VersionBump::Major => "major",
+        })
+    }
+}
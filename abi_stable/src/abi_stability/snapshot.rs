@@ -0,0 +1,1101 @@
+//! Owned, serializable snapshots of type layouts,
+//! which can be stored in an "ABI lockfile" to check layouts for compatibility
+//! without building (or loading) older versions of a library.
+//!
+//! # Example
+//!
+//! This is how a test could check that the layout of the root module of a library
+//! stays compatible with the one recorded in an `abi.lock.json` file,
+//! writing the file if it doesn't exist yet.
+//!
+//! ```rust,no_run
+//! use abi_stable::{
+//!     abi_stability::snapshot::{check_layout_against_snapshot, AbiSnapshot},
+//!     std_types::RVec,
+//!     StableAbi,
+//! };
+//!
+//! let layout = <RVec<u32> as StableAbi>::LAYOUT;
+//! let path = "abi.lock.json";
+//!
+//! if std::path::Path::new(path).exists() {
+//!     let snapshot = AbiSnapshot::read_file(path).unwrap();
+//!     if let Err(e) = check_layout_against_snapshot(&snapshot, layout) {
+//!         panic!("{}", e);
+//!     }
+//! } else {
+//!     AbiSnapshot::new(layout).write_file(path).unwrap();
+//! }
+//!
+//! ```
+//!
+//! # Differences with layout checking
+//!
+//! Checking against a snapshot is done by the same checker as
+//! [`check_layout_compatibility`](../fn.check_layout_compatibility.html),
+//! returning [`AbiInstabilityErrors<AbiSnapshot>`](../abi_checking/struct.AbiInstabilityErrors.html),
+//! which describes fields and function pointers with strings.
+//!
+//! The differences are that:
+//!
+//! - Prefix types and nonexhaustive enums are only checked against the snapshot,
+//!     they're not recorded to be checked against other libraries.
+//!
+//! - Const parameters are compared by their `Debug` output.
+//!
+//! - [`ExtraChecks`] are compared by their `Display` output,
+//!     and the type layouts that they reference,
+//!     since they can't be deserialized.
+//!
+//! [`ExtraChecks`]: ../extra_checks/trait.ExtraChecks.html
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    iter, slice,
+};
+
+#[cfg(feature = "serde_json")]
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    abi_stability::abi_checking::{
+        check_unrecorded_compatibility, push_err, AbiChecker, AbiInstability, AbiInstability as AI,
+        AbiInstabilityError, AbiInstabilityErrors, CheckableLayout, ExpectedFound, ExtraCheckError,
+        LayoutData, LayoutSource,
+    },
+    prefix_type::{FieldAccessibility, FieldConditionality},
+    sabi_types::{CmpIgnored, VersionNumber},
+    std_types::{RArc, RBox, RBoxError, RStr, RVec, UTypeId},
+    type_layout::{
+        tagging::{CTVariant, CheckableTag, KeyValue, Primitive},
+        DiscriminantRepr, LifetimeIndexPair, ReprAttr, TLData, TLDataDiscriminant, TLDiscriminant,
+        TLField, TLPrimitive, TypeLayout,
+    },
+    utils::leak_value,
+};
+
+/// The version of the snapshot format,
+/// snapshots with another format version are rejected when deserialized.
+const FORMAT_VERSION: u32 = 1;
+
+/// An owned snapshot of a [`TypeLayout`],
+/// along with every type that it references.
+///
+/// The types are stored in a table, in the order that they're found in,
+/// and reference each other by their index in that table.
+///
+/// [`TypeLayout`]: ../../type_layout/struct.TypeLayout.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedAbiSnapshot")]
+pub struct AbiSnapshot {
+    format_version: u32,
+    types: Vec<TypeSnapshot>,
+}
+
+/// An `AbiSnapshot` whose type references haven't been checked to be in bounds.
+#[derive(Deserialize)]
+struct UncheckedAbiSnapshot {
+    format_version: u32,
+    types: Vec<TypeSnapshot>,
+}
+
+/// A snapshot of a single type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeSnapshot {
+    /// The name of the type, without generic parameters.
+    pub name: String,
+    /// The name of the type, including generic parameters.
+    pub full_type: String,
+    /// The package that the type was declared in.
+    pub package: String,
+    /// The version of the package that the type was declared in.
+    pub version: String,
+    /// The packages and names that the type was moved from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub moved_from: Vec<MovedFromSnapshot>,
+    /// Whether the type uses non-zero value optimization.
+    pub is_nonzero: bool,
+    /// The size of the type.
+    pub size: usize,
+    /// The alignment of the type.
+    pub alignment: usize,
+    /// The representation attribute(s) of the type.
+    pub repr_attr: ReprAttr,
+    /// The amount of lifetime parameters of the type.
+    pub lifetime_count: usize,
+    /// The `Debug` output of each const parameter.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub const_params: Vec<String>,
+    /// Fields which don't have a runtime component, but are still checked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub phantom_fields: Vec<FieldSnapshot>,
+    /// What kind of type this is, Primitive/Struct/Enum/PrefixType.
+    pub data: DataSnapshot,
+    /// The tag of the type, in the form that's checked for compatibility.
+    #[serde(default, skip_serializing_if = "TagSnapshot::is_null")]
+    pub tag: TagSnapshot,
+    /// The extra checks of the type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_checks: Option<ExtraChecksSnapshot>,
+}
+
+/// A package and type name that a type was moved from,
+/// equivalent to the items of [`ItemInfo::moved_from`].
+///
+/// [`ItemInfo::moved_from`]: ../../type_layout/struct.ItemInfo.html#method.moved_from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MovedFromSnapshot {
+    /// The package that the type was declared in.
+    pub package: String,
+    /// The name of the type in that package.
+    pub name: String,
+}
+
+/// A snapshot of the data of a type, equivalent to [`TLData`].
+///
+/// [`TLData`]: ../../type_layout/enum.TLData.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DataSnapshot {
+    /// Types defined in the compiler.
+    Primitive(TLPrimitive),
+    /// A type that's only compared for size and alignment.
+    Opaque,
+    /// A struct.
+    Struct {
+        /// The fields of the struct.
+        fields: Vec<FieldSnapshot>,
+    },
+    /// A union.
+    Union {
+        /// The fields of the union.
+        fields: Vec<FieldSnapshot>,
+    },
+    /// An enum.
+    Enum(EnumSnapshot),
+    /// A prefix type (vtables and modules).
+    PrefixType(PrefixTypeSnapshot),
+}
+
+/// A snapshot of an enum, equivalent to [`TLEnum`].
+///
+/// [`TLEnum`]: ../../type_layout/struct.TLEnum.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnumSnapshot {
+    /// The name of each variant.
+    pub variant_names: Vec<String>,
+    /// The names that each variant was renamed from,
+    /// empty if no variant was renamed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variant_renamed_from: Vec<Vec<String>>,
+    /// The amount of fields of each variant.
+    pub field_counts: Vec<u8>,
+    /// All the fields of the enum, not separated by variant.
+    pub fields: Vec<FieldSnapshot>,
+    /// The original size and alignment of a nonexhaustive enum,
+    /// `None` if the enum is exhaustive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonexhaustive: Option<NonExhaustiveSnapshot>,
+    /// The type of the discriminant.
+    pub discriminant_repr: DiscriminantRepr,
+    /// The discriminant of each variant.
+    pub discriminants: Vec<TLDiscriminant>,
+}
+
+/// The size and alignment of a nonexhaustive enum,
+/// before it's wrapped in `NonExhaustive`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonExhaustiveSnapshot {
+    /// The size of the enum.
+    pub original_size: usize,
+    /// The alignment of the enum.
+    pub original_alignment: usize,
+}
+
+/// A snapshot of a prefix type, equivalent to [`TLPrefixType`].
+///
+/// [`TLPrefixType`]: ../../type_layout/struct.TLPrefixType.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrefixTypeSnapshot {
+    /// The index of the first field in the suffix.
+    pub first_suffix_field: u8,
+    /// A bitset of the fields in the prefix that are conditionally accessible.
+    pub conditional_prefix_fields: u64,
+    /// A bitset of the fields that are accessible.
+    pub accessible_fields: u64,
+    /// All the fields of the prefix type, even if they are inaccessible.
+    pub fields: Vec<FieldSnapshot>,
+}
+
+/// A snapshot of a field, equivalent to [`TLField`].
+///
+/// [`TLField`]: ../../type_layout/struct.TLField.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSnapshot {
+    /// The name of the field.
+    pub name: String,
+    /// The names that the field was renamed from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub renamed_from: Vec<String>,
+    /// The lifetimes that the field references.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lifetime_indices: Vec<String>,
+    /// The index of the type of the field, in the table of the `AbiSnapshot`.
+    pub layout: usize,
+    /// The function pointers that the type of the field contains.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub functions: Vec<FunctionSnapshot>,
+}
+
+/// A snapshot of a function pointer, equivalent to [`TLFunction`].
+///
+/// [`TLFunction`]: ../../type_layout/struct.TLFunction.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionSnapshot {
+    /// The name of the field that the function pointer is in.
+    pub name: String,
+    /// Whether the function is `unsafe`.
+    pub is_unsafe: bool,
+    /// The lifetimes that the parameters and return type reference.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paramret_lifetime_indices: Vec<String>,
+    /// The parameters of the function, followed by the return type.
+    pub params_and_return: Vec<FieldSnapshot>,
+}
+
+/// A snapshot of a [`Tag`], in the form that it's checked for compatibility in.
+///
+/// [`Tag`]: ../../type_layout/struct.Tag.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TagSnapshot {
+    /// The null tag.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// A signed integer.
+    Int(i64),
+    /// An unsigned integer.
+    UInt(u64),
+    /// A string.
+    String(String),
+    /// A tag that's considered compatible with any other.
+    Ignored(Box<TagSnapshot>),
+    /// An array of tags.
+    Array(Vec<TagSnapshot>),
+    /// A set of tags.
+    Set(Vec<TagSnapshot>),
+    /// A map from tags to tags.
+    Map(Vec<(TagSnapshot, TagSnapshot)>),
+}
+
+/// A snapshot of the [`ExtraChecks`] of a type.
+///
+/// [`ExtraChecks`]: ../extra_checks/trait.ExtraChecks.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtraChecksSnapshot {
+    /// The `Display` output of the extra checks.
+    pub description: String,
+    /// The indices of the types that the extra checks reference,
+    /// in the table of the `AbiSnapshot`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nested_layouts: Vec<usize>,
+}
+
+//////////////////////////////////////////////////////////////////////
+
+impl AbiSnapshot {
+    /// Takes a snapshot of `layout`, and every type that it references.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use abi_stable::{abi_stability::snapshot::AbiSnapshot, std_types::RString, StableAbi};
+    ///
+    /// let snapshot = AbiSnapshot::new(<RString as StableAbi>::LAYOUT);
+    ///
+    /// assert_eq!(snapshot.root().name, "RString");
+    ///
+    /// ```
+    pub fn new(layout: &'static TypeLayout) -> Self {
+        let mut builder = SnapshotBuilder {
+            indices: HashMap::new(),
+            layouts: Vec::new(),
+        };
+        builder.layout_ref(layout);
+
+        let mut types = Vec::new();
+        while let Some(&layout) = builder.layouts.get(types.len()) {
+            types.push(builder.type_snapshot(layout));
+        }
+
+        Self {
+            format_version: FORMAT_VERSION,
+            types,
+        }
+    }
+
+    /// The snapshot of the type that this was created from.
+    pub fn root(&self) -> &TypeSnapshot {
+        &self.types[0]
+    }
+
+    /// The snapshots of all the types,
+    /// the type at index 0 is the one that this was created from.
+    pub fn types(&self) -> &[TypeSnapshot] {
+        &self.types
+    }
+
+    /// Checks that the type in `implementation` is compatible with the one in `self`,
+    /// with the same rules as [`check_layout_compatibility`].
+    ///
+    /// [`check_layout_compatibility`]: ../fn.check_layout_compatibility.html
+    pub fn check_compatibility(
+        &self,
+        implementation: &AbiSnapshot,
+    ) -> Result<(), AbiInstabilityErrors<AbiSnapshot>> {
+        check_unrecorded_compatibility(
+            SnapshotLayout::root(self),
+            SnapshotLayout::root(implementation),
+        )
+    }
+
+    /// Serializes this snapshot to pretty-printed json.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("AbiSnapshot is always serializable")
+    }
+
+    /// Deserializes a snapshot from json.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if the json isn't a valid snapshot,
+    /// or if it has another format version.
+    #[cfg(feature = "serde_json")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Writes this snapshot as json to the file at `path`.
+    #[cfg(feature = "serde_json")]
+    pub fn write_file<P>(&self, path: P) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let mut json = self.to_json();
+        json.push('\n');
+        fs::write(path, json)
+    }
+
+    /// Reads a snapshot from the json file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if the file can't be read, or isn't a valid snapshot.
+    #[cfg(feature = "serde_json")]
+    pub fn read_file<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let json = fs::read_to_string(path)?;
+        Ok(Self::from_json(&json)?)
+    }
+}
+
+impl TryFrom<UncheckedAbiSnapshot> for AbiSnapshot {
+    type Error = String;
+
+    fn try_from(this: UncheckedAbiSnapshot) -> Result<Self, String> {
+        let UncheckedAbiSnapshot {
+            format_version,
+            types,
+        } = this;
+
+        if format_version != FORMAT_VERSION {
+            return Err(format!(
+                "expected version {} of the snapshot format, found version {}",
+                FORMAT_VERSION, format_version,
+            ));
+        }
+        if types.is_empty() {
+            return Err("a snapshot must contain at least one type".to_string());
+        }
+
+        for type_ in &types {
+            let mut referenced = type_
+                .phantom_fields
+                .iter()
+                .chain(type_.data.fields())
+                .map(|x| x.layout)
+                .chain(
+                    type_
+                        .extra_checks
+                        .iter()
+                        .flat_map(|x| x.nested_layouts.clone()),
+                );
+            if !referenced.all(|x| x < types.len()) {
+                return Err(format!(
+                    "the `{}` type references a type that isn't in the snapshot",
+                    type_.full_type,
+                ));
+            }
+        }
+
+        Ok(Self {
+            format_version,
+            types,
+        })
+    }
+}
+
+impl TypeSnapshot {
+    /// The package version of this type, parsed.
+    fn parsed_version(&self) -> Result<VersionNumber, String> {
+        VersionNumber::parse_str(&self.version).map_err(|(which_field, e)| {
+            format!(
+                "Invalid version string:'{}'\nerror at the {} field:{}",
+                self.version, which_field, e,
+            )
+        })
+    }
+
+    /// Whether this type was moved from the package and name of `other`.
+    fn was_moved_from(&self, other: &TypeSnapshot) -> bool {
+        self.moved_from
+            .iter()
+            .any(|x| x.package == other.package && x.name == other.name)
+    }
+}
+
+impl DataSnapshot {
+    /// The discriminant-only version of this.
+    pub fn discriminant(&self) -> TLDataDiscriminant {
+        match self {
+            DataSnapshot::Primitive(_) => TLDataDiscriminant::Primitive,
+            DataSnapshot::Opaque => TLDataDiscriminant::Opaque,
+            DataSnapshot::Struct { .. } => TLDataDiscriminant::Struct,
+            DataSnapshot::Union { .. } => TLDataDiscriminant::Union,
+            DataSnapshot::Enum(_) => TLDataDiscriminant::Enum,
+            DataSnapshot::PrefixType(_) => TLDataDiscriminant::PrefixType,
+        }
+    }
+
+    /// All the fields, including the parameters and return types of function pointers.
+    fn fields(&self) -> Vec<&FieldSnapshot> {
+        fn add_fields<'a>(fields: &'a [FieldSnapshot], out: &mut Vec<&'a FieldSnapshot>) {
+            for field in fields {
+                out.push(field);
+                for function in &field.functions {
+                    add_fields(&function.params_and_return, out);
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        match self {
+            DataSnapshot::Primitive(_) | DataSnapshot::Opaque => {}
+            DataSnapshot::Struct { fields } | DataSnapshot::Union { fields } => {
+                add_fields(fields, &mut out)
+            }
+            DataSnapshot::Enum(enum_) => add_fields(&enum_.fields, &mut out),
+            DataSnapshot::PrefixType(prefix) => add_fields(&prefix.fields, &mut out),
+        }
+        out
+    }
+}
+
+impl TagSnapshot {
+    /// Whether this is the null tag.
+    pub fn is_null(&self) -> bool {
+        matches!(self, TagSnapshot::Null)
+    }
+
+    fn new(tag: &CheckableTag) -> Self {
+        match tag.variant() {
+            CTVariant::Primitive(Primitive::Null) => TagSnapshot::Null,
+            CTVariant::Primitive(Primitive::Bool(b)) => TagSnapshot::Bool(*b),
+            CTVariant::Primitive(Primitive::Int(n)) => TagSnapshot::Int(*n),
+            CTVariant::Primitive(Primitive::UInt(n)) => TagSnapshot::UInt(*n),
+            CTVariant::Primitive(Primitive::String_(s)) => TagSnapshot::String(s.to_string()),
+            CTVariant::Ignored(tag) => TagSnapshot::Ignored(Box::new(Self::new(tag))),
+            CTVariant::Array(tags) => TagSnapshot::Array(tags.iter().map(Self::new).collect()),
+            CTVariant::Set(tags) => {
+                TagSnapshot::Set(tags.iter().map(|x| Self::new(&x.key)).collect())
+            }
+            CTVariant::Map(tags) => TagSnapshot::Map(
+                tags.iter()
+                    .map(|x| (Self::new(&x.key), Self::new(&x.value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Converts this back into a `CheckableTag`.
+    ///
+    /// Strings are leaked, since `CheckableTag` can only store `'static` strings,
+    /// this only happens for tags that contain strings.
+    fn to_checkable(&self) -> CheckableTag {
+        let sorted = |pairs: BTreeMap<CheckableTag, CheckableTag>| {
+            pairs
+                .into_iter()
+                .map(KeyValue::from_pair)
+                .collect::<RVec<_>>()
+        };
+
+        let variant = match self {
+            TagSnapshot::Null => CTVariant::Primitive(Primitive::Null),
+            TagSnapshot::Bool(b) => CTVariant::Primitive(Primitive::Bool(*b)),
+            TagSnapshot::Int(n) => CTVariant::Primitive(Primitive::Int(*n)),
+            TagSnapshot::UInt(n) => CTVariant::Primitive(Primitive::UInt(*n)),
+            TagSnapshot::String(s) => {
+                let s: &'static String = leak_value(s.clone());
+                CTVariant::Primitive(Primitive::String_(RStr::from_str(s)))
+            }
+            TagSnapshot::Ignored(tag) => CTVariant::Ignored(RBox::new(tag.to_checkable())),
+            TagSnapshot::Array(tags) => {
+                CTVariant::Array(tags.iter().map(Self::to_checkable).collect())
+            }
+            TagSnapshot::Set(tags) => CTVariant::Set(sorted(
+                tags.iter()
+                    .map(|x| (x.to_checkable(), TagSnapshot::Null.to_checkable()))
+                    .collect(),
+            )),
+            TagSnapshot::Map(tags) => CTVariant::Map(sorted(
+                tags.iter()
+                    .map(|(k, v)| (k.to_checkable(), v.to_checkable()))
+                    .collect(),
+            )),
+        };
+        CheckableTag::new(variant)
+    }
+}
+
+impl Default for TagSnapshot {
+    fn default() -> Self {
+        TagSnapshot::Null
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// Checks that `implementation` is compatible with the type in the `interface` snapshot,
+/// with the same rules as [`check_layout_compatibility`].
+///
+/// The snapshot is treated as the interface,
+/// so this checks that libraries built with the current version of the type
+/// can be loaded by executables built with the version that the snapshot was taken of.
+///
+/// # Example
+///
+/// ```
+/// use abi_stable::{
+///     abi_stability::snapshot::{check_layout_against_snapshot, AbiSnapshot},
+///     std_types::{RString, RVec},
+///     StableAbi,
+/// };
+///
+/// let snapshot = AbiSnapshot::new(<RVec<RString> as StableAbi>::LAYOUT);
+///
+/// let json = snapshot.to_json();
+/// let snapshot = AbiSnapshot::from_json(&json).unwrap();
+///
+/// assert!(check_layout_against_snapshot(&snapshot, <RVec<RString>>::LAYOUT).is_ok());
+/// assert!(check_layout_against_snapshot(&snapshot, <RVec<u8>>::LAYOUT).is_err());
+///
+/// ```
+///
+/// [`check_layout_compatibility`]: ../fn.check_layout_compatibility.html
+pub fn check_layout_against_snapshot(
+    interface: &AbiSnapshot,
+    implementation: &'static TypeLayout,
+) -> Result<(), AbiInstabilityErrors<AbiSnapshot>> {
+    interface.check_compatibility(&AbiSnapshot::new(implementation))
+}
+
+//////////////////////////////////////////////////////////////////////
+
+struct SnapshotBuilder {
+    /// Maps the types that were found so far to their index in the snapshot.
+    indices: HashMap<UTypeId, usize>,
+    /// The types in the order they were found.
+    layouts: Vec<&'static TypeLayout>,
+}
+
+impl SnapshotBuilder {
+    /// Gets the index of `layout` in the snapshot,
+    /// queueing it to be added if it wasn't found before.
+    fn layout_ref(&mut self, layout: &'static TypeLayout) -> usize {
+        let next_index = self.indices.len();
+        let layouts = &mut self.layouts;
+        *self.indices.entry(layout.get_utypeid()).or_insert_with(|| {
+            layouts.push(layout);
+            next_index
+        })
+    }
+
+    fn type_snapshot(&mut self, layout: &'static TypeLayout) -> TypeSnapshot {
+        let (package, version) = layout.item_info().package_and_version();
+        let generics = layout.generics();
+
+        let data = match layout.data() {
+            TLData::Primitive(primitive) => DataSnapshot::Primitive(primitive),
+            TLData::Opaque => DataSnapshot::Opaque,
+            TLData::Struct { fields } => DataSnapshot::Struct {
+                fields: self.fields(fields),
+            },
+            TLData::Union { fields } => DataSnapshot::Union {
+                fields: self.fields(fields),
+            },
+            TLData::Enum(enum_) => DataSnapshot::Enum(EnumSnapshot {
+                variant_names: enum_
+                    .variant_names
+                    .as_str()
+                    .split(';')
+                    .take(enum_.field_count.len())
+                    .map(String::from)
+                    .collect(),
+                variant_renamed_from: if layout.item_info().renamed_variants().is_empty() {
+                    Vec::new()
+                } else {
+                    enum_
+                        .variant_names_iter()
+                        .map(|name| {
+                            layout
+                                .item_info()
+                                .variant_renamed_from(name)
+                                .map(String::from)
+                                .collect()
+                        })
+                        .collect()
+                },
+                field_counts: enum_.field_count.to_vec(),
+                fields: self.fields(enum_.fields),
+                nonexhaustive: enum_.exhaustiveness.as_nonexhaustive().map(|ne| {
+                    NonExhaustiveSnapshot {
+                        original_size: ne.original_size(),
+                        original_alignment: ne.original_alignment(),
+                    }
+                }),
+                discriminant_repr: enum_.discriminants.discriminant_repr(),
+                discriminants: enum_.discriminants.to_vec(),
+            }),
+            TLData::PrefixType(prefix) => DataSnapshot::PrefixType(PrefixTypeSnapshot {
+                first_suffix_field: prefix.first_suffix_field,
+                conditional_prefix_fields: prefix.conditional_prefix_fields.bits(),
+                accessible_fields: prefix.accessible_fields.bits(),
+                fields: self.fields(prefix.fields),
+            }),
+        };
+
+        TypeSnapshot {
+            name: layout.name().to_string(),
+            full_type: layout.full_type().to_string(),
+            package: package.to_string(),
+            version: version.to_string(),
+            moved_from: layout
+                .item_info()
+                .moved_from()
+                .map(|(package, name)| MovedFromSnapshot {
+                    package: package.to_string(),
+                    name: name.to_string(),
+                })
+                .collect(),
+            is_nonzero: layout.is_nonzero(),
+            size: layout.size(),
+            alignment: layout.alignment(),
+            repr_attr: layout.repr_attr(),
+            lifetime_count: generics.lifetime_count(),
+            const_params: generics
+                .const_params()
+                .iter()
+                .map(|x| format!("{:?}", x))
+                .collect(),
+            phantom_fields: self.fields(layout.phantom_fields()),
+            data,
+            tag: TagSnapshot::new(&layout.tag().to_checkable()),
+            extra_checks: layout
+                .extra_checks()
+                .map(|extra_checks| ExtraChecksSnapshot {
+                    description: extra_checks.to_string(),
+                    nested_layouts: extra_checks
+                        .nested_type_layouts()
+                        .iter()
+                        .map(|&x| self.layout_ref(x))
+                        .collect(),
+                }),
+        }
+    }
+
+    fn fields<I>(&mut self, fields: I) -> Vec<FieldSnapshot>
+    where
+        I: IntoIterator<Item = TLField>,
+    {
+        fields.into_iter().map(|x| self.field(x)).collect()
+    }
+
+    fn field(&mut self, field: TLField) -> FieldSnapshot {
+        FieldSnapshot {
+            name: field.name().to_string(),
+            renamed_from: field.renamed_from().map(String::from).collect(),
+            lifetime_indices: lifetime_indices(&field.lifetime_indices()),
+            layout: self.layout_ref(field.layout()),
+            functions: field
+                .function_range()
+                .iter()
+                .map(|function| FunctionSnapshot {
+                    name: function.name.to_string(),
+                    is_unsafe: function.qualifiers().is_unsafe(),
+                    paramret_lifetime_indices: lifetime_indices(
+                        &function.paramret_lifetime_indices,
+                    ),
+                    params_and_return: self.fields(function.get_params_ret_iter()),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn lifetime_indices(indices: &[LifetimeIndexPair]) -> Vec<String> {
+    indices
+        .iter()
+        .flat_map(|pair| {
+            let (first, second) = pair.both();
+            vec![first.to_string(), second.to_string()]
+        })
+        .collect()
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// The layouts in a snapshot are described with strings in the errors from checking them,
+/// since snapshots don't contain `'static` type layouts.
+///
+/// Fields and function pointers are described with their name and types,
+/// and `AbiInstability::MismatchedExhaustiveness` stores whether each enum is exhaustive.
+impl LayoutSource for AbiSnapshot {
+    type Type = String;
+    type FullType = String;
+    type Str = String;
+    type Version = String;
+    type VersionError = String;
+    type Field = String;
+    type Function = String;
+    type FieldOrFunction = String;
+    type ConstParam = String;
+    type Exhaustiveness = bool;
+    type NonExhaustiveError = String;
+
+    fn formatted_layout(_: &String) -> Option<String> {
+        None
+    }
+}
+
+/// A type in an `AbiSnapshot`, which the layout checker can traverse.
+#[derive(Copy, Clone)]
+struct SnapshotLayout<'a> {
+    snapshot: &'a AbiSnapshot,
+    index: usize,
+}
+
+impl<'a> SnapshotLayout<'a> {
+    fn root(snapshot: &'a AbiSnapshot) -> Self {
+        Self { snapshot, index: 0 }
+    }
+
+    fn get(self) -> &'a TypeSnapshot {
+        &self.snapshot.types[self.index]
+    }
+
+    fn at(self, index: usize) -> Self {
+        Self {
+            snapshot: self.snapshot,
+            index,
+        }
+    }
+}
+
+impl<'a> CheckableLayout for SnapshotLayout<'a> {
+    type Source = AbiSnapshot;
+    type Id = usize;
+    type Field = &'a FieldSnapshot;
+    type Function = &'a FunctionSnapshot;
+    type Enum = &'a EnumSnapshot;
+    type Prefix = &'a PrefixTypeSnapshot;
+    type Fields = slice::Iter<'a, FieldSnapshot>;
+    type Functions = slice::Iter<'a, FunctionSnapshot>;
+    type ParamsRet = slice::Iter<'a, FieldSnapshot>;
+    type VariantNames = iter::Cloned<slice::Iter<'a, String>>;
+
+    fn id(self) -> usize {
+        self.index
+    }
+    fn to_type(self) -> String {
+        self.get().full_type.clone()
+    }
+    fn name(&self) -> &str {
+        &self.get().name
+    }
+    fn full_type(self) -> String {
+        self.get().full_type.clone()
+    }
+    fn package(self) -> String {
+        self.get().package.clone()
+    }
+    fn package_version(self) -> String {
+        self.get().version.clone()
+    }
+    fn parsed_version(self) -> Result<VersionNumber, String> {
+        self.get().parsed_version()
+    }
+    fn is_moved(self, other: Self) -> bool {
+        self.get().was_moved_from(other.get()) || other.get().was_moved_from(self.get())
+    }
+    fn is_nonzero(self) -> bool {
+        self.get().is_nonzero
+    }
+    fn repr_attr(self) -> ReprAttr {
+        self.get().repr_attr
+    }
+    fn lifetime_count(self) -> usize {
+        self.get().lifetime_count
+    }
+    fn const_param_count(self) -> usize {
+        self.get().const_params.len()
+    }
+    fn size(self) -> usize {
+        self.get().size
+    }
+    fn alignment(self) -> usize {
+        self.get().alignment
+    }
+    fn data_discriminant(self) -> TLDataDiscriminant {
+        self.get().data.discriminant()
+    }
+    fn tag(self) -> CheckableTag {
+        self.get().tag.to_checkable()
+    }
+    fn has_extra_checks(self) -> bool {
+        self.get().extra_checks.is_some()
+    }
+    fn phantom_fields(self) -> Self::Fields {
+        self.get().phantom_fields.iter()
+    }
+    fn data(self) -> LayoutData<Self> {
+        match &self.get().data {
+            DataSnapshot::Primitive(x) => LayoutData::Primitive(*x),
+            DataSnapshot::Opaque => LayoutData::Opaque,
+            DataSnapshot::Struct { fields } => LayoutData::Struct(fields.iter()),
+            DataSnapshot::Union { fields } => LayoutData::Union(fields.iter()),
+            DataSnapshot::Enum(x) => LayoutData::Enum(x),
+            DataSnapshot::PrefixType(x) => LayoutData::PrefixType(x),
+        }
+    }
+
+    fn is_same_field(this: &&'a FieldSnapshot, other: &&'a FieldSnapshot) -> bool {
+        this.name == other.name
+            || this.renamed_from.contains(&other.name)
+            || other.renamed_from.contains(&this.name)
+    }
+    fn same_field_lifetimes(this: &&'a FieldSnapshot, other: &&'a FieldSnapshot) -> bool {
+        this.lifetime_indices == other.lifetime_indices
+    }
+    fn field_payload(self, field: &'a FieldSnapshot) -> String {
+        let mut out = format!("{}: {}", field.name, self.at(field.layout).get().full_type);
+        if !field.lifetime_indices.is_empty() {
+            out.push_str(&format!("\nlifetime indices:{:?}", field.lifetime_indices));
+        }
+        out
+    }
+    fn field_layout(self, field: &'a FieldSnapshot) -> Self {
+        self.at(field.layout)
+    }
+    fn field_functions(self, field: &'a FieldSnapshot) -> Self::Functions {
+        field.functions.iter()
+    }
+
+    fn same_function_lifetimes(this: &&'a FunctionSnapshot, other: &&'a FunctionSnapshot) -> bool {
+        this.paramret_lifetime_indices == other.paramret_lifetime_indices
+    }
+    fn same_function_qualifiers(this: &&'a FunctionSnapshot, other: &&'a FunctionSnapshot) -> bool {
+        this.is_unsafe == other.is_unsafe
+    }
+    fn function_payload(self, func: &'a FunctionSnapshot) -> String {
+        let mut out = String::new();
+        if func.is_unsafe {
+            out.push_str("unsafe ");
+        }
+        out.push_str("fn(");
+        let (ret, params) = func
+            .params_and_return
+            .split_last()
+            .expect("functions always have a return type");
+        for (i, param) in params.iter().enumerate() {
+            if i != 0 {
+                out.push_str(", ");
+            }
+            out.push_str(&param.name);
+            out.push_str(": ");
+            out.push_str(&self.at(param.layout).get().full_type);
+        }
+        out.push_str(")->");
+        out.push_str(&self.at(ret.layout).get().full_type);
+        if !func.paramret_lifetime_indices.is_empty() {
+            out.push_str(&format!(
+                "\nlifetime indices:{:?}",
+                func.paramret_lifetime_indices
+            ));
+        }
+        out
+    }
+    fn function_params_ret(self, func: &'a FunctionSnapshot) -> Self::ParamsRet {
+        func.params_and_return.iter()
+    }
+
+    fn is_exhaustive(enum_: &&'a EnumSnapshot) -> bool {
+        enum_.nonexhaustive.is_none()
+    }
+    fn exhaustiveness(enum_: &&'a EnumSnapshot) -> bool {
+        enum_.nonexhaustive.is_none()
+    }
+    fn check_nonexhaustive(self, enum_: &&'a EnumSnapshot) -> Result<(), String> {
+        match enum_.nonexhaustive {
+            Some(nonexhaustive) => check_nonexhaustive(nonexhaustive, self.get()),
+            None => Ok(()),
+        }
+    }
+    fn variant_field_counts<'e>(enum_: &'e &'a EnumSnapshot) -> &'e [u8] {
+        &enum_.field_counts
+    }
+    fn compare_discriminants(
+        this: &&'a EnumSnapshot,
+        other: &&'a EnumSnapshot,
+    ) -> Result<(), RVec<AbiInstability<AbiSnapshot>>> {
+        let mut errs = RVec::new();
+        if this.discriminant_repr != other.discriminant_repr {
+            push_err(
+                &mut errs,
+                this,
+                other,
+                |x| ReprAttr::Int(x.discriminant_repr),
+                AI::ReprAttr,
+            );
+        } else {
+            for (&t_discr, &o_discr) in this.discriminants.iter().zip(&other.discriminants) {
+                if t_discr != o_discr {
+                    errs.push(AI::EnumDiscriminant(ExpectedFound {
+                        expected: t_discr,
+                        found: o_discr,
+                    }));
+                }
+            }
+        }
+        if errs.is_empty() {
+            Ok(())
+        } else {
+            Err(errs)
+        }
+    }
+    fn variant_names(self, enum_: &'a EnumSnapshot) -> Self::VariantNames {
+        enum_.variant_names.iter().cloned()
+    }
+    fn is_variant_renamed_from(
+        self,
+        enum_: &&'a EnumSnapshot,
+        variant_index: usize,
+        _name: &str,
+        old_name: &str,
+    ) -> bool {
+        enum_
+            .variant_renamed_from
+            .get(variant_index)
+            .map_or(false, |x| x.iter().any(|x| x == old_name))
+    }
+    fn enum_fields(self, enum_: &'a EnumSnapshot) -> Self::Fields {
+        enum_.fields.iter()
+    }
+
+    fn prefix_field_count(prefix: &&'a PrefixTypeSnapshot) -> u8 {
+        prefix.first_suffix_field
+    }
+    fn conditional_prefix_fields(prefix: &&'a PrefixTypeSnapshot) -> FieldConditionality {
+        FieldConditionality::from_u64(prefix.conditional_prefix_fields)
+    }
+    fn accessible_fields(prefix: &&'a PrefixTypeSnapshot) -> FieldAccessibility {
+        FieldAccessibility::from_u64(prefix.accessible_fields)
+    }
+    fn prefix_fields(self, prefix: &'a PrefixTypeSnapshot) -> Self::Fields {
+        prefix.fields.iter()
+    }
+
+    fn check_const_params(
+        _checker: &mut AbiChecker<Self>,
+        errs: &mut RVec<AbiInstability<AbiSnapshot>>,
+        this: Self,
+        other: Self,
+    ) {
+        for (l, r) in this
+            .get()
+            .const_params
+            .iter()
+            .zip(&other.get().const_params)
+        {
+            if l != r {
+                push_err(errs, l, r, String::clone, AI::MismatchedConstParam);
+            }
+        }
+    }
+
+    fn check_extra_checks(
+        checker: &mut AbiChecker<Self>,
+        errs: &mut RVec<AbiInstability<AbiSnapshot>>,
+        _top_level_errs: &mut RVec<AbiInstabilityError<AbiSnapshot>>,
+        this: Self,
+        other: Self,
+    ) {
+        let (t_extra_checks, o_extra_checks) =
+            match (&this.get().extra_checks, &other.get().extra_checks) {
+                (Some(t_extra_checks), Some(o_extra_checks)) => (t_extra_checks, o_extra_checks),
+                _ => return,
+            };
+
+        if t_extra_checks.description != o_extra_checks.description {
+            let description =
+                |x: &ExtraChecksSnapshot| RArc::new(RBoxError::from_fmt(&x.description));
+            errs.push(AI::ExtraCheckError(CmpIgnored::new(ExtraCheckError {
+                err: RArc::new(RBoxError::from_fmt(
+                    &"the extra checks have a different `Display` output",
+                )),
+                expected_err: ExpectedFound::new(t_extra_checks, o_extra_checks, description),
+            })));
+        }
+
+        let nested = t_extra_checks
+            .nested_layouts
+            .iter()
+            .zip(&o_extra_checks.nested_layouts);
+        for (&t_nested, &o_nested) in nested {
+            let _ = checker.check_inner(this.at(t_nested), other.at(o_nested));
+        }
+    }
+}
+
+/// Checks that the storage of a nonexhaustive enum can fit the enum,
+/// like `TLNonExhaustive::check_compatible`.
+fn check_nonexhaustive(ne: NonExhaustiveSnapshot, layout: &TypeSnapshot) -> Result<(), String> {
+    if layout.size < ne.original_size || layout.alignment < ne.original_alignment {
+        Err(format!(
+            "Type '{ty}' has an incompatible layout for the storage.\n\
+             Type    size:{t_size} alignment:{t_align}\n\
+             Storage size:{s_size} alignment:{s_align}",
+            ty = layout.full_type,
+            t_size = ne.original_size,
+            t_align = ne.original_alignment,
+            s_size = layout.size,
+            s_align = layout.alignment,
+        ))
+    } else {
+        Ok(())
+    }
+}
@@ -28,8 +28,10 @@ pub use crate::{
     },
     reflection::ModReflMode,
     sabi_trait::vtable::{GetRObjectVTable, RObjectVtable, RObjectVtable_Ref},
-    sabi_types::{Constructor, MovePtr, RMut, RRef, VersionStrings},
-    std_types::{utypeid::new_utypeid, RErr, RNone, ROk, ROption, RResult, RSlice, RSome},
+    sabi_types::{Constructor, MovePtr, RFuture, RMut, RRef, VersionStrings},
+    std_types::{
+        utypeid::new_utypeid, RErr, RNone, ROk, ROption, RPanicPayload, RResult, RSlice, RSome,
+    },
     type_layout::{
         CompTLFields, CompTLFunction, DiscriminantRepr, FieldAccessor, GenericTLData,
         GenericTLEnum, GenericTLPrefixType, IsExhaustive, LifetimeIndex, MakeTLNonExhaustive,
@@ -18,13 +18,21 @@ allowed/disallowed in semver compatible versions
 
 It is never allowed to remove fields or variants in newer versions of a library.
 
-Types cannot be renamed.
+Types cannot be renamed,or moved to a different crate,
+unless they use the `#[sabi(moved_from(package = "old_package", name = "OldName"))]` attribute,
+which stores the old package and name in the layout of the type,
+so that layout checking accepts both the old and new identities.
 
 A type cannot be replaced with a `#[repr(transparent)]` types wrappig it.
 
 If you rename a field,remember to use the `#[sabi(rename=the_old_name)]` attribute,
 field names are part of the ABI of a type.
 
+Alternatively,fields,variants,and `#[sabi_trait]` methods can be renamed
+with the `#[sabi(renamed_from = "the_old_name")]` attribute,
+which stores the old name in the layout of the type,
+so that layout checking accepts both the old and new names.
+
 
 ### Structs
 
@@ -363,6 +363,15 @@ pub mod globals {
     pub extern "C" fn initialize_globals_with(globs: &'static Globals) {
         GLOBALS.init(|| globs);
     }
+
+    /// Whether the globals were created by this copy of abi_stable,
+    /// rather than passed from the executable with `initialize_globals_with`,
+    /// in which case the layout checker of the globals uses the
+    /// `CheckingGlobals` of this copy of abi_stable.
+    pub(crate) fn are_local_globals() -> bool {
+        let local: extern "C" fn(_, _) -> _ = check_layout_compatibility_for_ffi;
+        initialized_globals().layout_checking as usize == local as usize
+    }
 }
 
 #[cfg(all(test, not(feature = "testing")))]
@@ -13,16 +13,101 @@
 //!     compatible with the loader's, upgrading to a [`&'static LibHeader`] on success.
 //! 4. The [`LibHeader`] checks that the layout of the types in the root module
 //!     (and everything it references) are compatible with the loader's
-//! 5. The [root module](./trait.RootModule.html)
+//! 5. The [`HostModule`]s that the library uses are passed to it,
+//!     after checking that their layout is compatible.
+//! 6. The [root module](./trait.RootModule.html)
 //!     is loaded using the function from the loaded library
 //!     that was annotated with [`#[export_root_module]`](../attr.export_root_module.html).
-//! 6. [`RootModule::initialize`] is called on the root module.
+//! 7. [`RootModule::initialize`] is called on the root module.
 //!
 //! All steps can return errors.
 //!
+//! To do steps 1 to 4 without loading the root module,
+//! use [`RootModule::validate_library`],
+//! which returns a [`LoadReport`] with the outcome of each step.
+//! [`PreflightCheck`] does the same in a child process,
+//! so that a library that crashes while it's being opened can't crash the loader.
+//!
+//! Every step of loading a root module can be observed (eg: for logging or timing)
+//! by setting a [`LoadObserver`] with [`set_load_observer`].
+//!
+//! Layout checking (step 4) can be skipped for libraries that didn't change
+//! since they were last loaded, by setting a [`LayoutCache`] with [`set_layout_cache`].
+//!
+//! # Lifecycle hooks
+//!
+//! [`RootModule`] has optional lifecycle hooks,
+//! [`on_load`] is called when a root module is loaded,
+//! [`on_shutdown`] is called for every loaded root module
+//! (in the reverse order that they were loaded) by [`shutdown_all`],
+//! and [`on_unload`] is called before unloading the library of a [`ReloadableModule`].
+//!
+//! # Dependencies between root modules
+//!
+//! A root module can declare the other root modules that must be loaded before it
+//! in [`RootModule::DEPENDENCIES`], which is stored in its dynamic library.
+//! [`RootModule::load_with_dependencies`] reads those dependencies (transitively)
+//! from the libraries, then loads them from a [`ModuleSet`],
+//! checking their versions before loading any of them.
+//!
+//! # Passing modules from the host to libraries
+//!
+//! Libraries can use modules provided by the host (the executable that loads them),
+//! declared as [`HostModule`]s.
+//! The host provides them with [`provide_host_module`] before loading libraries,
+//! and they're passed to libraries (after checking their layout) right before
+//! the root module is constructed.
+//!
+//! # Finding compatible libraries
+//!
+//! [`scan_directory`] reads the headers of every dynamic library in a directory,
+//! classifying whether each one is compatible with a [`RootModule`],
+//! without loading any root module.
+//!
+//! # Build metadata
+//!
+//! Every library that exports a root module also exports a [`BuildMetadata`],
+//! with its package name and version, the rustc version and target that it was compiled with,
+//! which can be read with [`build_metadata_from_path`] without loading the root module.
+//!
+//! # Loading many libraries with the same root module
+//!
+//! The [`RootModule`]`::load_from*` associated functions load a single root module
+//! per [`RootModule`] type.
+//! To load the same type of root module from many libraries, use a [`ModuleRegistry`].
+//!
+//! # Reloading the root module
+//!
+//! Libraries loaded with the [`RootModule`]`::load_from*` associated functions
+//! are never unloaded.
+//! To unload a library and load it again, use a [`ReloadableModule`] instead,
+//! which refuses to unload the library while leases over its root module are alive.
+//!
 //! [`RawLibrary`]: ./struct.RawLibrary.html
 //! [`AbiHeaderRef`]: ./struct.AbiHeaderRef.html
 //! [`RootModule`]: ./trait.RootModule.html
+//! [`ReloadableModule`]: ./struct.ReloadableModule.html
+//! [`RootModule::validate_library`]: ./trait.RootModule.html#method.validate_library
+//! [`LoadReport`]: ./struct.LoadReport.html
+//! [`PreflightCheck`]: ./struct.PreflightCheck.html
+//! [`RootModule::DEPENDENCIES`]: ./trait.RootModule.html#associatedconstant.DEPENDENCIES
+//! [`RootModule::load_with_dependencies`]:
+//! ./trait.RootModule.html#method.load_with_dependencies
+//! [`ModuleSet`]: ./struct.ModuleSet.html
+//! [`LoadObserver`]: ./trait.LoadObserver.html
+//! [`set_load_observer`]: ./fn.set_load_observer.html
+//! [`LayoutCache`]: ./struct.LayoutCache.html
+//! [`set_layout_cache`]: ./fn.set_layout_cache.html
+//! [`ModuleRegistry`]: ./struct.ModuleRegistry.html
+//! [`HostModule`]: ./trait.HostModule.html
+//! [`on_load`]: ./trait.RootModule.html#method.on_load
+//! [`on_shutdown`]: ./trait.RootModule.html#method.on_shutdown
+//! [`on_unload`]: ./trait.RootModule.html#method.on_unload
+//! [`shutdown_all`]: ./fn.shutdown_all.html
+//! [`provide_host_module`]: ./fn.provide_host_module.html
+//! [`scan_directory`]: ./fn.scan_directory.html
+//! [`BuildMetadata`]: ./struct.BuildMetadata.html
+//! [`build_metadata_from_path`]: ./fn.build_metadata_from_path.html
 //! [`RootModule::initialize`]: ./trait.RootModule.html#method.initialization
 //! [`&'static LibHeader`]: ./struct.LibHeader.html
 
@@ -48,28 +133,63 @@ use crate::{
     type_layout::TypeLayout,
 };
 
+mod build_metadata;
 pub mod c_abi_testing;
+mod catalog;
+mod dependencies;
 pub mod development_utils;
 mod errors;
+mod host_module;
+mod layout_cache;
 mod lib_header;
+mod lifecycle;
+mod load_observer;
+mod load_options;
+mod load_report;
+mod module_registry;
+mod preflight;
 
 #[cfg(test)]
 mod library_tests;
 
 mod raw_library;
+mod reloadable;
 mod root_mod_trait;
+mod version_policy;
 
 #[doc(no_inline)]
 pub use self::c_abi_testing::{CAbiTestingFns, C_ABI_TESTING_FNS};
 
 pub use self::{
-    errors::{IntoRootModuleResult, LibraryError, RootModuleError},
+    build_metadata::{
+        build_metadata_from_path, build_metadata_from_raw_library, BuildMetadata,
+        BuildMetadata_Ref, MetadataEntry, BUILD_METADATA_NAME, BUILD_METADATA_NAME_WITH_NUL,
+    },
+    catalog::{scan_directory, CatalogEntry, LibraryCatalog, LibraryStatus},
+    dependencies::{
+        dependencies_from_path, dependencies_from_raw_library, ModuleDependency, ModuleSet,
+        MODULE_DEPENDENCIES_NAME, MODULE_DEPENDENCIES_NAME_WITH_NUL,
+    },
+    errors::{ExtendedLibraryError, IntoRootModuleResult, LibraryError, RootModuleError},
+    host_module::{
+        provide_host_module, HostModule, HostModuleSlot, HostModuleStatics, HOST_MODULE_SLOTS_NAME,
+        HOST_MODULE_SLOTS_NAME_WITH_NUL,
+    },
+    layout_cache::{remove_layout_cache, set_layout_cache, LayoutCache},
     lib_header::{AbiHeader, AbiHeaderRef, LibHeader},
+    lifecycle::{shutdown_all, LifecycleHook},
+    load_observer::{remove_load_observer, set_load_observer, LoadEvent, LoadObserver, LoadStage},
+    load_options::LoadOptions,
+    load_report::{CheckOutcome, LoadCheck, LoadReport},
+    module_registry::ModuleRegistry,
+    preflight::{run_preflight_child, PreflightCheck},
     raw_library::RawLibrary,
+    reloadable::{ModuleLease, ReloadableModule},
     root_mod_trait::{
         abi_header_from_path, abi_header_from_raw_library, lib_header_from_path,
         lib_header_from_raw_library, RootModule, RootModuleConsts,
     },
+    version_policy::{VersionPolicy, VersionRange, VersionRejection, VersionRule},
 };
 
 ///////////////////////////////////////////////////////////////////////////////
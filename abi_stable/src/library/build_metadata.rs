@@ -0,0 +1,317 @@
+use super::*;
+
+use std::fmt::{self, Debug, Display};
+
+use crate::std_types::{ROption, RSlice};
+
+/// Information about how a dynamic library was built,
+/// exported by every library that uses the [`export_root_module`] attribute.
+///
+/// The package name, package version, and whether debug assertions are enabled
+/// are those of the crate that uses [`export_root_module`],
+/// captured when the attribute is expanded in that crate.
+///
+/// The rustc version and target are the ones that abi_stable was compiled with,
+/// which are the same as those of the library,
+/// because every crate in a library must be compiled by the same rustc, for the same target.
+///
+/// The git hash and the custom entries are provided with the
+/// `#[build_metadata(...)]` helper attribute of [`export_root_module`].
+///
+/// This is read from a library with [`build_metadata_from_path`] or
+/// [`build_metadata_from_raw_library`].
+///
+/// The metadata is exported in its own static, instead of in the [`LibHeader`],
+/// because adding fields to the `LibHeader` would make loaders read past its end
+/// in libraries built with older 0.11 versions of abi_stable.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use abi_stable::library::{build_metadata_from_path, LibraryError};
+///
+/// match build_metadata_from_path("plugins/libfoo.so".as_ref())? {
+///     Some(metadata) => println!("{}", metadata),
+///     None => println!("the library was built with an older version of abi_stable"),
+/// }
+///
+/// # Ok::<(), LibraryError>(())
+/// ```
+///
+/// [`export_root_module`]: ../attr.export_root_module.html
+/// [`build_metadata_from_path`]: ./fn.build_metadata_from_path.html
+/// [`build_metadata_from_raw_library`]: ./fn.build_metadata_from_raw_library.html
+/// [`LibHeader`]: ./struct.LibHeader.html
+#[repr(C)]
+#[derive(Debug, StableAbi)]
+#[sabi(kind(Prefix(prefix_ref = BuildMetadata_Ref)))]
+pub struct BuildMetadata {
+    /// The name of the package that exported the root module.
+    pub package_name: RStr<'static>,
+    /// The version of the package that exported the root module.
+    pub package_version: RStr<'static>,
+    /// Whether the crate that exported the root module was compiled with debug assertions,
+    /// which are enabled by default in the `dev` profile, and disabled in `release`.
+    pub debug_assertions: bool,
+    /// The version of rustc that compiled the library.
+    pub rustc_version: RStr<'static>,
+    /// The target triple that the library was compiled for.
+    pub target: RStr<'static>,
+    /// The git commit hash of the library,
+    /// if it was passed to the `#[build_metadata(git_hash = ...)]` attribute.
+    pub git_hash: ROption<RStr<'static>>,
+    /// Arbitrary key-value pairs,
+    /// passed to the `#[build_metadata(custom(...))]` attribute.
+    #[sabi(last_prefix_field)]
+    pub custom: RSlice<'static, MetadataEntry>,
+}
+
+/// A key-value pair in [`BuildMetadata::custom`].
+///
+/// [`BuildMetadata::custom`]: ./struct.BuildMetadata.html#structfield.custom
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, StableAbi)]
+pub struct MetadataEntry {
+    /// The name of the entry.
+    pub key: RStr<'static>,
+    /// The value of the entry.
+    pub value: RStr<'static>,
+}
+
+impl MetadataEntry {
+    /// Constructs a `MetadataEntry`.
+    pub const fn new(key: &'static str, value: &'static str) -> Self {
+        Self {
+            key: RStr::from_str(key),
+            value: RStr::from_str(value),
+        }
+    }
+}
+
+impl BuildMetadata {
+    /// Constructs a `BuildMetadata`,
+    /// with the rustc version and target that abi_stable was compiled with.
+    ///
+    /// The `package_name`, `package_version`, and `debug_assertions` arguments
+    /// must be those of the crate that exports the root module,
+    /// which [`export_root_module`] gets with
+    /// `env!("CARGO_PKG_NAME")`, `env!("CARGO_PKG_VERSION")`, and `cfg!(debug_assertions)`.
+    ///
+    /// [`export_root_module`]: ../attr.export_root_module.html
+    pub const fn new(
+        package_name: &'static str,
+        package_version: &'static str,
+        debug_assertions: bool,
+        git_hash: Option<&'static str>,
+        custom: &'static [MetadataEntry],
+    ) -> Self {
+        let git_hash = match git_hash {
+            Some(x) => ROption::RSome(RStr::from_str(x)),
+            None => ROption::RNone,
+        };
+        Self::__new(
+            package_name,
+            package_version,
+            debug_assertions,
+            git_hash,
+            custom,
+        )
+    }
+
+    #[doc(hidden)]
+    pub const fn __new(
+        package_name: &'static str,
+        package_version: &'static str,
+        debug_assertions: bool,
+        git_hash: ROption<RStr<'static>>,
+        custom: &'static [MetadataEntry],
+    ) -> Self {
+        Self {
+            package_name: RStr::from_str(package_name),
+            package_version: RStr::from_str(package_version),
+            debug_assertions,
+            rustc_version: RStr::from_str(env!("ABI_STABLE_RUSTC_VERSION")),
+            target: RStr::from_str(env!("ABI_STABLE_TARGET")),
+            git_hash,
+            custom: RSlice::from_slice(custom),
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn __git_hash(git_hash: &'static str) -> ROption<RStr<'static>> {
+        ROption::RSome(RStr::from_str(git_hash))
+    }
+}
+
+impl BuildMetadata_Ref {
+    /// Gets the value of the custom entry with the `key` key.
+    pub fn get_custom(self, key: &str) -> Option<&'static str> {
+        self.custom()
+            .iter()
+            .find(|entry| entry.key.as_str() == key)
+            .map(|entry| entry.value.as_str())
+    }
+}
+
+impl Display for BuildMetadata_Ref {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "package: {} {}",
+            self.package_name(),
+            self.package_version()
+        )?;
+        writeln!(f, "debug assertions: {}", self.debug_assertions())?;
+        writeln!(f, "rustc version: {}", self.rustc_version())?;
+        writeln!(f, "target: {}", self.target())?;
+        if let ROption::RSome(git_hash) = self.git_hash() {
+            writeln!(f, "git hash: {}", git_hash)?;
+        }
+        for entry in self.custom().iter() {
+            writeln!(f, "{}: {}", entry.key, entry.value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for BuildMetadata_Ref {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BuildMetadata")
+            .field("package_name", &self.package_name())
+            .field("package_version", &self.package_version())
+            .field("debug_assertions", &self.debug_assertions())
+            .field("rustc_version", &self.rustc_version())
+            .field("target", &self.target())
+            .field("git_hash", &self.git_hash())
+            .field("custom", &self.custom())
+            .finish()
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// The name of the `static` that contains the [`BuildMetadata`] of an abi_stable library.
+///
+/// [`BuildMetadata`]: ./struct.BuildMetadata.html
+pub const BUILD_METADATA_NAME: &str = PRIV_MANGLED_BUILD_METADATA_NAME;
+
+/// A nul-terminated equivalent of [`BUILD_METADATA_NAME`].
+///
+/// [`BUILD_METADATA_NAME`]: ./constant.BUILD_METADATA_NAME.html
+pub const BUILD_METADATA_NAME_WITH_NUL: &str = PRIV_MANGLED_BUILD_METADATA_NAME_NUL;
+
+/// Gets the [`BuildMetadata`] of a library.
+///
+/// This returns `Ok(None)` if the library was built with a version of abi_stable
+/// that didn't export build metadata.
+///
+/// # Errors
+///
+/// This will return these errors:
+///
+/// - `LibraryError::GetSymbolError`:
+///     If the root module was not exported.
+///
+/// - `LibraryError::InvalidAbiHeader`:
+///     If the abi_stable version used by the library is not compatible.
+///
+/// - `LibraryError::InvalidCAbi`:
+///     If the C abi that the library was compiled with is incompatible.
+///
+/// # Safety
+///
+/// The BuildMetadata_Ref is implicitly tied to the lifetime of the library,
+/// it will contain dangling `'static` references if the library is dropped before it does.
+///
+/// [`BuildMetadata`]: ./struct.BuildMetadata.html
+pub unsafe fn build_metadata_from_raw_library(
+    raw_library: &RawLibrary,
+) -> Result<Option<BuildMetadata_Ref>, LibraryError> {
+    // Ensures that the library uses a compatible version of abi_stable
+    // before reading anything else from it.
+    unsafe { lib_header_from_raw_library(raw_library)? };
+
+    let mangled = BUILD_METADATA_NAME_WITH_NUL;
+    // The symbol is the address of the static, which contains the `BuildMetadata_Ref`.
+    match unsafe { raw_library.get::<&'static BuildMetadata_Ref>(mangled.as_bytes()) } {
+        Ok(metadata) => Ok(Some(**metadata)),
+        Err(LibraryError::GetSymbolError { .. }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Gets the [`BuildMetadata`] of the library at the path.
+///
+/// This leaks the underlying dynamic library,
+/// if you need to do this without leaking you'll need to use
+/// `build_metadata_from_raw_library` instead.
+///
+/// This returns `Ok(None)` if the library was built with a version of abi_stable
+/// that didn't export build metadata.
+///
+/// # Errors
+///
+/// This will return these errors:
+///
+/// - `LibraryError::OpenError`:
+///     If the dynamic library itself could not be loaded.
+///
+/// - `LibraryError::GetSymbolError`:
+///     If the root module was not exported.
+///
+/// - `LibraryError::InvalidAbiHeader`:
+///     If the abi_stable version used by the library is not compatible.
+///
+/// - `LibraryError::InvalidCAbi`:
+///     If the C abi that the library was compiled with is incompatible.
+///
+/// [`BuildMetadata`]: ./struct.BuildMetadata.html
+pub fn build_metadata_from_path(path: &Path) -> Result<Option<BuildMetadata_Ref>, LibraryError> {
+    let raw_lib = RawLibrary::load_at(path)?;
+
+    let metadata = unsafe { build_metadata_from_raw_library(&raw_lib)? };
+
+    mem::forget(raw_lib);
+
+    Ok(metadata)
+}
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests {
+    use super::*;
+
+    use crate::prefix_type::WithMetadata;
+
+    #[test]
+    fn construction() {
+        const CUSTOM: &[MetadataEntry] = &[
+            MetadataEntry::new("feature", "foo"),
+            MetadataEntry::new("built_by", "ci"),
+        ];
+        const METADATA: &WithMetadata<BuildMetadata> = &WithMetadata::new(BuildMetadata::new(
+            "foo",
+            "1.2.3",
+            true,
+            Some("0123abc"),
+            CUSTOM,
+        ));
+        let metadata = BuildMetadata_Ref(METADATA.static_as_prefix());
+
+        assert_eq!(metadata.package_name().as_str(), "foo");
+        assert_eq!(metadata.package_version().as_str(), "1.2.3");
+        assert!(metadata.debug_assertions());
+        assert_eq!(metadata.target().as_str(), env!("ABI_STABLE_TARGET"));
+        assert_eq!(
+            metadata.git_hash(),
+            ROption::RSome(RStr::from_str("0123abc"))
+        );
+        assert_eq!(metadata.custom().as_slice(), CUSTOM);
+        assert_eq!(metadata.get_custom("built_by"), Some("ci"));
+        assert_eq!(metadata.get_custom("missing"), None);
+
+        let display = metadata.to_string();
+        assert!(display.contains("package: foo 1.2.3"), "{}", display);
+        assert!(display.contains("git hash: 0123abc"), "{}", display);
+        assert!(display.contains("feature: foo"), "{}", display);
+    }
+}
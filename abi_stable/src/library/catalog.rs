@@ -0,0 +1,295 @@
+use super::*;
+
+use std::{
+    env::consts::DLL_SUFFIX,
+    fmt::{self, Debug},
+    io,
+};
+
+/// Finds every dynamic library in the `directory` directory (non-recursively),
+/// classifying whether each one is compatible with the `M` root module.
+///
+/// This reads the [`AbiHeader`] and [`LibHeader`] of each library
+/// without calling the function that constructs its root module,
+/// nor checking the layout of the root module.
+///
+/// Like [`lib_header_from_path`], this leaks every library that could be opened.
+///
+/// The entries in the returned catalog are sorted by path.
+///
+/// # Errors
+///
+/// This returns an `io::Error` if the contents of the directory could not be read.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use abi_stable::{
+///     for_examples::Module_Ref,
+///     library::{scan_directory, LibraryStatus},
+/// };
+///
+/// let catalog = scan_directory::<Module_Ref>("plugins".as_ref())?;
+///
+/// for entry in catalog.entries() {
+///     match &entry.status {
+///         LibraryStatus::Compatible(header) => {
+///             println!("{}: version {}", entry.path.display(), header.version_strings());
+///         }
+///         status => {
+///             println!("{}: {}", entry.path.display(), status.error().unwrap());
+///         }
+///     }
+/// }
+///
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`AbiHeader`]: ./struct.AbiHeader.html
+/// [`LibHeader`]: ./struct.LibHeader.html
+/// [`lib_header_from_path`]: ./fn.lib_header_from_path.html
+pub fn scan_directory<M>(directory: &Path) -> io::Result<LibraryCatalog>
+where
+    M: RootModule,
+{
+    let mut entries = Vec::new();
+
+    for dir_entry in std::fs::read_dir(directory)? {
+        let path = dir_entry?.path();
+        if !path.is_file() || !is_dynamic_library(&path) {
+            continue;
+        }
+
+        let status = classify_library::<M>(&path);
+        entries.push(CatalogEntry { path, status });
+    }
+
+    entries.sort_by(|l, r| l.path.cmp(&r.path));
+
+    Ok(LibraryCatalog { entries })
+}
+
+fn is_dynamic_library(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map_or(false, |name| name.ends_with(DLL_SUFFIX))
+}
+
+fn classify_library<M>(path: &Path) -> LibraryStatus
+where
+    M: RootModule,
+{
+    let header = match lib_header_from_path(path) {
+        Ok(header) => header,
+        Err(e @ LibraryError::OpenError { .. }) => return LibraryStatus::OpenError(e),
+        Err(e @ LibraryError::GetSymbolError { .. }) => return LibraryStatus::NotAbiStable(e),
+        Err(e) => return LibraryStatus::IncompatibleAbiStable(e),
+    };
+
+    match header.check_version::<M>() {
+        Ok(()) => LibraryStatus::Compatible(header),
+        Err(e) => LibraryStatus::IncompatibleVersion(e),
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// The dynamic libraries found by [`scan_directory`].
+///
+/// [`scan_directory`]: ./fn.scan_directory.html
+#[derive(Debug)]
+pub struct LibraryCatalog {
+    entries: Vec<CatalogEntry>,
+}
+
+impl LibraryCatalog {
+    /// All the dynamic libraries that were found, sorted by path.
+    pub fn entries(&self) -> &[CatalogEntry] {
+        &self.entries
+    }
+
+    /// Iterates over the libraries that are compatible with the root module.
+    pub fn compatible(&self) -> impl Iterator<Item = &CatalogEntry> + '_ {
+        self.entries.iter().filter(|e| e.status.is_compatible())
+    }
+
+    /// Iterates over the libraries that are not compatible with the root module.
+    pub fn incompatible(&self) -> impl Iterator<Item = &CatalogEntry> + '_ {
+        self.entries.iter().filter(|e| !e.status.is_compatible())
+    }
+
+    /// Converts this into a `Vec` of all the dynamic libraries that were found.
+    pub fn into_entries(self) -> Vec<CatalogEntry> {
+        self.entries
+    }
+}
+
+/// A dynamic library found by [`scan_directory`].
+///
+/// [`scan_directory`]: ./fn.scan_directory.html
+#[derive(Debug)]
+pub struct CatalogEntry {
+    /// The path to the library.
+    pub path: PathBuf,
+    /// Whether the library is compatible with the root module.
+    pub status: LibraryStatus,
+}
+
+/// Whether a dynamic library found by [`scan_directory`]
+/// is compatible with the root module.
+///
+/// [`scan_directory`]: ./fn.scan_directory.html
+pub enum LibraryStatus {
+    /// The abi_stable version and the version number of the library are compatible.
+    ///
+    /// The layout of the root module is only checked when it's loaded.
+    Compatible(&'static LibHeader),
+    /// The library was built with an incompatible version of abi_stable,
+    /// or with an incompatible C abi.
+    ///
+    /// This contains either a `LibraryError::InvalidAbiHeader`
+    /// or a `LibraryError::InvalidCAbi`.
+    IncompatibleAbiStable(LibraryError),
+    /// The version number of the library is incompatible,
+    /// was rejected by the [version policy] of the root module,
+    /// or its version strings couldn't be parsed.
+    ///
+    /// This contains one of:
+    ///
+    /// - `LibraryError::IncompatibleVersionNumber`:
+    ///     If the version isn't compatible according to the [`VersionRule`] of the policy.
+    ///
+    /// - `ExtendedLibraryError::RejectedVersionNumber`:
+    ///     If the version is denied or blocked by the policy.
+    ///
+    /// - `LibraryError::ParseVersionError`:
+    ///     If the version strings couldn't be parsed as version numbers.
+    ///
+    /// [version policy]: ./trait.RootModule.html#method.version_policy
+    /// [`VersionRule`]: ./enum.VersionRule.html
+    IncompatibleVersion(LibraryError),
+    /// The library doesn't export an abi_stable root module.
+    ///
+    /// This contains a `LibraryError::GetSymbolError`.
+    NotAbiStable(LibraryError),
+    /// The library could not be opened.
+    ///
+    /// This contains a `LibraryError::OpenError`.
+    OpenError(LibraryError),
+}
+
+impl LibraryStatus {
+    /// Whether the library is compatible with the root module.
+    pub const fn is_compatible(&self) -> bool {
+        matches!(self, LibraryStatus::Compatible(_))
+    }
+
+    /// Gets the `LibHeader` of the library,
+    /// returning None if it isn't compatible.
+    pub const fn lib_header(&self) -> Option<&'static LibHeader> {
+        match self {
+            LibraryStatus::Compatible(header) => Some(*header),
+            _ => None,
+        }
+    }
+
+    /// Gets the error that makes the library incompatible,
+    /// returning None if it is compatible.
+    pub const fn error(&self) -> Option<&LibraryError> {
+        match self {
+            LibraryStatus::Compatible(_) => None,
+            LibraryStatus::IncompatibleAbiStable(e)
+            | LibraryStatus::IncompatibleVersion(e)
+            | LibraryStatus::NotAbiStable(e)
+            | LibraryStatus::OpenError(e) => Some(e),
+        }
+    }
+}
+
+impl Debug for LibraryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LibraryStatus::Compatible(header) => f
+                .debug_struct("Compatible")
+                .field("version_strings", &header.version_strings())
+                .finish(),
+            LibraryStatus::IncompatibleAbiStable(e) => {
+                f.debug_tuple("IncompatibleAbiStable").field(e).finish()
+            }
+            LibraryStatus::IncompatibleVersion(e) => {
+                f.debug_tuple("IncompatibleVersion").field(e).finish()
+            }
+            LibraryStatus::NotAbiStable(e) => f.debug_tuple("NotAbiStable").field(e).finish(),
+            LibraryStatus::OpenError(e) => f.debug_tuple("OpenError").field(e).finish(),
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests {
+    use super::*;
+
+    use crate::for_examples::Module_Ref;
+
+    #[test]
+    fn dynamic_library_names() {
+        let library = format!("libfoo{}", DLL_SUFFIX);
+        assert!(is_dynamic_library(library.as_ref()));
+        assert!(is_dynamic_library(&Path::new("plugins").join(&library)));
+        assert!(!is_dynamic_library("libfoo.txt".as_ref()));
+        assert!(!is_dynamic_library(format!("{}.txt", library).as_ref()));
+        assert!(!is_dynamic_library("".as_ref()));
+    }
+
+    #[test]
+    fn scanned_directory() {
+        let dir = std::env::temp_dir().join(format!("abi_stable-catalog-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join(format!("directory{}", DLL_SUFFIX))).unwrap();
+        for name in ["b_not_a_library", "a_not_a_library"] {
+            let path = dir.join(format!("{}{}", name, DLL_SUFFIX));
+            std::fs::write(path, b"not a library").unwrap();
+        }
+        std::fs::write(dir.join("notes.txt"), b"not a library either").unwrap();
+
+        let catalog = scan_directory::<Module_Ref>(&dir).unwrap();
+        let names = catalog
+            .entries()
+            .iter()
+            .map(|e| e.path.file_name().unwrap().to_str().unwrap().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            names,
+            [
+                format!("a_not_a_library{}", DLL_SUFFIX),
+                format!("b_not_a_library{}", DLL_SUFFIX),
+            ]
+        );
+
+        for entry in catalog.entries() {
+            let status = &entry.status;
+            assert!(
+                matches!(status, LibraryStatus::OpenError(_)),
+                "{:?}",
+                status
+            );
+            assert!(!status.is_compatible());
+            assert!(status.lib_header().is_none());
+            assert!(
+                matches!(status.error(), Some(LibraryError::OpenError { .. })),
+                "{:?}",
+                status
+            );
+        }
+        assert_eq!(catalog.compatible().count(), 0);
+        assert_eq!(catalog.incompatible().count(), 2);
+        assert_eq!(catalog.into_entries().len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let err = scan_directory::<Module_Ref>(&dir).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,666 @@
+use super::*;
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Display},
+};
+
+use crate::std_types::RSlice;
+
+/// A root module that another root module requires to be loaded first,
+/// declared in [`RootModule::DEPENDENCIES`].
+///
+/// A dependency is identified by the [`RootModule::NAME`] of its root module,
+/// and requires the version of its library to be compatible with
+/// [`version`](#method.version) according to [`rule`](#method.rule).
+///
+/// The dependencies of a root module are stored in its dynamic library
+/// by the [`export_root_module`] attribute,
+/// and can be read with [`dependencies_from_path`] or [`dependencies_from_raw_library`].
+///
+/// # Example
+///
+/// ```rust
+/// use abi_stable::{
+///     library::{ModuleDependency, RootModule, VersionRule},
+///     package_version_strings,
+///     sabi_types::VersionStrings,
+///     StableAbi,
+/// };
+///
+/// #[repr(C)]
+/// #[derive(StableAbi)]
+/// #[sabi(kind(Prefix))]
+/// pub struct CodecRegistry {
+///     #[sabi(last_prefix_field)]
+///     pub codec_count: extern "C" fn() -> u32,
+/// }
+///
+/// impl RootModule for CodecRegistry_Ref {
+///     abi_stable::declare_root_module_statics! {CodecRegistry_Ref}
+///     const BASE_NAME: &'static str = "codec_registry";
+///     const NAME: &'static str = "codec_registry";
+///     const VERSION_STRINGS: VersionStrings = package_version_strings!();
+/// }
+///
+/// #[repr(C)]
+/// #[derive(StableAbi)]
+/// #[sabi(kind(Prefix))]
+/// pub struct Plugin {
+///     #[sabi(last_prefix_field)]
+///     pub run: extern "C" fn(),
+/// }
+///
+/// impl RootModule for Plugin_Ref {
+///     abi_stable::declare_root_module_statics! {Plugin_Ref}
+///     const BASE_NAME: &'static str = "plugin";
+///     const NAME: &'static str = "plugin";
+///     const VERSION_STRINGS: VersionStrings = package_version_strings!();
+///     const DEPENDENCIES: &'static [ModuleDependency] = &[
+///         ModuleDependency::of::<CodecRegistry_Ref>().with_rule(VersionRule::Exact),
+///     ];
+/// }
+///
+/// ```
+///
+/// [`RootModule::DEPENDENCIES`]: ./trait.RootModule.html#associatedconstant.DEPENDENCIES
+/// [`RootModule::NAME`]: ./trait.RootModule.html#associatedconstant.NAME
+/// [`export_root_module`]: ../attr.export_root_module.html
+/// [`dependencies_from_path`]: ./fn.dependencies_from_path.html
+/// [`dependencies_from_raw_library`]: ./fn.dependencies_from_raw_library.html
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, StableAbi)]
+pub struct ModuleDependency {
+    name: RStr<'static>,
+    version: VersionStrings,
+    // A `VersionRule` converted with `rule_to_u8`,
+    // stored as an integer so that libraries can use rules unknown to the loader.
+    rule: u8,
+}
+
+impl ModuleDependency {
+    /// Constructs a dependency on the root module named `name`,
+    /// which requires the library version to be compatible with `version`
+    /// according to `VersionRule::Default`.
+    pub const fn new(name: &'static str, version: VersionStrings) -> Self {
+        Self {
+            name: RStr::from_str(name),
+            version,
+            rule: rule_to_u8(VersionRule::Default),
+        }
+    }
+
+    /// Constructs a dependency on the `M` root module,
+    /// which requires the library version to be compatible with
+    /// `M::VERSION_STRINGS` according to `VersionRule::Default`.
+    pub const fn of<M>() -> Self
+    where
+        M: RootModule,
+    {
+        Self::new(M::NAME, M::VERSION_STRINGS)
+    }
+
+    /// Sets the rule used to check that the library version is compatible.
+    pub const fn with_rule(mut self, rule: VersionRule) -> Self {
+        self.rule = rule_to_u8(rule);
+        self
+    }
+
+    /// The [`RootModule::NAME`] of the dependency.
+    ///
+    /// [`RootModule::NAME`]: ./trait.RootModule.html#associatedconstant.NAME
+    pub fn name(&self) -> &'static str {
+        self.name.as_str()
+    }
+
+    /// The version that the library version must be compatible with.
+    pub const fn version(&self) -> VersionStrings {
+        self.version
+    }
+
+    /// The rule used to check that the library version is compatible.
+    ///
+    /// This returns `VersionRule::OnlyAllowed`,
+    /// which doesn't accept any version,
+    /// if the dependency was declared by a library with a rule
+    /// that this version of abi_stable doesn't know about.
+    pub const fn rule(&self) -> VersionRule {
+        rule_from_u8(self.rule)
+    }
+
+    /// Checks that the `actual` version of the library is compatible with this dependency.
+    fn check_version(
+        &self,
+        dependent: &'static str,
+        actual: VersionNumber,
+    ) -> Result<(), LibraryError> {
+        let expected = VersionNumber::new(self.version)?;
+        if self.rule().is_compatible(expected, actual) {
+            Ok(())
+        } else {
+            Err(ExtendedLibraryError::IncompatibleDependency {
+                module_name: dependent,
+                dependency: *self,
+                actual_version: actual,
+            }
+            .into())
+        }
+    }
+}
+
+const fn rule_to_u8(rule: VersionRule) -> u8 {
+    match rule {
+        VersionRule::Default => 0,
+        VersionRule::Exact => 1,
+        VersionRule::NewerMinor => 2,
+        VersionRule::AllowOlderMinor => 3,
+        VersionRule::OnlyAllowed => 4,
+    }
+}
+
+const fn rule_from_u8(rule: u8) -> VersionRule {
+    match rule {
+        0 => VersionRule::Default,
+        1 => VersionRule::Exact,
+        2 => VersionRule::NewerMinor,
+        3 => VersionRule::AllowOlderMinor,
+        _ => VersionRule::OnlyAllowed,
+    }
+}
+
+impl Debug for ModuleDependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModuleDependency")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .field("rule", &self.rule())
+            .finish()
+    }
+}
+
+impl Display for ModuleDependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' compatible with {} ({:?} rule)",
+            self.name,
+            self.version,
+            self.rule()
+        )
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// The root modules that [`RootModule::load_with_dependencies`]
+/// can load as dependencies, keyed by their [`RootModule::NAME`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use abi_stable::{
+///     for_examples::Module_Ref,
+///     library::{LibraryError, ModuleSet, RootModule},
+/// };
+///
+/// # type CodecRegistry_Ref = Module_Ref;
+/// let modules = ModuleSet::new().with::<CodecRegistry_Ref>();
+///
+/// // Loads the dependencies of `Module_Ref` (from `Module_Ref::DEPENDENCIES`)
+/// // before loading it.
+/// let module = Module_Ref::load_with_dependencies("plugins".as_ref(), &modules)?;
+///
+/// # Ok::<(), LibraryError>(())
+/// ```
+///
+/// [`RootModule::load_with_dependencies`]:
+/// ./trait.RootModule.html#method.load_with_dependencies
+/// [`RootModule::NAME`]: ./trait.RootModule.html#associatedconstant.NAME
+#[derive(Clone, Default)]
+pub struct ModuleSet {
+    modules: HashMap<&'static str, ModuleEntry>,
+}
+
+impl ModuleSet {
+    /// Constructs an empty `ModuleSet`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the `M` root module to this set, returning it.
+    pub fn with<M>(mut self) -> Self
+    where
+        M: RootModule,
+    {
+        self.add::<M>();
+        self
+    }
+
+    /// Adds the `M` root module to this set,
+    /// replacing any root module with the same `NAME`.
+    pub fn add<M>(&mut self) -> &mut Self
+    where
+        M: RootModule,
+    {
+        self.modules.insert(M::NAME, ModuleEntry::of::<M>());
+        self
+    }
+
+    /// Whether a root module with the `name` [`RootModule::NAME`] is in this set.
+    ///
+    /// [`RootModule::NAME`]: ./trait.RootModule.html#associatedconstant.NAME
+    pub fn contains(&self, name: &str) -> bool {
+        self.modules.contains_key(name)
+    }
+}
+
+impl Debug for ModuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.modules.keys()).finish()
+    }
+}
+
+/// The type-erased operations on a root module,
+/// used to load it as a dependency.
+#[derive(Copy, Clone)]
+struct ModuleEntry {
+    name: &'static str,
+    read_library: fn(&Path) -> Result<LibraryInfo, LibraryError>,
+    load: fn(&Path) -> Result<(), LibraryError>,
+}
+
+impl ModuleEntry {
+    fn of<M>() -> Self
+    where
+        M: RootModule,
+    {
+        Self {
+            name: M::NAME,
+            read_library: read_library::<M>,
+            load: |directory| M::load_from_directory(directory).map(drop),
+        }
+    }
+}
+
+/// What `load_with_dependencies` reads from the library of a root module
+/// before loading any root module.
+#[derive(Copy, Clone)]
+struct LibraryInfo {
+    version: VersionNumber,
+    dependencies: &'static [ModuleDependency],
+}
+
+/// Reads the version and dependencies of the library of `M`,
+/// checking that the version is compatible with `M`.
+///
+/// This opens the library without constructing its root module,
+/// using the already loaded library of `M` if there is one.
+fn read_library<M>(directory: &Path) -> Result<LibraryInfo, LibraryError>
+where
+    M: RootModule,
+{
+    match M::get_raw_library() {
+        // safety: libraries loaded into the statics of `M` are never unloaded.
+        Some(raw_library) => unsafe { read_raw_library::<M>(raw_library) },
+        None => {
+            let raw_library = RawLibrary::load_at(&M::get_library_path(directory))?;
+            // safety: the library is leaked right after this.
+            let info = unsafe { read_raw_library::<M>(&raw_library) };
+            mem::forget(raw_library);
+            info
+        }
+    }
+}
+
+/// Reads the version and dependencies of `raw_library`,
+/// checking that the version is compatible with `M`.
+///
+/// # Safety
+///
+/// The library must not be unloaded while the returned dependencies are used.
+unsafe fn read_raw_library<M>(raw_library: &RawLibrary) -> Result<LibraryInfo, LibraryError>
+where
+    M: RootModule,
+{
+    let header = unsafe { lib_header_from_raw_library(raw_library)? };
+    header.check_version::<M>()?;
+    Ok(LibraryInfo {
+        version: VersionNumber::new(header.version_strings())?,
+        dependencies: unsafe { dependencies_from_raw_library(raw_library)? },
+    })
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// The name of the `static` that contains the [`ModuleDependency`]s
+/// of the root module of an abi_stable library.
+///
+/// [`ModuleDependency`]: ./struct.ModuleDependency.html
+pub const MODULE_DEPENDENCIES_NAME: &str = PRIV_MANGLED_MODULE_DEPENDENCIES_NAME;
+
+/// A nul-terminated equivalent of [`MODULE_DEPENDENCIES_NAME`].
+///
+/// [`MODULE_DEPENDENCIES_NAME`]: ./constant.MODULE_DEPENDENCIES_NAME.html
+pub const MODULE_DEPENDENCIES_NAME_WITH_NUL: &str = PRIV_MANGLED_MODULE_DEPENDENCIES_NAME_NUL;
+
+/// Gets the dependencies of the root module of a library,
+/// which [`export_root_module`] stores from [`RootModule::DEPENDENCIES`].
+///
+/// This doesn't construct the root module.
+///
+/// The dependencies are exported in their own static, instead of in the [`LibHeader`],
+/// because adding fields to the `LibHeader` would make loaders read past its end
+/// in libraries built with older 0.11 versions of abi_stable.
+/// Libraries built with those versions have no dependencies.
+///
+/// # Errors
+///
+/// This will return these errors:
+///
+/// - `LibraryError::GetSymbolError`:
+///     If the root module was not exported.
+///
+/// - `LibraryError::InvalidAbiHeader`:
+///     If the abi_stable version used by the library is not compatible.
+///
+/// - `LibraryError::InvalidCAbi`:
+///     If the C abi that the library was compiled with is incompatible.
+///
+/// # Safety
+///
+/// The returned slice is implicitly tied to the lifetime of the library,
+/// it will contain dangling `'static` references if the library is dropped before it does.
+///
+/// [`export_root_module`]: ../attr.export_root_module.html
+/// [`RootModule::DEPENDENCIES`]: ./trait.RootModule.html#associatedconstant.DEPENDENCIES
+/// [`LibHeader`]: ./struct.LibHeader.html
+pub unsafe fn dependencies_from_raw_library(
+    raw_library: &RawLibrary,
+) -> Result<&'static [ModuleDependency], LibraryError> {
+    // Ensures that the library uses a compatible version of abi_stable
+    // before reading anything else from it.
+    unsafe { lib_header_from_raw_library(raw_library)? };
+
+    let mangled = MODULE_DEPENDENCIES_NAME_WITH_NUL;
+    // The symbol is the address of the static, which contains the `RSlice`.
+    match unsafe {
+        raw_library.get::<&'static RSlice<'static, ModuleDependency>>(mangled.as_bytes())
+    } {
+        Ok(dependencies) => Ok(dependencies.as_slice()),
+        Err(LibraryError::GetSymbolError { .. }) => Ok(&[]),
+        Err(e) => Err(e),
+    }
+}
+
+/// Gets the dependencies of the root module of the library at the path,
+/// which [`export_root_module`] stores from [`RootModule::DEPENDENCIES`].
+///
+/// This doesn't construct the root module.
+///
+/// This leaks the underlying dynamic library,
+/// if you need to do this without leaking you'll need to use
+/// `dependencies_from_raw_library` instead.
+///
+/// # Errors
+///
+/// This will return these errors:
+///
+/// - `LibraryError::OpenError`:
+///     If the dynamic library itself could not be loaded.
+///
+/// - `LibraryError::GetSymbolError`:
+///     If the root module was not exported.
+///
+/// - `LibraryError::InvalidAbiHeader`:
+///     If the abi_stable version used by the library is not compatible.
+///
+/// - `LibraryError::InvalidCAbi`:
+///     If the C abi that the library was compiled with is incompatible.
+///
+/// [`export_root_module`]: ../attr.export_root_module.html
+/// [`RootModule::DEPENDENCIES`]: ./trait.RootModule.html#associatedconstant.DEPENDENCIES
+pub fn dependencies_from_path(path: &Path) -> Result<&'static [ModuleDependency], LibraryError> {
+    let raw_lib = RawLibrary::load_at(path)?;
+
+    let dependencies = unsafe { dependencies_from_raw_library(&raw_lib)? };
+
+    mem::forget(raw_lib);
+
+    Ok(dependencies)
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// Implementation of `RootModule::load_with_dependencies`.
+pub(super) fn load_with_dependencies<M>(
+    directory: &Path,
+    modules: &ModuleSet,
+) -> Result<M, LibraryError>
+where
+    M: RootModule,
+{
+    let order = resolve(ModuleEntry::of::<M>(), directory, modules)?;
+
+    // Checking that the versions of all the libraries are compatible
+    // before loading any of them.
+    let versions = order
+        .iter()
+        .map(|(entry, info)| (entry.name, info.version))
+        .collect::<HashMap<_, _>>();
+    for (entry, info) in &order {
+        for dependency in info.dependencies {
+            dependency.check_version(entry.name, versions[dependency.name()])?;
+        }
+    }
+
+    // The root module is last in the order.
+    let (_, dependencies) = order.split_last().expect("BUG: M is always in the order");
+    for (entry, _) in dependencies {
+        (entry.load)(directory)?;
+    }
+
+    M::load_from_directory(directory)
+}
+
+/// Gets the root module of `root` and all its transitive dependencies,
+/// ordered so that every module comes after its dependencies,
+/// reading the dependencies of each module from its library.
+fn resolve(
+    root: ModuleEntry,
+    directory: &Path,
+    modules: &ModuleSet,
+) -> Result<Vec<(ModuleEntry, LibraryInfo)>, LibraryError> {
+    let mut resolver = Resolver {
+        directory,
+        modules,
+        state: HashMap::new(),
+        stack: Vec::new(),
+        order: Vec::new(),
+    };
+    resolver.visit(root)?;
+    Ok(resolver.order)
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum VisitState {
+    InProgress,
+    Done,
+}
+
+struct Resolver<'a> {
+    directory: &'a Path,
+    modules: &'a ModuleSet,
+    state: HashMap<&'static str, VisitState>,
+    // The modules that are currently being visited, used to report cycles.
+    stack: Vec<&'static str>,
+    order: Vec<(ModuleEntry, LibraryInfo)>,
+}
+
+impl Resolver<'_> {
+    fn visit(&mut self, entry: ModuleEntry) -> Result<(), LibraryError> {
+        match self.state.get(entry.name) {
+            Some(VisitState::Done) => return Ok(()),
+            Some(VisitState::InProgress) => {
+                let start = self.stack.iter().position(|&x| x == entry.name).unwrap();
+                let mut cycle = self.stack[start..].to_vec();
+                cycle.push(entry.name);
+                return Err(ExtendedLibraryError::DependencyCycle { cycle }.into());
+            }
+            None => {}
+        }
+
+        self.state.insert(entry.name, VisitState::InProgress);
+        self.stack.push(entry.name);
+
+        let info = (entry.read_library)(self.directory)?;
+
+        for dependency in info.dependencies {
+            let dep_entry = match self.modules.modules.get(dependency.name()) {
+                Some(x) => *x,
+                None => {
+                    return Err(ExtendedLibraryError::MissingDependency {
+                        module_name: entry.name,
+                        dependency: *dependency,
+                    }
+                    .into())
+                }
+            };
+            self.visit(dep_entry)?;
+        }
+
+        self.stack.pop();
+        self.state.insert(entry.name, VisitState::Done);
+        self.order.push((entry, info));
+        Ok(())
+    }
+}
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests {
+    use super::*;
+
+    use crate::sabi_types::VersionStrings;
+
+    type ReadLibrary = fn(&Path) -> Result<LibraryInfo, LibraryError>;
+
+    fn entry(name: &'static str, read_library: ReadLibrary) -> ModuleEntry {
+        ModuleEntry {
+            name,
+            read_library,
+            load: |_| unreachable!(),
+        }
+    }
+
+    fn deps(dependencies: &'static [ModuleDependency]) -> Result<LibraryInfo, LibraryError> {
+        Ok(LibraryInfo {
+            version: VersionNumber::new(V).unwrap(),
+            dependencies,
+        })
+    }
+
+    fn resolve(
+        root: ModuleEntry,
+        modules: &ModuleSet,
+    ) -> Result<Vec<(ModuleEntry, LibraryInfo)>, LibraryError> {
+        super::resolve(root, "".as_ref(), modules)
+    }
+
+    const V: VersionStrings = VersionStrings::new("1.0.0");
+
+    fn module_set(entries: &[ModuleEntry]) -> ModuleSet {
+        ModuleSet {
+            modules: entries.iter().map(|e| (e.name, *e)).collect(),
+        }
+    }
+
+    fn names(order: &[(ModuleEntry, LibraryInfo)]) -> Vec<&'static str> {
+        order.iter().map(|(e, _)| e.name).collect()
+    }
+
+    #[test]
+    fn topological_order() {
+        const ROOT_DEPS: &[ModuleDependency] =
+            &[ModuleDependency::new("b", V), ModuleDependency::new("c", V)];
+        const B_DEPS: &[ModuleDependency] = &[ModuleDependency::new("d", V)];
+        const C_DEPS: &[ModuleDependency] =
+            &[ModuleDependency::new("d", V), ModuleDependency::new("b", V)];
+
+        let set = module_set(&[
+            entry("b", |_| deps(B_DEPS)),
+            entry("c", |_| deps(C_DEPS)),
+            entry("d", |_| deps(&[])),
+        ]);
+
+        let order = resolve(entry("root", |_| deps(ROOT_DEPS)), &set).unwrap();
+        assert_eq!(names(&order), ["d", "b", "c", "root"]);
+
+        let order = resolve(entry("root", |_| deps(&[])), &set).unwrap();
+        assert_eq!(names(&order), ["root"]);
+    }
+
+    #[test]
+    fn missing_dependency() {
+        const ROOT_DEPS: &[ModuleDependency] = &[ModuleDependency::new("b", V)];
+        const B_DEPS: &[ModuleDependency] = &[ModuleDependency::new("c", V)];
+
+        let set = module_set(&[entry("b", |_| deps(B_DEPS))]);
+
+        match resolve(entry("root", |_| deps(ROOT_DEPS)), &set) {
+            Err(LibraryError::Extended(ExtendedLibraryError::MissingDependency {
+                module_name,
+                dependency,
+            })) => {
+                assert_eq!(module_name, "b");
+                assert_eq!(dependency.name(), "c");
+            }
+            x => panic!("{:?}", x.map(|o| names(&o))),
+        }
+    }
+
+    #[test]
+    fn dependency_cycle() {
+        const ROOT_DEPS: &[ModuleDependency] = &[ModuleDependency::new("b", V)];
+        const B_DEPS: &[ModuleDependency] = &[ModuleDependency::new("c", V)];
+        const C_DEPS: &[ModuleDependency] = &[ModuleDependency::new("b", V)];
+
+        let set = module_set(&[entry("b", |_| deps(B_DEPS)), entry("c", |_| deps(C_DEPS))]);
+
+        match resolve(entry("root", |_| deps(ROOT_DEPS)), &set) {
+            Err(LibraryError::Extended(ExtendedLibraryError::DependencyCycle { cycle })) => {
+                assert_eq!(cycle, ["b", "c", "b"]);
+            }
+            x => panic!("{:?}", x.map(|o| names(&o))),
+        }
+    }
+
+    #[test]
+    fn dependency_version() {
+        const fn version(major: u32, minor: u32, patch: u32) -> VersionNumber {
+            VersionNumber {
+                major,
+                minor,
+                patch,
+            }
+        }
+
+        let dep = ModuleDependency::new("b", VersionStrings::new("1.2.0"));
+        assert!(dep.check_version("root", version(1, 2, 0)).is_ok());
+        assert!(dep.check_version("root", version(1, 3, 0)).is_ok());
+        assert!(dep.check_version("root", version(2, 0, 0)).is_err());
+
+        let dep = dep.with_rule(VersionRule::Exact);
+        assert!(dep.check_version("root", version(1, 2, 0)).is_ok());
+        match dep.check_version("root", version(1, 3, 0)) {
+            Err(LibraryError::Extended(ExtendedLibraryError::IncompatibleDependency {
+                module_name,
+                dependency,
+                actual_version,
+            })) => {
+                assert_eq!(module_name, "root");
+                assert_eq!(dependency, dep);
+                assert_eq!(actual_version, version(1, 3, 0));
+            }
+            x => panic!("{:?}", x),
+        }
+    }
+}
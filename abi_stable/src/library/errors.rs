@@ -1,15 +1,22 @@
 #![allow(clippy::missing_const_for_fn)]
 
-use super::{lib_header::AbiHeader, root_mod_trait::RootModule};
+use super::{
+    dependencies::ModuleDependency, lib_header::AbiHeader, lifecycle::LifecycleHook,
+    load_options::LoadOptions, load_report::LoadCheck, root_mod_trait::RootModule,
+    version_policy::VersionRejection,
+};
 
 use crate::{
+    abi_stability::abi_checking::{AbiInstabilityErrors, AbiMismatchReport},
     sabi_types::{ParseVersionError, VersionNumber, VersionStrings},
     std_types::{RBoxError, RResult, RVec},
 };
 
 use std::{
     fmt::{self, Display},
+    io,
     path::PathBuf,
+    process::ExitStatus,
 };
 
 #[allow(unused_imports)]
@@ -62,10 +69,51 @@ pub enum LibraryError {
         expected: RBoxError,
         found: RBoxError,
     },
+    /// The errors that were added after the 0.11.0 release.
+    Extended(ExtendedLibraryError),
     /// There could have been 0 or more errors in the function.
     Many(RVec<Self>),
 }
 
+impl LibraryError {
+    /// Constructs a `LibraryError::AbiInstability` from an error returned by
+    /// the layout checker.
+    ///
+    /// The error is formatted so that it doesn't reference the library,
+    /// and has an [`AbiMismatchReport`] attached if it's an `AbiInstabilityErrors`.
+    pub(crate) fn abi_instability(err: &RBoxError) -> Self {
+        let formatted = err.to_formatted_error();
+        match err.downcast_ref::<AbiInstabilityErrors>() {
+            Some(errors) => LibraryError::AbiInstability(RBoxError::new(ReportedAbiInstability {
+                formatted,
+                report: errors.report(),
+            })),
+            None => LibraryError::AbiInstability(formatted),
+        }
+    }
+
+    /// Gets a structured report of the layout checking errors,
+    /// if this is a `LibraryError::AbiInstability`.
+    ///
+    /// This returns `None` for any other variant,
+    /// and if the layout checker that produced the error is from
+    /// another copy of `abi_stable`.
+    pub fn abi_mismatch_report(&self) -> Option<&AbiMismatchReport> {
+        match self {
+            LibraryError::AbiInstability(err) => err
+                .downcast_ref::<ReportedAbiInstability>()
+                .map(|x| &x.report),
+            _ => None,
+        }
+    }
+}
+
+impl From<ExtendedLibraryError> for LibraryError {
+    fn from(v: ExtendedLibraryError) -> LibraryError {
+        LibraryError::Extended(v)
+    }
+}
+
 impl From<ParseVersionError> for LibraryError {
     fn from(v: ParseVersionError) -> LibraryError {
         LibraryError::ParseVersionError(v)
@@ -137,6 +185,7 @@ impl Display for LibraryError {
                     expected=expected,
                 }
             }
+            LibraryError::Extended(x) => fmt::Display::fmt(x, f),
             LibraryError::Many(list) => {
                 for e in list {
                     Display::fmt(e, f)?;
@@ -153,6 +202,280 @@ impl ::std::error::Error for LibraryError {}
 
 //////////////////////////////////////////////////////////////////////
 
+/// The errors that can happen when loading a library or a module,
+/// that were added after the 0.11.0 release.
+///
+/// This is stored in `LibraryError::Extended`,
+/// and is `#[non_exhaustive]` so that errors can be added without
+/// breaking exhaustive matches on `LibraryError`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ExtendedLibraryError {
+    /// When a library image loaded from memory can't be written to an in-memory file,
+    /// or loading libraries from memory isn't supported on the platform.
+    InMemoryLibraryError {
+        /// The synthetic path of the library
+        path: PathBuf,
+        /// The cause of the error
+        err: io::Error,
+    },
+    /// When a library is loaded with [`LoadOptions`] that aren't supported on the platform.
+    ///
+    /// [`LoadOptions`]: ./struct.LoadOptions.html
+    UnsupportedLoadOptions {
+        /// The path to the library
+        path: PathBuf,
+        /// The options that the library was loaded with.
+        options: LoadOptions,
+    },
+    /// The version number of the library was rejected by the [`VersionPolicy`]
+    /// of the root module, because it's denied or in the blocklist.
+    ///
+    /// [`VersionPolicy`]: ./struct.VersionPolicy.html
+    RejectedVersionNumber {
+        library_name: &'static str,
+        expected_version: VersionNumber,
+        actual_version: VersionNumber,
+        /// Why the version number was rejected.
+        reason: VersionRejection,
+    },
+    /// When a [`ReloadableModule`] can't unload a library,
+    /// because [`ModuleLease`]s over its root module are still alive.
+    ///
+    /// [`ReloadableModule`]: ./struct.ReloadableModule.html
+    /// [`ModuleLease`]: ./struct.ModuleLease.html
+    UnloadBlocked {
+        /// The path to the library
+        library: PathBuf,
+        /// The amount of `ModuleLease`s that are still alive.
+        leases: usize,
+    },
+    /// When a dependency of a root module is not in the [`ModuleSet`]
+    /// passed to [`RootModule::load_with_dependencies`].
+    ///
+    /// [`ModuleSet`]: ./struct.ModuleSet.html
+    /// [`RootModule::load_with_dependencies`]:
+    /// ./trait.RootModule.html#method.load_with_dependencies
+    MissingDependency {
+        /// The name of the root module that declared the dependency.
+        module_name: &'static str,
+        /// The dependency that is missing.
+        dependency: ModuleDependency,
+    },
+    /// When the version of the library of a dependency is not compatible
+    /// with the version required by a root module.
+    IncompatibleDependency {
+        /// The name of the root module that declared the dependency.
+        module_name: &'static str,
+        /// The dependency with the incompatible version.
+        dependency: ModuleDependency,
+        /// The version of the library of the dependency.
+        actual_version: VersionNumber,
+    },
+    /// When root modules depend on each other in a cycle.
+    DependencyCycle {
+        /// The names of the root modules in the cycle,
+        /// where the first and last names are the same.
+        cycle: Vec<&'static str>,
+    },
+    /// When a library uses a [`HostModule`] that wasn't provided by the host
+    /// with [`provide_host_module`].
+    ///
+    /// [`HostModule`]: ./trait.HostModule.html
+    /// [`provide_host_module`]: ./fn.provide_host_module.html
+    MissingHostModule {
+        /// The path to the library
+        library: PathBuf,
+        /// The name of the host module (from [`HostModule::NAME`]).
+        ///
+        /// [`HostModule::NAME`]: ./trait.HostModule.html#associatedconstant.NAME
+        module_name: String,
+    },
+    /// When a lifecycle hook of a root module returned an error or panicked.
+    HookFailed {
+        /// The name of the root module (from [`RootModule::NAME`]).
+        ///
+        /// [`RootModule::NAME`]: ./trait.RootModule.html#associatedconstant.NAME
+        module_name: &'static str,
+        /// The hook that failed.
+        hook: LifecycleHook,
+        /// The error returned by the hook, or `RootModuleError::Unwound` if it panicked.
+        err: RootModuleError,
+    },
+    /// When the child process of a [`PreflightCheck`] couldn't be spawned,
+    /// or it exited without doing the checks.
+    ///
+    /// [`PreflightCheck`]: ./struct.PreflightCheck.html
+    PreflightError {
+        /// The path to the library
+        library: PathBuf,
+        /// The cause of the error
+        err: io::Error,
+    },
+    /// When the child process of a [`PreflightCheck`] crashed,
+    /// or was killed for timing out, while doing a check.
+    ///
+    /// [`PreflightCheck`]: ./struct.PreflightCheck.html
+    PreflightCrashed {
+        /// The path to the library
+        library: PathBuf,
+        /// The check that the child process was doing.
+        check: LoadCheck,
+        /// The exit status of the child process,
+        /// None if it was killed for timing out.
+        status: Option<ExitStatus>,
+        /// What the child process wrote to stderr.
+        stderr: String,
+    },
+    /// The error of a check that failed in the child process of a [`PreflightCheck`],
+    /// formatted as a string, since errors can't be passed between processes.
+    ///
+    /// [`PreflightCheck`]: ./struct.PreflightCheck.html
+    PreflightCheckFailed {
+        /// The error formatted with `Display`.
+        message: String,
+    },
+}
+
+impl Display for ExtendedLibraryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InMemoryLibraryError { path, err } => writeln!(
+                f,
+                "Could not create in-memory library:\n\t{}\nbecause:\n\t{}",
+                path.display(),
+                err
+            ),
+            Self::UnsupportedLoadOptions { path, options } => writeln!(
+                f,
+                "Could not open library at:\n\t{}\n\
+                 because these load options aren't supported on this platform:\n\t{:?}",
+                path.display(),
+                options,
+            ),
+            Self::RejectedVersionNumber {
+                library_name,
+                expected_version,
+                actual_version,
+                reason,
+            } => writeln!(
+                f,
+                "\n'{}' library version rejected:\nuser:{}\nlibrary:{}\nthe library version is {}",
+                library_name, expected_version, actual_version, reason,
+            ),
+            Self::UnloadBlocked { library, leases } => writeln!(
+                f,
+                "Could not unload library at:\n\t{}\n\
+                 because {} lease(s) over its root module are still alive.",
+                library.display(),
+                leases,
+            ),
+            Self::MissingDependency {
+                module_name,
+                dependency,
+            } => writeln!(
+                f,
+                "'{}' depends on a root module that isn't available:\n\t{}",
+                module_name, dependency,
+            ),
+            Self::IncompatibleDependency {
+                module_name,
+                dependency,
+                actual_version,
+            } => writeln!(
+                f,
+                "'{}' depends on:\n\t{}\nbut the library version is:\n\t{}",
+                module_name, dependency, actual_version,
+            ),
+            Self::DependencyCycle { cycle } => {
+                writeln!(f, "These root modules depend on each other in a cycle:")?;
+                writeln!(f, "\t{}", cycle.join(" -> "))
+            }
+            Self::MissingHostModule {
+                library,
+                module_name,
+            } => writeln!(
+                f,
+                "The library at:\n\t{}\nuses the '{}' host module, \
+                 which wasn't provided by the host",
+                library.display(),
+                module_name,
+            ),
+            Self::HookFailed {
+                module_name,
+                hook,
+                err,
+            } => {
+                writeln!(
+                    f,
+                    "The `{}` lifecycle hook of '{}' failed:",
+                    hook, module_name
+                )?;
+                match err {
+                    RootModuleError::Returned(e) => fmt::Display::fmt(e, f),
+                    RootModuleError::Unwound => f.write_str("the hook panicked"),
+                }
+            }
+            Self::PreflightError { library, err } => writeln!(
+                f,
+                "Could not check the library at:\n\t{}\nin a child process, because:\n\t{}",
+                library.display(),
+                err
+            ),
+            Self::PreflightCrashed {
+                library,
+                check,
+                status,
+                stderr,
+            } => {
+                writeln!(
+                    f,
+                    "The child process checking the library at:\n\t{}\n\
+                     crashed during the '{}' check",
+                    library.display(),
+                    check,
+                )?;
+                match status {
+                    Some(status) => writeln!(f, "exit status: {}", status)?,
+                    None => writeln!(f, "it was killed for timing out")?,
+                }
+                if !stderr.is_empty() {
+                    writeln!(f, "stderr:\n{}", stderr)?;
+                }
+                Ok(())
+            }
+            Self::PreflightCheckFailed { message } => writeln!(f, "{}", message),
+        }
+    }
+}
+
+impl ::std::error::Error for ExtendedLibraryError {}
+
+//////////////////////////////////////////////////////////////////////
+
+/// The error stored in `LibraryError::AbiInstability`,
+/// which displays the same as the formatted `AbiInstabilityErrors`.
+struct ReportedAbiInstability {
+    formatted: RBoxError,
+    report: AbiMismatchReport,
+}
+
+impl fmt::Debug for ReportedAbiInstability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.formatted, f)
+    }
+}
+
+impl Display for ReportedAbiInstability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.formatted, f)
+    }
+}
+
+impl ::std::error::Error for ReportedAbiInstability {}
+
+//////////////////////////////////////////////////////////////////////
+
 /// The errors that a `#[export_root_module]` function can return.
 #[repr(C, u8)]
 #[derive(Debug, StableAbi)]
@@ -241,9 +564,11 @@ mod tests {
     use super::*;
 
     use crate::{
+        abi_stability::abi_checking::{check_layout_compatibility_with_globals, CheckingGlobals},
         for_examples::{Module, Module_Ref},
         prefix_type::WithMetadata,
         std_types::{RBox, RErr, ROk, RSome},
+        StableAbi,
     };
 
     use std::fmt::Error as FmtError;
@@ -302,4 +627,27 @@ mod tests {
             test_case(ok.into_root_module_result(), err.into_root_module_result());
         }
     }
+
+    #[test]
+    fn abi_instability_report() {
+        let globals = CheckingGlobals::new();
+        let errors = check_layout_compatibility_with_globals(
+            <u32 as StableAbi>::LAYOUT,
+            <u64 as StableAbi>::LAYOUT,
+            &globals,
+        )
+        .unwrap_err();
+        let displayed = errors.to_string();
+
+        let err = LibraryError::abi_instability(&RBoxError::new(errors));
+        assert!(err.to_string().contains(&displayed), "{}", err);
+
+        let report = err.abi_mismatch_report().unwrap();
+        assert_eq!(report.interface.type_name, "u32");
+        assert_eq!(report.implementation.type_name, "u64");
+
+        let other = LibraryError::abi_instability(&RBoxError::from_fmt(&displayed));
+        assert!(other.to_string().contains(&displayed), "{}", other);
+        assert!(other.abi_mismatch_report().is_none());
+    }
 }
@@ -0,0 +1,499 @@
+use super::*;
+
+use std::fmt::{self, Debug};
+
+use parking_lot::Mutex;
+
+use crate::std_types::RSlice;
+
+/// A module that the host (the executable, or the library loading other libraries)
+/// passes to the dynamic libraries it loads,
+/// the reverse of a [`RootModule`].
+///
+/// This is how libraries get services from the host
+/// (eg: logging, configuration, allocation pools),
+/// with the layout of the module checked like the layout of root modules is.
+///
+/// # Usage
+///
+/// The interface crate declares the host module,
+/// a prefix type that implements this trait.
+///
+/// The library lists the host modules it uses in the
+/// `#[host_modules(...)]` helper attribute of [`export_root_module`],
+/// and gets them with [`get_host_module`](#method.get_host_module)
+/// (which can be called within the function that constructs the root module).
+///
+/// The host passes its host modules to libraries with [`provide_host_module`],
+/// which must be called before loading the libraries that use them.
+/// When a library is loaded with the [`RootModule`]`::load_from*` associated functions,
+/// a [`ModuleRegistry`], or a [`ReloadableModule`],
+/// the layout of every host module that the library uses is checked to be
+/// compatible with the one the host provides,
+/// before constructing the root module of the library.
+///
+/// # Example
+///
+/// This example demonstrates an interface crate
+/// that declares a host module and a root module.
+///
+/// ```rust
+/// use abi_stable::{
+///     library::{HostModule, RootModule},
+///     sabi_types::VersionStrings,
+///     std_types::RStr,
+///     StableAbi,
+/// };
+///
+/// /// The services that the host provides to plugins.
+/// #[repr(C)]
+/// #[derive(StableAbi)]
+/// #[sabi(kind(Prefix(prefix_ref = HostServices_Ref)))]
+/// pub struct HostServices {
+///     #[sabi(last_prefix_field)]
+///     pub log: extern "C" fn(RStr<'_>),
+/// }
+///
+/// impl HostModule for HostServices_Ref {
+///     abi_stable::declare_host_module_statics! {HostServices_Ref}
+///     const NAME: &'static str = "host_services";
+/// }
+///
+/// #[repr(C)]
+/// #[derive(StableAbi)]
+/// #[sabi(kind(Prefix(prefix_ref = Plugin_Ref)))]
+/// pub struct Plugin {
+///     #[sabi(last_prefix_field)]
+///     pub run: extern "C" fn(),
+/// }
+///
+/// impl RootModule for Plugin_Ref {
+///     abi_stable::declare_root_module_statics! {Plugin_Ref}
+///     const BASE_NAME: &'static str = "plugin";
+///     const NAME: &'static str = "plugin";
+///     const VERSION_STRINGS: VersionStrings = abi_stable::package_version_strings!();
+/// }
+///
+/// # fn main(){}
+/// ```
+///
+/// The library that uses `HostServices`:
+///
+/// ```rust,ignore
+/// use abi_stable::{export_root_module, library::HostModule, prefix_type::PrefixTypeTrait};
+///
+/// #[export_root_module]
+/// #[host_modules(HostServices_Ref)]
+/// pub fn get_library() -> Plugin_Ref {
+///     Plugin { run }.leak_into_prefix()
+/// }
+///
+/// extern "C" fn run() {
+///     if let Some(host) = HostServices_Ref::get_host_module() {
+///         host.log()("running the plugin".into());
+///     }
+/// }
+/// ```
+///
+/// The host, which provides `HostServices` before loading the library:
+///
+/// ```rust,ignore
+/// use abi_stable::{
+///     library::{provide_host_module, LibraryError, RootModule},
+///     prefix_type::PrefixTypeTrait,
+///     std_types::RStr,
+/// };
+///
+/// extern "C" fn log(message: RStr<'_>) {
+///     println!("{}", message);
+/// }
+///
+/// provide_host_module(HostServices { log }.leak_into_prefix());
+///
+/// let plugin = Plugin_Ref::load_from_directory("plugins".as_ref())?;
+/// plugin.run()();
+///
+/// # Ok::<(), LibraryError>(())
+/// ```
+///
+/// [`RootModule`]: ./trait.RootModule.html
+/// [`export_root_module`]: ../attr.export_root_module.html
+/// [`provide_host_module`]: ./fn.provide_host_module.html
+/// [`ModuleRegistry`]: ./struct.ModuleRegistry.html
+/// [`ReloadableModule`]: ./struct.ReloadableModule.html
+pub trait HostModule: Sized + StableAbi + PrefixRefTrait + 'static {
+    /// The name of the host module,
+    /// used to match the host modules that libraries use with the ones the host provides.
+    const NAME: &'static str;
+
+    /// Gets the statics for Self.
+    ///
+    /// To define this associated function use:
+    /// [`abi_stable::declare_host_module_statics!{TypeOfSelf}`
+    /// ](../macro.declare_host_module_statics.html).
+    /// Passing `Self` instead of `TypeOfSelf` won't work.
+    ///
+    fn host_module_statics() -> &'static HostModuleStatics<Self>;
+
+    /// Gets the host module that the host passed to this library,
+    /// returning None if it wasn't passed yet.
+    ///
+    /// This always returns None outside of dynamic libraries
+    /// that list this type in their `#[host_modules(...)]` attribute.
+    #[inline]
+    fn get_host_module() -> Option<Self> {
+        Self::host_module_statics().module.get()
+    }
+}
+
+/// The static variables declared for some [`HostModule`] implementor.
+/// [`HostModule`]: ./trait.HostModule.html
+#[doc(hidden)]
+pub struct HostModuleStatics<H> {
+    module: LateStaticRef<H>,
+}
+
+impl<H> HostModuleStatics<H> {
+    ///
+    /// # Safety
+    ///
+    /// This must only be called from the `abi_stable::declare_host_module_statics` macro.
+    #[doc(hidden)]
+    #[inline]
+    pub const unsafe fn __private_new() -> Self {
+        Self {
+            module: LateStaticRef::new(),
+        }
+    }
+}
+
+/// Implements the [`HostModule::host_module_statics`] associated function.
+///
+/// To define the associated function use:
+/// `abi_stable::declare_host_module_statics!{TypeOfSelf}`.
+/// Passing `Self` instead of `TypeOfSelf` won't work.
+///
+/// For an example, look at [`HostModule`].
+///
+#[cfg_attr(
+    doctest,
+    doc = r###"
+
+```rust
+struct Foo;
+impl Foo {
+    abi_stable::declare_host_module_statics!{Foo}
+}
+```
+
+```compile_fail
+struct Foo;
+impl Foo {
+    abi_stable::declare_host_module_statics!{Self}
+}
+```
+
+"###
+)]
+/// [`HostModule`]: ./library/trait.HostModule.html
+/// [`HostModule::host_module_statics`]:
+/// ./library/trait.HostModule.html#tymethod.host_module_statics
+#[macro_export]
+macro_rules! declare_host_module_statics {
+    ( ( $($stuff:tt)* ) ) => (
+        $crate::declare_host_module_statics!{$($stuff)*}
+    );
+    ( Self ) => (
+        compile_error!{"Don't use `Self`, write the full type name"}
+    );
+    ( $this:ty ) => (
+        #[inline]
+        fn host_module_statics()->&'static $crate::library::HostModuleStatics<$this>{
+            static _HOST_MOD_STATICS:$crate::library::HostModuleStatics<$this>= unsafe{
+                $crate::library::HostModuleStatics::__private_new()
+            };
+
+            &_HOST_MOD_STATICS
+        }
+    );
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// A host module that a dynamic library uses,
+/// exported by the [`export_root_module`] attribute for every type in
+/// its `#[host_modules(...)]` helper attribute.
+///
+/// [`export_root_module`]: ../attr.export_root_module.html
+#[repr(C)]
+#[derive(Copy, Clone, StableAbi)]
+pub struct HostModuleSlot {
+    name: RStr<'static>,
+    layout: &'static TypeLayout,
+    set: extern "C" fn(PrefixRef<ErasedPrefix>),
+}
+
+impl HostModuleSlot {
+    /// Constructs the `HostModuleSlot` for `H`.
+    pub const fn new<H>() -> Self
+    where
+        H: HostModule,
+    {
+        Self {
+            name: RStr::from_str(H::NAME),
+            layout: H::LAYOUT,
+            set: set_host_module::<H>,
+        }
+    }
+
+    /// The name of the host module, from [`HostModule::NAME`].
+    ///
+    /// [`HostModule::NAME`]: ./trait.HostModule.html#associatedconstant.NAME
+    pub const fn name(&self) -> RStr<'static> {
+        self.name
+    }
+
+    /// The layout of the host module that the library expects.
+    pub const fn layout(&self) -> &'static TypeLayout {
+        self.layout
+    }
+}
+
+impl Debug for HostModuleSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostModuleSlot")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// Stores the host module passed by the host in the statics of `H`.
+extern "C" fn set_host_module<H>(module: PrefixRef<ErasedPrefix>)
+where
+    H: HostModule,
+{
+    // safety: the layout of `H` was checked to be compatible with the layout of
+    // the host module before calling this function.
+    let module = H::from_prefix_ref(unsafe { module.cast::<H::PrefixFields>() });
+    let _ = H::host_module_statics().module.init(|| module);
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// A host module passed to [`provide_host_module`].
+///
+/// [`provide_host_module`]: ./fn.provide_host_module.html
+struct ProvidedHostModule {
+    name: &'static str,
+    layout: &'static TypeLayout,
+    module: PrefixRef<ErasedPrefix>,
+}
+
+// safety: the `HostModule` trait requires the module to be `StableAbi`,
+// so it's a prefix type that only contains `'static` data shared between libraries.
+unsafe impl Send for ProvidedHostModule {}
+
+static PROVIDED_HOST_MODULES: Mutex<Vec<ProvidedHostModule>> = parking_lot::const_mutex(Vec::new());
+
+/// Provides the `module` host module to the dynamic libraries loaded after
+/// this is called, replacing the previously provided host module of the same type.
+///
+/// Libraries that were already loaded keep the host module they were passed.
+///
+/// Every copy of abi_stable has its own host modules,
+/// so these are only passed to the libraries loaded by the crate graph
+/// (executable or dynamic library) that calls this function.
+///
+/// For an example, look at [`HostModule`].
+///
+/// [`HostModule`]: ./trait.HostModule.html
+pub fn provide_host_module<H>(module: H)
+where
+    H: HostModule,
+{
+    let provided = ProvidedHostModule {
+        name: H::NAME,
+        layout: H::LAYOUT,
+        module: unsafe { module.to_prefix_ref().cast::<ErasedPrefix>() },
+    };
+
+    let mut modules = PROVIDED_HOST_MODULES.lock();
+    match modules.iter_mut().find(|m| m.name == H::NAME) {
+        Some(prev) => *prev = provided,
+        None => modules.push(provided),
+    }
+}
+
+/// The name of the `static` that contains the [`HostModuleSlot`]s of an abi_stable library.
+///
+/// [`HostModuleSlot`]: ./struct.HostModuleSlot.html
+pub const HOST_MODULE_SLOTS_NAME: &str = PRIV_MANGLED_HOST_MODULE_SLOTS_NAME;
+
+/// A nul-terminated equivalent of [`HOST_MODULE_SLOTS_NAME`].
+///
+/// [`HOST_MODULE_SLOTS_NAME`]: ./constant.HOST_MODULE_SLOTS_NAME.html
+pub const HOST_MODULE_SLOTS_NAME_WITH_NUL: &str = PRIV_MANGLED_HOST_MODULE_SLOTS_NAME_NUL;
+
+/// Passes the provided host modules that the library uses to it,
+/// first checking that all of them were provided with a compatible layout.
+///
+/// Libraries built with a version of abi_stable that
+/// didn't export host module slots use no host modules.
+///
+/// # Safety
+///
+/// The library must have been checked to use a compatible version of abi_stable.
+pub(super) unsafe fn inject_host_modules(raw_library: &RawLibrary) -> Result<(), LibraryError> {
+    let mangled = HOST_MODULE_SLOTS_NAME_WITH_NUL;
+    // The symbol is the address of the static, which contains the `RSlice`.
+    let slots = match unsafe {
+        raw_library.get::<&'static RSlice<'static, HostModuleSlot>>(mangled.as_bytes())
+    } {
+        Ok(slots) => **slots,
+        Err(LibraryError::GetSymbolError { .. }) => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    inject_into_slots(raw_library.path(), slots.as_slice())
+}
+
+/// Passes the provided host modules to the `slots` of the library at `library_path`,
+/// only passing them if all of them were provided with a compatible layout.
+fn inject_into_slots(library_path: &Path, slots: &[HostModuleSlot]) -> Result<(), LibraryError> {
+    let provided = PROVIDED_HOST_MODULES.lock();
+
+    let mut to_inject = Vec::with_capacity(slots.len());
+    for slot in slots.iter() {
+        let module = provided
+            .iter()
+            .find(|m| m.name == slot.name.as_str())
+            .ok_or_else(|| ExtendedLibraryError::MissingHostModule {
+                library: library_path.to_owned(),
+                module_name: slot.name.to_string(),
+            })?;
+
+        // The layout checker of the executable is used for the same reasons
+        // as in `LibHeader::ensure_layout`.
+        (globals::initialized_globals().layout_checking)(slot.layout, module.layout)
+            .into_result()
+            .map_err(|e| LibraryError::abi_instability(&e))?;
+
+        to_inject.push((slot.set, module.module));
+    }
+
+    atomic::compiler_fence(atomic::Ordering::SeqCst);
+
+    for (set, module) in to_inject {
+        set(module);
+    }
+
+    Ok(())
+}
+
+//////////////////////////////////////////////////////////////////////
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests {
+    use super::*;
+
+    use crate::prefix_type::PrefixTypeTrait;
+
+    mod first {
+        #![allow(dead_code)]
+
+        use super::*;
+
+        #[repr(C)]
+        #[derive(StableAbi)]
+        #[sabi(kind(Prefix(prefix_ref = Counter_Ref)))]
+        pub struct Counter {
+            #[sabi(last_prefix_field)]
+            pub value: u32,
+        }
+
+        impl HostModule for Counter_Ref {
+            crate::declare_host_module_statics! {Counter_Ref}
+
+            const NAME: &'static str = "tests::counter";
+        }
+
+        #[repr(C)]
+        #[derive(StableAbi)]
+        #[sabi(kind(Prefix(prefix_ref = Missing_Ref)))]
+        pub struct Missing {
+            #[sabi(last_prefix_field)]
+            pub value: u32,
+        }
+
+        impl HostModule for Missing_Ref {
+            crate::declare_host_module_statics! {Missing_Ref}
+
+            const NAME: &'static str = "tests::missing";
+        }
+    }
+
+    mod changed_field_type {
+        #![allow(dead_code)]
+
+        use super::*;
+
+        #[repr(C)]
+        #[derive(StableAbi)]
+        #[sabi(kind(Prefix(prefix_ref = Counter_Ref)))]
+        pub struct Counter {
+            #[sabi(last_prefix_field)]
+            pub value: u64,
+        }
+
+        impl HostModule for Counter_Ref {
+            crate::declare_host_module_statics! {Counter_Ref}
+
+            const NAME: &'static str = "tests::counter";
+        }
+    }
+
+    fn library_path() -> &'static Path {
+        "libraries/library.so".as_ref()
+    }
+
+    #[test]
+    fn injecting_host_modules() {
+        use self::first::{Counter, Counter_Ref, Missing_Ref};
+
+        provide_host_module(Counter { value: 3 }.leak_into_prefix());
+        provide_host_module(Counter { value: 5 }.leak_into_prefix());
+        assert!(Counter_Ref::get_host_module().is_none());
+
+        let slots = [HostModuleSlot::new::<Counter_Ref>()];
+        assert_eq!(slots[0].name(), "tests::counter");
+
+        // A host module that wasn't provided prevents injecting every host module.
+        let err = inject_into_slots(
+            library_path(),
+            &[slots[0], HostModuleSlot::new::<Missing_Ref>()],
+        )
+        .unwrap_err();
+        match err {
+            LibraryError::Extended(ExtendedLibraryError::MissingHostModule {
+                library,
+                module_name,
+            }) => {
+                assert_eq!(library, library_path());
+                assert_eq!(module_name, "tests::missing");
+            }
+            err => panic!("expected a MissingHostModule error, found {:?}", err),
+        }
+        assert!(Counter_Ref::get_host_module().is_none());
+
+        inject_into_slots(library_path(), &slots).unwrap();
+        // The last provided module replaces the previous one.
+        assert_eq!(Counter_Ref::get_host_module().unwrap().value(), 5);
+
+        // A library that expects a host module with an incompatible layout.
+        type Incompatible = self::changed_field_type::Counter_Ref;
+        let slots = [HostModuleSlot::new::<Incompatible>()];
+        let err = inject_into_slots(library_path(), &slots).unwrap_err();
+        assert!(matches!(err, LibraryError::AbiInstability(_)), "{:?}", err);
+        assert!(Incompatible::get_host_module().is_none());
+    }
+}
@@ -0,0 +1,509 @@
+use super::*;
+
+use std::{
+    collections::HashSet,
+    fmt::Write as _,
+    fs::{self, File},
+    hash::Hasher,
+    io::{self, Read},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use parking_lot::{Mutex, RwLock};
+
+use crate::utils::Fnv1a;
+
+/// The first line of a layout cache file,
+/// the cache is discarded if it was written by another version of abi_stable,
+/// since the rules for layout checking can change between versions.
+const HEADER: &str = concat!("abi_stable layout cache v1 ", env!("CARGO_PKG_VERSION"));
+
+/// An on-disk cache of the libraries whose root module layout was
+/// successfully checked, which allows skipping layout checking
+/// for libraries that didn't change since the last run.
+///
+/// The cache is only used once it's set with [`set_layout_cache`],
+/// after which it's used by the [`RootModule`]`::load_from*` associated functions,
+/// [`ModuleRegistry`], and [`ReloadableModule`].
+///
+/// # Keys
+///
+/// Every entry in the cache is keyed by:
+///
+/// - A hash of the contents of the library file,
+///     read before the library is opened.
+///     If the file is replaced or modified while the library is being opened,
+///     the cache isn't used for that library.
+///
+/// - The [fingerprint] of the layout of the root module that the loader expects
+///     (the [`RootModule`]`::LAYOUT` of the loader),
+///     which includes every type that it references.
+///
+/// so entries are invalidated when either the library
+/// or the layout of any type in the interface of the loader changes.
+///
+/// Only successful checks are stored, libraries with incompatible layouts
+/// are always checked, to get the errors.
+///
+/// # Caveats
+///
+/// Only use this cache when libraries come from a trusted source,
+/// the hashes are not cryptographic,
+/// and anyone that can write to the cache file can skip layout checking.
+///
+/// Layout checking also records the prefix types, nonexhaustive enums,
+/// and extra checks of every library, to check that libraries are compatible
+/// with each other.
+/// Libraries in the cache still have those recorded and checked,
+/// only the comparison between the library and the interface of the loader is skipped.
+///
+/// Errors writing the cache file don't cause loading the library to fail,
+/// they're reported to the [`LoadObserver`] with
+/// [`LoadObserver::layout_cache_write_failed`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use abi_stable::library::{set_layout_cache, LayoutCache};
+///
+/// let cache = LayoutCache::open("target/layout_cache.txt").unwrap();
+/// set_layout_cache(cache);
+///
+/// // load libraries here
+///
+/// ```
+///
+/// [fingerprint]: ../type_layout/struct.TypeLayout.html#method.fingerprint
+/// [`set_layout_cache`]: ./fn.set_layout_cache.html
+/// [`RootModule`]: ./trait.RootModule.html
+/// [`ModuleRegistry`]: ./struct.ModuleRegistry.html
+/// [`ReloadableModule`]: ./struct.ReloadableModule.html
+/// [`LoadObserver`]: ./trait.LoadObserver.html
+/// [`LoadObserver::layout_cache_write_failed`]:
+/// ./trait.LoadObserver.html#method.layout_cache_write_failed
+#[derive(Debug)]
+pub struct LayoutCache {
+    path: PathBuf,
+    entries: Mutex<HashSet<CacheKey>>,
+}
+
+/// Identifies a library that passed layout checking.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    library_len: u64,
+    library_hash: u64,
+    interface: u64,
+}
+
+impl LayoutCache {
+    /// Opens the layout cache stored at `path`.
+    ///
+    /// If the file doesn't exist, or it was written by another version of abi_stable,
+    /// this returns an empty cache that's written to `path` the first time that
+    /// a library passes layout checking.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if the file exists and can't be read.
+    pub fn open<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let entries = match fs::read_to_string(&path) {
+            Ok(contents) => parse_entries(&contents),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashSet::new(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// The path of the file that the cache is stored in.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// How many libraries are in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Whether the cache has no libraries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+
+    /// Removes every library from the cache, and from the file it's stored in.
+    pub fn clear(&self) -> io::Result<()> {
+        let mut entries = self.entries.lock();
+        entries.clear();
+        self.write_file(&entries)
+    }
+
+    fn contains(&self, key: &CacheKey) -> bool {
+        self.entries.lock().contains(key)
+    }
+
+    fn insert(&self, key: CacheKey) -> io::Result<()> {
+        let mut entries = self.entries.lock();
+        if entries.insert(key) {
+            self.write_file(&entries)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Writes the entire cache to a temporary file,
+    /// then renames it to the path of the cache,
+    /// so that the cache file is never partially written.
+    fn write_file(&self, entries: &HashSet<CacheKey>) -> io::Result<()> {
+        let mut contents = String::with_capacity(HEADER.len() + 1 + entries.len() * 51);
+        contents.push_str(HEADER);
+        contents.push('\n');
+        for key in entries {
+            let _ = writeln!(
+                contents,
+                "{:016x} {:016x} {:016x}",
+                key.library_len, key.library_hash, key.interface,
+            );
+        }
+
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, contents)?;
+        fs::rename(&temp_path, &self.path)
+    }
+}
+
+fn parse_entries(contents: &str) -> HashSet<CacheKey> {
+    let mut lines = contents.lines();
+    if lines.next() != Some(HEADER) {
+        return HashSet::new();
+    }
+
+    lines
+        .filter_map(|line| {
+            let mut fields = line
+                .split(' ')
+                .map(|field| u64::from_str_radix(field, 16).ok());
+            let key = CacheKey {
+                library_len: fields.next()??,
+                library_hash: fields.next()??,
+                interface: fields.next()??,
+            };
+            match fields.next() {
+                None => Some(key),
+                Some(_) => None,
+            }
+        })
+        .collect()
+}
+
+//////////////////////////////////////////////////////////////////////
+
+static LAYOUT_CACHE: RwLock<Option<Arc<LayoutCache>>> = parking_lot::const_rwlock(None);
+
+/// Sets the [`LayoutCache`] used when loading root modules,
+/// returning the previous one.
+///
+/// Every copy of abi_stable has its own cache,
+/// so this only affects libraries loaded by the crate graph
+/// (executable or dynamic library) that calls this function.
+///
+/// [`LayoutCache`]: ./struct.LayoutCache.html
+pub fn set_layout_cache(cache: LayoutCache) -> Option<Arc<LayoutCache>> {
+    LAYOUT_CACHE.write().replace(Arc::new(cache))
+}
+
+/// Removes the [`LayoutCache`] set with [`set_layout_cache`], returning it.
+///
+/// [`LayoutCache`]: ./struct.LayoutCache.html
+/// [`set_layout_cache`]: ./fn.set_layout_cache.html
+pub fn remove_layout_cache() -> Option<Arc<LayoutCache>> {
+    LAYOUT_CACHE.write().take()
+}
+
+/// A hash of the contents of a library file, used in the keys of the [`LayoutCache`].
+///
+/// [`LayoutCache`]: ./struct.LayoutCache.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) struct LibraryHash {
+    len: u64,
+    hash: u64,
+}
+
+/// Opens the library at `path` with `open`,
+/// hashing the library file before it's opened if a [`LayoutCache`] is set.
+///
+/// [`LayoutCache`]: ./struct.LayoutCache.html
+pub(super) fn open_hashed<T, E, F>(path: &Path, open: F) -> Result<(T, Option<LibraryHash>), E>
+where
+    F: FnOnce() -> Result<T, E>,
+{
+    if LAYOUT_CACHE.read().is_none() {
+        return open().map(|library| (library, None));
+    }
+    hash_while_opening(path, open)
+}
+
+/// Hashes the library file at `path`, then opens the library with `open`.
+///
+/// The hash is discarded if the file at `path` was replaced or modified
+/// while it was being hashed, or while the library was being opened,
+/// since the library would then not be the file that was hashed.
+fn hash_while_opening<T, E, F>(path: &Path, open: F) -> Result<(T, Option<LibraryHash>), E>
+where
+    F: FnOnce() -> Result<T, E>,
+{
+    let hashed = hash_file(path).ok();
+    let library = open()?;
+
+    let hash = hashed.and_then(|(identity, hash)| {
+        let current = fs::metadata(path).ok().map(|m| FileIdentity::new(&m));
+        (current == Some(identity)).then(|| hash)
+    });
+
+    Ok((library, hash))
+}
+
+/// Hashes `bytes`, the image of a library loaded from memory,
+/// if a [`LayoutCache`] is set.
+///
+/// [`LayoutCache`]: ./struct.LayoutCache.html
+pub(super) fn hash_bytes(bytes: &[u8]) -> Option<LibraryHash> {
+    LAYOUT_CACHE.read().as_ref()?;
+
+    let mut hasher = Fnv1a::new();
+    hasher.write(bytes);
+    Some(LibraryHash {
+        len: bytes.len() as u64,
+        hash: hasher.finish(),
+    })
+}
+
+fn hash_file(path: &Path) -> io::Result<(FileIdentity, LibraryHash)> {
+    let mut file = File::open(path)?;
+    let identity = FileIdentity::new(&file.metadata()?);
+
+    let mut hasher = Fnv1a::new();
+    let mut len = 0u64;
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => {
+                hasher.write(&buffer[..read]);
+                len += read as u64;
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    // The file was modified while it was being hashed.
+    if FileIdentity::new(&file.metadata()?) != identity {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "the library file was modified while it was being hashed",
+        ));
+    }
+
+    let hash = LibraryHash {
+        len,
+        hash: hasher.finish(),
+    };
+    Ok((identity, hash))
+}
+
+/// Identifies a version of a file,
+/// used to detect that a file was replaced or modified.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct FileIdentity {
+    len: u64,
+    modified: Option<SystemTime>,
+    #[cfg(unix)]
+    dev_ino: (u64, u64),
+}
+
+impl FileIdentity {
+    fn new(metadata: &fs::Metadata) -> Self {
+        #[cfg(unix)]
+        use std::os::unix::fs::MetadataExt;
+
+        Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            #[cfg(unix)]
+            dev_ino: (metadata.dev(), metadata.ino()),
+        }
+    }
+}
+
+/// Checks the layout of the `M` root module in the `raw_library` library,
+/// only recording the types of the library for the checks between libraries
+/// if the [`LayoutCache`] says that it already passed.
+///
+/// The cache is ignored if the library file wasn't hashed when it was opened,
+/// and errors writing the cache are reported to the [`LoadObserver`].
+///
+/// [`LoadObserver`]: ./trait.LoadObserver.html
+pub(super) fn ensure_layout<M>(
+    raw_library: &RawLibrary,
+    header: &LibHeader,
+) -> Result<(), LibraryError>
+where
+    M: RootModule,
+{
+    // Cloning the cache so that the lock isn't held while checking the layout.
+    let cache = match LAYOUT_CACHE.read().clone() {
+        Some(cache) => cache,
+        None => return header.ensure_layout::<M>(),
+    };
+
+    let key = match raw_library.library_hash() {
+        Some(library_hash) => cache_key(library_hash, M::LAYOUT),
+        None => return header.ensure_layout::<M>(),
+    };
+
+    if cache.contains(&key) {
+        return header.ensure_cached_layout::<M>();
+    }
+
+    header.ensure_layout::<M>()?;
+
+    if let Err(e) = cache.insert(key) {
+        load_observer::report_layout_cache_write_error(cache.path(), &e);
+    }
+
+    Ok(())
+}
+
+fn cache_key(library_hash: LibraryHash, interface: &'static TypeLayout) -> CacheKey {
+    CacheKey {
+        library_len: library_hash.len,
+        library_hash: library_hash.hash,
+        interface: interface.fingerprint(),
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "abi_stable-layout_cache-{}-{}",
+            std::process::id(),
+            name
+        ))
+    }
+
+    const KEY: CacheKey = CacheKey {
+        library_len: 100,
+        library_hash: 0xdead_beef,
+        interface: 0x1234_5678_9abc_def0,
+    };
+
+    #[test]
+    fn persisted_entries() {
+        let path = temp_path("persisted_entries");
+        let _ = fs::remove_file(&path);
+
+        let cache = LayoutCache::open(&path).unwrap();
+        assert!(cache.is_empty());
+        assert!(!cache.contains(&KEY));
+        cache.insert(KEY).unwrap();
+        assert!(cache.contains(&KEY));
+
+        let cache = LayoutCache::open(&path).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains(&KEY));
+
+        cache.clear().unwrap();
+        assert!(cache.is_empty());
+        assert!(LayoutCache::open(&path).unwrap().is_empty());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn discarded_entries() {
+        let line = format!(
+            "{:016x} {:016x} {:016x}",
+            KEY.library_len, KEY.library_hash, KEY.interface
+        );
+
+        let other_version = format!("abi_stable layout cache v1 0.0.0\n{}\n", line);
+        assert!(parse_entries(&other_version).is_empty());
+
+        let contents = format!("{}\n{}\n{} 0\nfoo\n\n", HEADER, line, line);
+        assert_eq!(parse_entries(&contents), [KEY].into_iter().collect());
+    }
+
+    #[test]
+    fn library_key() {
+        let path = temp_path("library_key");
+        fs::write(&path, "hello").unwrap();
+
+        let (_, hash) = hash_file(&path).unwrap();
+        assert_eq!(hash, hash_file(&path).unwrap().1);
+        assert_eq!(hash.len, 5);
+
+        let key = cache_key(hash, <u32 as StableAbi>::LAYOUT);
+        assert_eq!(key, cache_key(hash, <u32 as StableAbi>::LAYOUT));
+        assert_ne!(
+            key.interface,
+            cache_key(hash, <u64 as StableAbi>::LAYOUT).interface
+        );
+
+        fs::write(&path, "world").unwrap();
+        let (_, changed) = hash_file(&path).unwrap();
+        assert_ne!(hash.hash, changed.hash);
+        assert_eq!(
+            key.interface,
+            cache_key(changed, <u32 as StableAbi>::LAYOUT).interface
+        );
+
+        fs::remove_file(&path).unwrap();
+
+        assert!(hash_file(&path).is_err());
+    }
+
+    #[test]
+    fn hashing_before_opening() {
+        let path = temp_path("hashing_before_opening");
+        fs::write(&path, "hello").unwrap();
+        let (_, hash) = hash_file(&path).unwrap();
+
+        let (opened, hashed) = hash_while_opening(&path, || Ok::<_, ()>(3)).unwrap();
+        assert_eq!(opened, 3);
+        assert_eq!(hashed, Some(hash));
+
+        assert_eq!(hash_while_opening(&path, || Err::<(), _>(5)), Err(5));
+
+        // The file is replaced by another one while the library is being opened.
+        let replacement = temp_path("hashing_before_opening-replacement");
+        let (_, hashed) = hash_while_opening(&path, || {
+            fs::write(&replacement, "world").unwrap();
+            fs::rename(&replacement, &path)
+        })
+        .unwrap();
+        assert_eq!(hashed, None);
+
+        // The file is modified while the library is being opened.
+        let (_, hashed) = hash_while_opening(&path, || fs::write(&path, "hello world")).unwrap();
+        assert_eq!(hashed, None);
+
+        fs::remove_file(&path).unwrap();
+
+        let (_, hashed) = hash_while_opening(&path, || Ok::<_, ()>(())).unwrap();
+        assert_eq!(hashed, None);
+    }
+}
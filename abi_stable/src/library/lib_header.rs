@@ -1,8 +1,13 @@
 use super::*;
 
 use crate::{
+    abi_stability::abi_checking::{
+        check_layout_compatibility_with_globals, get_checking_globals, record_compatible_layout,
+        CheckingGlobals,
+    },
     prefix_type::{PrefixRef, PrefixRefTrait},
     sabi_types::RRef,
+    std_types::RBoxError,
 };
 
 /// Used to check the layout of modules returned by module-loading functions
@@ -87,7 +92,7 @@ impl LibHeader {
         (self.init_globals_with.0)(globals);
     }
 
-    fn check_version<M>(&self) -> Result<(), LibraryError>
+    pub(super) fn check_version<M>(&self) -> Result<(), LibraryError>
     where
         M: RootModule,
     {
@@ -95,16 +100,7 @@ impl LibHeader {
 
         let actual_version = self.version_strings().piped(VersionNumber::new)?;
 
-        if expected_version.major != actual_version.major
-            || (expected_version.major == 0) && expected_version.minor > actual_version.minor
-        {
-            return Err(LibraryError::IncompatibleVersionNumber {
-                library_name: M::NAME,
-                expected_version,
-                actual_version,
-            });
-        }
-        Ok(())
+        M::version_policy().check_library(M::NAME, expected_version, actual_version)
     }
 
     /// Checks that the library is compatible, returning the root module on success.
@@ -135,6 +131,10 @@ impl LibHeader {
     /// - `LibraryError::IncompatibleVersionNumber`:
     ///     If the version number of the library is incompatible.
     ///
+    /// - `ExtendedLibraryError::RejectedVersionNumber`:
+    ///     If the version number of the library is denied by
+    ///     the version policy of the root module.
+    ///
     /// - `LibraryError::AbiInstability`:
     ///     If the layout of the root module is not the expected one.
     ///
@@ -180,6 +180,10 @@ impl LibHeader {
     /// - `LibraryError::IncompatibleVersionNumber`:
     ///     If the version number of the library is incompatible.
     ///
+    /// - `ExtendedLibraryError::RejectedVersionNumber`:
+    ///     If the version number of the library is denied by
+    ///     the version policy of the root module.
+    ///
     /// - `LibraryError::RootModule` :
     ///     If the root module initializer returned an error or panicked.
     ///
@@ -227,8 +231,7 @@ impl LibHeader {
                     //
                     // This isn't strictly required anymore because abi_stable doesn't
                     // unload libraries right now.
-                    let formatted = e.to_formatted_error();
-                    LibraryError::AbiInstability(formatted)
+                    LibraryError::abi_instability(&e)
                 })?;
         }
 
@@ -237,6 +240,59 @@ impl LibHeader {
         Ok(())
     }
 
+    /// Checks that the layout of the `M` from the dynamic library is
+    /// compatible with the expected layout,
+    /// recording the prefix types and nonexhaustive enums of the library in `globals`,
+    /// instead of in the ones of the executable (like `ensure_layout` does).
+    ///
+    /// This is used to check a library without affecting how other libraries are checked.
+    pub(super) fn ensure_layout_with_globals<M>(
+        &self,
+        globals: &CheckingGlobals,
+    ) -> Result<(), LibraryError>
+    where
+        M: RootModule,
+    {
+        if let IsLayoutChecked::Yes(root_mod_layout) = self.root_mod_consts.layout() {
+            check_layout_compatibility_with_globals(<M>::LAYOUT, root_mod_layout, globals)
+                .map_err(|e| LibraryError::abi_instability(&RBoxError::new(e)))?;
+        }
+
+        atomic::compiler_fence(atomic::Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    /// Checks the layout of the `M` root module from a library that
+    /// already passed layout checking with the same `M`
+    /// (as recorded in the [`LayoutCache`]),
+    /// only recording the prefix types, nonexhaustive enums, and extra checks
+    /// of the library in the globals of the executable,
+    /// to check that libraries are compatible with each other.
+    ///
+    /// If this is called in a dynamic library with its own copy of abi_stable,
+    /// the globals of the executable aren't reachable from here,
+    /// so this does the full layout check with the layout checker of the executable.
+    ///
+    /// [`LayoutCache`]: ./struct.LayoutCache.html
+    pub(super) fn ensure_cached_layout<M>(&self) -> Result<(), LibraryError>
+    where
+        M: RootModule,
+    {
+        if !globals::are_local_globals() {
+            return self.ensure_layout::<M>();
+        }
+
+        if let IsLayoutChecked::Yes(root_mod_layout) = self.root_mod_consts.layout() {
+            record_compatible_layout(<M>::LAYOUT, root_mod_layout, get_checking_globals())
+                .map_err(|e| LibraryError::abi_instability(&RBoxError::new(e)))?;
+        }
+
+        atomic::compiler_fence(atomic::Ordering::SeqCst);
+
+        Ok(())
+    }
+
     /// Gets the root module,first
     /// checking that the layout of the `M` from the dynamic library is
     /// compatible with the expected layout.
@@ -403,11 +459,7 @@ impl AbiHeaderRef {
     /// - `LibraryError::InvalidCAbi`:
     ///     If the C abi used by the library is not compatible.
     pub fn upgrade(self) -> Result<&'static LibHeader, LibraryError> {
-        if !self.is_valid() {
-            return Err(LibraryError::InvalidAbiHeader(*self));
-        }
-
-        let lib_header: &'static LibHeader = unsafe { self.0.transmute_into_ref() };
+        let lib_header = self.lib_header_unchecked_c_abi()?;
 
         let c_abi_testing_fns = lib_header.root_mod_consts().c_abi_testing_fns();
         crate::library::c_abi_testing::run_tests(c_abi_testing_fns)?;
@@ -418,4 +470,19 @@ impl AbiHeaderRef {
 
         Ok(lib_header)
     }
+
+    /// Gets the LibHeader of a library,
+    /// without running the C abi tests nor initializing the globals of the library.
+    ///
+    /// # Errors
+    ///
+    /// This returns a `LibraryError::InvalidAbiHeader`
+    /// if the abi_stable used by the library is not compatible.
+    pub(super) fn lib_header_unchecked_c_abi(self) -> Result<&'static LibHeader, LibraryError> {
+        if !self.is_valid() {
+            return Err(LibraryError::InvalidAbiHeader(*self));
+        }
+
+        Ok(unsafe { self.0.transmute_into_ref() })
+    }
 }
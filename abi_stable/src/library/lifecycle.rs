@@ -0,0 +1,322 @@
+use super::*;
+
+use std::{
+    fmt::{self, Display},
+    panic::{self, AssertUnwindSafe},
+};
+
+use parking_lot::Mutex;
+
+use crate::std_types::RBoxError;
+
+/// One of the lifecycle hooks of [`RootModule`].
+///
+/// [`RootModule`]: ./trait.RootModule.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LifecycleHook {
+    /// [`RootModule::on_load`](./trait.RootModule.html#method.on_load)
+    OnLoad,
+    /// [`RootModule::on_shutdown`](./trait.RootModule.html#method.on_shutdown)
+    OnShutdown,
+    /// [`RootModule::on_unload`](./trait.RootModule.html#method.on_unload)
+    OnUnload,
+}
+
+impl Display for LifecycleHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LifecycleHook::OnLoad => "on_load",
+            LifecycleHook::OnShutdown => "on_shutdown",
+            LifecycleHook::OnUnload => "on_unload",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Calls the `hook` hook of `module`,
+/// turning the errors it returns and panics into `ExtendedLibraryError::HookFailed`.
+pub(super) fn call_hook<M>(module: M, hook: LifecycleHook) -> Result<(), LibraryError>
+where
+    M: RootModule,
+{
+    let res = panic::catch_unwind(AssertUnwindSafe(|| -> Result<(), RBoxError> {
+        match hook {
+            LifecycleHook::OnLoad => module.on_load(),
+            LifecycleHook::OnShutdown => module.on_shutdown(),
+            LifecycleHook::OnUnload => module.on_unload(),
+        }
+    }));
+
+    let err = match res {
+        Ok(Ok(())) => return Ok(()),
+        Ok(Err(e)) => RootModuleError::Returned(e.to_formatted_error()),
+        Err(_) => RootModuleError::Unwound,
+    };
+
+    Err(ExtendedLibraryError::HookFailed {
+        module_name: M::NAME,
+        hook,
+        err,
+    }
+    .into())
+}
+
+/// Calls the `hook` hook of the type-erased root module.
+///
+/// # Safety
+///
+/// `module` must have been erased from an `M`.
+unsafe fn call_erased_hook<M>(
+    module: PrefixRef<ErasedPrefix>,
+    hook: LifecycleHook,
+) -> Result<(), LibraryError>
+where
+    M: RootModule,
+{
+    let module = M::from_prefix_ref(unsafe { module.cast::<M::PrefixFields>() });
+    call_hook(module, hook)
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// Identifies a root module registered with [`register`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) struct LoadedId(u64);
+
+struct LoadedModules {
+    next_id: u64,
+    /// The loaded root modules, in the order that they were loaded.
+    entries: Vec<LoadedEntry>,
+}
+
+struct LoadedEntry {
+    id: LoadedId,
+    module: PrefixRef<ErasedPrefix>,
+    call_hook: unsafe fn(PrefixRef<ErasedPrefix>, LifecycleHook) -> Result<(), LibraryError>,
+    shut_down: bool,
+}
+
+// safety: root modules are prefix types that only contain `'static` data,
+// which are shared between the host and libraries.
+unsafe impl Send for LoadedEntry {}
+
+static LOADED_MODULES: Mutex<LoadedModules> = parking_lot::const_mutex(LoadedModules {
+    next_id: 0,
+    entries: Vec::new(),
+});
+
+/// Registers a loaded root module, so that its `on_shutdown` hook is called by `shutdown_all`.
+pub(super) fn register<M>(module: M) -> LoadedId
+where
+    M: RootModule,
+{
+    let mut loaded = LOADED_MODULES.lock();
+    let id = LoadedId(loaded.next_id);
+    loaded.next_id += 1;
+    loaded.entries.push(LoadedEntry {
+        id,
+        module: unsafe { module.to_prefix_ref().cast::<ErasedPrefix>() },
+        call_hook: call_erased_hook::<M>,
+        shut_down: false,
+    });
+    id
+}
+
+/// Unregisters the root module of a library that is about to be unloaded,
+/// calling its `on_shutdown` hook (if `shutdown_all` didn't already call it)
+/// followed by its `on_unload` hook.
+///
+/// This does nothing if the root module was already unregistered.
+pub(super) fn unregister(id: LoadedId) -> Result<(), LibraryError> {
+    let entry = {
+        let mut loaded = LOADED_MODULES.lock();
+        match loaded.entries.iter().position(|e| e.id == id) {
+            Some(i) => loaded.entries.remove(i),
+            None => return Ok(()),
+        }
+    };
+
+    let mut errors = Vec::new();
+    if !entry.shut_down {
+        errors.extend(unsafe { (entry.call_hook)(entry.module, LifecycleHook::OnShutdown) }.err());
+    }
+    errors.extend(unsafe { (entry.call_hook)(entry.module, LifecycleHook::OnUnload) }.err());
+    into_result(errors)
+}
+
+/// Calls the [`RootModule::on_shutdown`] hook of every loaded root module,
+/// in the reverse order that they were loaded.
+///
+/// This calls the hooks of the root modules loaded by the
+/// [`RootModule`]`::load_from*` associated functions, [`ModuleRegistry`]s,
+/// and [`ReloadableModule`]s (that aren't unloaded),
+/// calling the hook at most once for each loaded root module.
+///
+/// The libraries aren't unloaded,
+/// so the root modules are still usable after this is called.
+///
+/// Every copy of abi_stable has its own loaded root modules,
+/// so this only calls the hooks of the root modules loaded by the crate graph
+/// (executable or dynamic library) that calls this function.
+///
+/// # Errors
+///
+/// All the hooks are called even if some of them fail,
+/// returning `ExtendedLibraryError::HookFailed` for the hook that failed,
+/// or a `LibraryError::Many` if more than one did.
+///
+/// # Example
+///
+/// ```rust
+/// use abi_stable::library::{shutdown_all, LibraryError};
+///
+/// // Loading the root modules of libraries and running the program...
+///
+/// shutdown_all()?;
+///
+/// # Ok::<(), LibraryError>(())
+/// ```
+///
+/// [`RootModule::on_shutdown`]: ./trait.RootModule.html#method.on_shutdown
+/// [`RootModule`]: ./trait.RootModule.html
+/// [`ModuleRegistry`]: ./struct.ModuleRegistry.html
+/// [`ReloadableModule`]: ./struct.ReloadableModule.html
+pub fn shutdown_all() -> Result<(), LibraryError> {
+    // Collecting the hooks so that the lock isn't held while they're called,
+    // since they could load libraries.
+    let to_shut_down = LOADED_MODULES
+        .lock()
+        .entries
+        .iter_mut()
+        .rev()
+        .filter(|e| !e.shut_down)
+        .map(|e| {
+            e.shut_down = true;
+            (e.call_hook, e.module)
+        })
+        .collect::<Vec<_>>();
+
+    let errors = to_shut_down
+        .into_iter()
+        .filter_map(|(call_hook, module)| {
+            unsafe { call_hook(module, LifecycleHook::OnShutdown) }.err()
+        })
+        .collect::<Vec<_>>();
+    into_result(errors)
+}
+
+fn into_result(mut errors: Vec<LibraryError>) -> Result<(), LibraryError> {
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.pop().unwrap()),
+        _ => Err(LibraryError::Many(errors.into())),
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests {
+    use super::*;
+
+    use crate::prefix_type::WithMetadata;
+
+    #[repr(C)]
+    #[derive(StableAbi)]
+    #[sabi(kind(Prefix(prefix_ref = HookedMod_Ref)))]
+    pub struct HookedMod {
+        #[sabi(last_prefix_field)]
+        pub id: u32,
+    }
+
+    static CALLS: Mutex<Vec<(u32, LifecycleHook)>> = parking_lot::const_mutex(Vec::new());
+
+    impl HookedMod_Ref {
+        fn record(self, hook: LifecycleHook) -> Result<(), RBoxError> {
+            CALLS.lock().push((self.id(), hook));
+            match (self.id(), hook) {
+                (1, LifecycleHook::OnShutdown) => Err(RBoxError::from_fmt("not flushed")),
+                (2, LifecycleHook::OnShutdown) => panic!("oh no"),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    impl RootModule for HookedMod_Ref {
+        crate::declare_root_module_statics! {HookedMod_Ref}
+        const BASE_NAME: &'static str = "hooked";
+        const NAME: &'static str = "hooked";
+        const VERSION_STRINGS: VersionStrings = crate::package_version_strings!();
+
+        fn on_shutdown(self) -> Result<(), RBoxError> {
+            self.record(LifecycleHook::OnShutdown)
+        }
+
+        fn on_unload(self) -> Result<(), RBoxError> {
+            self.record(LifecycleHook::OnUnload)
+        }
+    }
+
+    fn new_module(id: u32) -> HookedMod_Ref {
+        HookedMod_Ref(
+            crate::utils::leak_value(WithMetadata::new(HookedMod { id })).static_as_prefix(),
+        )
+    }
+
+    #[test]
+    fn lifecycle_hooks() {
+        use LifecycleHook::{OnShutdown, OnUnload};
+
+        let loaded = (0..4)
+            .map(|id| register(new_module(id)))
+            .collect::<Vec<_>>();
+
+        unregister(loaded[3]).unwrap();
+        assert_eq!(*CALLS.lock(), [(3, OnShutdown), (3, OnUnload)]);
+        CALLS.lock().clear();
+
+        match shutdown_all().unwrap_err() {
+            LibraryError::Many(errors) => {
+                assert_eq!(errors.len(), 2);
+                assert!(
+                    matches!(
+                        errors[0],
+                        LibraryError::Extended(ExtendedLibraryError::HookFailed {
+                            hook: OnShutdown,
+                            err: RootModuleError::Unwound,
+                            ..
+                        })
+                    ),
+                    "{:?}",
+                    errors[0]
+                );
+                assert!(
+                    matches!(
+                        errors[1],
+                        LibraryError::Extended(ExtendedLibraryError::HookFailed {
+                            hook: OnShutdown,
+                            err: RootModuleError::Returned(_),
+                            ..
+                        })
+                    ),
+                    "{:?}",
+                    errors[1]
+                );
+            }
+            e => panic!("{:?}", e),
+        }
+        assert_eq!(
+            *CALLS.lock(),
+            [(2, OnShutdown), (1, OnShutdown), (0, OnShutdown)]
+        );
+        CALLS.lock().clear();
+
+        // The hooks are only called once.
+        shutdown_all().unwrap();
+        unregister(loaded[3]).unwrap();
+        assert_eq!(*CALLS.lock(), []);
+
+        unregister(loaded[0]).unwrap();
+        assert_eq!(*CALLS.lock(), [(0, OnUnload)]);
+    }
+}
@@ -0,0 +1,227 @@
+use super::*;
+
+use std::{
+    io,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::RwLock;
+
+/// A stage of loading the root module of a library,
+/// in the order that they're done.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub enum LoadStage {
+    /// Opening the dynamic library.
+    ///
+    /// This stage is skipped if the library was already opened
+    /// by a previous attempt to load the root module.
+    OpenLibrary,
+    /// Reading the [`AbiHeader`] of the library,
+    /// checking that the library uses a compatible version of abi_stable and C abi.
+    ///
+    /// [`AbiHeader`]: ./struct.AbiHeader.html
+    AbiHeader,
+    /// Checking that the layout of the root module is compatible.
+    Layout,
+    /// Checking that the version number of the library is compatible.
+    Version,
+    /// Checking and passing the [`HostModule`]s that the library uses.
+    ///
+    /// [`HostModule`]: ./trait.HostModule.html
+    HostModules,
+    /// Constructing the root module,
+    /// and calling [`RootModule::initialization`] on it.
+    ///
+    /// [`RootModule::initialization`]: ./trait.RootModule.html#method.initialization
+    RootModule,
+}
+
+/// Information about a [`LoadStage`], passed to the methods of [`LoadObserver`].
+///
+/// [`LoadStage`]: ./enum.LoadStage.html
+/// [`LoadObserver`]: ./trait.LoadObserver.html
+#[derive(Debug, Copy, Clone)]
+#[non_exhaustive]
+pub struct LoadEvent<'a> {
+    /// The path to the library.
+    pub path: &'a Path,
+    /// The name of the root module (from [`RootModule::NAME`]).
+    ///
+    /// [`RootModule::NAME`]: ./trait.RootModule.html#associatedconstant.NAME
+    pub module_name: &'static str,
+    /// The stage that completed or failed.
+    pub stage: LoadStage,
+    /// How long the stage took.
+    pub stage_duration: Duration,
+    /// How long it's been since loading the library started.
+    pub total_duration: Duration,
+}
+
+/// Observes every stage of loading root modules,
+/// for logging and measuring how long libraries take to load.
+///
+/// The observer is set with [`set_load_observer`],
+/// and is called when root modules are loaded with the
+/// [`RootModule`]`::load_from*` associated functions,
+/// a [`ModuleRegistry`], or a [`ReloadableModule`].
+///
+/// Panics in the methods of the observer propagate to the code loading the library.
+///
+/// # Example
+///
+/// ```rust
+/// use abi_stable::library::{set_load_observer, LibraryError, LoadEvent, LoadObserver};
+///
+/// struct Logger;
+///
+/// impl LoadObserver for Logger {
+///     fn stage_completed(&self, event: &LoadEvent<'_>) {
+///         println!(
+///             "{}: {:?} took {:?}",
+///             event.path.display(),
+///             event.stage,
+///             event.stage_duration,
+///         );
+///     }
+///
+///     fn load_failed(&self, event: &LoadEvent<'_>, error: &LibraryError) {
+///         eprintln!("{}: {:?} failed: {}", event.path.display(), event.stage, error);
+///     }
+/// }
+///
+/// set_load_observer(Logger);
+///
+/// ```
+///
+/// [`set_load_observer`]: ./fn.set_load_observer.html
+/// [`RootModule`]: ./trait.RootModule.html
+/// [`ModuleRegistry`]: ./struct.ModuleRegistry.html
+/// [`ReloadableModule`]: ./struct.ReloadableModule.html
+pub trait LoadObserver: Send + Sync {
+    /// Called after `event.stage` completes successfully.
+    ///
+    /// The default implementation does nothing.
+    fn stage_completed(&self, event: &LoadEvent<'_>) {
+        let _ = event;
+    }
+
+    /// Called when `event.stage` fails with `error`,
+    /// after which no more stages are done.
+    ///
+    /// The default implementation does nothing.
+    fn load_failed(&self, event: &LoadEvent<'_>, error: &LibraryError) {
+        let _ = (event, error);
+    }
+
+    /// Called when the [`LayoutCache`] stored at `cache_path` couldn't be written
+    /// after a library passed layout checking,
+    /// in which case the library is checked again the next time that the cache is opened.
+    ///
+    /// The default implementation does nothing.
+    ///
+    /// [`LayoutCache`]: ./struct.LayoutCache.html
+    fn layout_cache_write_failed(&self, cache_path: &Path, error: &io::Error) {
+        let _ = (cache_path, error);
+    }
+}
+
+static LOAD_OBSERVER: RwLock<Option<Arc<dyn LoadObserver>>> = parking_lot::const_rwlock(None);
+
+/// Sets the [`LoadObserver`] that is called when loading root modules,
+/// returning the previous one.
+///
+/// Every copy of abi_stable has its own observer,
+/// so this only observes libraries loaded by the crate graph
+/// (executable or dynamic library) that calls this function.
+///
+/// [`LoadObserver`]: ./trait.LoadObserver.html
+pub fn set_load_observer<O>(observer: O) -> Option<Arc<dyn LoadObserver>>
+where
+    O: LoadObserver + 'static,
+{
+    LOAD_OBSERVER.write().replace(Arc::new(observer))
+}
+
+/// Removes the [`LoadObserver`] set with [`set_load_observer`], returning it.
+///
+/// [`LoadObserver`]: ./trait.LoadObserver.html
+/// [`set_load_observer`]: ./fn.set_load_observer.html
+pub fn remove_load_observer() -> Option<Arc<dyn LoadObserver>> {
+    LOAD_OBSERVER.write().take()
+}
+
+/// Reports to the [`LoadObserver`] that the [`LayoutCache`] stored at `cache_path`
+/// couldn't be written.
+///
+/// [`LayoutCache`]: ./struct.LayoutCache.html
+pub(super) fn report_layout_cache_write_error(cache_path: &Path, error: &io::Error) {
+    // Cloning the observer so that the lock isn't held while calling it.
+    let observer = LOAD_OBSERVER.read().clone();
+    if let Some(observer) = observer {
+        observer.layout_cache_write_failed(cache_path, error);
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// Reports the stages of loading a library to the [`LoadObserver`],
+/// if there was one when this was constructed.
+pub(super) struct LoadTimer<'a> {
+    path: &'a Path,
+    module_name: &'static str,
+    observer: Option<ObserverState>,
+}
+
+struct ObserverState {
+    observer: Arc<dyn LoadObserver>,
+    start: Instant,
+    stage_start: Instant,
+}
+
+impl<'a> LoadTimer<'a> {
+    pub(super) fn new<M>(path: &'a Path) -> Self
+    where
+        M: RootModule,
+    {
+        // Cloning the observer so that the lock isn't held while loading the library.
+        let observer = LOAD_OBSERVER.read().clone().map(|observer| {
+            let start = Instant::now();
+            ObserverState {
+                observer,
+                start,
+                stage_start: start,
+            }
+        });
+
+        Self {
+            path,
+            module_name: M::NAME,
+            observer,
+        }
+    }
+
+    /// Reports the outcome of `stage`, passing it through.
+    pub(super) fn stage<T>(
+        &mut self,
+        stage: LoadStage,
+        res: Result<T, LibraryError>,
+    ) -> Result<T, LibraryError> {
+        if let Some(state) = &mut self.observer {
+            let now = Instant::now();
+            let event = LoadEvent {
+                path: self.path,
+                module_name: self.module_name,
+                stage,
+                stage_duration: now - state.stage_start,
+                total_duration: now - state.start,
+            };
+            match &res {
+                Ok(_) => state.observer.stage_completed(&event),
+                Err(e) => state.observer.load_failed(&event, e),
+            }
+            state.stage_start = Instant::now();
+        }
+        res
+    }
+}
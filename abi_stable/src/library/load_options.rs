@@ -0,0 +1,206 @@
+use super::*;
+
+/// Options for how a dynamic library is opened,
+/// passed to [`RawLibrary::load_at_with_options`] and the
+/// `*_with_options` functions that load root modules.
+///
+/// The default options are the same that [`RawLibrary::load_at`] uses,
+/// which on unix are `RTLD_LAZY | RTLD_LOCAL`.
+///
+/// These options map to the flags passed to `dlopen`,
+/// and are ignored on platforms that don't use `dlopen` (eg: Windows),
+/// except for [`deep_bind`](#method.deep_bind),
+/// which causes an error when loading a library on platforms that don't support it.
+///
+/// # Example
+///
+/// Loading a library that statically links a C library
+/// whose symbols clash with the ones of other libraries.
+///
+/// ```rust,no_run
+/// use abi_stable::{
+///     for_examples::Module_Ref,
+///     library::{LibraryError, LibraryPath, LoadOptions, RootModule},
+/// };
+///
+/// let options = LoadOptions::new().now().local().deep_bind(true);
+///
+/// let module = Module_Ref::load_from_with_options(
+///     LibraryPath::FullPath("plugins/libfoo.so".as_ref()),
+///     options,
+/// )?;
+///
+/// # Ok::<(), LibraryError>(())
+/// ```
+///
+/// [`RawLibrary::load_at`]: ./struct.RawLibrary.html#method.load_at
+/// [`RawLibrary::load_at_with_options`]: ./struct.RawLibrary.html#method.load_at_with_options
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct LoadOptions {
+    lazy: bool,
+    global: bool,
+    deep_bind: bool,
+}
+
+impl LoadOptions {
+    /// Constructs the default `LoadOptions`,
+    /// which resolve symbols lazily and make them local to the library.
+    pub const fn new() -> Self {
+        Self {
+            lazy: true,
+            global: false,
+            deep_bind: false,
+        }
+    }
+
+    /// Resolves the symbols of the library as functions are first called (`RTLD_LAZY`).
+    ///
+    /// This is the default.
+    pub const fn lazy(mut self) -> Self {
+        self.lazy = true;
+        self
+    }
+
+    /// Resolves all the symbols of the library before opening it returns (`RTLD_NOW`).
+    pub const fn now(mut self) -> Self {
+        self.lazy = false;
+        self
+    }
+
+    /// Makes the symbols of the library unavailable to resolve the symbols of
+    /// libraries opened later (`RTLD_LOCAL`).
+    ///
+    /// This is the default.
+    pub const fn local(mut self) -> Self {
+        self.global = false;
+        self
+    }
+
+    /// Makes the symbols of the library available to resolve the symbols of
+    /// libraries opened later (`RTLD_GLOBAL`).
+    pub const fn global(mut self) -> Self {
+        self.global = true;
+        self
+    }
+
+    /// Whether the library resolves symbols in itself before global symbols
+    /// (`RTLD_DEEPBIND`).
+    ///
+    /// This is only supported on Linux with glibc,
+    /// on other platforms loading a library with this enabled returns an
+    /// `ExtendedLibraryError::UnsupportedLoadOptions` error.
+    pub const fn deep_bind(mut self, deep_bind: bool) -> Self {
+        self.deep_bind = deep_bind;
+        self
+    }
+
+    /// Whether symbols are resolved lazily (`RTLD_LAZY`),
+    /// false if they are resolved when the library is opened (`RTLD_NOW`).
+    pub const fn is_lazy(&self) -> bool {
+        self.lazy
+    }
+
+    /// Whether the symbols of the library are available to libraries opened later
+    /// (`RTLD_GLOBAL`), false if they are local to the library (`RTLD_LOCAL`).
+    pub const fn is_global(&self) -> bool {
+        self.global
+    }
+
+    /// Whether the library resolves symbols in itself before global symbols
+    /// (`RTLD_DEEPBIND`).
+    pub const fn is_deep_bind(&self) -> bool {
+        self.deep_bind
+    }
+
+    /// Whether these options can be used to load libraries on this platform.
+    pub const fn is_supported(&self) -> bool {
+        !self.deep_bind || cfg!(all(target_os = "linux", target_env = "gnu"))
+    }
+
+    /// Returns an error if these options can't be used to load
+    /// the library at `path` on this platform.
+    pub(super) fn check_supported(&self, path: &Path) -> Result<(), LibraryError> {
+        if self.is_supported() {
+            Ok(())
+        } else {
+            Err(ExtendedLibraryError::UnsupportedLoadOptions {
+                path: path.to_owned(),
+                options: *self,
+            }
+            .into())
+        }
+    }
+
+    /// The flags passed to `dlopen`.
+    #[cfg(unix)]
+    pub(super) fn dlopen_flags(&self) -> std::os::raw::c_int {
+        use libloading::os::unix::{RTLD_GLOBAL, RTLD_LAZY, RTLD_LOCAL, RTLD_NOW};
+
+        #[cfg(all(target_os = "linux", target_env = "gnu"))]
+        use libc::RTLD_DEEPBIND;
+        // `check_supported` returns an error for `deep_bind(true)` on other platforms.
+        #[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+        const RTLD_DEEPBIND: std::os::raw::c_int = 0;
+
+        let resolution = if self.lazy { RTLD_LAZY } else { RTLD_NOW };
+        let visibility = if self.global { RTLD_GLOBAL } else { RTLD_LOCAL };
+        let deep_bind = if self.deep_bind { RTLD_DEEPBIND } else { 0 };
+
+        resolution | visibility | deep_bind
+    }
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, unix, not(feature = "only_new_tests")))]
+mod tests {
+    use super::*;
+
+    use libloading::os::unix::{RTLD_GLOBAL, RTLD_LAZY, RTLD_LOCAL, RTLD_NOW};
+
+    #[test]
+    fn dlopen_flags() {
+        assert_eq!(LoadOptions::new().dlopen_flags(), RTLD_LAZY | RTLD_LOCAL);
+        assert_eq!(
+            LoadOptions::new().now().global().dlopen_flags(),
+            RTLD_NOW | RTLD_GLOBAL
+        );
+        assert_eq!(
+            LoadOptions::new().global().local().now().lazy(),
+            LoadOptions::new(),
+        );
+
+        let deep_bind = LoadOptions::new().deep_bind(true);
+        assert!(deep_bind.is_deep_bind());
+        #[cfg(all(target_os = "linux", target_env = "gnu"))]
+        assert_eq!(
+            deep_bind.dlopen_flags(),
+            RTLD_LAZY | RTLD_LOCAL | libc::RTLD_DEEPBIND
+        );
+    }
+
+    #[test]
+    fn unsupported_options() {
+        let path = Path::new("foo.so");
+        assert!(LoadOptions::new().check_supported(path).is_ok());
+
+        let deep_bind = LoadOptions::new().deep_bind(true);
+        if cfg!(all(target_os = "linux", target_env = "gnu")) {
+            assert!(deep_bind.is_supported());
+            assert!(deep_bind.check_supported(path).is_ok());
+        } else {
+            assert!(!deep_bind.is_supported());
+            match deep_bind.check_supported(path).unwrap_err() {
+                LibraryError::Extended(ExtendedLibraryError::UnsupportedLoadOptions {
+                    options,
+                    ..
+                }) => assert_eq!(options, deep_bind),
+                err => panic!("expected an UnsupportedLoadOptions error, found {:?}", err),
+            }
+        }
+    }
+}
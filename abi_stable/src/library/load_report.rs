@@ -0,0 +1,274 @@
+use super::*;
+
+use std::fmt::{self, Display};
+
+use crate::{abi_stability::abi_checking::CheckingGlobals, std_types::RVec};
+
+/// The checks done while loading the root module of a library,
+/// in the order that they are done.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub enum LoadCheck {
+    /// Opening the dynamic library.
+    OpenLibrary,
+    /// Getting the [`AbiHeader`] of the library,
+    /// and checking that the library uses a compatible version of abi_stable.
+    ///
+    /// [`AbiHeader`]: ./struct.AbiHeader.html
+    AbiHeader,
+    /// Checking that the C abi of the library is the same as the loader's,
+    /// with the functions in [`c_abi_testing`].
+    ///
+    /// [`c_abi_testing`]: ./c_abi_testing/index.html
+    CAbi,
+    /// Checking that the version number of the library is compatible.
+    Version,
+    /// Checking that the layout of the root module is compatible.
+    Layout,
+}
+
+impl LoadCheck {
+    /// All the checks, in the order that they're done.
+    pub const ALL: [LoadCheck; 5] = [
+        LoadCheck::OpenLibrary,
+        LoadCheck::AbiHeader,
+        LoadCheck::CAbi,
+        LoadCheck::Version,
+        LoadCheck::Layout,
+    ];
+}
+
+impl Display for LoadCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            LoadCheck::OpenLibrary => "opening the library",
+            LoadCheck::AbiHeader => "abi_stable version",
+            LoadCheck::CAbi => "C abi",
+            LoadCheck::Version => "library version",
+            LoadCheck::Layout => "root module layout",
+        };
+        f.write_str(s)
+    }
+}
+
+/// The outcome of a [`LoadCheck`].
+///
+/// [`LoadCheck`]: ./enum.LoadCheck.html
+#[derive(Debug)]
+pub enum CheckOutcome {
+    /// The check passed.
+    Passed,
+    /// The check failed with this error.
+    Failed(LibraryError),
+    /// The check couldn't be done, because a check it depends on failed.
+    Skipped,
+}
+
+impl CheckOutcome {
+    /// Whether the check passed.
+    pub const fn is_passed(&self) -> bool {
+        matches!(self, CheckOutcome::Passed)
+    }
+
+    /// Gets the error that the check failed with,
+    /// returning None if it didn't fail.
+    pub const fn error(&self) -> Option<&LibraryError> {
+        match self {
+            CheckOutcome::Failed(e) => Some(e),
+            CheckOutcome::Passed | CheckOutcome::Skipped => None,
+        }
+    }
+}
+
+impl From<Result<(), LibraryError>> for CheckOutcome {
+    fn from(res: Result<(), LibraryError>) -> Self {
+        match res {
+            Ok(()) => CheckOutcome::Passed,
+            Err(e) => CheckOutcome::Failed(e),
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// The outcome of every check that would be done when loading the root module of a library,
+/// returned by [`RootModule::validate_library`].
+///
+/// [`RootModule::validate_library`]: ./trait.RootModule.html#method.validate_library
+#[derive(Debug)]
+pub struct LoadReport {
+    /// The path to the library.
+    pub path: PathBuf,
+    /// The name of the root module (from [`RootModule::NAME`]).
+    ///
+    /// [`RootModule::NAME`]: ./trait.RootModule.html#associatedconstant.NAME
+    pub module_name: &'static str,
+    /// The version of the library (from its `VersionStrings`),
+    /// None if it couldn't be read because an earlier check failed.
+    pub library_version: Option<String>,
+    /// The outcome of every check, in the order of [`LoadCheck::ALL`].
+    ///
+    /// [`LoadCheck::ALL`]: ./enum.LoadCheck.html#associatedconstant.ALL
+    pub checks: Vec<(LoadCheck, CheckOutcome)>,
+}
+
+impl LoadReport {
+    /// Whether all the checks passed,
+    /// meaning that loading the library could only fail because
+    /// the root module constructor returned an error or panicked.
+    pub fn is_loadable(&self) -> bool {
+        self.checks.iter().all(|(_, outcome)| outcome.is_passed())
+    }
+
+    /// Gets the outcome of `check`.
+    pub fn outcome(&self, check: LoadCheck) -> &CheckOutcome {
+        self.checks
+            .iter()
+            .find(|(c, _)| *c == check)
+            .map_or(&CheckOutcome::Skipped, |(_, outcome)| outcome)
+    }
+
+    /// Iterates over the checks that failed, along with their errors.
+    pub fn failures(&self) -> impl Iterator<Item = (LoadCheck, &LibraryError)> + '_ {
+        self.checks
+            .iter()
+            .filter_map(|(check, outcome)| outcome.error().map(|e| (*check, e)))
+    }
+
+    /// Converts this report into a `Result`,
+    /// returning an error if any check failed.
+    ///
+    /// # Errors
+    ///
+    /// If only one check failed, this returns its error,
+    /// otherwise this returns a `LibraryError::Many` with the errors of every failed check.
+    pub fn into_result(self) -> Result<(), LibraryError> {
+        let mut errors = self
+            .checks
+            .into_iter()
+            .filter_map(|(_, outcome)| match outcome {
+                CheckOutcome::Failed(e) => Some(e),
+                CheckOutcome::Passed | CheckOutcome::Skipped => None,
+            })
+            .collect::<RVec<LibraryError>>();
+
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.pop().unwrap()),
+            _ => Err(LibraryError::Many(errors)),
+        }
+    }
+}
+
+impl Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Checks for loading the '{}' root module from:\n\t{}",
+            self.module_name,
+            self.path.display(),
+        )?;
+        if let Some(version) = &self.library_version {
+            writeln!(f, "library version:\n\t{}", version)?;
+        }
+        for (check, outcome) in &self.checks {
+            match outcome {
+                CheckOutcome::Passed => writeln!(f, "{}: passed", check)?,
+                CheckOutcome::Skipped => writeln!(f, "{}: skipped", check)?,
+                CheckOutcome::Failed(e) => writeln!(f, "{}: failed{}", check, e)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// Implementation of `RootModule::validate_library`.
+pub(super) fn validate_library<M>(path: &Path) -> LoadReport
+where
+    M: RootModule,
+{
+    validate_library_with::<M>(path, &mut |_| {})
+}
+
+/// Does the checks of `RootModule::validate_library`,
+/// calling `on_check` after each check is added to the report.
+pub(super) fn validate_library_with<M>(
+    path: &Path,
+    on_check: &mut dyn FnMut(&LoadReport),
+) -> LoadReport
+where
+    M: RootModule,
+{
+    let mut builder = ReportBuilder {
+        report: LoadReport {
+            path: path.to_owned(),
+            module_name: M::NAME,
+            library_version: None,
+            checks: Vec::with_capacity(LoadCheck::ALL.len()),
+        },
+        on_check,
+    };
+
+    run_checks::<M>(&mut builder);
+
+    let mut report = builder.report;
+    for check in LoadCheck::ALL.iter().skip(report.checks.len()) {
+        report.checks.push((*check, CheckOutcome::Skipped));
+    }
+
+    report
+}
+
+struct ReportBuilder<'a> {
+    report: LoadReport,
+    on_check: &'a mut dyn FnMut(&LoadReport),
+}
+
+impl ReportBuilder<'_> {
+    fn push(&mut self, check: LoadCheck, outcome: CheckOutcome) {
+        self.report.checks.push((check, outcome));
+        (self.on_check)(&self.report);
+    }
+}
+
+fn run_checks<M>(builder: &mut ReportBuilder<'_>)
+where
+    M: RootModule,
+{
+    let raw_library = match RawLibrary::load_at(&builder.report.path) {
+        Ok(x) => x,
+        Err(e) => return builder.push(LoadCheck::OpenLibrary, CheckOutcome::Failed(e)),
+    };
+    builder.push(LoadCheck::OpenLibrary, CheckOutcome::Passed);
+
+    // The library is leaked, like `lib_header_from_path` does,
+    // since the `LibHeader` points into it.
+    let raw_library = crate::utils::leak_value(raw_library);
+
+    let header = match unsafe { abi_header_from_raw_library(raw_library) }
+        .and_then(AbiHeaderRef::lib_header_unchecked_c_abi)
+    {
+        Ok(x) => x,
+        Err(e) => return builder.push(LoadCheck::AbiHeader, CheckOutcome::Failed(e)),
+    };
+    builder.report.library_version = Some(header.version_strings().version.as_str().to_owned());
+    builder.push(LoadCheck::AbiHeader, CheckOutcome::Passed);
+
+    let c_abi_testing_fns = header.root_mod_consts().c_abi_testing_fns();
+    let c_abi = c_abi_testing::run_tests(c_abi_testing_fns);
+    builder.push(LoadCheck::CAbi, c_abi.into());
+
+    let version = header.check_version::<M>();
+    builder.push(LoadCheck::Version, version.into());
+
+    // The layout checker calls function pointers in the `TypeLayout`s of the library,
+    // so it's skipped if the C abi is different.
+    //
+    // The types of the library are recorded in local `CheckingGlobals`,
+    // so that validating a library doesn't affect how libraries are checked when loaded.
+    if builder.report.outcome(LoadCheck::CAbi).is_passed() {
+        let layout = header.ensure_layout_with_globals::<M>(&CheckingGlobals::new());
+        builder.push(LoadCheck::Layout, layout.into());
+    }
+}
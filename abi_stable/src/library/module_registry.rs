@@ -0,0 +1,400 @@
+use super::{load_observer::LoadTimer, root_mod_trait::root_module_from_raw_library, *};
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+};
+
+use parking_lot::{Mutex, ReentrantMutex};
+
+use crate::utils::leak_value;
+
+/// Loads the root modules of any number of dynamic libraries that export the same
+/// [`RootModule`] type, keyed by the canonical path of each library.
+///
+/// Unlike the [`RootModule`]`::load_from*` associated functions,
+/// which store a single root module per type in [`RootModuleStatics`],
+/// this returns an independent root module for every library.
+///
+/// Loading a library does the same checks as [`RootModule::load_from`],
+/// and leaks the library for the same reasons.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use abi_stable::{
+///     for_examples::Module_Ref,
+///     library::{LibraryError, ModuleRegistry},
+/// };
+///
+/// let registry = ModuleRegistry::<Module_Ref>::new();
+///
+/// let a = registry.load_from_file("plugins/liba.so".as_ref())?;
+/// let b = registry.load_from_file("plugins/libb.so".as_ref())?;
+///
+/// assert_eq!(registry.len(), 2);
+/// println!("{} {}", a.second(), b.second());
+///
+/// # Ok::<(), LibraryError>(())
+/// ```
+///
+/// [`RootModule`]: ./trait.RootModule.html
+/// [`RootModule::load_from`]: ./trait.RootModule.html#method.load_from
+/// [`RootModuleStatics`]: ./struct.RootModuleStatics.html
+pub struct ModuleRegistry<M> {
+    entries: Mutex<HashMap<PathBuf, RegistryEntry<M>>>,
+    // Serializes loading libraries, so that racing threads don't load the same library twice.
+    // It's reentrant so that the `RootModule::on_load` hook of a module,
+    // which runs while the library is being loaded, can use this registry.
+    loading: ReentrantMutex<()>,
+}
+
+struct RegistryEntry<M> {
+    module: M,
+    raw_library: &'static RawLibrary,
+}
+
+impl<M> ModuleRegistry<M>
+where
+    M: RootModule,
+{
+    /// Constructs an empty `ModuleRegistry`.
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            loading: ReentrantMutex::new(()),
+        }
+    }
+
+    /// Loads the root module of the library at `where_`,
+    /// first loading the dynamic library if it wasn't already loaded by this registry.
+    ///
+    /// Once the root module of a library is loaded,
+    /// this will return the already loaded root module for that library.
+    ///
+    /// Warnings and Errors are detailed in [`RootModule::load_from`].
+    ///
+    /// [`RootModule::load_from`]: ./trait.RootModule.html#method.load_from
+    pub fn load_from(&self, where_: LibraryPath<'_>) -> Result<M, LibraryError> {
+        self.load_from_with_options(where_, LoadOptions::new())
+    }
+
+    /// Loads the root module of the library at `where_`,
+    /// opening the dynamic library with the `options` options
+    /// if it wasn't already loaded by this registry.
+    ///
+    /// Once the root module of a library is loaded,
+    /// this will return the already loaded root module for that library,
+    /// ignoring `options`.
+    ///
+    /// The [`RootModule::on_load`] hook of the root module can use this registry,
+    /// including loading other libraries with it.
+    ///
+    /// Warnings and Errors are detailed in [`RootModule::load_from`].
+    ///
+    /// [`RootModule::load_from`]: ./trait.RootModule.html#method.load_from
+    /// [`RootModule::on_load`]: ./trait.RootModule.html#method.on_load
+    pub fn load_from_with_options(
+        &self,
+        where_: LibraryPath<'_>,
+        options: LoadOptions,
+    ) -> Result<M, LibraryError> {
+        let path = match where_ {
+            LibraryPath::Directory(directory) => M::get_library_path(directory),
+            LibraryPath::FullPath(full_path) => full_path.to_owned(),
+        };
+
+        self.get_or_load(&path, |timer| {
+            let raw_library = timer.stage(
+                LoadStage::OpenLibrary,
+                RawLibrary::load_at_with_options(&path, options),
+            )?;
+
+            // The library is leaked for the same reasons that `RootModule::load_from` does.
+            let raw_library = leak_value(raw_library);
+            let module = unsafe { root_module_from_raw_library::<M>(raw_library, timer)? };
+            lifecycle::register(module);
+
+            Ok(RegistryEntry {
+                module,
+                raw_library,
+            })
+        })
+    }
+
+    /// Gets the root module of the library at `path`,
+    /// calling `load` to load it if it wasn't already loaded.
+    ///
+    /// The `entries` lock isn't held while `load` runs,
+    /// since it calls the `RootModule::on_load` hook.
+    fn get_or_load<F>(&self, path: &Path, load: F) -> Result<M, LibraryError>
+    where
+        F: FnOnce(&mut LoadTimer<'_>) -> Result<RegistryEntry<M>, LibraryError>,
+    {
+        let key = canonical_path(path);
+
+        if let Some(module) = self.get_canonical(&key) {
+            return Ok(module);
+        }
+
+        let _loading = self.loading.lock();
+
+        // Another thread could have loaded the library while this one was waiting.
+        if let Some(module) = self.get_canonical(&key) {
+            return Ok(module);
+        }
+
+        let mut timer = LoadTimer::new::<M>(path);
+        let entry = load(&mut timer)?;
+        let module = entry.module;
+        self.entries.lock().insert(key, entry);
+        Ok(module)
+    }
+
+    fn get_canonical(&self, key: &Path) -> Option<M> {
+        self.entries.lock().get(key).map(|entry| entry.module)
+    }
+
+    /// Loads the root module of the library in the `directory` directory,
+    /// using [`RootModule::get_library_path`] to get its path.
+    ///
+    /// Warnings and Errors are detailed in [`RootModule::load_from`].
+    ///
+    /// [`RootModule::get_library_path`]: ./trait.RootModule.html#method.get_library_path
+    /// [`RootModule::load_from`]: ./trait.RootModule.html#method.load_from
+    pub fn load_from_directory(&self, directory: &Path) -> Result<M, LibraryError> {
+        self.load_from(LibraryPath::Directory(directory))
+    }
+
+    /// Loads the root module of the library at `path_`.
+    ///
+    /// Warnings and Errors are detailed in [`RootModule::load_from`].
+    ///
+    /// [`RootModule::load_from`]: ./trait.RootModule.html#method.load_from
+    pub fn load_from_file(&self, path_: &Path) -> Result<M, LibraryError> {
+        self.load_from(LibraryPath::FullPath(path_))
+    }
+
+    /// Gets the root module of the library at `path`,
+    /// returning None if it wasn't loaded by this registry.
+    pub fn get(&self, path: &Path) -> Option<M> {
+        self.get_canonical(&canonical_path(path))
+    }
+
+    /// Gets the RawLibrary of the library at `path`,
+    /// returning None if it wasn't loaded by this registry.
+    pub fn get_raw_library(&self, path: &Path) -> Option<&'static RawLibrary> {
+        self.entries
+            .lock()
+            .get(&canonical_path(path))
+            .map(|entry| entry.raw_library)
+    }
+
+    /// Returns the canonical paths of the loaded libraries,
+    /// along with their root modules.
+    pub fn modules(&self) -> Vec<(PathBuf, M)> {
+        self.entries
+            .lock()
+            .iter()
+            .map(|(path, entry)| (path.clone(), entry.module))
+            .collect()
+    }
+
+    /// The amount of libraries loaded by this registry.
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Whether no library has been loaded by this registry.
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+}
+
+impl<M> Default for ModuleRegistry<M>
+where
+    M: RootModule,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> Debug for ModuleRegistry<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.entries.lock().keys()).finish()
+    }
+}
+
+/// Gets the canonical form of `path`,
+/// falling back to `path` if it couldn't be canonicalized
+/// (in which case loading the library will fail with a `LibraryError::OpenError`).
+fn canonical_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_owned())
+}
+
+//////////////////////////////////////////////////////////////////////
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests {
+    use super::*;
+
+    use crate::{
+        for_examples::{Module, Module_Ref},
+        prefix_type::{PrefixRefTrait, PrefixTypeTrait},
+        std_types::RNone,
+    };
+
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Barrier,
+        },
+        thread,
+        time::Duration,
+    };
+
+    fn entry(third: usize) -> RegistryEntry<Module_Ref> {
+        RegistryEntry {
+            module: Module {
+                first: RNone,
+                second: "hello".into(),
+                third,
+            }
+            .leak_into_prefix(),
+            raw_library: leak_value(RawLibrary::this_program()),
+        }
+    }
+
+    #[test]
+    fn reentrant_loading() {
+        let registry = ModuleRegistry::<Module_Ref>::new();
+
+        // Uses the registry the way that a `RootModule::on_load` hook could.
+        let module = registry
+            .get_or_load("a".as_ref(), |_| {
+                assert!(registry.is_empty());
+                assert!(registry.get("a".as_ref()).is_none());
+
+                let dependency = registry
+                    .get_or_load("b".as_ref(), |_| Ok(entry(2)))
+                    .unwrap();
+                assert_eq!(dependency.third(), Some(2));
+                assert_eq!(registry.len(), 1);
+
+                Ok(entry(1))
+            })
+            .unwrap();
+
+        assert_eq!(module.third(), Some(1));
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.get("b".as_ref()).unwrap().third(), Some(2));
+    }
+
+    #[test]
+    fn concurrent_loading() {
+        const THREADS: usize = 4;
+
+        let registry = ModuleRegistry::<Module_Ref>::new();
+        let loads = AtomicUsize::new(0);
+        let barrier = Barrier::new(THREADS);
+
+        let modules = crossbeam_utils::thread::scope(|s| {
+            let handles = (0..THREADS)
+                .map(|i| {
+                    let (registry, loads, barrier) = (&registry, &loads, &barrier);
+                    s.spawn(move |_| {
+                        barrier.wait();
+                        registry.get_or_load("a".as_ref(), |_| {
+                            loads.fetch_add(1, Ordering::SeqCst);
+                            thread::sleep(Duration::from_millis(10));
+                            Ok(entry(i))
+                        })
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap().unwrap().to_prefix_ref().to_raw_ptr())
+                .collect::<Vec<_>>()
+        })
+        .unwrap();
+
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert!(modules.iter().all(|&m| m == modules[0]));
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn errors_are_not_stored() {
+        let registry = ModuleRegistry::<Module_Ref>::new();
+
+        let err = registry
+            .get_or_load("a".as_ref(), |_| {
+                Err(LibraryError::InvalidAbiHeader(AbiHeader::VALUE))
+            })
+            .err()
+            .unwrap();
+        assert!(
+            matches!(err, LibraryError::InvalidAbiHeader(_)),
+            "{:?}",
+            err
+        );
+        assert!(registry.is_empty());
+
+        let module = registry
+            .get_or_load("a".as_ref(), |_| Ok(entry(3)))
+            .unwrap();
+        assert_eq!(module.third(), Some(3));
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn deduplicated_by_canonical_path() {
+        let dir =
+            std::env::temp_dir().join(format!("abi_stable-module_registry-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        let file = dir.join("library.so");
+        std::fs::write(&file, b"not a library").unwrap();
+
+        let registry = ModuleRegistry::<Module_Ref>::new();
+        let loads = AtomicUsize::new(0);
+
+        let mut paths = vec![
+            file.clone(),
+            dir.join(".").join("library.so"),
+            dir.join("sub").join("..").join("library.so"),
+        ];
+        #[cfg(unix)]
+        {
+            let link = dir.join("link.so");
+            let _ = std::fs::remove_file(&link);
+            std::os::unix::fs::symlink(&file, &link).unwrap();
+            paths.push(link);
+        }
+
+        for path in &paths {
+            let module = registry
+                .get_or_load(path, |_| {
+                    loads.fetch_add(1, Ordering::SeqCst);
+                    Ok(entry(5))
+                })
+                .unwrap();
+            assert_eq!(module.third(), Some(5));
+            assert!(registry.get(path).is_some());
+        }
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.modules()[0].0, file.canonicalize().unwrap());
+
+        // Paths that can't be canonicalized are used as they are.
+        let missing = dir.join("missing.so");
+        assert!(registry.get(&missing).is_none());
+        registry.get_or_load(&missing, |_| Ok(entry(8))).unwrap();
+        assert_eq!(registry.get(&missing).unwrap().third(), Some(8));
+        assert_eq!(registry.len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,533 @@
+use super::*;
+
+use std::{
+    env,
+    ffi::OsString,
+    io::{self, Read, Write},
+    process::{self, Child, Command, ExitStatus, Stdio},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use super::load_report::validate_library_with;
+
+/// The environment variable with the path to the library that the child process checks.
+const LIBRARY_VAR: &str = "ABI_STABLE_PREFLIGHT_LIBRARY";
+
+/// The environment variable with the [`module_key`] of the root module
+/// that the child process checks.
+const MODULE_VAR: &str = "ABI_STABLE_PREFLIGHT_MODULE";
+
+/// The prefix of the lines that the child process writes to stdout,
+/// to tell them apart from anything else written to stdout (eg: by the library).
+const LINE_PREFIX: &str = "abi_stable-preflight\t";
+
+/// Does the checks of [`RootModule::validate_library`] in a child process,
+/// so that a library that crashes while it's being opened
+/// (eg: in its static initializers) can't crash the host.
+///
+/// The child process runs the current executable by default,
+/// or the [`program`](#method.program) helper binary,
+/// either of which must call [`run_preflight_child`] at the start of `main`
+/// for every [`RootModule`] type that libraries are checked for.
+///
+/// The library is not loaded by the process that does the preflight check,
+/// it has to be loaded afterwards if the [`LoadReport`] says that it's loadable.
+///
+/// # Errors of the checks
+///
+/// Errors can't be passed between processes,
+/// so the checks that fail in the child process have a
+/// `ExtendedLibraryError::PreflightCheckFailed` with the formatted error.
+///
+/// If the child process crashes (or times out) while doing a check,
+/// that check fails with a `ExtendedLibraryError::PreflightCrashed`,
+/// and the checks after it are skipped.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use abi_stable::{
+///     for_examples::Module_Ref,
+///     library::{run_preflight_child, LibraryError, PreflightCheck, RootModule},
+/// };
+///
+/// use std::time::Duration;
+///
+/// fn main() -> Result<(), LibraryError> {
+///     // Does the checks and exits when this is the child process of a `PreflightCheck`.
+///     run_preflight_child::<Module_Ref>();
+///
+///     let path = "plugins/libthird_party.so".as_ref();
+///
+///     let report = PreflightCheck::new()
+///         .timeout(Duration::from_secs(10))
+///         .run::<Module_Ref>(path)?;
+///
+///     if report.is_loadable() {
+///         let _module = Module_Ref::load_from_file(path)?;
+///     } else {
+///         eprintln!("{}", report);
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// [`RootModule::validate_library`]: ./trait.RootModule.html#method.validate_library
+/// [`RootModule`]: ./trait.RootModule.html
+/// [`LoadReport`]: ./struct.LoadReport.html
+/// [`run_preflight_child`]: ./fn.run_preflight_child.html
+#[derive(Debug, Clone, Default)]
+pub struct PreflightCheck {
+    program: Option<PathBuf>,
+    args: Vec<OsString>,
+    timeout: Option<Duration>,
+}
+
+impl PreflightCheck {
+    /// Constructs a `PreflightCheck` that re-executes the current executable,
+    /// without arguments or a timeout.
+    pub const fn new() -> Self {
+        Self {
+            program: None,
+            args: Vec::new(),
+            timeout: None,
+        }
+    }
+
+    /// Sets the program that the child process runs,
+    /// which must call [`run_preflight_child`] at the start of `main`.
+    ///
+    /// [`run_preflight_child`]: ./fn.run_preflight_child.html
+    pub fn program<P>(mut self, program: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.program = Some(program.into());
+        self
+    }
+
+    /// Adds a command line argument for the child process.
+    pub fn arg<A>(mut self, arg: A) -> Self
+    where
+        A: Into<OsString>,
+    {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Sets how long the child process can take to do the checks before it's killed,
+    /// failing the check that it was doing.
+    ///
+    /// By default there is no timeout.
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Does the checks for loading the `M` root module from the library at `path`
+    /// in a child process.
+    ///
+    /// # Errors
+    ///
+    /// This returns a `ExtendedLibraryError::PreflightError` if the child process
+    /// couldn't be spawned, or if it exited without doing the checks
+    /// (because it didn't call [`run_preflight_child`]).
+    ///
+    /// [`run_preflight_child`]: ./fn.run_preflight_child.html
+    pub fn run<M>(&self, path: &Path) -> Result<LoadReport, LibraryError>
+    where
+        M: RootModule,
+    {
+        let preflight_err = |err| ExtendedLibraryError::PreflightError {
+            library: path.to_owned(),
+            err,
+        };
+
+        let program = match &self.program {
+            Some(program) => program.clone(),
+            None => env::current_exe().map_err(preflight_err)?,
+        };
+
+        let mut child = Command::new(program)
+            .args(&self.args)
+            .env(LIBRARY_VAR, path)
+            .env(MODULE_VAR, module_key::<M>())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(preflight_err)?;
+
+        let stdout = read_in_thread(child.stdout.take());
+        let stderr = read_in_thread(child.stderr.take());
+
+        let status = wait_with_timeout(&mut child, self.timeout).map_err(preflight_err)?;
+
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+
+        report_from_output(
+            path,
+            M::NAME,
+            &String::from_utf8_lossy(&stdout),
+            status,
+            String::from_utf8_lossy(&stderr).into_owned(),
+        )
+    }
+}
+
+/// Does the checks requested by a [`PreflightCheck`] and exits the process,
+/// if this process is the child process of a `PreflightCheck` for the `M` root module,
+/// otherwise this does nothing.
+///
+/// This must be called at the start of `main` in the program that
+/// `PreflightCheck` runs (by default, the executable that uses `PreflightCheck`),
+/// for every [`RootModule`] type that libraries are checked for.
+///
+/// The outcome of the checks is written to stdout as each check is done,
+/// so that the parent process knows which check the child process crashed in.
+///
+/// # Example
+///
+/// ```rust
+/// use abi_stable::{for_examples::Module_Ref, library::run_preflight_child};
+///
+/// fn main() {
+///     run_preflight_child::<Module_Ref>();
+///
+///     // the rest of the program
+/// }
+/// ```
+///
+/// [`PreflightCheck`]: ./struct.PreflightCheck.html
+/// [`RootModule`]: ./trait.RootModule.html
+pub fn run_preflight_child<M>()
+where
+    M: RootModule,
+{
+    let path = match env::var_os(LIBRARY_VAR) {
+        Some(path) => PathBuf::from(path),
+        None => return,
+    };
+    if env::var_os(MODULE_VAR).map_or(true, |key| key != *module_key::<M>()) {
+        return;
+    }
+
+    write_line("start");
+
+    let mut wrote_version = false;
+    validate_library_with::<M>(&path, &mut |report| {
+        if let (false, Some(version)) = (wrote_version, &report.library_version) {
+            wrote_version = true;
+            write_line(&format!("version\t{}", escape(version)));
+        }
+        if let Some((check, outcome)) = report.checks.last() {
+            write_line(&encode_check(*check, outcome));
+        }
+    });
+
+    write_line("done");
+
+    process::exit(0);
+}
+
+/// Identifies the `M` root module type,
+/// since different root module types can have the same `RootModule::NAME`.
+fn module_key<M>() -> String
+where
+    M: RootModule,
+{
+    let layout = <M as StableAbi>::LAYOUT;
+    format!("{} ({})", M::NAME, layout.full_type())
+}
+
+/// Writes a line for the parent process, flushing it in case that the process crashes.
+fn write_line(line: &str) {
+    // Errors are ignored, since the parent process treats missing lines as a crash.
+    let mut stdout = io::stdout();
+    let _ = writeln!(stdout, "{}{}", LINE_PREFIX, line);
+    let _ = stdout.flush();
+}
+
+fn read_in_thread<R>(reader: Option<R>) -> JoinHandle<Vec<u8>>
+where
+    R: Read + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut reader) = reader {
+            // Whatever was read before an error is still used.
+            let _ = reader.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+/// Waits for the child process to exit,
+/// returning None if it was killed because it didn't exit before the timeout.
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Option<Duration>,
+) -> io::Result<Option<ExitStatus>> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return child.wait().map(Some),
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            // Killing fails if the process exited after `try_wait`,
+            // in which case it's still treated as timed out.
+            let _ = child.kill();
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+fn encode_check(check: LoadCheck, outcome: &CheckOutcome) -> String {
+    let index = LoadCheck::ALL.iter().position(|c| *c == check).unwrap();
+    match outcome {
+        CheckOutcome::Passed => format!("check\t{}\tpassed", index),
+        CheckOutcome::Skipped => format!("check\t{}\tskipped", index),
+        CheckOutcome::Failed(e) => {
+            format!("check\t{}\tfailed\t{}", index, escape(e.to_string().trim()))
+        }
+    }
+}
+
+fn decode_check(line: &str) -> Option<(LoadCheck, CheckOutcome)> {
+    let mut parts = line.splitn(3, '\t');
+    let check = *LoadCheck::ALL.get(parts.next()?.parse::<usize>().ok()?)?;
+    let outcome = match (parts.next()?, parts.next()) {
+        ("passed", None) => CheckOutcome::Passed,
+        ("skipped", None) => CheckOutcome::Skipped,
+        ("failed", Some(message)) => CheckOutcome::Failed(
+            ExtendedLibraryError::PreflightCheckFailed {
+                message: unescape(message),
+            }
+            .into(),
+        ),
+        _ => return None,
+    };
+    Some((check, outcome))
+}
+
+/// Builds the `LoadReport` from the output of the child process.
+fn report_from_output(
+    path: &Path,
+    module_name: &'static str,
+    stdout: &str,
+    status: Option<ExitStatus>,
+    stderr: String,
+) -> Result<LoadReport, LibraryError> {
+    let mut report = LoadReport {
+        path: path.to_owned(),
+        module_name,
+        library_version: None,
+        checks: Vec::with_capacity(LoadCheck::ALL.len()),
+    };
+
+    let mut started = false;
+    let mut done = false;
+
+    for line in stdout
+        .lines()
+        .filter_map(|line| line.strip_prefix(LINE_PREFIX))
+    {
+        if line == "start" {
+            started = true;
+        } else if line == "done" {
+            done = true;
+        } else if let Some(version) = line.strip_prefix("version\t") {
+            report.library_version = Some(unescape(version));
+        } else if let Some(check) = line.strip_prefix("check\t").and_then(decode_check) {
+            report.checks.push(check);
+        }
+    }
+
+    if !started {
+        let status = match status {
+            Some(status) => status.to_string(),
+            None => "timed out".to_string(),
+        };
+        let message = format!(
+            "the child process ({}) exited without doing the checks, \
+             it must call `run_preflight_child` at the start of `main`.\n\
+             stderr:\n{}",
+            status, stderr,
+        );
+        return Err(ExtendedLibraryError::PreflightError {
+            library: path.to_owned(),
+            err: io::Error::new(io::ErrorKind::Other, message),
+        }
+        .into());
+    }
+
+    if !done {
+        if let Some(check) = LoadCheck::ALL.get(report.checks.len()) {
+            let err = ExtendedLibraryError::PreflightCrashed {
+                library: path.to_owned(),
+                check: *check,
+                status,
+                stderr,
+            };
+            report
+                .checks
+                .push((*check, CheckOutcome::Failed(err.into())));
+        }
+    }
+
+    for check in LoadCheck::ALL.iter().skip(report.checks.len()) {
+        report.checks.push((*check, CheckOutcome::Skipped));
+    }
+
+    Ok(report)
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some(c) => out.push(c),
+            None => {}
+        }
+    }
+    out
+}
+
+//////////////////////////////////////////////////////////////////////
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests {
+    use super::*;
+
+    use crate::std_types::RBoxError;
+
+    fn output(lines: &[&str]) -> String {
+        lines
+            .iter()
+            .map(|line| format!("{}{}\n", LINE_PREFIX, line))
+            .collect()
+    }
+
+    #[test]
+    fn escaping() {
+        for s in ["", "hello", "a\tb\nc\r\\n\\", "\\\\\n\n"] {
+            assert_eq!(unescape(&escape(s)), s);
+            assert!(!escape(s).contains(['\n', '\t']));
+        }
+    }
+
+    #[test]
+    fn complete_output() {
+        let err = LibraryError::InvalidCAbi {
+            expected: RBoxError::from_fmt("foo"),
+            found: RBoxError::from_fmt("bar"),
+        };
+        let stdout = format!(
+            "library output\n{}",
+            output(&[
+                "start",
+                "check\t0\tpassed",
+                "version\t1.2.3",
+                "check\t1\tpassed",
+                &encode_check(LoadCheck::CAbi, &CheckOutcome::Failed(err)),
+                "check\t3\tpassed",
+                "done",
+            ])
+        );
+
+        let report =
+            report_from_output("foo.so".as_ref(), "foo", &stdout, None, String::new()).unwrap();
+
+        assert_eq!(report.library_version.as_deref(), Some("1.2.3"));
+        let failures = report.failures().collect::<Vec<_>>();
+        assert_eq!(failures.len(), 1);
+        match failures[0] {
+            (
+                LoadCheck::CAbi,
+                LibraryError::Extended(ExtendedLibraryError::PreflightCheckFailed { message }),
+            ) => {
+                assert!(
+                    message.contains("foo") && message.contains("bar"),
+                    "{}",
+                    message
+                );
+            }
+            x => panic!("{:?}", x),
+        }
+        assert!(report.outcome(LoadCheck::Version).is_passed());
+        assert!(matches!(
+            report.outcome(LoadCheck::Layout),
+            CheckOutcome::Skipped
+        ));
+    }
+
+    #[test]
+    fn crashed_output() {
+        let stdout = output(&["start", "check\t0\tpassed"]);
+
+        let report =
+            report_from_output("foo.so".as_ref(), "foo", &stdout, None, "oh no".into()).unwrap();
+
+        assert!(report.outcome(LoadCheck::OpenLibrary).is_passed());
+        match report.outcome(LoadCheck::AbiHeader).error() {
+            Some(LibraryError::Extended(ExtendedLibraryError::PreflightCrashed {
+                check: LoadCheck::AbiHeader,
+                status: None,
+                stderr,
+                ..
+            })) => assert_eq!(stderr, "oh no"),
+            x => panic!("{:?}", x),
+        }
+        for check in &LoadCheck::ALL[2..] {
+            assert!(matches!(report.outcome(*check), CheckOutcome::Skipped));
+        }
+    }
+
+    #[test]
+    fn not_started_output() {
+        let err = report_from_output("foo.so".as_ref(), "foo", "hello\n", None, String::new())
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                LibraryError::Extended(ExtendedLibraryError::PreflightError { .. })
+            ),
+            "{:?}",
+            err
+        );
+    }
+}
@@ -1,12 +1,29 @@
-use super::*;
+use super::{layout_cache::LibraryHash, *};
 
-use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::{
+    env::consts::{DLL_PREFIX, DLL_SUFFIX},
+    io,
+};
+
+#[cfg(target_os = "linux")]
+use std::{
+    fs::File,
+    os::unix::io::{AsRawFd, FromRawFd},
+};
 
 /// A handle to any dynamically loaded library,
 /// not necessarily ones that export abi_stable compatible modules.
 pub struct RawLibrary {
     path: PathBuf,
     library: LibLoadingLibrary,
+    // The hash of the library file, used by the layout cache.
+    library_hash: Option<LibraryHash>,
+    // The in-memory file that the library was loaded from,
+    // kept open so that its `/proc/self/fd/*` path isn't reused by another
+    // in-memory library while this one is loaded,
+    // since `dlopen` returns the already loaded library for a path.
+    #[cfg(target_os = "linux")]
+    _memfd: Option<File>,
 }
 
 impl RawLibrary {
@@ -36,10 +53,147 @@ impl RawLibrary {
     /// Loads the dynamic library at the `full_path` path.
     pub fn load_at(full_path: &Path) -> Result<Self, LibraryError> {
         // safety: not my problem if libraries have problematic static initializers
-        match unsafe { LibLoadingLibrary::new(full_path) } {
-            Ok(library) => Ok(Self {
+        Self::load_with(full_path, || {
+            layout_cache::open_hashed(full_path, || unsafe { LibLoadingLibrary::new(full_path) })
+        })
+    }
+
+    /// Loads the dynamic library at the `full_path` path,
+    /// opening it with the `options` options.
+    ///
+    /// On platforms that don't use `dlopen`, this ignores `options`,
+    /// behaving the same as [`load_at`](#method.load_at).
+    ///
+    /// This returns an `ExtendedLibraryError::UnsupportedLoadOptions` error
+    /// if `options` aren't supported on this platform
+    /// (see [`LoadOptions::is_supported`]).
+    ///
+    /// [`LoadOptions::is_supported`]: ./struct.LoadOptions.html#method.is_supported
+    pub fn load_at_with_options(
+        full_path: &Path,
+        options: LoadOptions,
+    ) -> Result<Self, LibraryError> {
+        options.check_supported(full_path)?;
+        Self::load_with(full_path, || {
+            layout_cache::open_hashed(full_path, || open_with_options(full_path, options))
+        })
+    }
+
+    /// Gets the synthetic path that identifies a library loaded from memory
+    /// with the `name` name, used in errors and in [`path`](#method.path).
+    pub fn in_memory_path(name: &str) -> PathBuf {
+        PathBuf::from(format!("memfd:{}", name))
+    }
+
+    /// Loads a dynamic library from `bytes`, an image of the library file,
+    /// without writing it to disk.
+    ///
+    /// The library is identified by the
+    /// [`in_memory_path`](#method.in_memory_path) of `name`.
+    ///
+    /// On Linux, this writes the image to an anonymous in-memory file
+    /// created with `memfd_create`, and then opens the library from that file.
+    ///
+    /// # Errors
+    ///
+    /// This will return these errors:
+    ///
+    /// - `ExtendedLibraryError::InMemoryLibraryError`:
+    ///     If the in-memory file couldn't be created,
+    ///     or on platforms other than Linux,
+    ///     where loading libraries from memory is not supported.
+    ///
+    /// - `LibraryError::OpenError`:
+    ///     If the dynamic library itself could not be loaded.
+    ///
+    pub fn load_from_bytes(name: &str, bytes: &[u8]) -> Result<Self, LibraryError> {
+        Self::load_from_bytes_with_options(name, bytes, LoadOptions::new())
+    }
+
+    /// Loads a dynamic library from `bytes`, an image of the library file,
+    /// opening it with the `options` options.
+    ///
+    /// Errors are detailed in [`load_from_bytes`](#method.load_from_bytes).
+    pub fn load_from_bytes_with_options(
+        name: &str,
+        bytes: &[u8],
+        options: LoadOptions,
+    ) -> Result<Self, LibraryError> {
+        let path = Self::in_memory_path(name);
+
+        #[cfg(target_os = "linux")]
+        {
+            options.check_supported(&path)?;
+
+            let file = memfd_with_contents(name, bytes).map_err(|err| {
+                ExtendedLibraryError::InMemoryLibraryError {
+                    path: path.clone(),
+                    err,
+                }
+            })?;
+
+            let fd_path = PathBuf::from(format!("/proc/self/fd/{}", file.as_raw_fd()));
+
+            let mut this = Self::load_with(&path, || {
+                open_with_options(&fd_path, options)
+                    .map(|library| (library, layout_cache::hash_bytes(bytes)))
+            })?;
+            this._memfd = Some(file);
+            Ok(this)
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = (bytes, options);
+            Err(ExtendedLibraryError::InMemoryLibraryError {
+                path,
+                err: io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "loading libraries from memory is only supported on Linux",
+                ),
+            }
+            .into())
+        }
+    }
+
+    /// The path that the library was loaded from,
+    /// or its synthetic path if it was loaded from memory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// A handle to the running program, for tests that need a `RawLibrary`.
+    #[cfg(all(test, not(feature = "only_new_tests")))]
+    pub(super) fn this_program() -> Self {
+        Self::load_with("this_program".as_ref(), || {
+            #[cfg(unix)]
+            let library = Ok(libloading::os::unix::Library::this());
+            #[cfg(windows)]
+            let library = libloading::os::windows::Library::this();
+
+            library.map(|library| (LibLoadingLibrary::from(library), None))
+        })
+        .unwrap()
+    }
+
+    /// The hash of the library file, computed when it was opened.
+    ///
+    /// This is None if no layout cache was set when the library was opened,
+    /// or if the file was modified while the library was being opened.
+    pub(super) const fn library_hash(&self) -> Option<LibraryHash> {
+        self.library_hash
+    }
+
+    fn load_with<F>(full_path: &Path, open: F) -> Result<Self, LibraryError>
+    where
+        F: FnOnce() -> Result<(LibLoadingLibrary, Option<LibraryHash>), libloading::Error>,
+    {
+        match open() {
+            Ok((library, library_hash)) => Ok(Self {
                 path: full_path.to_owned(),
                 library,
+                library_hash,
+                #[cfg(target_os = "linux")]
+                _memfd: None,
             }),
             Err(err) => Err(LibraryError::OpenError {
                 path: full_path.to_owned(),
@@ -71,3 +225,48 @@ impl RawLibrary {
         }
     }
 }
+
+fn open_with_options(
+    path: &Path,
+    options: LoadOptions,
+) -> Result<LibLoadingLibrary, libloading::Error> {
+    // safety: not my problem if libraries have problematic static initializers
+    #[cfg(unix)]
+    {
+        use libloading::os::unix::Library as UnixLibrary;
+
+        unsafe { UnixLibrary::open(Some(path), options.dlopen_flags()) }
+            .map(LibLoadingLibrary::from)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = options;
+        unsafe { LibLoadingLibrary::new(path) }
+    }
+}
+
+/// Creates an anonymous in-memory file that contains `bytes`.
+#[cfg(target_os = "linux")]
+fn memfd_with_contents(name: &str, bytes: &[u8]) -> io::Result<File> {
+    use std::{ffi::CString, io::Write};
+
+    let c_name = CString::new(name).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    // Using the syscall instead of `libc::memfd_create`,
+    // since the wrapper function was only added in glibc 2.27.
+    let fd = unsafe {
+        libc::syscall(
+            libc::SYS_memfd_create,
+            c_name.as_ptr(),
+            libc::MFD_CLOEXEC as std::os::raw::c_uint,
+        )
+    };
+    if fd == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // safety: the file descriptor was just created, and isn't owned by anything else.
+    let mut file = unsafe { File::from_raw_fd(fd as std::os::raw::c_int) };
+    file.write_all(bytes)?;
+    Ok(file)
+}
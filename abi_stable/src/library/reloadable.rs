@@ -0,0 +1,409 @@
+use super::{
+    lifecycle::LoadedId, load_observer::LoadTimer, root_mod_trait::root_module_from_raw_library, *,
+};
+
+use std::{
+    fmt::{self, Debug},
+    ops::Deref,
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+
+/// A handle to the root module of a dynamic library that can be unloaded
+/// and loaded again, usually to pick up a newer build of the library.
+///
+/// Unlike the [`RootModule`]`::load_from*` associated functions,
+/// this does not store the root module in [`RootModuleStatics`],
+/// nor does it leak the [`RawLibrary`].
+///
+/// # Unloading
+///
+/// [`unload`](#method.unload) and [`reload`](#method.reload)
+/// return a [`ExtendedLibraryError::UnloadBlocked`] while any [`ModuleLease`] is alive.
+///
+/// Leases are the only values that block unloading,
+/// the values that came from the library can't be detected,
+/// which is why [`unload`](#method.unload) and [`reload`](#method.reload) are unsafe.
+/// Every value from the library (`RBox`es, `RVec`s, `DynTrait`s, `RObject`s, etc)
+/// holds pointers to its vtables and destructors, which are in the library,
+/// but many of those values are created without running any code that could count them:
+///
+/// - `RVec::new`, `RString::new`, `DynTrait::from_const`, `RObject::with_vtable_const`,
+///   and the `from_const` constructor of `#[sabi_trait]` trait objects are `const fn`s,
+///   which embed the vtables of the library in the value.
+///
+/// - `'static` references and function pointers into the library,
+///   including copies of the root module (which is `Copy`,
+///   so it can be copied out of a [`ModuleLease`]).
+///
+/// # Lifecycle hooks
+///
+/// Before the library is unloaded (including when this is dropped),
+/// the [`RootModule::on_shutdown`] hook is called
+/// (if [`shutdown_all`] didn't already call it),
+/// followed by the [`RootModule::on_unload`] hook.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use abi_stable::{
+///     for_examples::Module_Ref,
+///     library::{LibraryError, ReloadableModule},
+/// };
+///
+/// let reloadable = ReloadableModule::<Module_Ref>::new("plugins/libexample.so");
+///
+/// {
+///     let lease = reloadable.load()?;
+///     // `second` is a string stored in the library
+///     println!("{}", lease.second());
+/// }
+///
+/// // Safety: nothing from the library outlived the lease.
+/// unsafe { reloadable.reload()? };
+///
+/// # Ok::<(), LibraryError>(())
+/// ```
+///
+/// [`RootModuleStatics`]: ./struct.RootModuleStatics.html
+/// [`RawLibrary`]: ./struct.RawLibrary.html
+/// [`ModuleLease`]: ./struct.ModuleLease.html
+/// [`ExtendedLibraryError::UnloadBlocked`]: ./enum.ExtendedLibraryError.html#variant.UnloadBlocked
+/// [`RootModule::on_shutdown`]: ./trait.RootModule.html#method.on_shutdown
+/// [`RootModule::on_unload`]: ./trait.RootModule.html#method.on_unload
+/// [`shutdown_all`]: ./fn.shutdown_all.html
+pub struct ReloadableModule<M> {
+    path: PathBuf,
+    options: LoadOptions,
+    loaded: Mutex<Option<LoadedModule<M>>>,
+}
+
+struct LoadedModule<M> {
+    module: M,
+    // Cloned into every `ModuleLease`, so that the leases can be counted.
+    leases: Arc<()>,
+    lifecycle: LoadedId,
+    // Only used to keep the library loaded.
+    _raw_library: RawLibrary,
+}
+
+impl<M> ReloadableModule<M>
+where
+    M: RootModule,
+{
+    /// Constructs a `ReloadableModule` for the library at the `full_path` path,
+    /// without loading it.
+    pub fn new<P>(full_path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self {
+            path: full_path.as_ref().to_owned(),
+            options: LoadOptions::new(),
+            loaded: Mutex::new(None),
+        }
+    }
+
+    /// Sets the options that the library is opened with every time that it's loaded.
+    pub fn with_load_options(mut self, options: LoadOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Constructs a `ReloadableModule` for the library in the `directory` directory,
+    /// using [`RootModule::get_library_path`] to get its path,
+    /// without loading it.
+    ///
+    /// [`RootModule::get_library_path`]: ./trait.RootModule.html#method.get_library_path
+    pub fn from_directory(directory: &Path) -> Self {
+        Self::new(M::get_library_path(directory))
+    }
+
+    /// The path that the library is loaded from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the library is currently loaded.
+    pub fn is_loaded(&self) -> bool {
+        self.loaded.lock().is_some()
+    }
+
+    /// Loads the library if it isn't loaded,
+    /// returning a lease over its root module.
+    ///
+    /// This does the same checks as [`RootModule::load_from`],
+    /// calling [`RootModule::initialization`] every time that the library is loaded.
+    ///
+    /// # Errors
+    ///
+    /// This returns the same errors as [`RootModule::load_from`].
+    ///
+    /// [`RootModule::load_from`]: ./trait.RootModule.html#method.load_from
+    /// [`RootModule::initialization`]: ./trait.RootModule.html#method.initialization
+    pub fn load(&self) -> Result<ModuleLease<M>, LibraryError> {
+        let mut guard = self.loaded.lock();
+        if let Some(loaded) = &*guard {
+            return Ok(loaded.lease());
+        }
+        let loaded = guard.insert(self.load_library()?);
+        Ok(loaded.lease())
+    }
+
+    /// Gets a lease over the root module,
+    /// returning None if the library is not loaded.
+    pub fn lease(&self) -> Option<ModuleLease<M>> {
+        self.loaded.lock().as_ref().map(LoadedModule::lease)
+    }
+
+    /// Returns the amount of [`ModuleLease`]s that currently prevent
+    /// the library from being unloaded.
+    ///
+    /// This returns 0 if the library is not loaded.
+    ///
+    /// Other values from the library don't prevent unloading it,
+    /// [more details here](#unloading).
+    ///
+    /// [`ModuleLease`]: ./struct.ModuleLease.html
+    pub fn lease_count(&self) -> usize {
+        self.loaded
+            .lock()
+            .as_ref()
+            .map_or(0, LoadedModule::lease_count)
+    }
+
+    /// Unloads the library, doing nothing if it is not loaded.
+    ///
+    /// # Safety
+    ///
+    /// Every value that came from the library,
+    /// including copies of the root module obtained from a [`ModuleLease`],
+    /// must have been dropped,
+    /// since using them after the library is unloaded is undefined behavior.
+    /// [More details here](#unloading).
+    ///
+    /// # Errors
+    ///
+    /// This returns a `ExtendedLibraryError::UnloadBlocked` if there are
+    /// any [`ModuleLease`]s alive.
+    ///
+    /// This returns a `ExtendedLibraryError::HookFailed` if the
+    /// [lifecycle hooks](#lifecycle-hooks) returned an error or panicked,
+    /// in which case the library is still unloaded.
+    ///
+    /// [`ModuleLease`]: ./struct.ModuleLease.html
+    pub unsafe fn unload(&self) -> Result<(), LibraryError> {
+        let mut guard = self.loaded.lock();
+        if let Some(loaded) = &*guard {
+            let leases = loaded.lease_count();
+            if leases != 0 {
+                return Err(ExtendedLibraryError::UnloadBlocked {
+                    library: self.path.clone(),
+                    leases,
+                }
+                .into());
+            }
+        }
+        match guard.take() {
+            Some(loaded) => lifecycle::unregister(loaded.lifecycle),
+            None => Ok(()),
+        }
+    }
+
+    /// Unloads the library if it's loaded, then loads it again.
+    ///
+    /// If the library could not be loaded again,
+    /// this `ReloadableModule` is left in the unloaded state.
+    ///
+    /// # Safety
+    ///
+    /// This has the same safety requirements as [`unload`](#method.unload).
+    ///
+    /// # Errors
+    ///
+    /// This returns the same errors as [`unload`](#method.unload)
+    /// and [`load`](#method.load).
+    pub unsafe fn reload(&self) -> Result<ModuleLease<M>, LibraryError> {
+        unsafe { self.unload()? };
+        self.load()
+    }
+
+    fn load_library(&self) -> Result<LoadedModule<M>, LibraryError> {
+        let mut timer = LoadTimer::new::<M>(&self.path);
+        let raw_library = timer.stage(
+            LoadStage::OpenLibrary,
+            RawLibrary::load_at_with_options(&self.path, self.options),
+        )?;
+
+        // safety: the module doesn't outlive `raw_library`,
+        // since `LoadedModule` only hands out the module through `ModuleLease`s,
+        // which prevent the library from being unloaded,
+        // and the callers of `unload` guarantee that nothing from the library is alive.
+        let module = unsafe { root_module_from_raw_library::<M>(&raw_library, &mut timer)? };
+
+        Ok(LoadedModule {
+            module,
+            leases: Arc::new(()),
+            lifecycle: lifecycle::register(module),
+            _raw_library: raw_library,
+        })
+    }
+}
+
+impl<M> Drop for LoadedModule<M> {
+    fn drop(&mut self) {
+        // Does nothing if `ReloadableModule::unload` already called the hooks.
+        let _ = lifecycle::unregister(self.lifecycle);
+    }
+}
+
+impl<M> LoadedModule<M>
+where
+    M: Copy,
+{
+    fn lease(&self) -> ModuleLease<M> {
+        ModuleLease {
+            module: self.module,
+            _lease: self.leases.clone(),
+        }
+    }
+}
+
+impl<M> LoadedModule<M> {
+    fn lease_count(&self) -> usize {
+        Arc::strong_count(&self.leases) - 1
+    }
+}
+
+impl<M> Debug for ReloadableModule<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReloadableModule")
+            .field("path", &self.path)
+            .field("options", &self.options)
+            .field("is_loaded", &self.loaded.lock().is_some())
+            .finish()
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// A lease over the root module of a [`ReloadableModule`],
+/// which prevents the library from being unloaded while it's alive.
+///
+/// This dereferences to the root module.
+/// Copies of the root module don't prevent unloading the library,
+/// so they must not be used after the lease is dropped,
+/// [more details here](./struct.ReloadableModule.html#unloading).
+///
+/// [`ReloadableModule`]: ./struct.ReloadableModule.html
+pub struct ModuleLease<M> {
+    module: M,
+    _lease: Arc<()>,
+}
+
+impl<M> Clone for ModuleLease<M>
+where
+    M: Copy,
+{
+    fn clone(&self) -> Self {
+        Self {
+            module: self.module,
+            _lease: self._lease.clone(),
+        }
+    }
+}
+
+impl<M> Deref for ModuleLease<M> {
+    type Target = M;
+
+    fn deref(&self) -> &M {
+        &self.module
+    }
+}
+
+impl<M> Debug for ModuleLease<M>
+where
+    M: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.module, f)
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests {
+    use super::*;
+
+    use crate::{
+        for_examples::{Module, Module_Ref},
+        prefix_type::PrefixTypeTrait,
+        std_types::RNone,
+    };
+
+    /// A `ReloadableModule` with a module that's already "loaded",
+    /// since loading one requires a dynamic library.
+    fn loaded_module(third: usize) -> ReloadableModule<Module_Ref> {
+        let module = Module {
+            first: RNone,
+            second: "hello".into(),
+            third,
+        }
+        .leak_into_prefix();
+
+        let reloadable = ReloadableModule::new("loaded/from/nowhere");
+        *reloadable.loaded.lock() = Some(LoadedModule {
+            module,
+            leases: Arc::new(()),
+            lifecycle: lifecycle::register(module),
+            _raw_library: RawLibrary::this_program(),
+        });
+        reloadable
+    }
+
+    #[test]
+    fn unloaded_module() {
+        let reloadable = ReloadableModule::<Module_Ref>::new("not/loaded");
+        assert_eq!(reloadable.path(), Path::new("not/loaded"));
+        assert!(!reloadable.is_loaded());
+        assert!(reloadable.lease().is_none());
+        assert_eq!(reloadable.lease_count(), 0);
+        unsafe { reloadable.unload().unwrap() };
+        assert!(!reloadable.is_loaded());
+    }
+
+    #[test]
+    fn leases_block_unloading() {
+        let reloadable = loaded_module(13);
+
+        let lease = reloadable.load().unwrap();
+        let other_lease = reloadable.lease().unwrap();
+        assert_eq!(lease.third(), Some(13));
+        assert_eq!(reloadable.lease_count(), 2);
+
+        let lease_clone = lease.clone();
+        assert_eq!(lease_clone.second().as_str(), "hello");
+        assert_eq!(reloadable.lease_count(), 3);
+        drop(lease);
+        drop(other_lease);
+
+        let err = unsafe { reloadable.unload() }.unwrap_err();
+        match err {
+            LibraryError::Extended(ExtendedLibraryError::UnloadBlocked { library, leases }) => {
+                assert_eq!(library, Path::new("loaded/from/nowhere"));
+                assert_eq!(leases, 1);
+            }
+            err => panic!("expected an UnloadBlocked error, found {:?}", err),
+        }
+        assert!(reloadable.is_loaded());
+
+        drop(lease_clone);
+        assert_eq!(reloadable.lease_count(), 0);
+
+        unsafe { reloadable.unload().unwrap() };
+        assert!(!reloadable.is_loaded());
+        assert_eq!(reloadable.lease_count(), 0);
+    }
+}
@@ -1,6 +1,6 @@
-use super::*;
+use super::{lifecycle::LifecycleHook, load_observer::LoadTimer, *};
 
-use crate::{prefix_type::PrefixRefTrait, utils::leak_value};
+use crate::{prefix_type::PrefixRefTrait, std_types::RBoxError, utils::leak_value};
 
 /// The root module of a dynamic library,
 /// which may contain other modules,function pointers,and static references.
@@ -52,6 +52,19 @@ pub trait RootModule: Sized + StableAbi + PrefixRefTrait + 'static {
     /// [`package_version_strings!()`](../macro.package_version_strings.html)
     const VERSION_STRINGS: VersionStrings;
 
+    /// The root modules that must be loaded before this one,
+    /// loaded by [`load_with_dependencies`](#method.load_with_dependencies).
+    ///
+    /// The other `load*` associated functions ignore this constant.
+    ///
+    /// This is stored in the dynamic library by the
+    /// [`export_root_module`](../attr.export_root_module.html) attribute,
+    /// and `load_with_dependencies` uses the dependencies stored in the library,
+    /// so the ones declared when the library was compiled are the ones that get loaded.
+    ///
+    /// For an example, look at [`ModuleDependency`](./struct.ModuleDependency.html).
+    const DEPENDENCIES: &'static [ModuleDependency] = &[];
+
     /// All the constants of this trait and supertraits.
     ///
     /// It can safely be used as a proxy for the associated constants of this trait.
@@ -71,6 +84,52 @@ pub trait RootModule: Sized + StableAbi + PrefixRefTrait + 'static {
         ..Self::CONSTANTS
     };
 
+    /// The policy used to decide whether the version number of a library is compatible
+    /// with `Self::VERSION_STRINGS`, checked every time that this module is loaded.
+    ///
+    /// The default implementation returns `VersionPolicy::default()`,
+    /// which requires the library to have the same major version,
+    /// and for 0.y.z versions, a minor version >= the expected one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use abi_stable::{
+    ///     library::{RootModule, VersionPolicy, VersionRule},
+    ///     sabi_types::{VersionNumber, VersionStrings},
+    ///     StableAbi,
+    /// };
+    ///
+    /// #[repr(C)]
+    /// #[derive(StableAbi)]
+    /// #[sabi(kind(Prefix(prefix_ref = Module_Ref)))]
+    /// pub struct Module {
+    ///     #[sabi(last_prefix_field)]
+    ///     pub first: u8,
+    /// }
+    ///
+    /// impl RootModule for Module_Ref {
+    ///     abi_stable::declare_root_module_statics! {Module_Ref}
+    ///     const BASE_NAME: &'static str = "example_root_module";
+    ///     const NAME: &'static str = "example_root_module";
+    ///     const VERSION_STRINGS: VersionStrings = VersionStrings::new("1.3.0");
+    ///
+    ///     fn version_policy() -> VersionPolicy {
+    ///         // Version 1.1.4 was released with a bug that corrupts data.
+    ///         VersionPolicy::new(VersionRule::AllowOlderMinor).block(VersionNumber {
+    ///             major: 1,
+    ///             minor: 1,
+    ///             patch: 4,
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// # fn main(){}
+    /// ```
+    fn version_policy() -> VersionPolicy {
+        VersionPolicy::default()
+    }
+
     /// Gets the statics for Self.
     ///
     /// To define this associated function use:
@@ -153,37 +212,102 @@ pub trait RootModule: Sized + StableAbi + PrefixRefTrait + 'static {
     /// - `LibraryError::IncompatibleVersionNumber`:
     ///     If the version number of the library is incompatible.
     ///
+    /// - `ExtendedLibraryError::RejectedVersionNumber`:
+    ///     If the version number of the library is denied by
+    ///     [`Self::version_policy`](#method.version_policy).
+    ///
     /// - `LibraryError::AbiInstability`:
-    ///     If the layout of the root module is not the expected one.
+    ///     If the layout of the root module is not the expected one,
+    ///     or the layout of a [`HostModule`](./trait.HostModule.html) used by the library
+    ///     is incompatible with the one provided by the host.
+    ///
+    /// - `ExtendedLibraryError::MissingHostModule`:
+    ///     If the library uses a host module that wasn't provided.
     ///
     /// - `LibraryError::RootModule` :
     ///     If the root module initializer returned an error or panicked.
     ///
+    /// - `ExtendedLibraryError::HookFailed` :
+    ///     If [`on_load`](#method.on_load) returned an error or panicked.
+    ///
     fn load_from(where_: LibraryPath<'_>) -> Result<Self, LibraryError> {
-        let statics = Self::root_module_statics();
-        statics.root_mod.try_init(|| {
-            let lib = statics.raw_lib.try_init(|| -> Result<_, LibraryError> {
-                let raw_library = load_raw_library::<Self>(where_)?;
-
-                // if the library isn't leaked
-                // it would cause any use of the module to be a use after free.
-                //
-                // By leaking the library
-                // this allows the root module loader to do anything that'd prevent
-                // sound library unloading.
-                Ok(leak_value(raw_library))
-            })?;
-            let items = unsafe { lib_header_from_raw_library(lib)? };
-
-            items.ensure_layout::<Self>()?;
-
-            // safety: the layout was checked in the code above,
-            unsafe {
-                items
-                    .init_root_module_with_unchecked_layout::<Self>()?
-                    .initialization()
-            }
-        })
+        Self::load_from_with_options(where_, LoadOptions::new())
+    }
+
+    /// Loads this module from the path specified by `where_`,
+    /// opening the dynamic library with the `options` options
+    /// if it wasn't already loaded.
+    ///
+    /// Once the root module is loaded,
+    /// this will return the already loaded root module,
+    /// ignoring `options`.
+    ///
+    /// Warnings and Errors are detailed in [`load_from`](#method.load_from),
+    ///
+    fn load_from_with_options(
+        where_: LibraryPath<'_>,
+        options: LoadOptions,
+    ) -> Result<Self, LibraryError> {
+        load_into_statics::<Self, _, _>(
+            || library_path::<Self>(where_),
+            |path| RawLibrary::load_at_with_options(path, options),
+        )
+    }
+
+    /// Loads this module from `bytes`, an image of the dynamic library file,
+    /// without writing the library to disk.
+    ///
+    /// The library is identified in errors by the
+    /// [`RawLibrary::in_memory_path`] of `name`,
+    /// and is loaded with [`RawLibrary::load_from_bytes`],
+    /// which is only supported on Linux.
+    ///
+    /// Once the root module is loaded,
+    /// this will return the already loaded root module,
+    /// ignoring `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// This returns the errors detailed in [`load_from`](#method.load_from),
+    /// and a `ExtendedLibraryError::InMemoryLibraryError` if the library image
+    /// couldn't be written to an in-memory file.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use abi_stable::{
+    ///     for_examples::Module_Ref,
+    ///     library::{LibraryError, RootModule},
+    /// };
+    ///
+    /// # fn read_from_archive(_: &str) -> Vec<u8> { Vec::new() }
+    /// let bytes: Vec<u8> = read_from_archive("plugins/libexample.so");
+    ///
+    /// let module = Module_Ref::load_from_bytes("libexample.so", &bytes)?;
+    ///
+    /// # Ok::<(), LibraryError>(())
+    /// ```
+    ///
+    /// [`RawLibrary::in_memory_path`]: ./struct.RawLibrary.html#method.in_memory_path
+    /// [`RawLibrary::load_from_bytes`]: ./struct.RawLibrary.html#method.load_from_bytes
+    fn load_from_bytes(name: &str, bytes: &[u8]) -> Result<Self, LibraryError> {
+        Self::load_from_bytes_with_options(name, bytes, LoadOptions::new())
+    }
+
+    /// Loads this module from `bytes`, an image of the dynamic library file,
+    /// opening the library with the `options` options.
+    ///
+    /// Warnings and Errors are detailed in [`load_from_bytes`](#method.load_from_bytes),
+    ///
+    fn load_from_bytes_with_options(
+        name: &str,
+        bytes: &[u8],
+        options: LoadOptions,
+    ) -> Result<Self, LibraryError> {
+        load_into_statics::<Self, _, _>(
+            || RawLibrary::in_memory_path(name),
+            |_| RawLibrary::load_from_bytes_with_options(name, bytes, options),
+        )
     }
 
     /// Loads this module from the directory specified by `where_`,
@@ -210,6 +334,94 @@ pub trait RootModule: Sized + StableAbi + PrefixRefTrait + 'static {
         Self::load_from(LibraryPath::FullPath(path_))
     }
 
+    /// Does all the checks that loading this module from the library at `path` would do,
+    /// reporting the outcome of each one.
+    ///
+    /// Unlike [`load_from_file`](#method.load_from_file),
+    /// this never calls the function that constructs the root module,
+    /// nor does it store anything in the statics of this module,
+    /// and the types of the library are not recorded for checking
+    /// the libraries loaded later,
+    /// so it can be used to check libraries before loading them.
+    ///
+    /// This leaks the dynamic library if it could be opened,
+    /// like [`lib_header_from_path`](./fn.lib_header_from_path.html) does.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use abi_stable::{for_examples::Module_Ref, library::RootModule};
+    ///
+    /// let report = Module_Ref::validate_library("plugins/libexample.so".as_ref());
+    ///
+    /// if !report.is_loadable() {
+    ///     eprintln!("{}", report);
+    /// }
+    /// ```
+    fn validate_library(path: &Path) -> LoadReport {
+        load_report::validate_library::<Self>(path)
+    }
+
+    /// Does the checks of [`validate_library`](#method.validate_library)
+    /// in a child process that re-executes the current executable,
+    /// so that a library that crashes while it's being opened can't crash this process.
+    ///
+    /// The current executable must call [`run_preflight_child`]`::<Self>()`
+    /// at the start of `main`.
+    /// To use a helper binary, or a timeout, use [`PreflightCheck`] directly.
+    ///
+    /// # Errors
+    ///
+    /// This returns the errors detailed in [`PreflightCheck::run`].
+    ///
+    /// [`run_preflight_child`]: ./fn.run_preflight_child.html
+    /// [`PreflightCheck`]: ./struct.PreflightCheck.html
+    /// [`PreflightCheck::run`]: ./struct.PreflightCheck.html#method.run
+    fn preflight_check(path: &Path) -> Result<LoadReport, LibraryError> {
+        PreflightCheck::new().run::<Self>(path)
+    }
+
+    /// Loads this module from the `directory` directory,
+    /// first loading all of its [`DEPENDENCIES`](#associatedconstant.DEPENDENCIES)
+    /// (and their dependencies) from the same directory.
+    ///
+    /// The dependencies of every root module are read from its library
+    /// (with [`dependencies_from_raw_library`](./fn.dependencies_from_raw_library.html)),
+    /// which opens the library without constructing its root module.
+    ///
+    /// The dependencies are looked up by name in `modules`,
+    /// and are loaded with their
+    /// [`load_from_directory`](#method.load_from_directory) function,
+    /// in an order where every root module is constructed after its dependencies.
+    ///
+    /// Before loading any root module,
+    /// this checks that the version numbers of all the libraries are compatible,
+    /// both with the version of their root module,
+    /// and with the versions required by the modules that depend on them.
+    ///
+    /// # Errors
+    ///
+    /// This returns the errors detailed in [`load_from`](#method.load_from),
+    /// from loading this module or any of its dependencies,
+    /// as well as these errors:
+    ///
+    /// - `ExtendedLibraryError::MissingDependency`:
+    ///     If a dependency is not in `modules`.
+    ///
+    /// - `ExtendedLibraryError::DependencyCycle`:
+    ///     If root modules depend on each other in a cycle.
+    ///
+    /// - `ExtendedLibraryError::IncompatibleDependency`:
+    ///     If the library version of a dependency is not compatible
+    ///     with the version required by a module that depends on it.
+    ///
+    /// # Example
+    ///
+    /// For an example, look at [`ModuleSet`](./struct.ModuleSet.html).
+    fn load_with_dependencies(directory: &Path, modules: &ModuleSet) -> Result<Self, LibraryError> {
+        dependencies::load_with_dependencies::<Self>(directory, modules)
+    }
+
     /// Defines behavior that happens once the module is loaded.
     ///
     /// This is ran in the `RootModule::load*` associated functions
@@ -219,18 +431,167 @@ pub trait RootModule: Sized + StableAbi + PrefixRefTrait + 'static {
     fn initialization(self) -> Result<Self, LibraryError> {
         Ok(self)
     }
+
+    /// Lifecycle hook called once for every library that this module is loaded from,
+    /// after [`initialization`](#method.initialization).
+    ///
+    /// If this returns an error or panics,
+    /// loading the module fails with a `ExtendedLibraryError::HookFailed`.
+    ///
+    /// The default implementation does nothing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use abi_stable::{
+    ///     library::RootModule, sabi_types::VersionStrings, std_types::RBoxError, StableAbi,
+    /// };
+    ///
+    /// #[repr(C)]
+    /// #[derive(StableAbi)]
+    /// #[sabi(kind(Prefix(prefix_ref = Plugin_Ref)))]
+    /// pub struct Plugin {
+    ///     pub start: extern "C" fn(),
+    ///     #[sabi(last_prefix_field)]
+    ///     pub flush: extern "C" fn(),
+    /// }
+    ///
+    /// impl RootModule for Plugin_Ref {
+    ///     abi_stable::declare_root_module_statics! {Plugin_Ref}
+    ///     const BASE_NAME: &'static str = "plugin";
+    ///     const NAME: &'static str = "plugin";
+    ///     const VERSION_STRINGS: VersionStrings = abi_stable::package_version_strings!();
+    ///
+    ///     fn on_load(self) -> Result<(), RBoxError> {
+    ///         self.start()();
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn on_shutdown(self) -> Result<(), RBoxError> {
+    ///         self.flush()();
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// # fn main(){}
+    /// ```
+    fn on_load(self) -> Result<(), RBoxError> {
+        Ok(())
+    }
+
+    /// Lifecycle hook called when the host is done with the module,
+    /// by [`shutdown_all`](./fn.shutdown_all.html),
+    /// or before unloading the library of a [`ReloadableModule`](./struct.ReloadableModule.html).
+    ///
+    /// This is called at most once for every library that this module was loaded from.
+    ///
+    /// The default implementation does nothing.
+    ///
+    /// For an example, look at [`on_load`](#method.on_load).
+    fn on_shutdown(self) -> Result<(), RBoxError> {
+        Ok(())
+    }
+
+    /// Lifecycle hook called right before the library of a
+    /// [`ReloadableModule`](./struct.ReloadableModule.html) is unloaded,
+    /// after [`on_shutdown`](#method.on_shutdown).
+    ///
+    /// This is never called for libraries that are leaked
+    /// (by the other ways to load root modules).
+    ///
+    /// The default implementation does nothing.
+    fn on_unload(self) -> Result<(), RBoxError> {
+        Ok(())
+    }
+}
+
+/// Loads the root module into the statics of `M`,
+/// opening the library at the path returned by `get_path` with `open`
+/// if it wasn't already loaded.
+fn load_into_statics<M, P, O>(get_path: P, open: O) -> Result<M, LibraryError>
+where
+    M: RootModule,
+    P: FnOnce() -> PathBuf,
+    O: FnOnce(&Path) -> Result<RawLibrary, LibraryError>,
+{
+    let statics = M::root_module_statics();
+    statics.root_mod.try_init(|| {
+        let path = get_path();
+        let mut timer = LoadTimer::new::<M>(&path);
+
+        let lib = statics.raw_lib.try_init(|| -> Result<_, LibraryError> {
+            let raw_library = timer.stage(LoadStage::OpenLibrary, open(&path))?;
+
+            // if the library isn't leaked
+            // it would cause any use of the module to be a use after free.
+            //
+            // By leaking the library
+            // this allows the root module loader to do anything that'd prevent
+            // sound library unloading.
+            Ok(leak_value(raw_library))
+        })?;
+
+        let module = unsafe { root_module_from_raw_library::<M>(lib, &mut timer)? };
+        lifecycle::register(module);
+        Ok(module)
+    })
 }
 
-/// Loads the raw library at `where_`
-fn load_raw_library<M>(where_: LibraryPath<'_>) -> Result<RawLibrary, LibraryError>
+/// Gets the path to the library at `where_`
+fn library_path<M>(where_: LibraryPath<'_>) -> PathBuf
 where
     M: RootModule,
 {
-    let path = match where_ {
+    match where_ {
         LibraryPath::Directory(directory) => M::get_library_path(directory),
         LibraryPath::FullPath(full_path) => full_path.to_owned(),
-    };
-    RawLibrary::load_at(&path)
+    }
+}
+
+/// Checks the library and constructs its root module,
+/// doing the same checks as `RootModule::load_from`,
+/// but without touching the statics of `M`.
+///
+/// Every stage after opening the library is reported to `timer`.
+///
+/// # Safety
+///
+/// The root module is implicitly tied to the lifetime of the library,
+/// it will contain dangling `'static` references if the library is dropped before it does.
+pub(super) unsafe fn root_module_from_raw_library<M>(
+    raw_library: &RawLibrary,
+    timer: &mut LoadTimer<'_>,
+) -> Result<M, LibraryError>
+where
+    M: RootModule,
+{
+    let items = timer.stage(LoadStage::AbiHeader, unsafe {
+        lib_header_from_raw_library(raw_library)
+    })?;
+
+    timer.stage(
+        LoadStage::Layout,
+        layout_cache::ensure_layout::<M>(raw_library, items),
+    )?;
+
+    timer.stage(LoadStage::Version, items.check_version::<M>())?;
+
+    timer.stage(LoadStage::HostModules, unsafe {
+        host_module::inject_host_modules(raw_library)
+    })?;
+
+    // safety: the layout and version were checked in the code above,
+    // the version is only checked once so that the `LoadStage::Version` stage
+    // is the only place where the version policy of `M` is used.
+    let module = unsafe { items.unchecked_layout::<M>() }
+        .map_err(RootModuleError::into_library_error::<M>)
+        .and_then(M::initialization)
+        .and_then(|module| {
+            lifecycle::call_hook(module, LifecycleHook::OnLoad)?;
+            Ok(module)
+        });
+
+    timer.stage(LoadStage::RootModule, module)
 }
 
 /// Gets the LibHeader of a library.
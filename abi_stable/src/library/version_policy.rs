@@ -0,0 +1,510 @@
+use super::*;
+
+use std::{
+    fmt::{self, Display},
+    ops::{Bound, RangeBounds},
+};
+
+/// Decides which version numbers of a library are compatible with
+/// the version number that the loader expects.
+///
+/// This is returned by [`RootModule::version_policy`],
+/// and checked every time that the root module is loaded.
+///
+/// # Order of checks
+///
+/// The version number of the library is checked in this order:
+///
+/// 1. If the version is in the [blocklist](#method.block),
+///     the library is rejected.
+///
+/// 2. If the version is in any of the [denied ranges](#method.deny),
+///     the library is rejected.
+///
+/// 3. If the version is in any of the [allowed ranges](#method.allow),
+///     the library is accepted.
+///
+/// 4. Otherwise, the library is accepted if the [`VersionRule`] accepts it.
+///
+/// # Example
+///
+/// ```rust
+/// use abi_stable::{
+///     library::{VersionPolicy, VersionRange, VersionRule},
+///     sabi_types::VersionNumber,
+/// };
+///
+/// const fn version(major: u32, minor: u32, patch: u32) -> VersionNumber {
+///     VersionNumber { major, minor, patch }
+/// }
+///
+/// let policy = VersionPolicy::new(VersionRule::AllowOlderMinor)
+///     .deny(VersionRange::new(..version(1, 2, 0)))
+///     .block(version(1, 4, 1));
+///
+/// let expected = version(1, 5, 0);
+///
+/// assert!(policy.check(expected, version(1, 3, 0)).is_ok());
+/// assert!(policy.check(expected, version(1, 6, 0)).is_ok());
+/// assert!(policy.check(expected, version(1, 1, 0)).is_err());
+/// assert!(policy.check(expected, version(1, 4, 1)).is_err());
+/// assert!(policy.check(expected, version(2, 0, 0)).is_err());
+///
+/// ```
+///
+/// [`RootModule::version_policy`]: ./trait.RootModule.html#method.version_policy
+/// [`VersionRule`]: ./enum.VersionRule.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionPolicy {
+    rule: VersionRule,
+    allowed: Vec<VersionRange>,
+    denied: Vec<VersionRange>,
+    blocklist: Vec<VersionNumber>,
+}
+
+impl VersionPolicy {
+    /// Constructs a `VersionPolicy` that only uses `rule` to check versions.
+    pub const fn new(rule: VersionRule) -> Self {
+        Self {
+            rule,
+            allowed: Vec::new(),
+            denied: Vec::new(),
+            blocklist: Vec::new(),
+        }
+    }
+
+    /// Accepts library versions in `range`,
+    /// even if the [`VersionRule`] of this policy doesn't.
+    ///
+    /// [`VersionRule`]: ./enum.VersionRule.html
+    pub fn allow(mut self, range: VersionRange) -> Self {
+        self.allowed.push(range);
+        self
+    }
+
+    /// Rejects library versions in `range`.
+    pub fn deny(mut self, range: VersionRange) -> Self {
+        self.denied.push(range);
+        self
+    }
+
+    /// Rejects the `version` library version, usually because it is known to be broken.
+    pub fn block(mut self, version: VersionNumber) -> Self {
+        self.blocklist.push(version);
+        self
+    }
+
+    /// The rule used for versions that aren't in any range of this policy.
+    pub const fn rule(&self) -> VersionRule {
+        self.rule
+    }
+
+    /// Checks whether the `actual` version number of a library is compatible with
+    /// the `expected` version number.
+    ///
+    /// # Errors
+    ///
+    /// This returns the reason why the version is rejected.
+    pub fn check(
+        &self,
+        expected: VersionNumber,
+        actual: VersionNumber,
+    ) -> Result<(), VersionRejection> {
+        if self.blocklist.contains(&actual) {
+            Err(VersionRejection::Blocked)
+        } else if let Some(range) = self.denied.iter().find(|r| r.contains(actual)) {
+            Err(VersionRejection::Denied(range.clone()))
+        } else if self.allowed.iter().any(|r| r.contains(actual))
+            || self.rule.is_compatible(expected, actual)
+        {
+            Ok(())
+        } else {
+            Err(VersionRejection::Incompatible(self.rule))
+        }
+    }
+
+    /// Checks the version of a library,
+    /// returning the same errors as loading the root module would.
+    ///
+    /// # Errors
+    ///
+    /// This returns:
+    ///
+    /// - `LibraryError::IncompatibleVersionNumber`:
+    ///     If the version isn't compatible according to the [`VersionRule`].
+    ///
+    /// - `ExtendedLibraryError::RejectedVersionNumber`:
+    ///     If the version is denied or blocked.
+    ///
+    /// [`VersionRule`]: ./enum.VersionRule.html
+    pub fn check_library(
+        &self,
+        library_name: &'static str,
+        expected_version: VersionNumber,
+        actual_version: VersionNumber,
+    ) -> Result<(), LibraryError> {
+        match self.check(expected_version, actual_version) {
+            Ok(()) => Ok(()),
+            Err(VersionRejection::Incompatible(_)) => {
+                Err(LibraryError::IncompatibleVersionNumber {
+                    library_name,
+                    expected_version,
+                    actual_version,
+                })
+            }
+            Err(reason) => Err(ExtendedLibraryError::RejectedVersionNumber {
+                library_name,
+                expected_version,
+                actual_version,
+                reason,
+            }
+            .into()),
+        }
+    }
+}
+
+impl Default for VersionPolicy {
+    /// Constructs a `VersionPolicy` with the [`VersionRule::Default`] rule.
+    ///
+    /// [`VersionRule::Default`]: ./enum.VersionRule.html#variant.Default
+    fn default() -> Self {
+        Self::new(VersionRule::Default)
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// The rule that a [`VersionPolicy`] uses for
+/// library versions that aren't in any of its ranges.
+///
+/// [`VersionPolicy`]: ./struct.VersionPolicy.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum VersionRule {
+    /// The rule that abi_stable uses by default,
+    /// the library must have the same major version,
+    /// and for 0.y.z versions it must have a minor version >= the expected one.
+    Default,
+    /// The library must have exactly the expected version.
+    Exact,
+    /// The library must have the same major version,
+    /// and a minor version >= the expected one,
+    /// where for 0.y.z versions, y is the major version and z is the minor version.
+    ///
+    /// This uses [`VersionNumber::is_compatible`].
+    ///
+    /// [`VersionNumber::is_compatible`]:
+    /// ../sabi_types/struct.VersionNumber.html#method.is_compatible
+    NewerMinor,
+    /// The library must have the same major version,
+    /// allowing minor versions older than the expected one,
+    /// for interface crates that promise to keep working with older implementations.
+    /// For 0.y.z versions, y is the major version and z is the minor version.
+    ///
+    /// This uses [`VersionNumber::is_loosely_compatible`].
+    ///
+    /// [`VersionNumber::is_loosely_compatible`]:
+    /// ../sabi_types/struct.VersionNumber.html#method.is_loosely_compatible
+    AllowOlderMinor,
+    /// Only the allowed ranges of the [`VersionPolicy`] are accepted.
+    ///
+    /// [`VersionPolicy`]: ./struct.VersionPolicy.html
+    OnlyAllowed,
+}
+
+impl VersionRule {
+    /// Whether the `actual` version of a library is compatible with `expected`,
+    /// according to this rule.
+    pub const fn is_compatible(self, expected: VersionNumber, actual: VersionNumber) -> bool {
+        match self {
+            VersionRule::Default => {
+                expected.major == actual.major
+                    && (expected.major != 0 || expected.minor <= actual.minor)
+            }
+            VersionRule::Exact => {
+                expected.major == actual.major
+                    && expected.minor == actual.minor
+                    && expected.patch == actual.patch
+            }
+            VersionRule::NewerMinor => expected.is_compatible(actual),
+            VersionRule::AllowOlderMinor => expected.is_loosely_compatible(actual),
+            VersionRule::OnlyAllowed => false,
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// A range of version numbers, used by [`VersionPolicy`].
+///
+/// # Example
+///
+/// ```rust
+/// use abi_stable::{library::VersionRange, sabi_types::VersionNumber};
+///
+/// const fn version(major: u32, minor: u32, patch: u32) -> VersionNumber {
+///     VersionNumber { major, minor, patch }
+/// }
+///
+/// let range = VersionRange::new(version(1, 2, 0)..version(1, 4, 0));
+///
+/// assert!(!range.contains(version(1, 1, 9)));
+/// assert!(range.contains(version(1, 2, 0)));
+/// assert!(range.contains(version(1, 3, 7)));
+/// assert!(!range.contains(version(1, 4, 0)));
+///
+/// ```
+///
+/// [`VersionPolicy`]: ./struct.VersionPolicy.html
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VersionRange {
+    start: Bound<VersionNumber>,
+    end: Bound<VersionNumber>,
+}
+
+impl VersionRange {
+    /// Constructs a `VersionRange` from any range of version numbers.
+    pub fn new<R>(range: R) -> Self
+    where
+        R: RangeBounds<VersionNumber>,
+    {
+        Self {
+            start: range.start_bound().cloned(),
+            end: range.end_bound().cloned(),
+        }
+    }
+
+    /// Constructs a `VersionRange` that only contains `version`.
+    pub const fn exactly(version: VersionNumber) -> Self {
+        Self {
+            start: Bound::Included(version),
+            end: Bound::Included(version),
+        }
+    }
+
+    /// Whether `version` is inside this range.
+    pub fn contains(&self, version: VersionNumber) -> bool {
+        RangeBounds::contains(self, &version)
+    }
+}
+
+impl RangeBounds<VersionNumber> for VersionRange {
+    fn start_bound(&self) -> Bound<&VersionNumber> {
+        bound_as_ref(&self.start)
+    }
+    fn end_bound(&self) -> Bound<&VersionNumber> {
+        bound_as_ref(&self.end)
+    }
+}
+
+// `Bound::as_ref` was stabilized after the minimum supported Rust version.
+const fn bound_as_ref(bound: &Bound<VersionNumber>) -> Bound<&VersionNumber> {
+    match bound {
+        Bound::Included(x) => Bound::Included(x),
+        Bound::Excluded(x) => Bound::Excluded(x),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+impl Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.start {
+            Bound::Included(x) => write!(f, "{}", x)?,
+            Bound::Excluded(x) => write!(f, "(excluded){}", x)?,
+            Bound::Unbounded => {}
+        }
+        match &self.end {
+            Bound::Included(x) => write!(f, "..={}", x),
+            Bound::Excluded(x) => write!(f, "..{}", x),
+            Bound::Unbounded => f.write_str(".."),
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+/// Why a [`VersionPolicy`] rejected the version of a library.
+///
+/// [`VersionPolicy`]: ./struct.VersionPolicy.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionRejection {
+    /// The version isn't compatible according to this rule.
+    Incompatible(VersionRule),
+    /// The version is inside this denied range.
+    Denied(VersionRange),
+    /// The version is in the blocklist.
+    Blocked,
+}
+
+impl Display for VersionRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionRejection::Incompatible(rule) => {
+                write!(f, "incompatible according to the {:?} rule", rule)
+            }
+            VersionRejection::Denied(range) => write!(f, "inside the denied {} range", range),
+            VersionRejection::Blocked => f.write_str("in the blocklist"),
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests {
+    use super::*;
+
+    const fn version(major: u32, minor: u32, patch: u32) -> VersionNumber {
+        VersionNumber {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    #[test]
+    fn rules() {
+        use self::VersionRule as VR;
+
+        let list = [
+            (version(0, 2, 0), version(0, 2, 0), [true, true, true, true]),
+            (
+                version(0, 2, 0),
+                version(0, 2, 5),
+                [true, false, true, true],
+            ),
+            (
+                version(0, 2, 5),
+                version(0, 2, 0),
+                [true, false, false, true],
+            ),
+            (
+                version(0, 2, 0),
+                version(0, 3, 0),
+                [true, false, false, false],
+            ),
+            (
+                version(0, 3, 0),
+                version(0, 2, 0),
+                [false, false, false, false],
+            ),
+            (version(1, 2, 0), version(1, 2, 0), [true, true, true, true]),
+            (
+                version(1, 2, 0),
+                version(1, 3, 0),
+                [true, false, true, true],
+            ),
+            (
+                version(1, 3, 0),
+                version(1, 2, 0),
+                [true, false, false, true],
+            ),
+            (
+                version(1, 0, 0),
+                version(2, 0, 0),
+                [false, false, false, false],
+            ),
+            (
+                version(2, 0, 0),
+                version(1, 0, 0),
+                [false, false, false, false],
+            ),
+        ];
+
+        for (expected, actual, [default, exact, newer_minor, older_minor]) in list {
+            let ctx = (expected, actual);
+            let rules = [
+                (VR::Default, default),
+                (VR::Exact, exact),
+                (VR::NewerMinor, newer_minor),
+                (VR::AllowOlderMinor, older_minor),
+                (VR::OnlyAllowed, false),
+            ];
+            for (rule, is_compatible) in rules {
+                assert_eq!(
+                    rule.is_compatible(expected, actual),
+                    is_compatible,
+                    "{:?} {:?}",
+                    rule,
+                    ctx
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ranges() {
+        let range = VersionRange::new(version(1, 2, 0)..=version(1, 4, 0));
+        assert!(!range.contains(version(1, 1, 9)));
+        assert!(range.contains(version(1, 2, 0)));
+        assert!(range.contains(version(1, 4, 0)));
+        assert!(!range.contains(version(1, 4, 1)));
+        assert_eq!(range.to_string(), "1.2.0..=1.4.0");
+
+        let range = VersionRange::new(version(0, 5, 0)..);
+        assert!(!range.contains(version(0, 4, 9)));
+        assert!(range.contains(version(0, 5, 0)));
+        assert!(range.contains(version(9, 0, 0)));
+        assert_eq!(range.to_string(), "0.5.0..");
+
+        let range = VersionRange::exactly(version(3, 1, 4));
+        assert!(!range.contains(version(3, 1, 3)));
+        assert!(range.contains(version(3, 1, 4)));
+        assert!(!range.contains(version(3, 1, 5)));
+    }
+
+    #[test]
+    fn policy_check_order() {
+        let expected = version(1, 5, 0);
+        let denied = VersionRange::new(version(1, 6, 0)..version(1, 8, 0));
+
+        let policy = VersionPolicy::new(VersionRule::Exact)
+            .allow(VersionRange::new(version(1, 0, 0)..version(2, 0, 0)))
+            .deny(denied.clone())
+            .block(version(1, 3, 2));
+
+        assert_eq!(policy.check(expected, version(1, 5, 0)), Ok(()));
+        assert_eq!(policy.check(expected, version(1, 0, 0)), Ok(()));
+        assert_eq!(policy.check(expected, version(1, 9, 3)), Ok(()));
+        assert_eq!(
+            policy.check(expected, version(1, 3, 2)),
+            Err(VersionRejection::Blocked)
+        );
+        assert_eq!(
+            policy.check(expected, version(1, 7, 0)),
+            Err(VersionRejection::Denied(denied))
+        );
+        assert_eq!(
+            policy.check(expected, version(2, 0, 0)),
+            Err(VersionRejection::Incompatible(VersionRule::Exact))
+        );
+    }
+
+    #[test]
+    fn check_library_errors() {
+        let policy = VersionPolicy::default().block(version(0, 4, 1));
+
+        assert!(policy
+            .check_library("foo", version(0, 4, 0), version(0, 4, 3))
+            .is_ok());
+
+        assert!(matches!(
+            policy.check_library("foo", version(0, 4, 0), version(0, 3, 0)),
+            Err(LibraryError::IncompatibleVersionNumber {
+                library_name: "foo",
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            policy.check_library("foo", version(0, 4, 0), version(0, 4, 1)),
+            Err(LibraryError::Extended(
+                ExtendedLibraryError::RejectedVersionNumber {
+                    library_name: "foo",
+                    reason: VersionRejection::Blocked,
+                    ..
+                }
+            ))
+        ));
+    }
+}
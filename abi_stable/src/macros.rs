@@ -191,6 +191,17 @@ macro_rules! check_unerased {
 /// `extern_fn_panic_handling!{no_early_return; <code here> }`,
 /// which *might* be cheaper(this has not been tested yet).
 ///
+/// # Catching panics
+///
+/// `extern_fn_panic_handling!{catch_unwind; <code here> }`
+/// catches panics instead of aborting,
+/// evaluating to an `RResult<T, RPanicPayload>`,
+/// with the value of the code in `ROk`,
+/// and the [`RPanicPayload`](./std_types/struct.RPanicPayload.html)
+/// of the panic in `RErr`.
+///
+/// This is what `#[sabi_extern_fn(catch_unwind)]` functions use.
+///
 /// # Example
 ///
 /// ```
@@ -258,9 +269,46 @@ macro_rules! check_unerased {
 ///
 /// ```
 ///
+/// # Example, catch_unwind
+///
+/// ```
+/// use abi_stable::{
+///     extern_fn_panic_handling,
+///     std_types::{RPanicPayload, RResult},
+/// };
+///
+/// pub extern "C" fn divide(l: u32, r: u32) -> RResult<u32, RPanicPayload> {
+///     extern_fn_panic_handling! {catch_unwind;
+///         if l == 0 {
+///             return 0;
+///         }
+///         l / r
+///     }
+/// }
+///
+/// assert_eq!(divide(0, 0).unwrap(), 0);
+/// assert_eq!(divide(10, 2).unwrap(), 5);
+///
+/// let payload = divide(10, 0).unwrap_err();
+/// assert!(payload.message().contains("divide by zero"));
+///
+/// ```
+///
 ///
 #[macro_export]
 macro_rules! extern_fn_panic_handling {
+    (catch_unwind; $($fn_contents:tt)* ) => (
+        $crate::extern_fn_panic_handling!{
+            no_early_return;
+            let a = $crate::marker_type::NotCopyNotClone;
+            $crate::std_types::RPanicPayload::catch_unwind(move||{
+                {a};
+                {
+                    $($fn_contents)*
+                }
+            })
+        }
+    );
     (no_early_return; $($fn_contents:tt)* ) => ({
         let aborter_guard = {
             use $crate::utils::{AbortBomb,PanicInfo};
@@ -398,12 +446,31 @@ macro_rules! assert_matches {
 
 /// Constructs an [`ItemInfo`], with information about the place where it's called.
 ///
+/// The `moved_from = "..."` argument is a sequence of `;old_package:OldName` entries,
+/// with the places that the type was moved from.
+///
+/// The `renamed_variants = "..."` argument is a sequence of
+/// `;VariantName=OldName,OlderName,` entries,
+/// with the names that enum variants were renamed from.
+///
 /// [`ItemInfo`]: ./type_layout/struct.ItemInfo.html
 #[macro_export]
 macro_rules! make_item_info {
     () => {
+        $crate::make_item_info!(moved_from = "", renamed_variants = "")
+    };
+    (moved_from = $moved_from:literal $(,)?) => {
+        $crate::make_item_info!(moved_from = $moved_from, renamed_variants = "")
+    };
+    (moved_from = $moved_from:literal, renamed_variants = $renamed_variants:literal $(,)?) => {
         $crate::type_layout::ItemInfo::new(
-            concat!(env!("CARGO_PKG_NAME"), ";", env!("CARGO_PKG_VERSION")),
+            concat!(
+                env!("CARGO_PKG_NAME"),
+                ";",
+                env!("CARGO_PKG_VERSION"),
+                $moved_from,
+                $renamed_variants
+            ),
             line!(),
             $crate::type_layout::ModPath::inside($crate::nulstr_trunc!(module_path!())),
         )
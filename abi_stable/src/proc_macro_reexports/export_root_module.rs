@@ -87,13 +87,123 @@ This attribute should not be used unconditionally,
 it should be disabled in Continuous Integration so that the 
 binary compatibility of a dynamic library is checked at some point before releasing it.
 
+# Build metadata
+
+Exporting the root module also creates a `#[no_mangle]` static with the
+[`BuildMetadata`] of the library,
+which records the name and version of the package that exports the root module,
+whether it was compiled with debug assertions,
+and the rustc version and target that the library was compiled with.
+
+The name used for this static is the value of
+[`abi_stable::library::BUILD_METADATA_NAME`](./library/constant.BUILD_METADATA_NAME.html),
+and it can be read from a library with [`build_metadata_from_path`].
+
+A git hash and arbitrary `&'static str` key-value pairs can be added to the metadata
+with the `#[build_metadata(...)]` attribute,
+which can be used any amount of times:
+
+```rust
+# use abi_stable::prefix_type::PrefixTypeTrait;
+#[abi_stable::export_root_module]
+#[build_metadata(git_hash = "0123abc")]
+#[build_metadata(custom("built_by" = "ci", "features" = "foo,bar"))]
+pub fn get_hello_world_mod() -> TextOperationsMod_Ref {
+    TextOperationsMod { reverse_string }.leak_into_prefix()
+}
+# #[repr(C)]
+# #[derive(abi_stable::StableAbi)]
+# #[sabi(kind(Prefix(prefix_ref= TextOperationsMod_Ref)))]
+# pub struct TextOperationsMod {
+#     #[sabi(last_prefix_field)]
+#     pub reverse_string: extern "C" fn(),
+# }
+# extern "C" fn reverse_string() {}
+# impl abi_stable::library::RootModule for TextOperationsMod_Ref {
+#     abi_stable::declare_root_module_statics!{TextOperationsMod_Ref}
+#     const BASE_NAME: &'static str = "stuff";
+#     const NAME: &'static str = "stuff";
+#     const VERSION_STRINGS: abi_stable::sabi_types::VersionStrings =
+#           abi_stable::package_version_strings!();
+# }
+# fn main(){}
+```
+
+The values can be any constant expression of type `&'static str`,
+eg: `env!("GIT_HASH")` to use an environment variable set by a build script.
+
+# Host modules
+
+The [`HostModule`]s that the library uses are listed in the `#[host_modules(...)]` attribute,
+which can be used any amount of times:
+
+```rust
+# use abi_stable::prefix_type::PrefixTypeTrait;
+#[abi_stable::export_root_module]
+#[host_modules(HostServices_Ref)]
+pub fn get_hello_world_mod() -> TextOperationsMod_Ref {
+    TextOperationsMod { reverse_string }.leak_into_prefix()
+}
+# #[repr(C)]
+# #[derive(abi_stable::StableAbi)]
+# #[sabi(kind(Prefix(prefix_ref= HostServices_Ref)))]
+# pub struct HostServices {
+#     #[sabi(last_prefix_field)]
+#     pub log: extern "C" fn(),
+# }
+# impl abi_stable::library::HostModule for HostServices_Ref {
+#     abi_stable::declare_host_module_statics!{HostServices_Ref}
+#     const NAME: &'static str = "host_services";
+# }
+# #[repr(C)]
+# #[derive(abi_stable::StableAbi)]
+# #[sabi(kind(Prefix(prefix_ref= TextOperationsMod_Ref)))]
+# pub struct TextOperationsMod {
+#     #[sabi(last_prefix_field)]
+#     pub reverse_string: extern "C" fn(),
+# }
+# extern "C" fn reverse_string() {}
+# impl abi_stable::library::RootModule for TextOperationsMod_Ref {
+#     abi_stable::declare_root_module_statics!{TextOperationsMod_Ref}
+#     const BASE_NAME: &'static str = "stuff";
+#     const NAME: &'static str = "stuff";
+#     const VERSION_STRINGS: abi_stable::sabi_types::VersionStrings =
+#           abi_stable::package_version_strings!();
+# }
+# fn main(){}
+```
+
+This exports a static with a [`HostModuleSlot`] for each of them,
+which the host uses to check the layout of the host modules and pass them to the library
+before calling the annotated function.
+
+# Dependencies
+
+Exporting the root module also creates a `#[no_mangle]` static with the
+[`RootModule::DEPENDENCIES`] of the root module,
+so that [`RootModule::load_with_dependencies`] can read them from the library
+without constructing the root module.
+
+The name used for this static is the value of
+[`abi_stable::library::MODULE_DEPENDENCIES_NAME`
+](./library/constant.MODULE_DEPENDENCIES_NAME.html),
+and it can be read from a library with [`dependencies_from_path`].
+
 # More examples
 
 For a more detailed example look in the README in the repository for this crate.
 
 
 
+[`BuildMetadata`]: ./library/struct.BuildMetadata.html
+[`build_metadata_from_path`]: ./library/fn.build_metadata_from_path.html
+[`dependencies_from_path`]: ./library/fn.dependencies_from_path.html
+[`RootModule::DEPENDENCIES`]: ./library/trait.RootModule.html#associatedconstant.DEPENDENCIES
+[`RootModule::load_with_dependencies`]:
+./library/trait.RootModule.html#method.load_with_dependencies
 [`IntoRootModuleResult`]: ./library/trait.IntoRootModuleResult.html
+[`HostModule`]: ./library/trait.HostModule.html
+[`HostModuleSlot`]: ./library/struct.HostModuleSlot.html
 [`LateStaticRef`]: ./sabi_types/struct.LateStaticRef.html
 [`LibHeader`]: ./library/struct.LibHeader.html
 
@@ -93,6 +93,42 @@ assert_eq!(hello(), vec!["hello".into_c(), "world".into()].into_c(),);
 
 ```
 
+# catch_unwind
+
+You can use `#[sabi_extern_fn(catch_unwind)]` to catch panics 
+instead of aborting the process,
+returning them to the caller as an [`RPanicPayload`] 
+(which has the message, location, and payload of the panic).
+
+This changes the return type of the function from `T` 
+(`()` if the return type is omitted) to `RResult<T, RPanicPayload>`,
+the caller can then handle the panic, or resume it with [`RPanicPayload::resume_unwind`].
+
+This can't be combined with `no_early_return`.
+
+### Example
+
+```rust
+use abi_stable::{
+    sabi_extern_fn,
+    std_types::{RPanicPayload, RStr},
+};
+
+#[sabi_extern_fn(catch_unwind)]
+pub fn parse_number(text: RStr<'_>) -> u64 {
+    text.as_str().parse().expect("not a number")
+}
+
+assert_eq!(parse_number("1000".into()).unwrap(), 1000);
+
+let payload: RPanicPayload = parse_number("hello".into()).unwrap_err();
+assert!(payload.message().starts_with("not a number"));
+
+```
+
+[`RPanicPayload`]: ./std_types/struct.RPanicPayload.html
+[`RPanicPayload::resume_unwind`]: 
+./std_types/struct.RPanicPayload.html#method.resume_unwind
 
 */
 #[doc(inline)]
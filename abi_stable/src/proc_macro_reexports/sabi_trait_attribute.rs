@@ -26,6 +26,12 @@ By default these are the supertraits that `#[sabi_trait]` traits can have:
 
 - `Unpin`
 
+- `serde::Serialize`: Written as `Serialize`, described in the
+    [serialization section](#serialization)
+
+- `serde::Deserialize`: Written as `Deserialize`, or `for<'a> Deserialize<'a>`,
+    described in the [serialization section](#serialization)
+
 To be able to have more supertraits you must use the `#[sabi(use_dyntrait)]` helper attribute,
 which changes the underlying implementation from [`RObject`] to [`DynTrait`],
 allowing these supertraits: 
@@ -54,7 +60,164 @@ allowing these supertraits:
 
 - `Hash`
 
+### `#[sabi_trait]` supertraits
+
+Traits declared with `#[sabi_trait]` can also be used as supertraits of
+other `#[sabi_trait]` traits(that don't use `#[sabi(use_dyntrait)]`).
+
+The trait object of the subtrait (`Trait_TO`) implements the supertrait,
+and can be converted into the trait object of the supertrait with `From`/`Into`,
+which reuses the same pointer and a vtable stored inside of the subtrait's vtable.
+
+The vtable of the supertrait is a prefix type,
+so it can be extended in minor versions the same way the vtable of the subtrait can.
+
+These are the restrictions on `#[sabi_trait]` supertraits:
+
+- The supertrait must be `'static` if and only if the subtrait is `'static`.
+
+- The `Supertrait_TO` trait object must be accessible at the same path as the supertrait,
+    by default it's reexported alongside the trait.
+
+- The supertraits of the supertrait must be repeated in the subtrait.
+
+- The supertrait can't have associated types.
+
+- Adding, removing, or reordering `#[sabi_trait]` supertraits is a breaking change.
+
+Any supertrait that isn't in the lists above is assumed to be a `#[sabi_trait]` trait,
+except for traits from `std`/`core`/`alloc`, which are an error.
+
+Example:
+
+```rust
+use abi_stable::{
+    sabi_trait,
+    sabi_trait::TD_Opaque,
+    std_types::{RBox, RString},
+};
+
+#[sabi_trait]
+pub trait Widget: Debug {
+    fn name(&self) -> RString;
+}
+
+#[sabi_trait]
+pub trait Button: Widget + Debug {
+    fn press(&mut self) -> u32;
+}
+
+#[derive(Debug)]
+struct Counter(u32);
+
+impl Widget for Counter {
+    fn name(&self) -> RString {
+        "counter".into()
+    }
+}
+
+impl Button for Counter {
+    fn press(&mut self) -> u32 {
+        self.0 += 1;
+        self.0
+    }
+}
+
+# fn main() {
+let mut button = Button_TO::from_value(Counter(0), TD_Opaque);
+
+assert_eq!(button.press(), 1);
+assert_eq!(button.name(), "counter");
+
+let widget: Widget_TO<'_, RBox<()>> = button.into();
+assert_eq!(widget.name(), "counter");
+# }
+```
+
+### Serialization
+
+`Serialize` and `Deserialize` supertraits are implemented by the trait object,
+using the same proxy-type design as [`DynTrait`],
+with the `Trait_Interface` type (declared in the `Trait_trait` module)
+as the interface type.
+
+They are not required of the types that implement the trait,
+instead these are the requirements:
 
+- `Serialize`: 
+    `Trait_Interface` must implement [`SerializeProxyType`],
+    and the types that the trait object is constructed from must implement
+    [`SerializeType`] with `Trait_Interface` as the `Interface`.
+
+- `Deserialize`:
+    `Trait_Interface` must implement [`DeserializeDyn`] for the trait object type
+    that's being deserialized.
+
+Example:
+
+```rust
+use abi_stable::{
+    erased_types::{DeserializeDyn, SerializeProxyType, SerializeType},
+    sabi_trait,
+    sabi_trait::TD_Opaque,
+    std_types::{RBox, RBoxError, RString},
+};
+
+#[sabi_trait]
+pub trait Plugin: Serialize + Deserialize {
+    fn count(&self) -> u32;
+}
+
+pub type PluginBox = Plugin_TO<'static, RBox<()>>;
+
+impl SerializeProxyType<'_> for Plugin_trait::Plugin_Interface {
+    type Proxy = RString;
+}
+
+impl<'de> DeserializeDyn<'de, PluginBox> for Plugin_trait::Plugin_Interface {
+    type Proxy = RString;
+
+    fn deserialize_dyn(s: RString) -> Result<PluginBox, RBoxError> {
+        // This would usually be a call to a function exported by a dynamic library.
+        match serde_json::from_str::<Counter>(&s) {
+            Ok(x) => Ok(Plugin_TO::from_value(x, TD_Opaque)),
+            Err(e) => Err(RBoxError::new(e)),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Counter {
+    count: u32,
+}
+
+impl Plugin for Counter {
+    fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+impl SerializeType<'_> for Counter {
+    type Interface = Plugin_trait::Plugin_Interface;
+
+    fn serialize_impl(&self) -> Result<RString, RBoxError> {
+        match serde_json::to_string(self) {
+            Ok(x) => Ok(x.into()),
+            Err(e) => Err(RBoxError::new(e)),
+        }
+    }
+}
+
+# fn main() {
+let plugin: PluginBox = Plugin_TO::from_value(Counter { count: 5 }, TD_Opaque);
+
+let json = serde_json::to_string(&plugin).unwrap();
+assert_eq!(json, r#""{\"count\":5}""#);
+
+let plugin: PluginBox = serde_json::from_str(&json).unwrap();
+assert_eq!(plugin.count(), 5);
+# }
+```
 
 ### Supertrait Extensibility
 
@@ -125,6 +288,11 @@ when the wrapped pointer type implements traits for these methods:
 
 <br>
 
+For every `#[sabi_trait]` supertrait, the trait object of that supertrait
+implements `From<Trait_TO<...>>`, so long as the pointer implements `AsPtr<PtrTarget = ()>`.
+
+<br>
+
 Trait_TO has these generic parameters(in order): 
 
 - `'trait_lifetime_n`: The lifetime parameters of the trait, if any.
@@ -200,6 +368,12 @@ These are the differences:
 - Lifetime supertraits are stripped, because they disallow the trait object to be 
     constructed with a reference of a smaller lifetime.
 
+- `Serialize` and `Deserialize` supertraits are stripped,
+    because they're only implemented by the trait object.
+
+- `async fn` methods are declared as normal methods that return an [`RFuture`],
+    as described in the [async methods](#async-methods) section.
+
 # VTable attributes
 
 To pass attributes to the generated vtable you can use the `#[sabi(  )]` attributes 
@@ -208,6 +382,10 @@ that are valid for `#[derive(StableAbi)]`.
 [Here is the documentation for the derive macro.
 ](./derive.StableAbi.html)
 
+For example, using `#[sabi(renamed_from = "old_name")]` on a method 
+allows renaming it in a semver compatible version, 
+since the name of the method is stored in the vtable's layout.
+
 # Trait attributes.
 
 These are attributes for the generated trait, applied on the trait(not on methods).
@@ -229,6 +407,49 @@ because the trait object comes from a previous version of the library.
 By using this attribute, defaulted methods will behave the same as 
 non-defaulted methods when they don't exist in the vtable.
 
+###  `#[sabi(catch_unwind)]`
+
+Makes the methods of the trait object resume panics in the caller,
+instead of aborting the process when the method implementation panics.
+
+The vtable entries of those methods catch the panic and return it as an 
+[`RPanicPayload`](./std_types/struct.RPanicPayload.html) 
+inside an `RResult`, which the trait object methods resume with 
+`RPanicPayload::resume_unwind`.
+
+This attribute can also be used on individual methods,
+to only catch panics in those methods.
+
+Adding or removing this attribute on a method changes the type of its vtable entry,
+so it's a breaking change for trait objects passed between dynamic libraries.
+
+Example:
+
+```rust
+use abi_stable::{sabi_trait, sabi_trait::TD_Opaque, std_types::RStr};
+
+#[sabi_trait]
+pub trait Parser {
+    #[sabi(catch_unwind)]
+    fn parse(&self, text: RStr<'_>) -> u64;
+}
+
+impl Parser for () {
+    fn parse(&self, text: RStr<'_>) -> u64 {
+        text.parse().expect("not a number")
+    }
+}
+
+# fn main() {
+let parser = Parser_TO::from_value((), TD_Opaque);
+
+assert_eq!(parser.parse("100".into()), 100);
+
+let res = std::panic::catch_unwind(|| parser.parse("hello".into()));
+assert!(res.is_err());
+# }
+```
+
 ### `#[sabi(debug_print_trait)]`
 
 Prints the output generated by the attribute macro,
@@ -260,6 +481,62 @@ Trait objects generated using this attribute have similar restrictions to built-
     this requires that the pointer that the generated trait object wraps 
     implements `abi_stable::pointer_trait::OwnedPointer`.
 
+# Async methods
+
+`async fn` methods are desugared into methods that return an [`RFuture`],
+an ffi-safe boxed future that can be driven by the executor of the caller,
+even if the future was created in a dynamic library.
+
+These are the differences from the declared `async fn`:
+
+- The method returns `RFuture<'_, Ret>` (borrowing from `self`),
+    where `Ret` is the declared return type.
+    Futures can only borrow the parameters that use the lifetime of `self`.
+
+- The default implementation (if any) becomes the body of the returned future,
+    as in `RFuture::new(async move { <default implementation> })`.
+
+Async methods must take a `&self` or `&mut self` receiver.
+
+Because `RFuture` is `Send`,the futures that the methods return must be `Send`.
+Traits with default async methods usually need a `Sync` supertrait 
+(`Send` for `&mut self` methods) for this.
+
+Example:
+
+```rust
+use abi_stable::{
+    sabi_trait,
+    sabi_trait::TD_Opaque,
+    sabi_types::RFuture,
+    std_types::{RString, RVec},
+};
+
+#[sabi_trait]
+pub trait Storage: Send + Sync {
+    async fn load(&self, index: u32) -> RString;
+
+    async fn load_upper(&self, index: u32) -> RString {
+        self.load(index).await.to_uppercase().into()
+    }
+}
+
+pub struct Memory(RVec<RString>);
+
+impl Storage for Memory {
+    fn load(&self, index: u32) -> RFuture<'_, RString> {
+        RFuture::new(async move { self.0[index as usize].clone() })
+    }
+}
+
+# fn main() {
+let storage = Storage_TO::from_value(Memory(vec!["hello".into()].into()), TD_Opaque);
+
+// The returned futures are polled like any other future.
+let _: RFuture<'_, RString> = storage.load_upper(0);
+# }
+```
+
 # Questions and Answers
 
 **Question: ** Why does Calling from_ptr/from_value give me a expected a `'static` value error?
@@ -508,6 +785,10 @@ impl Foo for SomeDay {}
 [`RObject::shallow_clone`]: crate::sabi_trait::RObject::shallow_clone
 [`DynTrait::shallow_clone`]: crate::DynTrait::shallow_clone
 [`DynTrait`]: crate::DynTrait
+[`SerializeProxyType`]: crate::erased_types::SerializeProxyType
+[`SerializeType`]: crate::erased_types::SerializeType
+[`DeserializeDyn`]: crate::erased_types::DeserializeDyn
+[`RFuture`]: crate::sabi_types::RFuture
 [`RBox<()>`]: ./std_types/struct.RBox.html
 [`RArc<()>`]: ./std_types/struct.RArc.html
 [`RArc`]: ./std_types/struct.RArc.html
@@ -250,6 +250,34 @@ but doesn't check their layout.
 This is unsafe because the layout of their type won't be verified when loading the library,
 which causes Undefined Behavior if the type has a different layout.
 
+###  `#[sabi(moved_from(package = "old_package", name = "OldName"))]`
+
+Declares that the type used to be defined in the `old_package` package with the `OldName` name,
+so that layout checking accepts the old package and name in place of the current ones.
+
+This allows moving types between crates(and renaming them) in semver compatible versions,
+since the package, name, and package version of a type are otherwise part of its layout.
+The package version is only checked when both types come from the same package.
+
+The `name = "..."` argument is optional, defaulting to the current name of the type.
+
+This attribute can be used multiple times on the same type,
+once for each place that the type was moved from.
+
+```rust
+use abi_stable::StableAbi;
+
+#[repr(C)]
+#[derive(StableAbi)]
+#[sabi(moved_from(package = "myapp_interface"))]
+#[sabi(moved_from(package = "myapp_interface", name = "OldPoint"))]
+pub struct Point {
+    pub x: u32,
+    pub y: u32,
+}
+
+```
+
 # Field attributes
 
 These helper attributes are applied to fields.
@@ -456,6 +484,44 @@ fn VariantNamed_NE(
 }
 ```
 
+# Field and/or Variant attributes
+
+###  `#[sabi(renamed_from = "old_name")]` 
+
+Declares that the field or variant used to be named `old_name`,
+so that layout checking accepts the old name in place of the current one.
+
+This allows renaming fields and variants in semver compatible versions,
+since the names of fields and variants are otherwise part of the layout of a type.
+
+This attribute can be used multiple times on the same field or variant,
+once for each previous name.
+
+When used on the methods of a trait with the [`sabi_trait`] attribute,
+this declares the previous names of the method in the vtable.
+
+```rust
+use abi_stable::StableAbi;
+
+#[repr(C)]
+#[derive(StableAbi)]
+pub struct Point {
+    pub x: u32,
+    #[sabi(renamed_from = "why")]
+    pub y: u32,
+}
+
+#[repr(u8)]
+#[derive(StableAbi)]
+pub enum Direction {
+    #[sabi(renamed_from = "Up")]
+    North,
+    #[sabi(renamed_from = "Down")]
+    South,
+}
+
+```
+
 
 
 # Supported repr attributes
@@ -563,6 +629,7 @@ For examples of using both [`#[derive(GetStaticEquivalent)]`][derive@GetStaticEq
 [`PrefixTypeTrait`]: crate::prefix_type::PrefixTypeTrait
 [`ExtraChecks`]: crate::abi_stability::extra_checks::ExtraChecks
 [`InterfaceType`]: crate::InterfaceType
+[`sabi_trait`]: macro@crate::sabi_trait
 
 */
 #[doc(inline)]
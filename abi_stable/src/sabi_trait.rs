@@ -13,26 +13,32 @@ pub mod reexports {
     pub mod __sabi_re {
         pub use abi_stable::{
             erased_types::{
-                DynTrait, MakeVTable as MakeDynTraitVTable, VTable_Ref as DynTraitVTable_Ref,
+                DeserializeDyn, DynTrait, MakeVTable as MakeDynTraitVTable,
+                VTable_Ref as DynTraitVTable_Ref,
             },
             extern_fn_panic_handling,
             marker_type::{
                 NonOwningPhantom, SyncSend, SyncUnsend, UnsafeIgnoredType, UnsyncSend, UnsyncUnsend,
             },
             pointer_trait::{AsMutPtr, AsPtr, CanTransmuteElement, OwnedPointer, TransmuteElement},
-            prefix_type::{PrefixRef, PrefixTypeTrait, WithMetadata},
+            prefix_type::{PrefixRef, PrefixRefTrait, PrefixTypeTrait, WithMetadata},
             sabi_trait::{
                 robject::RObject,
-                vtable::{GetRObjectVTable, RObjectVtable, RObjectVtable_Ref},
+                vtable::{
+                    GetRObjectVTable, GetSupertraitVTable, MakeSabiTraitVTable, RObjectVtable,
+                    RObjectVtable_Ref, SabiTraitObject, SupertraitVTable,
+                },
             },
-            sabi_types::{MovePtr, RMut, RRef},
-            std_types::RBox,
+            sabi_types::{MovePtr, RFuture, RMut, RRef},
+            std_types::{RBox, RPanicPayload, RResult},
             traits::IntoInner,
             utils::take_manuallydrop,
         };
 
         pub use core_extensions::{utils::transmute_ignore_size, TypeIdentity};
 
+        pub use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize, Serializer};
+
         pub use std::{
             marker::PhantomData,
             mem::{transmute, ManuallyDrop},
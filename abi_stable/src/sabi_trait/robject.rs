@@ -5,16 +5,21 @@ use std::fmt;
 #[allow(unused_imports)]
 use core_extensions::SelfOps;
 
+use serde::{ser, Serialize, Serializer};
+
 use crate::{
     abi_stability::PrefixStableAbi,
-    erased_types::{c_functions::adapt_std_fmt, InterfaceType, MakeRequiredTraits},
+    erased_types::{
+        c_functions::adapt_std_fmt, traits::GetSerializeProxyType, InterfaceType,
+        MakeRequiredTraits,
+    },
     pointer_trait::{
         AsMutPtr, AsPtr, CanTransmuteElement, GetPointerKind, PK_Reference, PK_SmartPointer,
         PointerKind, TransmuteElement,
     },
     sabi_trait::vtable::{BaseVtable_Prefix, BaseVtable_Ref},
     sabi_types::{MaybeCmp, RMut, RRef},
-    std_types::UTypeId,
+    std_types::{RBoxError, UTypeId},
     type_level::{
         impl_enum::{Implemented, Unimplemented},
         trait_marker,
@@ -52,6 +57,11 @@ use crate::{
 ///
 /// - [`Clone`]
 ///
+/// - [`serde::Serialize`]:
+///     first serializes the object into the proxy type of the `Interface`
+///     (using [`SerializeType`](crate::erased_types::SerializeType)),
+///     then serializes the proxy.
+///
 /// # Deconstruction
 ///
 /// `RObject<_>` can be unwrapped into a concrete type,
@@ -214,6 +224,30 @@ where
 {
 }
 
+/// `#[sabi_trait]` trait objects with a `Serialize` supertrait delegate to this impl.
+///
+/// For an example of how to make a type serializable through a proxy type,
+/// [look here](crate::erased_types::SerializeType#example)
+impl<'borr, P, I, V> Serialize for RObject<'borr, P, I, V>
+where
+    P: AsPtr<PtrTarget = ()>,
+    I: InterfaceType<Serialize = Implemented<trait_marker::Serialize>>,
+    I: GetSerializeProxyType<'borr>,
+    I::ProxyType: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        unsafe {
+            self.sabi_robject_vtable().serialize()(self.sabi_erased_ref_unbounded_lifetime())
+                .into_result()
+                .map_err(ser::Error::custom)?
+                .serialize(serializer)
+        }
+    }
+}
+
 unsafe impl<P, I, V> Send for RObject<'_, P, I, V>
 where
     P: GetPointerKind,
@@ -746,7 +780,7 @@ where
     }
 }
 
-impl<P, I, V> RObject<'_, P, I, V>
+impl<'lt, P, I, V> RObject<'lt, P, I, V>
 where
     P: GetPointerKind,
 {
@@ -776,6 +810,13 @@ where
         unsafe { RRef::from_raw(self.ptr.as_ptr() as *const _) }
     }
 
+    unsafe fn sabi_erased_ref_unbounded_lifetime<'a>(&self) -> RRef<'a, ErasedObject<()>>
+    where
+        P: AsPtr<PtrTarget = ()>,
+    {
+        unsafe { RRef::from_raw(self.ptr.as_ptr() as *const _) }
+    }
+
     /// Gets an `RMut` pointing to the erased object.
     pub fn sabi_erased_mut(&mut self) -> RMut<'_, ErasedObject<()>>
     where
@@ -809,6 +850,44 @@ where
     {
         OwnedPointer::with_move_ptr(self.sabi_into_erased_ptr(), f)
     }
+
+    /// Serializes this `RObject` into the proxy type of the `I` interface,
+    /// using `<ConcreteType as SerializeType>::serialize_impl`.
+    // I'm using the lifetime in the where clause, clippy <_<
+    #[allow(clippy::needless_lifetimes)]
+    pub fn serialize_into_proxy<'a>(&'a self) -> Result<I::ProxyType, RBoxError>
+    where
+        P: AsPtr<PtrTarget = ()>,
+        I: InterfaceType<Serialize = Implemented<trait_marker::Serialize>>,
+        I: GetSerializeProxyType<'a>,
+    {
+        unsafe { self.sabi_robject_vtable().serialize()(self.sabi_erased_ref()).into_result() }
+    }
+
+    /// Converts this RObject into one with a different interface and vtable,
+    /// pointing to the same erased object.
+    ///
+    /// This is mostly intended to be called by `#[sabi_trait]` generated trait objects,
+    /// to convert a trait object into one of its supertraits.
+    ///
+    /// # Safety
+    ///
+    /// `vtable` must have the same requirements as the vtable passed to
+    /// `RObject::with_vtable`,and must have been constructed for the same
+    /// `Self` type, pointer type, and `Downcasting` parameter as the vtable of this RObject.
+    ///
+    /// `I2` must describe the traits that the `RObjectVtable_Ref` in `vtable` was
+    /// constructed for.
+    pub unsafe fn sabi_upcast_with_vtable<I2, V2>(
+        self,
+        vtable: PrefixRef<V2>,
+    ) -> RObject<'lt, P, I2, V2> {
+        RObject {
+            vtable,
+            ptr: self.sabi_into_erased_ptr(),
+            _marker: PhantomData,
+        }
+    }
 }
 
 impl<I, V> RObject<'_, crate::std_types::RArc<()>, I, V> {
@@ -13,8 +13,9 @@ use std::{
 };
 
 use crate::{
+    erased_types::{DeserializeDyn, SerializeProxyType, SerializeType},
     sabi_trait,
-    std_types::RBox,
+    std_types::{RBox, RBoxError, RString},
     test_utils::{GetImpls, GetImplsHelper},
     type_level::downcasting::{TD_CanDowncast, TD_Opaque},
 };
@@ -276,73 +277,104 @@ pub mod only_debug {
     }
 }
 
-// pub mod only_serialize{
-//     use super::*;
-
-//     #[sabi_trait]
-//     #[sabi(use_dyntrait)]
-//     pub trait Trait:Serialize{
-//         fn method(&self){}
-//     }
-
-//     #[derive(::serde::Serialize)]
-//     pub struct Struct;
-
-//     impl Trait for Struct{}
-
-//     impl SerializeType for Struct {
-//         fn serialize_impl<'a>(&'a self) -> Result<RCow<'a, str>, RBoxError>{
-//             Ok(RCow::from("Struct"))
-//         }
-//     }
-
-//     fn assert_bound<T>(_:&T)
-//     where
-//         T:serde::Serialize
-//     {}
-
-//     fn test_constructible(){
-//         let object=Trait_TO::from_value(Struct,TD_CanDowncast);
-//         object.method();
-//         assert_bound(&object);
-//     }
-// }
-
-// pub mod only_deserialize_a{
-//     use super::*;
-//     use serde::Deserialize;
-
-//     #[sabi_trait]
-//     #[sabi(use_dyntrait)]
-//     //#[sabi(debug_print_trait)]
-//     pub trait Trait: for<'a> Deserialize<'a> {
-//         fn method(&self){}
-//     }
-
-//     impl<'a> DeserializeOwnedInterface<'a> for Trait_Interface{
-//         type Deserialized=Trait_Backend<'a,RBox<()>>;
-
-//         fn deserialize_impl(s: RStr<'_>) -> Result<Self::Deserialized, RBoxError>{
-//             Ok(DynTrait::from_value(Struct,Trait_Interface::NEW))
-//         }
-//     }
-
-//     #[derive(Deserialize)]
-//     pub struct Struct;
-
-//     impl Trait for Struct{}
-
-//     fn assert_bound<T>(_:&T)
-//     where
-//         T:for<'a>Deserialize<'a>
-//     {}
-
-//     fn test_constructible(){
-//         let object=Trait_TO::from_value(Struct,TD_CanDowncast);
-//         object.method();
-//         assert_bound(&object);
-//     }
-// }
+pub mod only_serialize {
+    use super::*;
+
+    #[sabi_trait]
+    pub trait Trait: Serialize {
+        fn method(&self) {}
+    }
+
+    impl SerializeProxyType<'_> for Trait_trait::Trait_Interface {
+        type Proxy = RString;
+    }
+
+    #[test]
+    fn test_impls() {
+        type GI = GetImpls<Trait_TO<'static, RBox<()>>>;
+        assert!(!GI::IMPLS_SEND);
+        assert!(!GI::IMPLS_SYNC);
+        assert!(!GI::IMPLS_UNPIN);
+        assert!(!GI::IMPLS_CLONE);
+        assert!(!GI::IMPLS_DISPLAY);
+        assert!(!GI::IMPLS_DEBUG);
+        assert!(GI::IMPLS_SERIALIZE);
+        assert!(!GI::IMPLS_DESERIALIZE);
+        assert!(!GI::IMPLS_ERROR);
+    }
+
+    pub struct Struct;
+
+    impl Trait for Struct {}
+
+    impl SerializeType<'_> for Struct {
+        type Interface = Trait_trait::Trait_Interface;
+
+        fn serialize_impl(&self) -> Result<RString, RBoxError> {
+            Ok(RString::from("Struct"))
+        }
+    }
+
+    fn assert_bound<T>(_: &T)
+    where
+        T: Trait + serde::Serialize,
+    {
+    }
+
+    fn test_constructible() {
+        let object = Trait_TO::from_value(Struct, TD_CanDowncast);
+        object.method();
+        assert_bound(&object);
+    }
+}
+
+pub mod only_deserialize {
+    use super::*;
+
+    #[sabi_trait]
+    #[sabi(use_dyntrait)]
+    pub trait Trait: for<'a> Deserialize<'a> {
+        fn method(&self) {}
+    }
+
+    impl<'a> DeserializeDyn<'a, Trait_TO<'static, RBox<()>>> for Trait_trait::Trait_Interface {
+        type Proxy = RString;
+
+        fn deserialize_dyn(_: RString) -> Result<Trait_TO<'static, RBox<()>>, RBoxError> {
+            Ok(Trait_TO::from_value(Struct, TD_Opaque))
+        }
+    }
+
+    #[test]
+    fn test_impls() {
+        type GI = GetImpls<Trait_TO<'static, RBox<()>>>;
+        assert!(!GI::IMPLS_SEND);
+        assert!(!GI::IMPLS_SYNC);
+        assert!(!GI::IMPLS_UNPIN);
+        assert!(!GI::IMPLS_CLONE);
+        assert!(!GI::IMPLS_DISPLAY);
+        assert!(!GI::IMPLS_DEBUG);
+        assert!(!GI::IMPLS_SERIALIZE);
+        assert!(GI::IMPLS_DESERIALIZE);
+        assert!(!GI::IMPLS_ERROR);
+    }
+
+    pub struct Struct;
+
+    impl Trait for Struct {}
+
+    fn assert_bound<T>(_: &T)
+    where
+        T: Trait + for<'a> serde::Deserialize<'a>,
+    {
+    }
+
+    fn test_constructible() {
+        let object = Trait_TO::from_value(Struct, TD_CanDowncast);
+        object.method();
+        assert_bound(&object);
+    }
+}
 
 pub mod only_partial_eq {
     use super::*;
@@ -297,6 +297,272 @@ fn borrow_kinds() {
     assert_eq!(obj.not_borrow(), 89);
 }
 
+/*////////////////////////////////////////////////////////////////////////////////
+Test that #[sabi(catch_unwind)] methods resume panics in the caller.
+*/////////////////////////////////////////////////////////////////////////////////
+
+#[sabi_trait]
+trait CatchUnwind {
+    #[sabi(catch_unwind)]
+    fn borrowed(&self, x: u32) -> &u32;
+
+    #[sabi(catch_unwind)]
+    fn mutated(&mut self, x: u32);
+
+    #[sabi(catch_unwind)]
+    fn consumed(self, x: u32) -> u32
+    where
+        Self: Sized;
+}
+
+impl CatchUnwind for u32 {
+    fn borrowed(&self, x: u32) -> &u32 {
+        assert_ne!(x, 0, "borrowed panicked");
+        self
+    }
+    fn mutated(&mut self, x: u32) {
+        assert_ne!(x, 0, "mutated panicked");
+        *self += x;
+    }
+    fn consumed(self, x: u32) -> u32 {
+        assert_ne!(x, 0, "consumed panicked");
+        self + x
+    }
+}
+
+#[test]
+fn catch_unwind_methods() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    fn panic_message(f: impl FnOnce()) -> String {
+        let payload = catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+        payload.downcast::<String>().map(|x| *x).unwrap()
+    }
+
+    let mut obj = CatchUnwind_TO::from_value(3u32, TD_Opaque);
+
+    assert_eq!(*obj.borrowed(1), 3);
+    obj.mutated(2);
+    assert_eq!(*obj.borrowed(1), 5);
+
+    assert!(panic_message(|| {
+        obj.borrowed(0);
+    })
+    .contains("borrowed panicked"));
+    assert!(panic_message(|| obj.mutated(0)).contains("mutated panicked"));
+
+    assert_eq!(obj.consumed(10), 15);
+
+    let obj = CatchUnwind_TO::from_value(3u32, TD_Opaque);
+    assert!(panic_message(|| {
+        obj.consumed(0);
+    })
+    .contains("consumed panicked"));
+}
+
+/*////////////////////////////////////////////////////////////////////////////////
+Test that trait objects with Serialize/Deserialize supertraits are (de)serialized
+through the proxy type,with both RObject and DynTrait as the backend.
+*/////////////////////////////////////////////////////////////////////////////////
+
+macro_rules! declare_serde_plugin {
+    ( $mod_:ident $(#[$attr:meta])* ) => {
+        mod $mod_ {
+            use super::*;
+
+            use crate::{
+                erased_types::{DeserializeDyn, SerializeProxyType, SerializeType},
+                std_types::{RBoxError, RString},
+            };
+
+            #[sabi_trait]
+            $(#[$attr])*
+            pub trait Plugin: Debug + Serialize + Deserialize {
+                fn count(&self) -> u32;
+            }
+
+            pub type PluginBox = Plugin_TO<'static, RBox<()>>;
+
+            impl SerializeProxyType<'_> for Plugin_trait::Plugin_Interface {
+                type Proxy = RString;
+            }
+
+            impl<'de> DeserializeDyn<'de, PluginBox> for Plugin_trait::Plugin_Interface {
+                type Proxy = RString;
+
+                fn deserialize_dyn(s: RString) -> Result<PluginBox, RBoxError> {
+                    match serde_json::from_str::<Counter>(&s) {
+                        Ok(x) => Ok(Plugin_TO::from_value(x, TD_CanDowncast)),
+                        Err(e) => Err(RBoxError::new(e)),
+                    }
+                }
+            }
+
+            #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+            pub struct Counter {
+                pub count: u32,
+            }
+
+            impl Plugin for Counter {
+                fn count(&self) -> u32 {
+                    self.count
+                }
+            }
+
+            impl SerializeType<'_> for Counter {
+                type Interface = Plugin_trait::Plugin_Interface;
+
+                fn serialize_impl(&self) -> Result<RString, RBoxError> {
+                    match serde_json::to_string(self) {
+                        Ok(x) => Ok(x.into()),
+                        Err(e) => Err(RBoxError::new(e)),
+                    }
+                }
+            }
+
+            #[test]
+            fn serialize_deserialize() {
+                let json = r#""{\"count\":3}""#;
+
+                let mut object: PluginBox = Plugin_TO::from_value(Counter { count: 3 }, TD_Opaque);
+                assert_eq!(serde_json::to_string(&object).unwrap(), json);
+                assert_eq!(serde_json::to_string(&object.sabi_reborrow()).unwrap(), json);
+                assert_eq!(serde_json::to_string(&object.sabi_reborrow_mut()).unwrap(), json);
+                assert_eq!(&*object.obj.serialize_into_proxy().unwrap(), r#"{"count":3}"#);
+
+                let object = serde_json::from_str::<PluginBox>(json).unwrap();
+                assert_eq!(object.count(), 3);
+                assert_eq!(
+                    object.obj.downcast_as::<Counter>().unwrap(),
+                    &Counter { count: 3 }
+                );
+
+                assert!(serde_json::from_str::<PluginBox>(r#""{}""#).is_err());
+            }
+        }
+    };
+}
+
+declare_serde_plugin! { serde_robject }
+
+declare_serde_plugin! { serde_dyntrait #[sabi(use_dyntrait)] }
+
+/*////////////////////////////////////////////////////////////////////////////////
+Test that `async fn` methods return `RFuture`s that can be driven by the caller's executor.
+*/////////////////////////////////////////////////////////////////////////////////
+
+mod async_methods {
+    use super::*;
+
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+        thread,
+    };
+
+    use crate::{
+        sabi_types::RFuture,
+        std_types::{RString, RVec},
+    };
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        struct ThreadWaker(thread::Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(x) => return x,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    /// Returns `Pending` once,waking the task from another thread.
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+            self.0 = true;
+            let waker = cx.waker().clone();
+            thread::spawn(move || waker.wake());
+            Poll::Pending
+        }
+    }
+
+    #[sabi_trait]
+    pub trait Store: Send + Sync {
+        async fn get(&self, key: u32) -> RString;
+
+        async fn set(&mut self, key: u32, value: RString);
+
+        #[sabi(last_prefix_field)]
+        async fn get_twice(&self, key: u32) -> RString {
+            let value = self.get(key).await;
+            format!("{0}{0}", value).into()
+        }
+    }
+
+    #[derive(Default)]
+    pub struct VecStore(RVec<RString>);
+
+    impl Store for VecStore {
+        fn get(&self, key: u32) -> RFuture<'_, RString> {
+            RFuture::new(async move {
+                YieldOnce(false).await;
+                self.0[key as usize].clone()
+            })
+        }
+
+        fn set(&mut self, key: u32, value: RString) -> RFuture<'_, ()> {
+            RFuture::new(async move {
+                YieldOnce(false).await;
+                let key = key as usize;
+                if self.0.len() <= key {
+                    self.0.resize(key + 1, RString::new());
+                }
+                self.0[key] = value;
+            })
+        }
+    }
+
+    #[test]
+    fn calling_async_methods() {
+        let mut object = Store_TO::from_value(VecStore::default(), TD_Opaque);
+
+        block_on(object.set(1, "hello".into()));
+        assert_eq!(block_on(object.get(1)), "hello");
+        assert_eq!(block_on(object.get(0)), "");
+        assert_eq!(block_on(object.get_twice(1)), "hellohello");
+
+        let handle = thread::spawn(move || block_on(object.get_twice(1)));
+        assert_eq!(handle.join().unwrap(), "hellohello");
+    }
+
+    #[test]
+    fn async_method_panics() {
+        let object = Store_TO::from_value(VecStore::default(), TD_Opaque);
+
+        let future = object.get(10);
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| block_on(future)));
+        assert!(res.is_err());
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 mod has_docs {
@@ -1,14 +1,20 @@
 use super::*;
 
 use crate::{
-    erased_types::{FormattingMode, InterfaceType, MakeRequiredTraits},
+    erased_types::{
+        traits::GetSerializeProxyType, vtable::UnerasedSerializeFn, FormattingMode, InterfaceType,
+        MakeRequiredTraits, SerializeProxyType, SerializeType,
+    },
     marker_type::NonOwningPhantom,
-    std_types::{RResult, RString, UTypeId},
+    pointer_trait::GetPointerKind,
+    prefix_type::PrefixRefTrait,
+    std_types::{RBoxError, RResult, RString, UTypeId},
     type_level::{
         downcasting::GetUTID,
         impl_enum::{Implemented, Unimplemented},
         trait_marker,
     },
+    utils::Transmuter,
 };
 
 use std::marker::PhantomData;
@@ -52,6 +58,84 @@ where
 
 //////////////////////////////////////////////////////////////////////////////
 
+/// Implemented by all `RObject`-based `#[sabi_trait]` generated trait objects,
+/// exposing the erased pointer and the type of the vtable.
+///
+/// This is used to implement `#[sabi_trait]` traits for the trait objects of
+/// their `#[sabi_trait]` subtraits.
+///
+/// # Safety
+///
+/// `VTable` must be the `*_Ref` type of the `#[sabi(kind(Prefix))]` vtable
+/// of the trait object,
+/// and the methods must forward to the ones of the same name on `RObject`.
+pub unsafe trait SabiTraitObject {
+    /// The type-erased pointer of the trait object.
+    type ErasedPtr: GetPointerKind;
+
+    /// The vtable of the trait object.
+    type VTable: Copy + PrefixRefTrait;
+
+    /// Gets an `RRef` pointing to the erased object.
+    fn sabi_as_rref(&self) -> RRef<'_, ()>
+    where
+        Self::ErasedPtr: AsPtr<PtrTarget = ()>;
+
+    /// Gets an `RMut` pointing to the erased object.
+    fn sabi_as_rmut(&mut self) -> RMut<'_, ()>
+    where
+        Self::ErasedPtr: AsMutPtr<PtrTarget = ()>;
+
+    /// Calls the `f` callback with an `MovePtr` pointing to the erased object.
+    fn sabi_with_value<F, R>(self, f: F) -> R
+    where
+        Self: Sized,
+        Self::ErasedPtr: OwnedPointer<PtrTarget = ()>,
+        F: FnOnce(MovePtr<'_, ()>) -> R;
+}
+
+/// Constructs the vtable of a `#[sabi_trait]` generated trait object,
+/// so that it can be embedded in the vtable of its `#[sabi_trait]` subtraits.
+///
+/// The type parameters are the same as those of `GetRObjectVTable`.
+///
+/// # Safety
+///
+/// `VTABLE` must be a vtable for `_Self` behind an `OrigPtr`,
+/// with type-erased pointer `Self::ErasedPtr`.
+pub unsafe trait MakeSabiTraitVTable<IA, _Self, OrigPtr>: SabiTraitObject {
+    /// The vtable of the trait object.
+    const VTABLE: SupertraitVTable<Self>;
+}
+
+/// The type of the field that stores the vtable of the `Super` supertrait
+/// in the vtable of a `#[sabi_trait]` subtrait.
+// Using `PrefixRef` instead of `Super::VTable`,
+// because the compiler can't tell that the projection has no interior mutability,
+// which prevents the vtable of the subtrait from being promoted to a static.
+pub type SupertraitVTable<Super> =
+    PrefixRef<<<Super as SabiTraitObject>::VTable as PrefixRefTrait>::PrefixFields>;
+
+/// Gets the vtable of the `Super` supertrait,
+/// which is stored in the vtable of `Self`.
+///
+/// This is implemented by the trait objects of `#[sabi_trait]` traits
+/// for their `#[sabi_trait]` supertraits.
+///
+/// # Safety
+///
+/// The returned vtable must have been constructed for the same type and pointer
+/// as the vtable of `Self`.
+pub unsafe trait GetSupertraitVTable<Super>: SabiTraitObject
+where
+    Super: SabiTraitObject<ErasedPtr = Self::ErasedPtr>,
+{
+    /// Gets the vtable of `Super` for the object that `self` points to.
+    fn sabi_supertrait_vtable(&self) -> Super::VTable;
+}
+
+//////////////////////////////////////////////////////////////////////////////
+
 #[doc(hidden)]
 pub trait AreTraitsImpld<IA, _Self, ErasedPtr, OrigPtr>: Sized {
     const VTABLE_VAL: RObjectVtable<_Self, ErasedPtr, Self>;
@@ -65,6 +149,7 @@ where
     I::Clone: InitCloneField<_Self, ErasedPtr, OrigPtr>,
     I::Debug: InitDebugField<_Self, ErasedPtr, OrigPtr>,
     I::Display: InitDisplayField<_Self, ErasedPtr, OrigPtr>,
+    I::Serialize: InitSerializeField<_Self, I>,
     IA: GetUTID<_Self>,
 {
     const VTABLE_VAL: RObjectVtable<_Self, ErasedPtr, I> = RObjectVtable {
@@ -74,6 +159,7 @@ where
         _sabi_clone: <I::Clone as InitCloneField<_Self, ErasedPtr, OrigPtr>>::VALUE,
         _sabi_debug: <I::Debug as InitDebugField<_Self, ErasedPtr, OrigPtr>>::VALUE,
         _sabi_display: <I::Display as InitDisplayField<_Self, ErasedPtr, OrigPtr>>::VALUE,
+        _sabi_serialize: <I::Serialize as InitSerializeField<_Self, I>>::VALUE,
     };
 }
 
@@ -99,6 +185,10 @@ where
 #[derive(StableAbi)]
 #[sabi(kind(Prefix))]
 #[sabi(missing_field(default))]
+#[sabi(
+    bound(I: for<'s> GetSerializeProxyType<'s>),
+    bound(for<'s> <I as GetSerializeProxyType<'s>>::ProxyType: crate::StableAbi),
+)]
 pub struct RObjectVtable<_Self, ErasedPtr, I> {
     pub _sabi_tys: NonOwningPhantom<(_Self, ErasedPtr, I)>,
 
@@ -121,6 +211,33 @@ pub struct RObjectVtable<_Self, ErasedPtr, I> {
             &mut RString,
         ) -> RResult<(), ()>,
     >,
+    #[sabi(unsafe_change_type =
+        Option<for<'s> unsafe extern "C" fn(
+            RRef<'s, ErasedObject>
+        ) -> RResult<<I as GetSerializeProxyType<'s>>::ProxyType, RBoxError>>
+    )]
+    pub _sabi_serialize: Option<ErasedSerializeFn>,
+}
+
+/// The type of the `_sabi_serialize` field,
+/// with the proxy type that the object is serialized into erased.
+pub type ErasedSerializeFn =
+    unsafe extern "C" fn(RRef<'_, ErasedObject>) -> RResult<ErasedObject, RBoxError>;
+
+impl<_Self, ErasedPtr, I> RObjectVtable_Ref<_Self, ErasedPtr, I> {
+    /// Gets the function that serializes the object into
+    /// the proxy type of `I`.
+    pub fn serialize<'s>(&self) -> UnerasedSerializeFn<'s, I>
+    where
+        I: InterfaceType<Serialize = Implemented<trait_marker::Serialize>>,
+        I: GetSerializeProxyType<'s>,
+    {
+        unsafe {
+            std::mem::transmute::<ErasedSerializeFn, UnerasedSerializeFn<'s, I>>(
+                self._sabi_serialize().unwrap(),
+            )
+        }
+    }
 }
 
 /// The common prefix of all `#[trait_object]` derived vtables,
@@ -129,6 +246,8 @@ pub struct RObjectVtable<_Self, ErasedPtr, I> {
 #[derive(StableAbi)]
 #[sabi(
     bound(I: InterfaceType),
+    bound(I: for<'s> GetSerializeProxyType<'s>),
+    bound(for<'s> <I as GetSerializeProxyType<'s>>::ProxyType: crate::StableAbi),
     extra_checks = <I as MakeRequiredTraits>::MAKE,
     kind(Prefix)
 )]
@@ -224,4 +343,33 @@ pub mod trait_bounds {
         type=unsafe extern "C" fn(RRef<'_, ErasedObject>,FormattingMode,&mut RString)->RResult<(),()>,
         value=c_functions::display_impl::<_Self>,
     }
+
+    /// Initializes the `_sabi_serialize` field,
+    /// which requires `_Self` to be serializable through the proxy type of `I`.
+    pub trait InitSerializeField<_Self, I> {
+        const VALUE: Option<ErasedSerializeFn>;
+    }
+
+    impl<_Self, I> InitSerializeField<_Self, I> for Unimplemented<trait_marker::Serialize> {
+        const VALUE: Option<ErasedSerializeFn> = None;
+    }
+
+    impl<_Self, I> InitSerializeField<_Self, I> for Implemented<trait_marker::Serialize>
+    where
+        _Self: for<'s> SerializeType<'s, Interface = I>,
+        I: for<'s> SerializeProxyType<'s>,
+    {
+        const VALUE: Option<ErasedSerializeFn> = Some(unsafe {
+            Transmuter::<
+                unsafe extern "C" fn(
+                    RRef<'_, ErasedObject>,
+                )
+                    -> RResult<<I as SerializeProxyType<'_>>::Proxy, RBoxError>,
+                ErasedSerializeFn,
+            > {
+                from: c_functions::serialize_impl::<_Self, I>,
+            }
+            .to
+        });
+    }
 }
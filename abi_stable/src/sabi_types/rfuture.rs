@@ -0,0 +1,534 @@
+//! Contains the `RFuture<'a, T>` type,
+//! and the `RWaker` and `RPoll<T>` types it's polled with.
+
+use std::{
+    fmt::{self, Debug},
+    future::Future,
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    pin::Pin,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+use crate::{
+    marker_type::ErasedObject,
+    prefix_type::WithMetadata,
+    sabi_types::RMut,
+    std_types::{RBox, RPanicPayload, RResult},
+};
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests;
+
+//////////////////////////////////////////////////////////////////////////////
+
+/// Ffi-safe equivalent of `Pin<Box<dyn Future<Output = T> + Send + 'a>>`.
+///
+/// The future is polled through a vtable,
+/// passing it an [`RWaker`] that wraps the [`Waker`] of the caller's executor,
+/// so that futures created in a dynamic library can be driven by
+/// the executor of the binary that loaded it.
+///
+/// Panics inside the future are caught in the library,
+/// and resumed in the caller of `poll`.
+///
+/// This is the return type of `async fn` methods in
+/// [`#[sabi_trait]`](macro@crate::sabi_trait) traits.
+///
+/// # Example
+///
+/// ```
+/// use std::{
+///     future::Future,
+///     pin::Pin,
+///     sync::Arc,
+///     task::{Context, Poll, Wake},
+/// };
+///
+/// use abi_stable::sabi_types::RFuture;
+///
+/// struct NoopWaker;
+///
+/// impl Wake for NoopWaker {
+///     fn wake(self: Arc<Self>) {}
+/// }
+///
+/// let text = String::from("hello");
+///
+/// let mut future: RFuture<'_, usize> = RFuture::new(async { text.len() });
+///
+/// let waker = Arc::new(NoopWaker).into();
+/// let mut cx = Context::from_waker(&waker);
+///
+/// assert_eq!(Pin::new(&mut future).poll(&mut cx), Poll::Ready(5));
+///
+/// ```
+#[repr(C)]
+#[derive(StableAbi)]
+pub struct RFuture<'a, T> {
+    future: RBox<ErasedObject>,
+    vtable: FutureVTable_Ref<T>,
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a, T> RFuture<'a, T> {
+    /// Constructs an `RFuture` from a future.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use abi_stable::sabi_types::RFuture;
+    ///
+    /// async fn add(l: u32, r: u32) -> u32 {
+    ///     l + r
+    /// }
+    ///
+    /// let _future: RFuture<'static, u32> = RFuture::new(add(3, 5));
+    ///
+    /// ```
+    pub fn new<F>(future: F) -> Self
+    where
+        F: Future<Output = T> + Send + 'a,
+    {
+        let future = RBox::new(future);
+        Self {
+            // Safety: the `RBox` vtable drops the future as an `F`.
+            future: unsafe { std::mem::transmute::<RBox<F>, RBox<ErasedObject>>(future) },
+            vtable: MakeFutureVTable::<F>::VTABLE,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Future for RFuture<'a, T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let this = self.get_mut();
+        let poll = this.vtable.poll();
+        let future = RMut::new(&mut *this.future);
+
+        // Safety: the future was pinned in its `RBox` since it was constructed.
+        let res = RWaker::with_borrowed(cx.waker(), |waker| unsafe { poll(future, waker) });
+
+        match res {
+            RResult::ROk(x) => x.into(),
+            RResult::RErr(payload) => payload.resume_unwind(),
+        }
+    }
+}
+
+impl<'a, T> Debug for RFuture<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RFuture").finish_non_exhaustive()
+    }
+}
+
+#[repr(C)]
+#[derive(StableAbi)]
+#[sabi(kind(Prefix))]
+#[sabi(missing_field(panic))]
+struct FutureVTable<T> {
+    #[sabi(last_prefix_field)]
+    poll: unsafe extern "C" fn(RMut<'_, ErasedObject>, &RWaker) -> RResult<RPoll<T>, RPanicPayload>,
+}
+
+struct MakeFutureVTable<F>(F);
+
+impl<F: Future> MakeFutureVTable<F> {
+    staticref! {
+        const WM_VTABLE: WithMetadata<FutureVTable<F::Output>> = WithMetadata::new(
+            FutureVTable {
+                poll: poll_future::<F>,
+            },
+        )
+    }
+
+    const VTABLE: FutureVTable_Ref<F::Output> = FutureVTable_Ref(Self::WM_VTABLE.as_prefix());
+}
+
+unsafe extern "C" fn poll_future<F: Future>(
+    this: RMut<'_, ErasedObject>,
+    waker: &RWaker,
+) -> RResult<RPoll<F::Output>, RPanicPayload> {
+    extern_fn_panic_handling! {catch_unwind;
+        let future = unsafe { Pin::new_unchecked(&mut *this.transmute_into_raw::<F>()) };
+        waker
+            .with_std_waker(|waker| future.poll(&mut Context::from_waker(waker)))
+            .into()
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+
+/// Ffi-safe equivalent of `std::task::Waker`.
+///
+/// An `RWaker` constructed from a `Waker` calls that `Waker` through a vtable,
+/// so it can be sent to (and cloned by) a dynamic library
+/// compiled separately from the executor that created the `Waker`.
+///
+/// # Example
+///
+/// ```
+/// use std::{
+///     sync::{
+///         atomic::{AtomicUsize, Ordering},
+///         Arc,
+///     },
+///     task::{Wake, Waker},
+/// };
+///
+/// use abi_stable::sabi_types::RWaker;
+///
+/// struct CountingWaker(AtomicUsize);
+///
+/// impl Wake for CountingWaker {
+///     fn wake(self: Arc<Self>) {
+///         self.0.fetch_add(1, Ordering::SeqCst);
+///     }
+/// }
+///
+/// let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+/// let waker = RWaker::new(&Waker::from(counter.clone()));
+///
+/// waker.wake_by_ref();
+/// waker.clone().wake();
+///
+/// // Converting it back into a `std::task::Waker`
+/// let std_waker: Waker = waker.into_waker();
+/// std_waker.wake();
+///
+/// assert_eq!(counter.0.load(Ordering::SeqCst), 3);
+///
+/// ```
+#[repr(C)]
+#[derive(StableAbi)]
+pub struct RWaker {
+    data: *const (),
+    vtable: WakerVTable_Ref,
+}
+
+unsafe impl Send for RWaker {}
+unsafe impl Sync for RWaker {}
+
+impl RWaker {
+    /// Constructs an `RWaker` that wakes a clone of `waker`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::{
+    ///     sync::Arc,
+    ///     task::{Wake, Waker},
+    /// };
+    ///
+    /// use abi_stable::sabi_types::RWaker;
+    ///
+    /// struct NoopWaker;
+    ///
+    /// impl Wake for NoopWaker {
+    ///     fn wake(self: Arc<Self>) {}
+    /// }
+    ///
+    /// let waker = RWaker::new(&Waker::from(Arc::new(NoopWaker)));
+    /// waker.wake();
+    ///
+    /// ```
+    pub fn new(waker: &Waker) -> Self {
+        Self {
+            data: Box::into_raw(Box::new(waker.clone())) as *const (),
+            vtable: MakeWakerVTable::OWNED,
+        }
+    }
+
+    /// Calls `f` with an `RWaker` that borrows `waker`,
+    /// only cloning it when the `RWaker` is cloned.
+    fn with_borrowed<R, F>(waker: &Waker, f: F) -> R
+    where
+        F: FnOnce(&RWaker) -> R,
+    {
+        let this = ManuallyDrop::new(Self {
+            data: waker as *const Waker as *const (),
+            vtable: MakeWakerVTable::BORROWED,
+        });
+        f(&this)
+    }
+
+    /// Calls `f` with a `Waker` that borrows `self`,
+    /// only cloning it when the `Waker` is cloned.
+    fn with_std_waker<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Waker) -> R,
+    {
+        let raw = RawWaker::new(self as *const Self as *const (), &BORROWED_RAW_VTABLE);
+        // Safety: `BORROWED_RAW_VTABLE` never drops the `RWaker`,
+        // and the `Waker` can't be moved out of the `ManuallyDrop`.
+        let waker = ManuallyDrop::new(unsafe { Waker::from_raw(raw) });
+        f(&waker)
+    }
+
+    /// Wakes up the task associated with this `RWaker`.
+    pub fn wake(self) {
+        let this = ManuallyDrop::new(self);
+        unsafe { (this.vtable.wake())(this.data) }
+    }
+
+    /// Wakes up the task associated with this `RWaker`, without consuming it.
+    pub fn wake_by_ref(&self) {
+        unsafe { (self.vtable.wake_by_ref())(self.data) }
+    }
+
+    /// Converts this `RWaker` into a `std::task::Waker`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::{
+    ///     sync::Arc,
+    ///     task::{Wake, Waker},
+    /// };
+    ///
+    /// use abi_stable::sabi_types::RWaker;
+    ///
+    /// struct NoopWaker;
+    ///
+    /// impl Wake for NoopWaker {
+    ///     fn wake(self: Arc<Self>) {}
+    /// }
+    ///
+    /// let waker: Waker = RWaker::new(&Waker::from(Arc::new(NoopWaker))).into_waker();
+    /// waker.wake_by_ref();
+    ///
+    /// ```
+    pub fn into_waker(self) -> Waker {
+        let raw = RawWaker::new(
+            Box::into_raw(Box::new(self)) as *const (),
+            &OWNED_RAW_VTABLE,
+        );
+        unsafe { Waker::from_raw(raw) }
+    }
+}
+
+impl Clone for RWaker {
+    fn clone(&self) -> Self {
+        unsafe { (self.vtable.clone_())(self.data) }
+    }
+}
+
+impl Drop for RWaker {
+    fn drop(&mut self) {
+        unsafe { (self.vtable.drop_())(self.data) }
+    }
+}
+
+impl Debug for RWaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RWaker").finish_non_exhaustive()
+    }
+}
+
+impl From<RWaker> for Waker {
+    fn from(waker: RWaker) -> Waker {
+        waker.into_waker()
+    }
+}
+
+#[repr(C)]
+#[derive(StableAbi)]
+#[sabi(kind(Prefix))]
+#[sabi(missing_field(panic))]
+struct WakerVTable {
+    clone_: unsafe extern "C" fn(*const ()) -> RWaker,
+    wake: unsafe extern "C" fn(*const ()),
+    wake_by_ref: unsafe extern "C" fn(*const ()),
+    #[sabi(last_prefix_field)]
+    drop_: unsafe extern "C" fn(*const ()),
+}
+
+struct MakeWakerVTable;
+
+impl MakeWakerVTable {
+    // The vtable for `RWaker`s that own a `Box<Waker>`
+    const WM_OWNED: &'static WithMetadata<WakerVTable> = &WithMetadata::new(WakerVTable {
+        clone_: clone_waker,
+        wake: wake_waker,
+        wake_by_ref: wake_waker_by_ref,
+        drop_: drop_waker,
+    });
+
+    const OWNED: WakerVTable_Ref = WakerVTable_Ref(Self::WM_OWNED.static_as_prefix());
+
+    // The vtable for `RWaker`s that borrow a `Waker`
+    const WM_BORROWED: &'static WithMetadata<WakerVTable> = &WithMetadata::new(WakerVTable {
+        clone_: clone_waker,
+        wake: wake_waker_by_ref,
+        wake_by_ref: wake_waker_by_ref,
+        drop_: noop_drop,
+    });
+
+    const BORROWED: WakerVTable_Ref = WakerVTable_Ref(Self::WM_BORROWED.static_as_prefix());
+}
+
+unsafe extern "C" fn clone_waker(data: *const ()) -> RWaker {
+    extern_fn_panic_handling! {
+        RWaker::new(unsafe { &*(data as *const Waker) })
+    }
+}
+
+unsafe extern "C" fn wake_waker(data: *const ()) {
+    extern_fn_panic_handling! {
+        unsafe { Box::from_raw(data as *mut Waker) }.wake();
+    }
+}
+
+unsafe extern "C" fn wake_waker_by_ref(data: *const ()) {
+    extern_fn_panic_handling! {
+        unsafe { &*(data as *const Waker) }.wake_by_ref();
+    }
+}
+
+unsafe extern "C" fn drop_waker(data: *const ()) {
+    extern_fn_panic_handling! {
+        drop(unsafe { Box::from_raw(data as *mut Waker) });
+    }
+}
+
+unsafe extern "C" fn noop_drop(_: *const ()) {}
+
+// The `std::task::Waker` vtable for `Waker`s that own a `Box<RWaker>`
+static OWNED_RAW_VTABLE: RawWakerVTable = RawWakerVTable::new(
+    clone_raw_waker,
+    wake_raw_waker,
+    wake_raw_waker_by_ref,
+    drop_raw_waker,
+);
+
+// The `std::task::Waker` vtable for `Waker`s that borrow an `RWaker`
+static BORROWED_RAW_VTABLE: RawWakerVTable = RawWakerVTable::new(
+    clone_raw_waker,
+    wake_raw_waker_by_ref,
+    wake_raw_waker_by_ref,
+    noop_raw_drop,
+);
+
+unsafe fn clone_raw_waker(data: *const ()) -> RawWaker {
+    let waker = unsafe { (*(data as *const RWaker)).clone() };
+    RawWaker::new(
+        Box::into_raw(Box::new(waker)) as *const (),
+        &OWNED_RAW_VTABLE,
+    )
+}
+
+unsafe fn wake_raw_waker(data: *const ()) {
+    unsafe { Box::from_raw(data as *mut RWaker) }.wake();
+}
+
+unsafe fn wake_raw_waker_by_ref(data: *const ()) {
+    unsafe { (*(data as *const RWaker)).wake_by_ref() }
+}
+
+unsafe fn drop_raw_waker(data: *const ()) {
+    drop(unsafe { Box::from_raw(data as *mut RWaker) });
+}
+
+unsafe fn noop_raw_drop(_: *const ()) {}
+
+//////////////////////////////////////////////////////////////////////////////
+
+/// Ffi-safe equivalent of `std::task::Poll`.
+///
+/// # Example
+///
+/// ```
+/// use std::task::Poll;
+///
+/// use abi_stable::sabi_types::RPoll;
+///
+/// assert_eq!(RPoll::from(Poll::Ready(3)), RPoll::Ready(3));
+/// assert_eq!(Poll::<u32>::from(RPoll::Pending), Poll::Pending);
+///
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[repr(C, u8)]
+#[derive(StableAbi)]
+pub enum RPoll<T> {
+    /// The value is ready.
+    Ready(T),
+    /// The value is not ready yet.
+    Pending,
+}
+
+impl<T> RPoll<T> {
+    /// Returns whether `self` is an `RPoll::Ready`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use abi_stable::sabi_types::RPoll;
+    ///
+    /// assert!(RPoll::Ready(10).is_ready());
+    /// assert!(!RPoll::<u32>::Pending.is_ready());
+    ///
+    /// ```
+    pub const fn is_ready(&self) -> bool {
+        matches!(self, RPoll::Ready { .. })
+    }
+
+    /// Returns whether `self` is an `RPoll::Pending`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use abi_stable::sabi_types::RPoll;
+    ///
+    /// assert!(!RPoll::Ready(10).is_pending());
+    /// assert!(RPoll::<u32>::Pending.is_pending());
+    ///
+    /// ```
+    pub const fn is_pending(&self) -> bool {
+        matches!(self, RPoll::Pending)
+    }
+
+    /// Maps the value inside an `RPoll::Ready`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use abi_stable::sabi_types::RPoll;
+    ///
+    /// assert_eq!(RPoll::Ready(10).map(|x| x * 2), RPoll::Ready(20));
+    /// assert_eq!(RPoll::<u32>::Pending.map(|x| x * 2), RPoll::Pending);
+    ///
+    /// ```
+    pub fn map<U, F>(self, f: F) -> RPoll<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        match self {
+            RPoll::Ready(x) => RPoll::Ready(f(x)),
+            RPoll::Pending => RPoll::Pending,
+        }
+    }
+}
+
+impl_from_rust_repr! {
+    impl[T] From<Poll<T>> for RPoll<T> {
+        fn(this){
+            match this {
+                Poll::Ready(x) => RPoll::Ready(x),
+                Poll::Pending => RPoll::Pending,
+            }
+        }
+    }
+}
+
+impl_into_rust_repr! {
+    impl[T] Into<Poll<T>> for RPoll<T> {
+        fn(this){
+            match this {
+                RPoll::Ready(x) => Poll::Ready(x),
+                RPoll::Pending => Poll::Pending,
+            }
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use super::*;
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::Wake,
+    thread,
+};
+
+struct CountingWaker {
+    wakes: AtomicUsize,
+}
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.wakes.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn counting_waker() -> (Arc<CountingWaker>, Waker) {
+    let counter = Arc::new(CountingWaker {
+        wakes: AtomicUsize::new(0),
+    });
+    (counter.clone(), Waker::from(counter))
+}
+
+fn poll_once<F: Future + Unpin>(future: &mut F, waker: &Waker) -> Poll<F::Output> {
+    Pin::new(future).poll(&mut Context::from_waker(waker))
+}
+
+/// A future that returns `Pending` until it's been woken from another thread.
+struct WokenFromThread {
+    state: Arc<Mutex<(bool, Option<Waker>)>>,
+}
+
+impl Future for WokenFromThread {
+    type Output = u32;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<u32> {
+        let mut state = self.state.lock().unwrap();
+        if state.0 {
+            return Poll::Ready(100);
+        }
+        if state.1.is_none() {
+            state.1 = Some(cx.waker().clone());
+            let state = self.state.clone();
+            thread::spawn(move || {
+                let waker = {
+                    let mut state = state.lock().unwrap();
+                    state.0 = true;
+                    state.1.take().unwrap()
+                };
+                waker.wake();
+            });
+        }
+        Poll::Pending
+    }
+}
+
+#[test]
+fn ready_future() {
+    let (_, waker) = counting_waker();
+    let text = String::from("hello");
+
+    let mut future = RFuture::new(async { text.len() });
+    assert_eq!(poll_once(&mut future, &waker), Poll::Ready(5));
+}
+
+#[test]
+fn drops_future() {
+    let arc = Arc::new(0);
+    {
+        let cloned = arc.clone();
+        let future = RFuture::new(async move { *cloned });
+        assert_eq!(Arc::strong_count(&arc), 2);
+        drop(future);
+    }
+    assert_eq!(Arc::strong_count(&arc), 1);
+}
+
+#[test]
+fn woken_from_other_thread() {
+    let (counter, waker) = counting_waker();
+
+    let mut future = RFuture::new(async {
+        let x = WokenFromThread {
+            state: Default::default(),
+        }
+        .await;
+        x + 1
+    });
+
+    assert_eq!(poll_once(&mut future, &waker), Poll::Pending);
+
+    while counter.wakes.load(Ordering::SeqCst) == 0 {
+        thread::yield_now();
+    }
+
+    assert_eq!(poll_once(&mut future, &waker), Poll::Ready(101));
+    assert_eq!(counter.wakes.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn nested_rfutures() {
+    let (counter, waker) = counting_waker();
+
+    let inner = RFuture::new(WokenFromThread {
+        state: Default::default(),
+    });
+    let mut outer = RFuture::new(async move { inner.await * 2 });
+
+    assert_eq!(poll_once(&mut outer, &waker), Poll::Pending);
+
+    while counter.wakes.load(Ordering::SeqCst) == 0 {
+        thread::yield_now();
+    }
+
+    assert_eq!(poll_once(&mut outer, &waker), Poll::Ready(200));
+}
+
+#[test]
+fn resumes_panics() {
+    let (_, waker) = counting_waker();
+
+    let mut future = RFuture::new(async {
+        if true {
+            panic!("oh no");
+        }
+    });
+
+    let err = catch_unwind(AssertUnwindSafe(|| poll_once(&mut future, &waker))).unwrap_err();
+    assert_eq!(*err.downcast_ref::<&str>().unwrap(), "oh no");
+}
+
+#[test]
+fn rwaker_wakes() {
+    let (counter, waker) = counting_waker();
+
+    let rwaker = RWaker::new(&waker);
+    rwaker.wake_by_ref();
+    rwaker.clone().wake();
+    assert_eq!(counter.wakes.load(Ordering::SeqCst), 2);
+
+    let std_waker = rwaker.into_waker();
+    let cloned = std_waker.clone();
+    std_waker.wake_by_ref();
+    assert_eq!(Arc::strong_count(&counter), 4);
+
+    cloned.wake();
+    assert_eq!(counter.wakes.load(Ordering::SeqCst), 4);
+    assert_eq!(Arc::strong_count(&counter), 3);
+
+    drop(std_waker);
+    assert_eq!(Arc::strong_count(&counter), 2);
+}
+
+#[test]
+fn borrowed_wakers() {
+    let (counter, waker) = counting_waker();
+
+    let cloned = RWaker::with_borrowed(&waker, |rwaker| {
+        rwaker.wake_by_ref();
+        rwaker.with_std_waker(|std_waker| {
+            std_waker.wake_by_ref();
+            std_waker.clone()
+        })
+    });
+    assert_eq!(counter.wakes.load(Ordering::SeqCst), 2);
+    assert_eq!(Arc::strong_count(&counter), 3);
+
+    cloned.wake();
+    assert_eq!(counter.wakes.load(Ordering::SeqCst), 3);
+    assert_eq!(Arc::strong_count(&counter), 2);
+}
+
+#[test]
+fn rpoll_conversions() {
+    assert_eq!(RPoll::from(Poll::Ready(3)), RPoll::Ready(3));
+    assert_eq!(RPoll::<u32>::from(Poll::Pending), RPoll::Pending);
+    assert_eq!(Poll::from(RPoll::Ready(3)), Poll::Ready(3));
+    assert_eq!(Poll::<u32>::from(RPoll::Pending), Poll::Pending);
+}
+
+#[test]
+fn layout_depends_on_output() {
+    use crate::{abi_stability::abi_checking::check_layout_compatibility, StableAbi};
+
+    let u32_layout = <RFuture<'_, u32> as StableAbi>::LAYOUT;
+    let u64_layout = <RFuture<'_, u64> as StableAbi>::LAYOUT;
+
+    check_layout_compatibility(u32_layout, u32_layout).unwrap();
+    check_layout_compatibility(u32_layout, u64_layout).unwrap_err();
+}
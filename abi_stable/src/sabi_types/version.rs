@@ -93,7 +93,7 @@ pub struct VersionStrings {
 /// assert!(!v0_1_8.is_compatible(v0_2_0), "'{}' '{}'", v0_1_8, v0_2_0);
 ///
 /// ```
-#[derive(Debug, Copy, Clone, PartialEq, Eq, StableAbi)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, StableAbi)]
 #[repr(C)]
 pub struct VersionNumber {
     pub major: u32,
@@ -181,19 +181,26 @@ impl VersionNumber {
     ///
     /// ```
     pub fn new(vn: VersionStrings) -> Result<Self, ParseVersionError> {
-        let mut iter = vn.version.splitn(3, '.');
+        Self::parse_str(vn.version.as_str())
+            .map_err(|(which_field, x)| ParseVersionError::new(vn, which_field, x))
+    }
+
+    /// Parses a version string that isn't necessarily `'static`,
+    /// returning the name of the field that couldn't be parsed on error.
+    pub(crate) fn parse_str(version: &str) -> Result<Self, (&'static str, ParseIntError)> {
+        let mut iter = version.splitn(3, '.');
 
         VersionNumber {
             major: iter
                 .next()
                 .unwrap_or("")
                 .parse()
-                .map_err(|x| ParseVersionError::new(vn, "major", x))?,
+                .map_err(|x| ("major", x))?,
             minor: iter
                 .next()
                 .unwrap_or("")
                 .parse()
-                .map_err(|x| ParseVersionError::new(vn, "minor", x))?,
+                .map_err(|x| ("minor", x))?,
             patch: iter
                 .next()
                 .unwrap_or("")
@@ -201,7 +208,7 @@ impl VersionNumber {
                 .find(|x| x.key)
                 .map_or("0", |x| x.str)
                 .parse()
-                .map_err(|x| ParseVersionError::new(vn, "patch", x))?,
+                .map_err(|x| ("patch", x))?,
         }
         .piped(Ok)
     }
@@ -6,10 +6,12 @@
 
 pub(crate) mod arc;
 pub(crate) mod boxed;
+pub mod closures;
 pub(crate) mod cmp_ordering;
 pub mod cow;
 pub mod map;
 pub(crate) mod option;
+pub(crate) mod panic_payload;
 pub(crate) mod range;
 pub(crate) mod result;
 pub(crate) mod slice_mut;
@@ -38,10 +40,12 @@ pub mod sync {}
 pub use self::{
     arc::RArc,
     boxed::RBox,
+    closures::{RBoxFn, RBoxFnMut, RBoxFnOnce, RRefFn},
     cmp_ordering::RCmpOrdering,
     cow::{RCow, RCowSlice, RCowStr, RCowVal},
     map::RHashMap,
     option::{RNone, ROption, RSome},
+    panic_payload::{RPanicLocation, RPanicPayload},
     result::{RErr, ROk, RResult},
     slice_mut::RSliceMut,
     slices::RSlice,
@@ -0,0 +1,656 @@
+//! Contains ffi-safe equivalents of boxed and borrowed closures.
+
+use std::{
+    fmt::{self, Debug},
+    marker::PhantomData,
+    mem,
+};
+
+use crate::{
+    marker_type::ErasedObject,
+    pointer_trait::OwnedPointer,
+    prefix_type::WithMetadata,
+    sabi_types::{MovePtr, RMut, RRef},
+    std_types::{RBox, RPanicPayload, RResult, Tuple1, Tuple2, Tuple3, Tuple4},
+};
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests;
+
+//////////////////////////////////////////////////////////////////////////////
+
+/// Implemented by closures that take the elements of the `A` tuple as parameters,
+/// and return `R`.
+///
+/// `A` can be `()`, [`Tuple1`], [`Tuple2`], [`Tuple3`], or [`Tuple4`].
+///
+/// This is implemented for every `Fn` closure,
+/// and is used to construct [`RBoxFn`]s and [`RRefFn`]s.
+pub trait FnTuple<A, R> {
+    /// Calls this closure with the elements of `args` as the parameters.
+    fn call_tuple(&self, args: A) -> R;
+}
+
+/// Implemented by closures that take the elements of the `A` tuple as parameters,
+/// and return `R`.
+///
+/// `A` can be `()`, [`Tuple1`], [`Tuple2`], [`Tuple3`], or [`Tuple4`].
+///
+/// This is implemented for every `FnMut` closure,
+/// and is used to construct [`RBoxFnMut`]s.
+pub trait FnMutTuple<A, R> {
+    /// Calls this closure with the elements of `args` as the parameters.
+    fn call_mut_tuple(&mut self, args: A) -> R;
+}
+
+/// Implemented by closures that take the elements of the `A` tuple as parameters,
+/// and return `R`.
+///
+/// `A` can be `()`, [`Tuple1`], [`Tuple2`], [`Tuple3`], or [`Tuple4`].
+///
+/// This is implemented for every `FnOnce` closure,
+/// and is used to construct [`RBoxFnOnce`]s.
+pub trait FnOnceTuple<A, R> {
+    /// Calls this closure with the elements of `args` as the parameters.
+    fn call_once_tuple(self, args: A) -> R;
+}
+
+//////////////////////////////////////////////////////////////////////////////
+
+/// Ffi-safe equivalent of `Box<dyn Fn(..) -> R + Send + Sync + 'a>`.
+///
+/// `A` is the tuple of the parameter types of the closure,
+/// which can be `()`, [`Tuple1`], [`Tuple2`], [`Tuple3`], or [`Tuple4`].
+///
+/// The closure is called with the `call` method,
+/// which takes as many parameters as there are elements in `A`,
+/// or with the `call_tuple` method.
+///
+/// Panics inside the closure are caught,and resumed in the caller.
+///
+/// # Example
+///
+/// Passing a callback to a function that could be defined in a dynamic library.
+///
+/// ```
+/// use abi_stable::{
+///     sabi_extern_fn,
+///     std_types::{RBoxFn, RString, Tuple2},
+/// };
+///
+/// #[sabi_extern_fn]
+/// pub fn greet(people: u32, callback: RBoxFn<'_, Tuple2<u32, RString>, bool>) -> u32 {
+///     let mut greeted = 0;
+///     for i in 0..people {
+///         if callback.call(i, "hello".into()) {
+///             greeted += 1;
+///         }
+///     }
+///     greeted
+/// }
+///
+/// let greeted = greet(
+///     10,
+///     RBoxFn::new(|i, greeting: RString| {
+///         assert_eq!(greeting, "hello");
+///         i % 2 == 0
+///     }),
+/// );
+/// assert_eq!(greeted, 5);
+///
+/// ```
+#[repr(C)]
+#[derive(StableAbi)]
+pub struct RBoxFn<'a, A, R> {
+    closure: RBox<ErasedObject>,
+    vtable: FnVTable_Ref<A, R>,
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a, A, R> RBoxFn<'a, A, R> {
+    /// Constructs an `RBoxFn` from a closure.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use abi_stable::std_types::{RBoxFn, Tuple2};
+    ///
+    /// let add: RBoxFn<'_, Tuple2<u32, u32>, u32> = RBoxFn::new(|l, r| l + r);
+    /// assert_eq!(add.call(3, 5), 8);
+    ///
+    /// let double = RBoxFn::new(|x: u32| x * 2);
+    /// assert_eq!(double.call(5), 10);
+    ///
+    /// ```
+    pub fn new<F>(closure: F) -> Self
+    where
+        F: FnTuple<A, R> + Send + Sync + 'a,
+    {
+        Self {
+            closure: unsafe { erase_box(closure) },
+            vtable: MakeFnVTable::<F, A, R>::VTABLE,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Calls the closure with the elements of `args` as the parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use abi_stable::std_types::{RBoxFn, Tuple2};
+    ///
+    /// let add = RBoxFn::new(|l: u32, r: u32| l + r);
+    /// assert_eq!(add.call_tuple(Tuple2(3, 5)), 8);
+    ///
+    /// ```
+    pub fn call_tuple(&self, args: A) -> R {
+        unsafe { resume((self.vtable.call())(RRef::new(&*self.closure), args)) }
+    }
+
+    /// Gets a borrowed version of this closure.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use abi_stable::std_types::{RBoxFn, RRefFn, Tuple1};
+    ///
+    /// let double = RBoxFn::new(|x: u32| x * 2);
+    ///
+    /// let borrowed: RRefFn<'_, Tuple1<u32>, u32> = double.as_rref_fn();
+    /// assert_eq!(borrowed.call(5), 10);
+    ///
+    /// ```
+    pub fn as_rref_fn(&self) -> RRefFn<'_, A, R> {
+        RRefFn {
+            closure: RRef::new(&*self.closure),
+            vtable: self.vtable,
+        }
+    }
+}
+
+impl<'a, A, R> Debug for RBoxFn<'a, A, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RBoxFn").finish_non_exhaustive()
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+
+/// Ffi-safe equivalent of `Box<dyn FnMut(..) -> R + Send + 'a>`.
+///
+/// `A` is the tuple of the parameter types of the closure,
+/// which can be `()`, [`Tuple1`], [`Tuple2`], [`Tuple3`], or [`Tuple4`].
+///
+/// The closure is called with the `call` method,
+/// which takes as many parameters as there are elements in `A`,
+/// or with the `call_tuple` method.
+///
+/// Panics inside the closure are caught,and resumed in the caller.
+///
+/// # Example
+///
+/// ```
+/// use abi_stable::std_types::{RBoxFnMut, RVec};
+///
+/// let mut list = RVec::new();
+/// {
+///     let mut push = RBoxFnMut::new(|x: u32| list.push(x));
+///     push.call(3);
+///     push.call(5);
+/// }
+/// assert_eq!(list, vec![3, 5]);
+///
+/// ```
+#[repr(C)]
+#[derive(StableAbi)]
+pub struct RBoxFnMut<'a, A, R> {
+    closure: RBox<ErasedObject>,
+    vtable: FnMutVTable_Ref<A, R>,
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a, A, R> RBoxFnMut<'a, A, R> {
+    /// Constructs an `RBoxFnMut` from a closure.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use abi_stable::std_types::RBoxFnMut;
+    ///
+    /// let mut count = 0;
+    /// let mut counter = RBoxFnMut::new(|| {
+    ///     count += 1;
+    ///     count
+    /// });
+    ///
+    /// assert_eq!(counter.call(), 1);
+    /// assert_eq!(counter.call(), 2);
+    ///
+    /// ```
+    pub fn new<F>(closure: F) -> Self
+    where
+        F: FnMutTuple<A, R> + Send + 'a,
+    {
+        Self {
+            closure: unsafe { erase_box(closure) },
+            vtable: MakeFnVTable::<F, A, R>::MUT_VTABLE,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Calls the closure with the elements of `args` as the parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use abi_stable::std_types::{RBoxFnMut, Tuple1};
+    ///
+    /// let mut sum = 0;
+    /// let mut add = RBoxFnMut::new(|x: u32| sum += x);
+    ///
+    /// add.call_tuple(Tuple1(3));
+    /// add.call_tuple(Tuple1(5));
+    /// drop(add);
+    ///
+    /// assert_eq!(sum, 8);
+    ///
+    /// ```
+    pub fn call_tuple(&mut self, args: A) -> R {
+        unsafe {
+            resume((self.vtable.call_mut())(
+                RMut::new(&mut *self.closure),
+                args,
+            ))
+        }
+    }
+}
+
+impl<'a, A, R> Debug for RBoxFnMut<'a, A, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RBoxFnMut").finish_non_exhaustive()
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+
+/// Ffi-safe equivalent of `Box<dyn FnOnce(..) -> R + Send + 'a>`.
+///
+/// `A` is the tuple of the parameter types of the closure,
+/// which can be `()`, [`Tuple1`], [`Tuple2`], [`Tuple3`], or [`Tuple4`].
+///
+/// The closure is called with the `call` method,
+/// which takes as many parameters as there are elements in `A`,
+/// or with the `call_tuple` method.
+///
+/// Panics inside the closure are caught,and resumed in the caller.
+///
+/// # Example
+///
+/// ```
+/// use abi_stable::std_types::{RBoxFnOnce, RString};
+///
+/// let text = RString::from("hello");
+///
+/// let into_upper = RBoxFnOnce::new(move || text.to_uppercase());
+/// assert_eq!(into_upper.call(), "HELLO");
+///
+/// ```
+#[repr(C)]
+#[derive(StableAbi)]
+pub struct RBoxFnOnce<'a, A, R> {
+    closure: RBox<ErasedObject>,
+    vtable: FnOnceVTable_Ref<A, R>,
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a, A, R> RBoxFnOnce<'a, A, R> {
+    /// Constructs an `RBoxFnOnce` from a closure.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use abi_stable::std_types::{RBoxFnOnce, RVec};
+    ///
+    /// let list = RVec::from(vec![3, 5, 8]);
+    ///
+    /// let into_sum = RBoxFnOnce::new(move |start: u32| list.into_iter().fold(start, |l, r| l + r));
+    /// assert_eq!(into_sum.call(100), 116);
+    ///
+    /// ```
+    pub fn new<F>(closure: F) -> Self
+    where
+        F: FnOnceTuple<A, R> + Send + 'a,
+    {
+        Self {
+            closure: unsafe { erase_box(closure) },
+            vtable: MakeFnVTable::<F, A, R>::ONCE_VTABLE,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Calls the closure with the elements of `args` as the parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use abi_stable::std_types::{RBoxFnOnce, RString, Tuple1};
+    ///
+    /// let text = RString::from("hello");
+    ///
+    /// let append = RBoxFnOnce::new(move |suffix: &str| {
+    ///     let mut text = text;
+    ///     text.push_str(suffix);
+    ///     text
+    /// });
+    /// assert_eq!(append.call_tuple(Tuple1(" world")), "hello world");
+    ///
+    /// ```
+    pub fn call_tuple(self, args: A) -> R {
+        let call_once = self.vtable.call_once();
+        self.closure.in_move_ptr(|closure| unsafe {
+            resume(call_once(MovePtr::into_raw(closure) as *mut (), args))
+        })
+    }
+}
+
+impl<'a, A, R> Debug for RBoxFnOnce<'a, A, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RBoxFnOnce").finish_non_exhaustive()
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+
+/// Ffi-safe equivalent of `&'a (dyn Fn(..) -> R + Sync)`.
+///
+/// `A` is the tuple of the parameter types of the closure,
+/// which can be `()`, [`Tuple1`], [`Tuple2`], [`Tuple3`], or [`Tuple4`].
+///
+/// The closure is called with the `call` method,
+/// which takes as many parameters as there are elements in `A`,
+/// or with the `call_tuple` method.
+///
+/// Panics inside the closure are caught,and resumed in the caller.
+///
+/// # Example
+///
+/// ```
+/// use abi_stable::{
+///     sabi_extern_fn,
+///     std_types::{RRefFn, RVec, Tuple1},
+/// };
+///
+/// #[sabi_extern_fn]
+/// pub fn count_matching(list: &RVec<u32>, predicate: RRefFn<'_, Tuple1<u32>, bool>) -> usize {
+///     list.iter().filter(|&&x| predicate.call(x)).count()
+/// }
+///
+/// let list = RVec::from(vec![3, 5, 8, 13, 21]);
+///
+/// let is_odd = |x: u32| x % 2 == 1;
+/// assert_eq!(count_matching(&list, RRefFn::new(&is_odd)), 4);
+///
+/// ```
+#[repr(C)]
+#[derive(StableAbi)]
+pub struct RRefFn<'a, A, R> {
+    closure: RRef<'a, ErasedObject>,
+    vtable: FnVTable_Ref<A, R>,
+}
+
+impl<'a, A, R> RRefFn<'a, A, R> {
+    /// Constructs an `RRefFn` from a reference to a closure.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use abi_stable::std_types::{RRefFn, Tuple2};
+    ///
+    /// let closure = |l: u32, r: u32| l * r;
+    ///
+    /// let mul: RRefFn<'_, Tuple2<u32, u32>, u32> = RRefFn::new(&closure);
+    /// assert_eq!(mul.call(3, 5), 15);
+    ///
+    /// ```
+    pub fn new<F>(closure: &'a F) -> Self
+    where
+        F: FnTuple<A, R> + Sync,
+    {
+        Self {
+            closure: unsafe { RRef::from_raw(closure as *const F as *const ErasedObject) },
+            vtable: MakeFnVTable::<F, A, R>::VTABLE,
+        }
+    }
+
+    /// Calls the closure with the elements of `args` as the parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use abi_stable::std_types::{RRefFn, Tuple2};
+    ///
+    /// let closure = |l: u32, r: u32| l * r;
+    ///
+    /// let mul = RRefFn::new(&closure);
+    /// assert_eq!(mul.call_tuple(Tuple2(3, 5)), 15);
+    ///
+    /// ```
+    pub fn call_tuple(&self, args: A) -> R {
+        unsafe { resume((self.vtable.call())(self.closure, args)) }
+    }
+}
+
+impl<'a, A, R> Copy for RRefFn<'a, A, R> {}
+
+impl<'a, A, R> Clone for RRefFn<'a, A, R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, A, R> Debug for RRefFn<'a, A, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RRefFn").finish_non_exhaustive()
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+
+macro_rules! impl_arity {
+    ( $args_ty:ty, $($tuple:ident)? ( $($param:ident: $param_ty:ident),* ) ) => {
+        impl<F, $($param_ty,)* R> FnTuple<$args_ty, R> for F
+        where
+            F: Fn($($param_ty),*) -> R,
+        {
+            #[inline]
+            fn call_tuple(&self, $($tuple)?($($param),*): $args_ty) -> R {
+                self($($param),*)
+            }
+        }
+
+        impl<F, $($param_ty,)* R> FnMutTuple<$args_ty, R> for F
+        where
+            F: FnMut($($param_ty),*) -> R,
+        {
+            #[inline]
+            fn call_mut_tuple(&mut self, $($tuple)?($($param),*): $args_ty) -> R {
+                self($($param),*)
+            }
+        }
+
+        impl<F, $($param_ty,)* R> FnOnceTuple<$args_ty, R> for F
+        where
+            F: FnOnce($($param_ty),*) -> R,
+        {
+            #[inline]
+            fn call_once_tuple(self, $($tuple)?($($param),*): $args_ty) -> R {
+                self($($param),*)
+            }
+        }
+
+        impl<'a, $($param_ty,)* R> RBoxFn<'a, $args_ty, R> {
+            /// Calls the closure.
+            #[inline]
+            pub fn call(&self, $($param: $param_ty),*) -> R {
+                self.call_tuple($($tuple)?($($param),*))
+            }
+        }
+
+        impl<'a, $($param_ty,)* R> RBoxFnMut<'a, $args_ty, R> {
+            /// Calls the closure.
+            #[inline]
+            pub fn call(&mut self, $($param: $param_ty),*) -> R {
+                self.call_tuple($($tuple)?($($param),*))
+            }
+        }
+
+        impl<'a, $($param_ty,)* R> RBoxFnOnce<'a, $args_ty, R> {
+            /// Calls the closure.
+            #[inline]
+            pub fn call(self, $($param: $param_ty),*) -> R {
+                self.call_tuple($($tuple)?($($param),*))
+            }
+        }
+
+        impl<'a, $($param_ty,)* R> RRefFn<'a, $args_ty, R> {
+            /// Calls the closure.
+            #[inline]
+            pub fn call(&self, $($param: $param_ty),*) -> R {
+                self.call_tuple($($tuple)?($($param),*))
+            }
+        }
+    };
+}
+
+impl_arity! { (), () }
+impl_arity! { Tuple1<A0>, Tuple1(a0: A0) }
+impl_arity! { Tuple2<A0, A1>, Tuple2(a0: A0, a1: A1) }
+impl_arity! { Tuple3<A0, A1, A2>, Tuple3(a0: A0, a1: A1, a2: A2) }
+impl_arity! { Tuple4<A0, A1, A2, A3>, Tuple4(a0: A0, a1: A1, a2: A2, a3: A3) }
+
+//////////////////////////////////////////////////////////////////////////////
+
+/// Boxes `closure`,erasing its type.
+///
+/// # Safety
+///
+/// The returned `RBox` must only be accessed as an `F`.
+unsafe fn erase_box<F>(closure: F) -> RBox<ErasedObject> {
+    // The vtable of the `RBox` drops the closure as an `F`.
+    unsafe { mem::transmute::<RBox<F>, RBox<ErasedObject>>(RBox::new(closure)) }
+}
+
+fn resume<R>(res: RResult<R, RPanicPayload>) -> R {
+    res.unwrap_or_else(|payload| payload.resume_unwind())
+}
+
+#[repr(C)]
+#[derive(StableAbi)]
+#[sabi(kind(Prefix))]
+#[sabi(missing_field(panic))]
+struct FnVTable<A, R> {
+    #[sabi(last_prefix_field)]
+    call: unsafe extern "C" fn(RRef<'_, ErasedObject>, A) -> RResult<R, RPanicPayload>,
+}
+
+#[repr(C)]
+#[derive(StableAbi)]
+#[sabi(kind(Prefix))]
+#[sabi(missing_field(panic))]
+struct FnMutVTable<A, R> {
+    #[sabi(last_prefix_field)]
+    call_mut: unsafe extern "C" fn(RMut<'_, ErasedObject>, A) -> RResult<R, RPanicPayload>,
+}
+
+#[repr(C)]
+#[derive(StableAbi)]
+#[sabi(kind(Prefix))]
+#[sabi(missing_field(panic))]
+struct FnOnceVTable<A, R> {
+    #[sabi(last_prefix_field)]
+    call_once: unsafe extern "C" fn(*mut (), A) -> RResult<R, RPanicPayload>,
+}
+
+struct MakeFnVTable<F, A, R>(F, A, R);
+
+impl<F, A, R> MakeFnVTable<F, A, R>
+where
+    F: FnTuple<A, R>,
+{
+    staticref! {
+        const WM_VTABLE: WithMetadata<FnVTable<A, R>> = WithMetadata::new(
+            FnVTable {
+                call: call_fn::<F, A, R>,
+            },
+        )
+    }
+
+    const VTABLE: FnVTable_Ref<A, R> = FnVTable_Ref(Self::WM_VTABLE.as_prefix());
+}
+
+impl<F, A, R> MakeFnVTable<F, A, R>
+where
+    F: FnMutTuple<A, R>,
+{
+    staticref! {
+        const WM_MUT_VTABLE: WithMetadata<FnMutVTable<A, R>> = WithMetadata::new(
+            FnMutVTable {
+                call_mut: call_mut_fn::<F, A, R>,
+            },
+        )
+    }
+
+    const MUT_VTABLE: FnMutVTable_Ref<A, R> = FnMutVTable_Ref(Self::WM_MUT_VTABLE.as_prefix());
+}
+
+impl<F, A, R> MakeFnVTable<F, A, R>
+where
+    F: FnOnceTuple<A, R>,
+{
+    staticref! {
+        const WM_ONCE_VTABLE: WithMetadata<FnOnceVTable<A, R>> = WithMetadata::new(
+            FnOnceVTable {
+                call_once: call_once_fn::<F, A, R>,
+            },
+        )
+    }
+
+    const ONCE_VTABLE: FnOnceVTable_Ref<A, R> = FnOnceVTable_Ref(Self::WM_ONCE_VTABLE.as_prefix());
+}
+
+unsafe extern "C" fn call_fn<F, A, R>(
+    this: RRef<'_, ErasedObject>,
+    args: A,
+) -> RResult<R, RPanicPayload>
+where
+    F: FnTuple<A, R>,
+{
+    extern_fn_panic_handling! {catch_unwind;
+        let this = unsafe { &*this.transmute_into_raw::<F>() };
+        this.call_tuple(args)
+    }
+}
+
+unsafe extern "C" fn call_mut_fn<F, A, R>(
+    this: RMut<'_, ErasedObject>,
+    args: A,
+) -> RResult<R, RPanicPayload>
+where
+    F: FnMutTuple<A, R>,
+{
+    extern_fn_panic_handling! {catch_unwind;
+        let this = unsafe { &mut *this.transmute_into_raw::<F>() };
+        this.call_mut_tuple(args)
+    }
+}
+
+unsafe extern "C" fn call_once_fn<F, A, R>(this: *mut (), args: A) -> RResult<R, RPanicPayload>
+where
+    F: FnOnceTuple<A, R>,
+{
+    extern_fn_panic_handling! {catch_unwind;
+        let this = unsafe { (this as *mut F).read() };
+        this.call_once_tuple(args)
+    }
+}
@@ -0,0 +1,148 @@
+use super::*;
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::Arc,
+    thread,
+};
+
+use crate::{
+    abi_stability::abi_checking::check_layout_compatibility,
+    std_types::{RString, RVec},
+    StableAbi,
+};
+
+fn panic_message(f: impl FnOnce()) -> String {
+    let payload = catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+    payload.downcast::<&str>().map(|x| x.to_string()).unwrap()
+}
+
+#[test]
+fn rboxfn_arities() {
+    let zero = RBoxFn::new(|| 1u32);
+    let one = RBoxFn::new(|a: u32| a);
+    let two = RBoxFn::new(|a: u32, b: u32| a + b);
+    let three = RBoxFn::new(|a: u32, b: u32, c: u32| a + b + c);
+    let four = RBoxFn::new(|a: u32, b: u32, c: u32, d: u32| a + b + c + d);
+
+    assert_eq!(zero.call(), 1);
+    assert_eq!(one.call(2), 2);
+    assert_eq!(two.call(2, 3), 5);
+    assert_eq!(three.call(2, 3, 5), 10);
+    assert_eq!(four.call(2, 3, 5, 8), 18);
+
+    assert_eq!(zero.call_tuple(()), 1);
+    assert_eq!(one.call_tuple(Tuple1(2)), 2);
+    assert_eq!(two.call_tuple(Tuple2(2, 3)), 5);
+    assert_eq!(three.call_tuple(Tuple3(2, 3, 5)), 10);
+    assert_eq!(four.call_tuple(Tuple4(2, 3, 5, 8)), 18);
+}
+
+#[test]
+fn rboxfn_as_parameter() {
+    fn apply(f: RBoxFn<'_, Tuple2<RString, u32>, RString>) -> RString {
+        f.call("ab".into(), 3)
+    }
+
+    let repeat = RBoxFn::new(|s: RString, n: u32| s.repeat(n as usize).into());
+    assert_eq!(apply(repeat), "ababab");
+}
+
+#[test]
+fn rboxfn_borrowing_and_threads() {
+    let text = String::from("hello");
+    let len = RBoxFn::new(|| text.len());
+    assert_eq!(len.call(), 5);
+
+    let borrowed = len.as_rref_fn();
+    assert_eq!(borrowed.call(), 5);
+    assert_eq!(borrowed.clone().call(), 5);
+
+    let shared = RBoxFn::new(|x: u32| x * 2);
+    let handle = thread::spawn(move || shared.call(21));
+    assert_eq!(handle.join().unwrap(), 42);
+}
+
+#[test]
+fn rboxfnmut_mutates() {
+    let mut list = RVec::new();
+    {
+        let mut push = RBoxFnMut::new(|x: u32, y: u32| {
+            list.push(x);
+            list.push(y);
+            list.len()
+        });
+        assert_eq!(push.call(3, 5), 2);
+        assert_eq!(push.call_tuple(Tuple2(8, 13)), 4);
+    }
+    assert_eq!(list, vec![3, 5, 8, 13]);
+
+    let mut from_fn = RBoxFnMut::new(|| 10);
+    assert_eq!(from_fn.call(), 10);
+}
+
+#[test]
+fn rboxfnonce_moves() {
+    let arc = Arc::new(0);
+
+    let cloned = arc.clone();
+    let into_count = RBoxFnOnce::new(move |x: usize| Arc::strong_count(&cloned) + x);
+    assert_eq!(Arc::strong_count(&arc), 2);
+    assert_eq!(into_count.call(100), 102);
+    assert_eq!(Arc::strong_count(&arc), 1);
+
+    let cloned = arc.clone();
+    let not_called = RBoxFnOnce::new(move || drop(cloned));
+    assert_eq!(Arc::strong_count(&arc), 2);
+    drop(not_called);
+    assert_eq!(Arc::strong_count(&arc), 1);
+}
+
+#[test]
+fn rreffn_calls() {
+    let closure = |a: u32, b: u32, c: u32| a * b * c;
+    let f: RRefFn<'_, Tuple3<u32, u32, u32>, u32> = RRefFn::new(&closure);
+    let copy = f;
+    assert_eq!(f.call(2, 3, 5), 30);
+    assert_eq!(copy.call_tuple(Tuple3(1, 1, 1)), 1);
+}
+
+#[test]
+fn resumes_panics() {
+    let boxed = RBoxFn::new(|| panic!("rboxfn panicked"));
+    assert_eq!(panic_message(|| boxed.call()), "rboxfn panicked");
+    assert_eq!(
+        panic_message(|| boxed.as_rref_fn().call()),
+        "rboxfn panicked"
+    );
+
+    let mut boxed_mut = RBoxFnMut::new(|| panic!("rboxfnmut panicked"));
+    assert_eq!(panic_message(|| boxed_mut.call()), "rboxfnmut panicked");
+
+    let arc = Arc::new(0);
+    let cloned = arc.clone();
+    let boxed_once = RBoxFnOnce::new(move || {
+        let _cloned = cloned;
+        panic!("rboxfnonce panicked")
+    });
+    assert_eq!(panic_message(|| boxed_once.call()), "rboxfnonce panicked");
+    assert_eq!(Arc::strong_count(&arc), 1);
+}
+
+#[test]
+fn layout_depends_on_signature() {
+    let layouts = [
+        <RBoxFn<'_, Tuple1<u32>, u32> as StableAbi>::LAYOUT,
+        <RBoxFn<'_, Tuple1<u64>, u32> as StableAbi>::LAYOUT,
+        <RBoxFn<'_, Tuple1<u32>, u64> as StableAbi>::LAYOUT,
+        <RBoxFn<'_, Tuple2<u32, u32>, u32> as StableAbi>::LAYOUT,
+        <RBoxFnMut<'_, Tuple1<u32>, u32> as StableAbi>::LAYOUT,
+    ];
+
+    for (l_i, l_layout) in layouts.iter().enumerate() {
+        for (r_i, r_layout) in layouts.iter().enumerate() {
+            let res = check_layout_compatibility(l_layout, r_layout);
+            assert_eq!(res.is_ok(), l_i == r_i, "{} {}", l_i, r_i);
+        }
+    }
+}
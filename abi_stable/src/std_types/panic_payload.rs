@@ -0,0 +1,311 @@
+//! Contains the ffi-safe equivalent of the payload of a panic.
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    fmt::{self, Debug, Display},
+    panic::{self, AssertUnwindSafe},
+    sync::Once,
+};
+
+use crate::{
+    erased_types::DynTrait,
+    std_types::{RBox, ROption, RResult, RString},
+    StableAbi,
+};
+
+/// Ffi-safe equivalent of the `Box<dyn Any + Send>` payload of a panic,
+/// along with the message and location of the panic.
+///
+/// This is how panics are passed across the ffi boundary by functions that use
+/// `#[sabi_extern_fn(catch_unwind)]` and `#[sabi(catch_unwind)]` `#[sabi_trait]` methods,
+/// which return it in an `RResult` instead of aborting the process.
+///
+/// # Downcasting
+///
+/// The original payload can only be accessed by the
+/// [`downcast_ref`](#method.downcast_ref) and [`into_any`](#method.into_any) methods
+/// in the same dynamic library (or executable) that caught the panic,
+/// the message and location of the panic are available everywhere.
+///
+/// # Location
+///
+/// The location of the panic is recorded by a panic hook that is installed
+/// (wrapping the previous hook) the first time that [`catch_unwind`](#method.catch_unwind)
+/// is called.
+/// Panics that happen before that, or after the hook is replaced, don't have a location.
+///
+/// # Example
+///
+/// ```rust
+/// use abi_stable::{sabi_extern_fn, std_types::RPanicPayload};
+///
+/// #[sabi_extern_fn(catch_unwind)]
+/// fn parse(s: &str) -> u32 {
+///     s.parse().unwrap()
+/// }
+///
+/// assert_eq!(parse("100").unwrap(), 100);
+///
+/// let payload: RPanicPayload = parse("hello").unwrap_err();
+/// assert!(payload.message().contains("ParseIntError"), "{}", payload);
+/// assert!(payload.location().is_some());
+///
+/// // resuming the panic in the caller
+/// let res = std::panic::catch_unwind(|| payload.resume_unwind());
+/// assert!(res.is_err());
+///
+/// ```
+#[repr(C)]
+#[derive(StableAbi)]
+pub struct RPanicPayload {
+    message: RString,
+    location: ROption<RPanicLocation>,
+    payload: DynTrait<'static, RBox<()>, PanicPayloadInterface>,
+}
+
+/// The location in the source code where a panic happened.
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, StableAbi)]
+pub struct RPanicLocation {
+    /// The path of the source file.
+    pub file: RString,
+    /// The line in the source file, starting from 1.
+    pub line: u32,
+    /// The column in the line, starting from 1.
+    pub column: u32,
+}
+
+impl Display for RPanicLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+#[repr(C)]
+#[derive(StableAbi)]
+#[sabi(impl_InterfaceType(Send))]
+struct PanicPayloadInterface;
+
+type AnyPayload = Box<dyn Any + Send>;
+
+impl RPanicPayload {
+    /// Converts the payload of a panic (as returned by `std::panic::catch_unwind`)
+    /// into an `RPanicPayload`.
+    ///
+    /// If the payload is an `RPanicPayload` (from [`resume_unwind`](#method.resume_unwind))
+    /// this returns it.
+    ///
+    /// The message is the payload itself if it's a `&'static str` or a `String`
+    /// (the payloads of the `panic` macro), otherwise it's `"Box<dyn Any>"`.
+    pub fn new(payload: Box<dyn Any + Send>) -> Self {
+        let payload = match payload.downcast::<RPanicPayload>() {
+            Ok(this) => return *this,
+            Err(payload) => payload,
+        };
+        let location = take_location();
+
+        let message = if let Some(x) = payload.downcast_ref::<&'static str>() {
+            RString::from(*x)
+        } else if let Some(x) = payload.downcast_ref::<String>() {
+            RString::from(x.as_str())
+        } else {
+            RString::from("Box<dyn Any>")
+        };
+
+        Self {
+            message,
+            location: location.into(),
+            payload: DynTrait::from_value(payload),
+        }
+    }
+
+    /// Calls `f`, returning the payload of the panic if it panics.
+    ///
+    /// This is used by `#[sabi_extern_fn(catch_unwind)]` functions.
+    pub fn catch_unwind<F, R>(f: F) -> RResult<R, RPanicPayload>
+    where
+        F: FnOnce() -> R,
+    {
+        install_location_hook();
+        // Discards the location of a panic that was caught without using it,
+        // so that it isn't attached to a payload whose panic didn't record a location.
+        take_location();
+        panic::catch_unwind(AssertUnwindSafe(f))
+            .map_err(RPanicPayload::new)
+            .into()
+    }
+
+    /// The panic message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The location where the panic happened, if it was recorded.
+    pub fn location(&self) -> Option<&RPanicLocation> {
+        self.location.as_ref().into_option()
+    }
+
+    /// Gets a reference to the original payload as a `T`,
+    /// returning None if it's not a `T`, or if this is called
+    /// outside the dynamic library (or executable) where the panic was caught.
+    pub fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: 'static,
+    {
+        let payload: &(dyn Any + Send) = &**self.payload.downcast_as::<AnyPayload>().ok()?;
+        payload.downcast_ref::<T>()
+    }
+
+    /// Converts this into a `Box<dyn Any + Send>` that can be passed to
+    /// `std::panic::resume_unwind`.
+    ///
+    /// This returns the original payload if this is called in the
+    /// dynamic library (or executable) where the panic was caught,
+    /// otherwise it returns this `RPanicPayload` boxed,
+    /// which can be converted back with [`RPanicPayload::new`](#method.new).
+    pub fn into_any(self) -> Box<dyn Any + Send> {
+        let Self {
+            message,
+            location,
+            payload,
+        } = self;
+
+        match payload.downcast_into::<AnyPayload>() {
+            Ok(payload) => RBox::into_inner(payload),
+            Err(e) => Box::new(Self {
+                message,
+                location,
+                payload: e.into_inner(),
+            }),
+        }
+    }
+
+    /// Resumes the panic in the caller, with the payload returned by
+    /// [`into_any`](#method.into_any).
+    ///
+    /// This doesn't call the panic hook.
+    pub fn resume_unwind(self) -> ! {
+        panic::resume_unwind(self.into_any())
+    }
+}
+
+impl Debug for RPanicPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RPanicPayload")
+            .field("message", &self.message)
+            .field("location", &self.location)
+            .finish()
+    }
+}
+
+impl Display for RPanicPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location() {
+            Some(location) => write!(f, "panicked at '{}', {}", self.message, location),
+            None => write!(f, "panicked at '{}'", self.message),
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+thread_local! {
+    static LAST_LOCATION: RefCell<Option<RPanicLocation>> = const { RefCell::new(None) };
+}
+
+static INSTALL_LOCATION_HOOK: Once = Once::new();
+
+/// Wraps the panic hook to record the location of panics in `LAST_LOCATION`.
+fn install_location_hook() {
+    // `set_hook` panics when called while panicking.
+    if std::thread::panicking() {
+        return;
+    }
+    INSTALL_LOCATION_HOOK.call_once(|| {
+        let prev_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if let Some(location) = info.location() {
+                let location = RPanicLocation {
+                    file: location.file().into(),
+                    line: location.line(),
+                    column: location.column(),
+                };
+                let _ = LAST_LOCATION.try_with(|last| *last.borrow_mut() = Some(location));
+            }
+            prev_hook(info);
+        }));
+    });
+}
+
+fn take_location() -> Option<RPanicLocation> {
+    LAST_LOCATION
+        .try_with(|last| last.borrow_mut().take())
+        .ok()
+        .flatten()
+}
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catching_panics() {
+        let payload = RPanicPayload::catch_unwind(|| panic!("hello")).unwrap_err();
+        assert_eq!(payload.message(), "hello");
+        assert_eq!(payload.location().unwrap().file, file!());
+        assert_eq!(*payload.downcast_ref::<&str>().unwrap(), "hello");
+        assert!(payload.downcast_ref::<String>().is_none());
+
+        let payload =
+            RPanicPayload::catch_unwind(|| panic::panic_any(format!("hello {}", 100))).unwrap_err();
+        assert_eq!(payload.message(), "hello 100");
+        assert_eq!(payload.downcast_ref::<String>().unwrap(), "hello 100");
+
+        let payload = RPanicPayload::catch_unwind(|| panic::panic_any(3u8)).unwrap_err();
+        assert_eq!(payload.message(), "Box<dyn Any>");
+        assert_eq!(payload.downcast_ref::<u8>(), Some(&3));
+
+        let resumed = panic::catch_unwind(|| payload.resume_unwind()).unwrap_err();
+        assert_eq!(resumed.downcast_ref::<u8>(), Some(&3));
+
+        assert_eq!(RPanicPayload::catch_unwind(|| 5).unwrap(), 5);
+    }
+
+    #[test]
+    fn boxed_payload() {
+        let payload = RPanicPayload::catch_unwind(|| panic!("world")).unwrap_err();
+        let location = payload.location().cloned();
+        let boxed: Box<dyn Any + Send> = Box::new(payload);
+
+        let payload = RPanicPayload::new(boxed);
+        assert_eq!(payload.message(), "world");
+        assert_eq!(payload.location().cloned(), location);
+        assert_eq!(
+            payload.to_string(),
+            format!("panicked at 'world', {}", location.unwrap())
+        );
+    }
+
+    #[test]
+    fn stale_locations() {
+        RPanicPayload::catch_unwind(|| ()).unwrap();
+
+        // The location of this panic is recorded, but never taken.
+        let _ = panic::catch_unwind(|| panic!("stale"));
+
+        // `resume_unwind` doesn't call the panic hook, so there's no location.
+        let payload =
+            RPanicPayload::catch_unwind(|| panic::resume_unwind(Box::new(7u8))).unwrap_err();
+        assert_eq!(payload.downcast_ref::<u8>(), Some(&7));
+        assert_eq!(payload.location(), None);
+
+        let boxed: Box<dyn Any + Send> = Box::new(payload);
+        let line = line!() + 1;
+        let _ = panic::catch_unwind(|| panic!("recorded"));
+
+        // Returning an existing `RPanicPayload` leaves the recorded location alone.
+        assert_eq!(RPanicPayload::new(boxed).location(), None);
+        assert_eq!(take_location().map(|x| x.line), Some(line));
+    }
+}
@@ -10,6 +10,8 @@ use std::{
 
 use core_extensions::{matches, StringExt};
 
+use serde::{Deserialize, Serialize};
+
 use crate::{
     abi_stability::{
         extra_checks::{ExtraChecksStaticRef, StoredExtraChecks},
@@ -24,6 +26,7 @@ use crate::{
 
 mod construction;
 pub mod data_structures;
+mod fingerprint;
 mod iterators;
 mod printing;
 mod shared_vars;
@@ -40,10 +43,13 @@ mod tl_other;
 mod tl_prefix;
 mod tl_reflection;
 
-pub(crate) use self::iterators::ChainOnce;
+pub(crate) use self::{
+    fingerprint::{identical_layouts, layout_fingerprint},
+    iterators::ChainOnce,
+};
 
 pub use self::{
-    construction::{ItemInfo, _private_MonoTypeLayoutDerive, _private_TypeLayoutDerive},
+    construction::{_private_MonoTypeLayoutDerive, _private_TypeLayoutDerive, ItemInfo},
     shared_vars::{MonoSharedVars, SharedVars},
     small_types::{OptionU16, OptionU8, StartLen, StartLenConverter, StartLenRepr},
     tagging::Tag,
@@ -356,6 +362,47 @@ impl TypeLayout {
     pub const fn mono_type_layout(&self) -> &MonoTypeLayout {
         self.mono
     }
+
+    /// Computes a hash of the structure of this type,
+    /// and every type that it references.
+    ///
+    /// This hashes the name, package, package version, size, alignment,
+    /// representation attribute, generic parameters, fields,
+    /// enum variants, prefix type properties, and tag of every type,
+    /// as well as the `Display` output of any extra checks.
+    ///
+    /// Integers are hashed as little-endian bytes,
+    /// so the fingerprint doesn't change between runs of a program,
+    /// between compilations of the same type definitions,
+    /// or between platforms where the types have the same size and alignment,
+    /// so it can be stored to compare the layouts of types across builds.
+    ///
+    /// If two types have the same fingerprint,
+    /// they are very likely to be identical
+    /// (ignoring where they were declared in their crate).
+    /// Different fingerprints do not imply that the types are incompatible,
+    /// use [`check_layout_compatibility`] for that.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use abi_stable::{
+    ///     std_types::{RString, RVec},
+    ///     StableAbi,
+    /// };
+    ///
+    /// let fingerprint = <RVec<RString>>::LAYOUT.fingerprint();
+    ///
+    /// assert_eq!(fingerprint, <RVec<RString>>::LAYOUT.fingerprint());
+    /// assert_ne!(fingerprint, <RVec<u8>>::LAYOUT.fingerprint());
+    ///
+    /// ```
+    ///
+    /// [`check_layout_compatibility`]:
+    /// ../abi_stability/abi_checking/fn.check_layout_compatibility.html
+    pub fn fingerprint(&'static self) -> u64 {
+        layout_fingerprint(self)
+    }
 }
 
 impl PartialEq for TypeLayout {
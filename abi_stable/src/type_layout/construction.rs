@@ -34,7 +34,10 @@ pub struct _private_MonoTypeLayoutDerive {
 #[sabi(unsafe_sabi_opaque_fields)]
 pub struct ItemInfo {
     /// The package where the type was defined,and the version string.
-    /// With the `package;version_number` format.
+    /// With the `package;version_number` format,
+    /// followed by a `;old_package:OldName` entry for every place
+    /// that the type was moved from,
+    /// and a `;VariantName=OldName,OlderName,` entry for every enum variant that was renamed.
     package_and_version: RStr<'static>,
     /// The line in the file where the type was defined.
     pub line: u32,
@@ -89,13 +92,57 @@ impl ItemInfo {
 
     /// Gets the package name and an unparsed package version.
     pub fn package_and_version(&self) -> (&'static str, &'static str) {
-        let pav = self.package_and_version.as_str();
-        match pav.find(';') {
-            Some(separator) => (&pav[..separator], &pav[(separator + 1)..]),
-            None => (pav, ""),
+        let mut iter = self.package_and_version.as_str().split(';');
+        let package = iter.next().unwrap_or("");
+        let version = iter.next().unwrap_or("");
+        (package, version)
+    }
+
+    /// Gets the `(package, type_name)` pairs that the type was moved from,
+    /// declared with the `#[sabi(moved_from(package = "...", name = "..."))]` attribute.
+    pub fn moved_from(
+        &self,
+    ) -> impl Iterator<Item = (&'static str, &'static str)> + Clone + Debug + 'static {
+        self.package_and_version
+            .as_str()
+            .split(';')
+            .skip(2)
+            .filter_map(|x| x.split_once(':'))
+    }
+
+    /// Gets the `;` separated `VariantName=OldName,OlderName,` entries
+    /// for the enum variants that were renamed,
+    /// declared with the `#[sabi(renamed_from = "...")]` attribute.
+    pub(crate) fn renamed_variants(&self) -> &'static str {
+        let package_and_version = self.package_and_version.as_str();
+        // These entries come after every other one, and are the only ones with a `=`.
+        match package_and_version.find('=') {
+            Some(equals) => {
+                let start = package_and_version[..equals]
+                    .rfind(';')
+                    .map_or(0, |i| i + 1);
+                &package_and_version[start..]
+            }
+            None => "",
         }
     }
 
+    /// Gets the names that the `variant` variant of the enum was renamed from,
+    /// declared with the `#[sabi(renamed_from = "...")]` attribute.
+    pub fn variant_renamed_from(
+        &self,
+        variant: &str,
+    ) -> impl Iterator<Item = &'static str> + Clone + Debug + 'static {
+        let old_names = self
+            .renamed_variants()
+            .split_terminator(';')
+            .filter_map(|x| x.split_once('='))
+            .find(|&(name, _)| name == variant)
+            .map_or("", |(_, old_names)| old_names);
+
+        old_names.split_terminator(',')
+    }
+
     /// Gets the package name.
     pub fn package(&self) -> &'static str {
         self.package_and_version().0
@@ -0,0 +1,509 @@
+use super::*;
+
+use std::{collections::HashMap, fmt::Write as _, hash::Hasher};
+
+use crate::{
+    reflection::ModReflMode,
+    type_layout::tagging::{Primitive, TagVariant},
+    utils::Fnv1a,
+};
+
+/// Computes the fingerprint of `layout`,
+/// see `TypeLayout::fingerprint` for what's hashed.
+///
+/// Every value is hashed with an explicit encoding,
+/// integers are hashed as little-endian bytes, and enums by their variant index,
+/// so that the fingerprint is the same on every platform.
+/// The only exceptions are the values of const parameters,
+/// and extra checks, which can only be formatted.
+pub(crate) fn layout_fingerprint(layout: &'static TypeLayout) -> u64 {
+    let mut this = Fingerprinter::new(layout, false);
+    while this.hash_next() {}
+    this.hasher.finish()
+}
+
+/// Whether `interface` and `implementation` are structurally identical,
+/// by comparing the data that's hashed by their fingerprints,
+/// so that a hash collision can't make different layouts be considered identical.
+///
+/// This returns the pairs of types referenced by both layouts,
+/// in the same order for both, with `interface` and `implementation` first.
+///
+/// This returns `None` if the layouts aren't identical,
+/// or if either references a type with extra checks,
+/// since those need to be checked with `ExtraChecks::check_compatibility`.
+#[allow(clippy::type_complexity)]
+pub(crate) fn identical_layouts(
+    interface: &'static TypeLayout,
+    implementation: &'static TypeLayout,
+) -> Option<Vec<(&'static TypeLayout, &'static TypeLayout)>> {
+    let mut left = Fingerprinter::new(interface, true);
+    let mut right = Fingerprinter::new(implementation, true);
+
+    loop {
+        // Comparing after every type, so that most differences are found early.
+        if left.encoded != right.encoded || left.has_extra_checks || right.has_extra_checks {
+            return None;
+        }
+        left.encoded.clear();
+        right.encoded.clear();
+
+        match (left.hash_next(), right.hash_next()) {
+            (true, true) => {}
+            (false, false) => break,
+            _ => return None,
+        }
+    }
+
+    Some(left.layouts.into_iter().zip(right.layouts).collect())
+}
+
+struct Fingerprinter {
+    hasher: Fnv1a,
+    /// Whether the hashed bytes are also written to `encoded`.
+    is_encoding: bool,
+    encoded: Vec<u8>,
+    buffer: String,
+    /// Maps the types that were found so far to the order they were found in,
+    /// which is hashed instead of the `UTypeId`, since it's the same across runs.
+    indices: HashMap<UTypeId, usize>,
+    /// The types in the order they were found,
+    /// hashed in that order, grows as the types referenced by hashed types are found.
+    layouts: Vec<&'static TypeLayout>,
+    /// The index in `layouts` of the next type to hash.
+    next: usize,
+    has_extra_checks: bool,
+}
+
+impl Fingerprinter {
+    fn new(layout: &'static TypeLayout, is_encoding: bool) -> Self {
+        let mut this = Fingerprinter {
+            hasher: Fnv1a::new(),
+            is_encoding,
+            encoded: Vec::new(),
+            buffer: String::new(),
+            indices: HashMap::new(),
+            layouts: Vec::new(),
+            next: 0,
+            has_extra_checks: false,
+        };
+        this.layout_ref(layout);
+        this
+    }
+
+    /// Hashes the next type that was found,
+    /// returning whether there was one.
+    fn hash_next(&mut self) -> bool {
+        match self.layouts.get(self.next) {
+            Some(&layout) => {
+                self.next += 1;
+                self.layout(layout);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.hasher.write(bytes);
+        if self.is_encoding {
+            self.encoded.extend_from_slice(bytes);
+        }
+    }
+
+    fn write_u8(&mut self, n: u8) {
+        self.write(&[n]);
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_i64(&mut self, n: i64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_str(&mut self, s: &str) {
+        self.write_usize(s.len());
+        self.write(s.as_bytes());
+    }
+
+    fn write_debug<T: Debug + ?Sized>(&mut self, value: &T) {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        let _ = write!(buffer, "{:?}", value);
+        self.write_str(&buffer);
+        self.buffer = buffer;
+    }
+
+    fn write_display<T: Display + ?Sized>(&mut self, value: &T) {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        let _ = write!(buffer, "{}", value);
+        self.write_str(&buffer);
+        self.buffer = buffer;
+    }
+
+    /// Hashes a reference to another type,
+    /// queueing it to be hashed if it wasn't found before.
+    fn layout_ref(&mut self, layout: &'static TypeLayout) {
+        let next_index = self.indices.len();
+        let layouts = &mut self.layouts;
+        let index = *self.indices.entry(layout.get_utypeid()).or_insert_with(|| {
+            layouts.push(layout);
+            next_index
+        });
+        self.write_usize(index);
+    }
+
+    fn layout(&mut self, layout: &'static TypeLayout) {
+        let (package, version) = layout.item_info().package_and_version();
+        self.write_str(layout.name());
+        self.write_str(package);
+        self.write_str(version);
+        self.write_u8(layout.is_nonzero() as u8);
+        self.write_usize(layout.size());
+        self.write_usize(layout.alignment());
+        self.repr_attr(layout.repr_attr());
+        self.mod_refl_mode(layout.mod_refl_mode());
+
+        let generics = layout.generics();
+        self.write_usize(generics.lifetime_count());
+        self.write_usize(generics.type_params().len());
+        for type_param in generics.type_params() {
+            self.layout_ref(type_param());
+        }
+        self.write_usize(generics.const_params().len());
+        for const_param in generics.const_params() {
+            // Const parameters can only be integers, `bool`s, and `char`s,
+            // whose `Debug` output doesn't change between platforms.
+            self.write_debug(const_param);
+        }
+
+        self.fields(layout.phantom_fields());
+
+        self.write_u8(layout.data_discriminant() as u8);
+        match layout.data() {
+            TLData::Primitive(primitive) => self.write_u8(primitive as u8),
+            TLData::Opaque => {}
+            TLData::Struct { fields } | TLData::Union { fields } => self.fields(fields),
+            TLData::Enum(enum_) => {
+                self.write_usize(enum_.field_count.len());
+                self.write(enum_.field_count.as_slice());
+                self.write_str(enum_.variant_names.as_str());
+                self.exhaustiveness(enum_.exhaustiveness);
+                self.discriminants(enum_.discriminants);
+                self.fields(enum_.fields);
+            }
+            TLData::PrefixType(prefix) => {
+                self.write_u8(prefix.first_suffix_field);
+                self.write_u64(prefix.conditional_prefix_fields.bits());
+                self.write_u64(prefix.accessible_fields.bits());
+                self.fields(prefix.fields);
+            }
+        }
+
+        self.tag(layout.tag());
+
+        match layout.extra_checks() {
+            Some(extra_checks) => {
+                self.has_extra_checks = true;
+                self.write_u8(1);
+                self.write_display(&extra_checks);
+                let nested = extra_checks.nested_type_layouts();
+                self.write_usize(nested.len());
+                for &nested_layout in &*nested {
+                    self.layout_ref(nested_layout);
+                }
+            }
+            None => self.write_u8(0),
+        }
+    }
+
+    fn fields<I>(&mut self, fields: I)
+    where
+        I: IntoIterator<Item = TLField>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let fields = fields.into_iter();
+        self.write_usize(fields.len());
+        for field in fields {
+            self.write_str(field.name());
+            self.lifetime_indices(&field.lifetime_indices());
+            self.field_accessor(field.field_accessor());
+            self.write_u8(field.is_function() as u8);
+            self.layout_ref(field.layout());
+
+            let functions = field.function_range();
+            self.write_usize(functions.len());
+            for function in functions {
+                self.write_str(function.name.as_str());
+                self.write_str(function.bound_lifetimes.as_str());
+                self.lifetime_indices(&function.paramret_lifetime_indices);
+                self.write_u8(function.qualifiers().is_unsafe() as u8);
+
+                let params = function.get_params_ret_iter();
+                self.write_usize(params.len());
+                for param in params {
+                    self.write_str(param.name());
+                    self.layout_ref(param.layout());
+                }
+            }
+        }
+    }
+
+    fn repr_attr(&mut self, repr: ReprAttr) {
+        match repr {
+            ReprAttr::OptionNonZero => self.write_u8(0),
+            ReprAttr::Primitive => self.write_u8(1),
+            ReprAttr::C => self.write_u8(2),
+            ReprAttr::CAndInt(discr_repr) => {
+                self.write_u8(3);
+                self.write_u8(discr_repr as u8);
+            }
+            ReprAttr::Transparent => self.write_u8(4),
+            ReprAttr::Int(discr_repr) => {
+                self.write_u8(5);
+                self.write_u8(discr_repr as u8);
+            }
+            ReprAttr::Packed {
+                alignment_power_of_two,
+            } => {
+                self.write_u8(6);
+                self.write_u8(alignment_power_of_two);
+            }
+        }
+    }
+
+    fn mod_refl_mode(&mut self, mode: ModReflMode) {
+        match mode {
+            ModReflMode::Module => self.write_u8(0),
+            ModReflMode::Opaque => self.write_u8(1),
+            ModReflMode::DelegateDeref { layout_index } => {
+                self.write_u8(2);
+                self.write_u8(layout_index);
+            }
+        }
+    }
+
+    fn exhaustiveness(&mut self, exhaustiveness: IsExhaustive) {
+        match exhaustiveness.as_nonexhaustive() {
+            Some(nonexhaustive) => {
+                self.write_u8(1);
+                self.write_usize(nonexhaustive.original_size());
+                self.write_usize(nonexhaustive.original_alignment());
+            }
+            None => self.write_u8(0),
+        }
+    }
+
+    fn discriminants(&mut self, discriminants: TLDiscriminants) {
+        self.write_u8(discriminants.discriminant_repr() as u8);
+        let discriminants = discriminants.to_vec();
+        self.write_usize(discriminants.len());
+        for discriminant in discriminants {
+            match discriminant {
+                TLDiscriminant::Isize(n) => self.write_i64(n as i64),
+                TLDiscriminant::Usize(n) => self.write_u64(n as u64),
+                TLDiscriminant::Signed(n) => self.write_i64(n),
+                TLDiscriminant::Unsigned(n) => self.write_u64(n),
+            }
+        }
+    }
+
+    fn lifetime_indices(&mut self, indices: &[LifetimeIndexPair]) {
+        self.write_usize(indices.len());
+        for pair in indices {
+            self.write_u8(pair.to_u8());
+        }
+    }
+
+    fn field_accessor(&mut self, accessor: FieldAccessor) {
+        match accessor {
+            FieldAccessor::Direct => self.write_u8(0),
+            FieldAccessor::Method => self.write_u8(1),
+            FieldAccessor::MethodNamed { name } => {
+                self.write_u8(2);
+                self.write_str(name.as_str());
+            }
+            FieldAccessor::MethodOption => self.write_u8(3),
+            FieldAccessor::Opaque => self.write_u8(4),
+        }
+    }
+
+    fn tag(&mut self, tag: &Tag) {
+        match tag.variant() {
+            TagVariant::Primitive(primitive) => {
+                self.write_u8(0);
+                match *primitive {
+                    Primitive::Null => self.write_u8(0),
+                    Primitive::Bool(b) => {
+                        self.write_u8(1);
+                        self.write_u8(b as u8);
+                    }
+                    Primitive::Int(n) => {
+                        self.write_u8(2);
+                        self.write_i64(n);
+                    }
+                    Primitive::UInt(n) => {
+                        self.write_u8(3);
+                        self.write_u64(n);
+                    }
+                    Primitive::String_(s) => {
+                        self.write_u8(4);
+                        self.write_str(s.as_str());
+                    }
+                }
+            }
+            TagVariant::Ignored(ignored) => {
+                self.write_u8(1);
+                self.tag(ignored);
+            }
+            TagVariant::Array(tags) => {
+                self.write_u8(2);
+                self.write_usize(tags.len());
+                for tag in tags.iter() {
+                    self.tag(tag);
+                }
+            }
+            TagVariant::Set(tags) => {
+                self.write_u8(3);
+                self.write_usize(tags.len());
+                for tag in tags.iter() {
+                    self.tag(tag);
+                }
+            }
+            TagVariant::Map(pairs) => {
+                self.write_u8(4);
+                self.write_usize(pairs.len());
+                for pair in pairs.iter() {
+                    self.tag(&pair.key);
+                    self.tag(&pair.value);
+                }
+            }
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
+#[cfg(all(test, not(feature = "only_new_tests")))]
+mod tests {
+    use super::*;
+
+    use crate::{
+        std_types::{RBox, RStr, RString, RVec},
+        DynTrait, StableAbi,
+    };
+
+    mod first {
+        #[repr(C)]
+        #[derive(crate::StableAbi)]
+        pub struct Point {
+            pub x: u32,
+            pub y: u32,
+        }
+    }
+
+    mod second {
+        #[repr(C)]
+        #[derive(crate::StableAbi)]
+        pub struct Point {
+            pub x: u32,
+            pub y: u32,
+        }
+    }
+
+    mod renamed_field {
+        #[repr(C)]
+        #[derive(crate::StableAbi)]
+        pub struct Point {
+            pub x: u32,
+            pub z: u32,
+        }
+    }
+
+    mod changed_field_type {
+        #[repr(C)]
+        #[derive(crate::StableAbi)]
+        pub struct Point {
+            pub x: u32,
+            pub y: i32,
+        }
+    }
+
+    #[repr(C)]
+    #[derive(StableAbi)]
+    struct List {
+        value: u32,
+        next: *const List,
+    }
+
+    #[test]
+    fn same_structure() {
+        let first = first::Point::LAYOUT.fingerprint();
+        assert_eq!(first, first::Point::LAYOUT.fingerprint());
+        assert_eq!(first, second::Point::LAYOUT.fingerprint());
+
+        assert_ne!(first, renamed_field::Point::LAYOUT.fingerprint());
+        assert_ne!(first, changed_field_type::Point::LAYOUT.fingerprint());
+    }
+
+    #[test]
+    fn stable_across_platforms() {
+        // These types have the same layout on every platform,
+        // so their fingerprints must not change between platforms or builds.
+        assert_eq!(<u32>::LAYOUT.fingerprint(), 0x4fe5498c85b205ff);
+        assert_eq!(<[u32; 4]>::LAYOUT.fingerprint(), 0x33341bf7ee255fda);
+    }
+
+    #[test]
+    fn nested_layouts() {
+        let vec_string = <RVec<RString>>::LAYOUT.fingerprint();
+        assert_eq!(vec_string, <RVec<RString>>::LAYOUT.fingerprint());
+        assert_ne!(vec_string, <RVec<u8>>::LAYOUT.fingerprint());
+        assert_ne!(<u32>::LAYOUT.fingerprint(), <i32>::LAYOUT.fingerprint());
+    }
+
+    #[test]
+    fn recursive_type() {
+        let pairs = identical_layouts(List::LAYOUT, List::LAYOUT).unwrap();
+        let pairs = pairs
+            .iter()
+            .map(|(l, r)| (l.get_utypeid(), r.get_utypeid()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pairs[0],
+            (List::LAYOUT.get_utypeid(), List::LAYOUT.get_utypeid())
+        );
+        assert_eq!(pairs.len(), 3, "{:?}", pairs);
+    }
+
+    #[test]
+    fn identical() {
+        let points = identical_layouts(first::Point::LAYOUT, second::Point::LAYOUT).unwrap();
+        assert_eq!(points.len(), 2);
+
+        assert!(identical_layouts(first::Point::LAYOUT, renamed_field::Point::LAYOUT).is_none());
+        assert!(
+            identical_layouts(first::Point::LAYOUT, changed_field_type::Point::LAYOUT).is_none()
+        );
+        assert!(identical_layouts(<RVec<RString>>::LAYOUT, <RVec<u8>>::LAYOUT).is_none());
+        assert!(identical_layouts(<RStr<'static>>::LAYOUT, <RStr<'static>>::LAYOUT).is_some());
+    }
+
+    #[test]
+    fn prefix_types_and_extra_checks() {
+        // `RBox` has a vtable, which is a prefix type.
+        let pairs = identical_layouts(<RBox<u8>>::LAYOUT, <RBox<u8>>::LAYOUT).unwrap();
+        assert!(pairs.iter().any(|(l, _)| l.is_prefix_kind()));
+        assert!(identical_layouts(<RVec<u8>>::LAYOUT, <RVec<u8>>::LAYOUT).is_some());
+
+        type Object = DynTrait<'static, RBox<()>, ()>;
+        assert!(identical_layouts(Object::LAYOUT, Object::LAYOUT).is_none());
+    }
+}
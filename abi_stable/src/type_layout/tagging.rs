@@ -340,6 +340,10 @@ impl Tag {
         Self { variant }
     }
 
+    pub(crate) const fn variant(&self) -> &TagVariant {
+        &self.variant
+    }
+
     /// Constructs the Null variant.
     pub const NULL: &'static Tag = &Tag::null();
 
@@ -447,6 +451,14 @@ where
 }
 
 impl CheckableTag {
+    pub(crate) const fn new(variant: CTVariant) -> Self {
+        Self { variant }
+    }
+
+    pub(crate) const fn variant(&self) -> &CTVariant {
+        &self.variant
+    }
+
     /// Checks that this `CheckableTag` is compatible with another one,
     /// returning `Ok` if it is compatible, `Err` if it was not.
     pub fn check_compatible(&self, other: &Self) -> Result<(), TagErrors> {
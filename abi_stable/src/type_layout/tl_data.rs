@@ -166,7 +166,7 @@ impl Display for MismatchedTLDataVariant {
 
 /// A discriminant-only version of TLData.
 #[repr(u8)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq, StableAbi)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, StableAbi, Serialize, Deserialize)]
 #[sabi(unsafe_sabi_opaque_fields)]
 pub enum TLDataDiscriminant {
     Primitive,
@@ -250,6 +250,20 @@ macro_rules! declare_tl_discriminants {
                 }
             }
 
+            /// Gets the discriminants of all the variants.
+            pub(crate) fn to_vec(self)->Vec<TLDiscriminant>{
+                match self.inner {
+                    $(
+                        TLDiscrsInner::$variant{discriminants,len}=>unsafe{
+                            std::slice::from_raw_parts(discriminants,len as usize)
+                                .iter()
+                                .map(|&x| TLDiscriminant::$single(x as _))
+                                .collect()
+                        }
+                    )*
+                }
+            }
+
             /// Compares this `TLDiscriminants` with another,
             ///
             /// # Errors
@@ -375,7 +389,7 @@ declare_tl_discriminants! {
 
 /// A discriminant of an enum variant.
 #[repr(C, u8)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq, StableAbi)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, StableAbi, Serialize, Deserialize)]
 #[sabi(unsafe_sabi_opaque_fields)]
 pub enum TLDiscriminant {
     /// The assigned value of a discriminant in a `#[repr(isize)]` enum.
@@ -390,7 +404,7 @@ pub enum TLDiscriminant {
 
 /// How the discriminant of an enum is represented.
 #[repr(u8)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq, StableAbi)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, StableAbi, Serialize, Deserialize)]
 #[sabi(unsafe_sabi_opaque_fields)]
 pub enum DiscriminantRepr {
     /// The type of the discriminant for a `#[repr(u8)]`enum
@@ -473,11 +487,11 @@ impl TLNonExhaustive {
     }
 
     #[inline]
-    const fn original_size(&self) -> usize {
+    pub(crate) const fn original_size(&self) -> usize {
         self.original_size
     }
     #[inline]
-    const fn original_alignment(&self) -> usize {
+    pub(crate) const fn original_alignment(&self) -> usize {
         1_usize << (self.original_alignment_pow2 as u32)
     }
 
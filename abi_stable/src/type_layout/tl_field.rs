@@ -26,6 +26,9 @@ pub struct TLField {
 
     /// How this field is accessed.
     field_accessor: FieldAccessor,
+
+    /// A `,` separated list of the names this field was renamed from.
+    renamed_from: RStr<'static>,
 }
 
 ///////////////////////////
@@ -44,6 +47,7 @@ impl TLField {
             function_range: TLFunctionSlice::empty(vars),
             is_function: false,
             field_accessor: FieldAccessor::Direct,
+            renamed_from: RStr::empty(),
         }
     }
 
@@ -79,6 +83,12 @@ impl TLField {
         self.field_accessor
     }
 
+    /// Gets the names that this field was renamed from,
+    /// declared with the `#[sabi(renamed_from = "old_name")]` attribute.
+    pub fn renamed_from(&self) -> impl Iterator<Item = &'static str> + Clone + Debug + 'static {
+        self.renamed_from.as_str().split_terminator(',')
+    }
+
     /// Used for calling recursive methods,
     /// so as to avoid infinite recursion in types that reference themselves(even indirectly).
     fn recursive<F, U>(self, f: F) -> U
@@ -275,6 +285,17 @@ impl CompTLField {
         type_layouts[self.type_layout_index()]
     }
 
+    /// Gets the `,` separated list of names that the field was renamed from,
+    /// from `SharedVars`'s string slice.
+    pub fn renamed_from(&self, strings: &'static str) -> &'static str {
+        if self.has_renamed_from() {
+            let name_end = self.name_start_len().end();
+            strings[name_end..].split(';').nth(1).unwrap_or("")
+        } else {
+            ""
+        }
+    }
+
     /// Expands this CompTLField into a TLField.
     pub fn expand(
         &self,
@@ -292,6 +313,7 @@ impl CompTLField {
             function_range,
             is_function: self.is_function(),
             field_accessor: self.field_accessor(strings),
+            renamed_from: self.renamed_from(strings).into(),
         }
     }
 }
@@ -337,4 +359,37 @@ mod tests {
         assert_eq!(field.type_layout(vars.type_layouts()), RSTRING_CTOR,);
         assert_eq!(field.field_accessor(vars.strings()), FieldAccessor::Direct,);
     }
+
+    #[test]
+    fn renamed_from() {
+        const U32_CTOR: extern "C" fn() -> &'static TypeLayout = get_type_layout::<u32>;
+
+        const MONO_VARS: &MonoSharedVars =
+            &MonoSharedVars::new(rstr!("foo;bar,baz,;qux;"), rslice![]);
+
+        const VARS: &SharedVars = &SharedVars::new(MONO_VARS, rslice![U32_CTOR], rslice![]);
+
+        let new_field = |name: StartLen| {
+            CompTLField::new(
+                name,
+                LifetimeRange::EMPTY,
+                CompFieldAccessor::DIRECT,
+                TypeLayoutIndex::from_u10(0),
+                false,
+            )
+        };
+
+        let field = new_field(StartLen::new(0, 3)).with_renamed_from();
+        assert!(field.has_renamed_from());
+        assert_eq!(field.renamed_from(VARS.strings()), "bar,baz,");
+
+        let expanded = field.expand(0, None, VARS);
+        assert_eq!(expanded.name(), "foo");
+        assert_eq!(expanded.renamed_from().collect::<Vec<_>>(), ["bar", "baz"]);
+
+        let field = new_field(StartLen::new(13, 3));
+        assert!(!field.has_renamed_from());
+        assert_eq!(field.name(VARS.strings()), "qux");
+        assert_eq!(field.expand(0, None, VARS).renamed_from().count(), 0);
+    }
 }
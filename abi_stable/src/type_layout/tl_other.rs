@@ -6,7 +6,7 @@ use crate::{abi_stability::ConstGeneric, sabi_types::Constructor};
 
 /// The `repr(..)` attribute used on a type.
 #[repr(C, u8)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq, StableAbi)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, StableAbi, Serialize, Deserialize)]
 #[sabi(unsafe_sabi_opaque_fields)]
 pub enum ReprAttr {
     /// This is an `Option<NonZeroType>`.
@@ -172,7 +172,7 @@ impl Display for GenericParams {
 
 /// Types defined in the compiler
 #[repr(u8)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq, StableAbi)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, StableAbi, Serialize, Deserialize)]
 #[sabi(unsafe_sabi_opaque_fields)]
 pub enum TLPrimitive {
     U8,
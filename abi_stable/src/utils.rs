@@ -3,6 +3,7 @@
 use std::{
     cmp::Ord,
     fmt::{self, Debug, Display},
+    hash::Hasher,
     mem::{self, ManuallyDrop},
     ptr::NonNull,
 };
@@ -282,6 +283,31 @@ impl_fmt_padding! { RString }
 
 //////////////////////////////////////////////////////////////////////
 
+/// The 64 bit FNV-1a hash function,
+/// used because the hash must not change between runs, or versions of Rust.
+pub(crate) struct Fnv1a(u64);
+
+impl Fnv1a {
+    pub(crate) const fn new() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+//////////////////////////////////////////////////////////////////////
+
 /// Takes the contents out of a `ManuallyDrop<T>`.
 ///
 /// # Safety
@@ -0,0 +1,181 @@
+use abi_stable::{
+    abi_stability::{
+        abi_checking::{check_layout_compatibility_with_globals, CheckingGlobals},
+        AbiMismatchReport, MismatchSeverity,
+    },
+    type_layout::TypeLayout,
+    StableAbi,
+};
+
+mod point_a {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Point {
+        pub x: u32,
+        pub y: u32,
+    }
+}
+
+mod point_changed_field {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Point {
+        pub x: u32,
+        pub y: u64,
+    }
+}
+
+mod point_renamed_field {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Point {
+        pub x: u32,
+        pub w: u32,
+    }
+}
+
+mod root_a {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct RootMod {
+        pub handlers: Handlers,
+    }
+
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Handlers {
+        pub callback: extern "C" fn(u8, u16) -> u32,
+    }
+}
+
+mod root_param {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct RootMod {
+        pub handlers: Handlers,
+    }
+
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Handlers {
+        pub callback: extern "C" fn(u8, u32) -> u32,
+    }
+}
+
+mod root_return {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct RootMod {
+        pub handlers: Handlers,
+    }
+
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Handlers {
+        pub callback: extern "C" fn(u8, u16) -> u64,
+    }
+}
+
+fn report_of(
+    interface: &'static TypeLayout,
+    implementation: &'static TypeLayout,
+) -> AbiMismatchReport {
+    let globals = CheckingGlobals::new();
+    check_layout_compatibility_with_globals(interface, implementation, &globals)
+        .unwrap_err()
+        .report()
+}
+
+#[test]
+fn field_paths() {
+    let report = report_of(
+        <point_a::Point as StableAbi>::LAYOUT,
+        <point_changed_field::Point as StableAbi>::LAYOUT,
+    );
+
+    assert_eq!(report.interface.type_name, "Point");
+    assert_eq!(report.interface.package, "abi_stable");
+    assert_eq!(report.interface.version, env!("CARGO_PKG_VERSION"));
+
+    let size = report.mismatches.iter().find(|m| m.kind == "Size").unwrap();
+    assert_eq!(size.to_string(), "Point: Size expected 8 found 16");
+
+    let name = report.mismatches.iter().find(|m| m.kind == "Name").unwrap();
+    assert_eq!(name.path, "Point.y");
+    assert_eq!(name.severity, MismatchSeverity::Error);
+    assert_eq!(name.interface.type_name, "u32");
+    assert_eq!(name.implementation.type_name, "u64");
+    assert_eq!(name.expected.as_deref(), Some("u32"));
+    assert_eq!(name.found.as_deref(), Some("u64"));
+    assert_eq!(name.to_string(), "Point.y: Name expected u32 found u64");
+
+    let report = report_of(
+        <point_a::Point as StableAbi>::LAYOUT,
+        <point_renamed_field::Point as StableAbi>::LAYOUT,
+    );
+    let unexpected = &report.mismatches[0];
+    assert_eq!(unexpected.path, "Point");
+    assert_eq!(unexpected.kind, "UnexpectedField");
+    assert_eq!(unexpected.expected.as_deref(), Some("y: u32"));
+    assert_eq!(unexpected.found.as_deref(), Some("w: u32"));
+}
+
+#[test]
+fn function_paths() {
+    let interface = <root_a::RootMod as StableAbi>::LAYOUT;
+
+    let report = report_of(interface, <root_param::RootMod as StableAbi>::LAYOUT);
+    let lines = report
+        .mismatches
+        .iter()
+        .map(|m| m.to_string())
+        .collect::<Vec<String>>();
+    assert!(
+        lines.contains(
+            &"RootMod.handlers.callback.param[1]: Name expected u16 found u32".to_string()
+        ),
+        "{}",
+        report,
+    );
+
+    let report = report_of(interface, <root_return::RootMod as StableAbi>::LAYOUT);
+    assert!(
+        report
+            .mismatches
+            .iter()
+            .any(|m| m.path == "RootMod.handlers.callback.return" && m.kind == "Name"),
+        "{}",
+        report,
+    );
+}
+
+#[test]
+fn display_and_json() {
+    let report = report_of(
+        <point_a::Point as StableAbi>::LAYOUT,
+        <point_changed_field::Point as StableAbi>::LAYOUT,
+    );
+
+    let displayed = report.to_string();
+    assert!(
+        displayed.contains("interface `Point` (package `abi_stable`"),
+        "{}",
+        displayed
+    );
+    assert!(
+        displayed.contains("    Point.y: Name expected u32 found u64"),
+        "{}",
+        displayed
+    );
+
+    #[cfg(feature = "serde_json")]
+    {
+        let json = report.to_json();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["interface"]["type_name"], "Point");
+        assert_eq!(value["mismatches"][0]["severity"], "Error");
+
+        let deserialized: AbiMismatchReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, report);
+    }
+}
@@ -0,0 +1,169 @@
+#![allow(dead_code)]
+
+use abi_stable::{
+    abi_stability::{
+        abi_checking::{check_layout_compatibility_with_globals, AbiInstability, CheckingGlobals},
+        semver::{classify_changes, ChangeKind, VersionBump},
+    },
+    type_layout::TypeLayout,
+    StableAbi,
+};
+
+mod old_place {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi, Copy, Clone)]
+    pub struct Point {
+        pub x: u32,
+        pub y: u32,
+    }
+
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    #[sabi(kind(Prefix))]
+    pub struct Module {
+        #[sabi(last_prefix_field)]
+        pub point: Point,
+    }
+}
+
+mod new_place {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi, Copy, Clone)]
+    #[sabi(moved_from(package = "old_types"))]
+    #[sabi(moved_from(package = "abi_stable", name = "Point"))]
+    pub struct Coordinates {
+        pub x: u32,
+        pub y: u32,
+    }
+
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    #[sabi(kind(Prefix))]
+    pub struct Module {
+        #[sabi(last_prefix_field)]
+        pub point: Coordinates,
+    }
+}
+
+mod unmarked {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Coordinates {
+        pub x: u32,
+        pub y: u32,
+    }
+}
+
+mod moved_changed_field {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    #[sabi(moved_from(package = "abi_stable", name = "Point"))]
+    pub struct Coordinates {
+        pub x: u32,
+        pub y: u64,
+    }
+}
+
+fn check(
+    interface: &'static TypeLayout,
+    implementation: &'static TypeLayout,
+) -> Result<(), Vec<AbiInstability>> {
+    let globals = CheckingGlobals::new();
+    check_layout_compatibility_with_globals(interface, implementation, &globals)
+        .map_err(|e| e.flattened_errors().collect())
+}
+
+#[test]
+fn moved_from_item_info() {
+    let layout = <new_place::Coordinates as StableAbi>::LAYOUT;
+    let item_info = layout.item_info();
+
+    assert_eq!(item_info.package(), "abi_stable");
+    assert_eq!(item_info.version(), env!("CARGO_PKG_VERSION"));
+    assert_eq!(
+        item_info.moved_from().collect::<Vec<_>>(),
+        vec![("old_types", "Coordinates"), ("abi_stable", "Point")],
+    );
+    assert_eq!(layout.package_version().parsed().unwrap().major, 0);
+
+    let old = <old_place::Point as StableAbi>::LAYOUT;
+    assert_eq!(old.item_info().moved_from().count(), 0);
+}
+
+#[test]
+fn moved_types() {
+    let old = <old_place::Point as StableAbi>::LAYOUT;
+    let new = <new_place::Coordinates as StableAbi>::LAYOUT;
+
+    check(old, new).unwrap();
+    check(new, old).unwrap();
+
+    let errs = check(old, <unmarked::Coordinates as StableAbi>::LAYOUT).unwrap_err();
+    assert!(
+        errs.iter()
+            .any(|e| matches!(e, AbiInstability::Name { .. })),
+        "{:#?}",
+        errs,
+    );
+
+    let errs = check(old, <moved_changed_field::Coordinates as StableAbi>::LAYOUT).unwrap_err();
+    assert!(
+        !errs.iter().any(|e| matches!(e, AbiInstability::Name(x)
+            if x.expected.name() == "Point"
+        )),
+        "{:#?}",
+        errs,
+    );
+    assert!(!errs.is_empty());
+}
+
+#[test]
+fn moved_prefix_type_fields() {
+    let old = <old_place::Module_Ref as StableAbi>::LAYOUT;
+    let new = <new_place::Module_Ref as StableAbi>::LAYOUT;
+
+    check(old, new).unwrap();
+
+    let report = classify_changes(old, new);
+    assert_eq!(report.changes.len(), 1, "{}", report);
+    assert_eq!(report.changes[0].path, "PrefixRef.0.point");
+    assert!(
+        matches!(
+            &report.changes[0].kind,
+            ChangeKind::Moved { old, new }
+            if old.type_name == "Point" && new.type_name == "Coordinates"
+        ),
+        "{}",
+        report,
+    );
+    assert_eq!(report.recommended_bump(), VersionBump::Minor);
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn moved_from_another_package() {
+    use abi_stable::abi_stability::snapshot::{check_layout_against_snapshot, AbiSnapshot};
+
+    let layout = <new_place::Coordinates as StableAbi>::LAYOUT;
+    let mut json: serde_json::Value =
+        serde_json::from_str(&AbiSnapshot::new(layout).to_json()).unwrap();
+
+    // Pretends that the snapshot was taken when the type was in the `old_types` package,
+    // with a version that's incompatible with the current one.
+    json["types"][0]["package"] = "old_types".into();
+    json["types"][0]["version"] = "3.0.0".into();
+    json["types"][0]
+        .as_object_mut()
+        .unwrap()
+        .remove("moved_from");
+
+    let snapshot = AbiSnapshot::from_json(&json.to_string()).unwrap();
+    check_layout_against_snapshot(&snapshot, layout).unwrap();
+
+    json["types"][0]["package"] = "other_types".into();
+    let snapshot = AbiSnapshot::from_json(&json.to_string()).unwrap();
+    let errors = check_layout_against_snapshot(&snapshot, layout).unwrap_err();
+    assert!(errors
+        .flattened_errors()
+        .any(|e| matches!(e, AbiInstability::Package(_))));
+}
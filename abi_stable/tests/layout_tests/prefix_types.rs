@@ -10,7 +10,7 @@ use abi_stable::{
     abi_stability::abi_checking::{
         check_layout_compatibility_with_globals, AbiInstability, CheckingGlobals,
     },
-    prefix_type::{WithMetadata, __PrefixTypeMetadata},
+    prefix_type::{__PrefixTypeMetadata, WithMetadata},
     test_utils::must_panic,
     type_layout::TypeLayout,
     type_level::bools::*,
@@ -315,6 +315,7 @@ fn hierarchical_prefix_test() {
             (library_1, library_10),
             (library_1, library_11),
             (library_1, library_12),
+            (library_10, library_11),
         ];
         checks.shuffle(&mut rng);
 
@@ -324,6 +325,16 @@ fn hierarchical_prefix_test() {
     }
 }
 
+#[cfg_attr(not(miri), test)]
+fn identical_prefix_types() {
+    let globals = CheckingGlobals::new();
+
+    for pref in new_list() {
+        // This asserts that the prefix type was recorded in the globals.
+        check_interface_impl_pair(&globals, pref, pref);
+    }
+}
+
 #[cfg_attr(not(miri), test)]
 fn prefix_is_same_alignment() {
     let globals = CheckingGlobals::new();
@@ -864,6 +875,7 @@ fn hierarchical_prefix_cond_field_test() {
             (library_1, library_10),
             (library_1, library_11),
             (library_1, library_12),
+            (library_10, library_11),
         ];
         checks.shuffle(&mut rng);
 
@@ -0,0 +1,434 @@
+#![allow(dead_code)]
+
+use abi_stable::{
+    abi_stability::{
+        abi_checking::{check_layout_compatibility_with_globals, AbiInstability, CheckingGlobals},
+        semver::{classify_changes, ChangeKind, VersionBump},
+    },
+    nonexhaustive_enum::GetEnumInfo,
+    sabi_trait,
+    std_types::RBox,
+    type_layout::{TLData, TypeLayout},
+    StableAbi,
+};
+
+mod point_a {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Point {
+        pub x: u32,
+        pub y: u32,
+    }
+}
+
+mod point_renamed {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Point {
+        pub x: u32,
+        #[sabi(renamed_from = "why")]
+        #[sabi(renamed_from = "y")]
+        pub height: u32,
+    }
+}
+
+mod point_unmarked_rename {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Point {
+        pub x: u32,
+        pub height: u32,
+    }
+}
+
+mod point_renamed_changed_type {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Point {
+        pub x: u32,
+        #[sabi(renamed_from = "y")]
+        pub height: u64,
+    }
+}
+
+mod enum_a {
+    #[repr(u8)]
+    #[derive(abi_stable::StableAbi)]
+    pub enum Enum {
+        A,
+        B(u32),
+        C { x: u8 },
+    }
+}
+
+mod enum_renamed {
+    #[repr(u8)]
+    #[derive(abi_stable::StableAbi)]
+    pub enum Enum {
+        A,
+        #[sabi(renamed_from = "B")]
+        Bee(u32),
+        #[sabi(renamed_from = "Sea")]
+        #[sabi(renamed_from = "C")]
+        Cee {
+            #[sabi(renamed_from = "x")]
+            ex: u8,
+        },
+    }
+}
+
+mod enum_unmarked_rename {
+    #[repr(u8)]
+    #[derive(abi_stable::StableAbi)]
+    pub enum Enum {
+        A,
+        Bee(u32),
+        C { x: u8 },
+    }
+}
+
+mod enum_renamed_variants {
+    #[repr(u8)]
+    #[derive(abi_stable::StableAbi)]
+    pub enum Enum {
+        A,
+        #[sabi(renamed_from = "B")]
+        Bee(u32),
+        #[sabi(renamed_from = "C")]
+        C2 {
+            x: u8,
+        },
+    }
+}
+
+mod enum_without_renames {
+    #[repr(u8)]
+    #[derive(abi_stable::StableAbi)]
+    pub enum Enum {
+        A,
+        Bee(u32),
+        C2 { x: u8 },
+    }
+}
+
+mod nonexhaustive_a {
+    #[repr(u8)]
+    #[derive(abi_stable::StableAbi, Debug, Clone, PartialEq)]
+    #[sabi(kind(WithNonExhaustive(size = 8, traits(Debug, Clone, PartialEq))))]
+    pub enum Enum {
+        #[sabi(renamed_from = "Older")]
+        Old,
+        #[sabi(renamed_from = "B")]
+        New(u32),
+    }
+}
+
+mod module_a {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    #[sabi(kind(Prefix))]
+    pub struct Module {
+        pub first: u32,
+        #[sabi(last_prefix_field)]
+        pub second: u32,
+    }
+}
+
+mod module_renamed {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    #[sabi(kind(Prefix))]
+    pub struct Module {
+        pub first: u32,
+        #[sabi(last_prefix_field)]
+        #[sabi(renamed_from = "second")]
+        pub next: u32,
+        pub third: u32,
+    }
+}
+
+mod method_a {
+    use super::*;
+    #[sabi_trait]
+    pub trait Trait {
+        #[sabi(last_prefix_field)]
+        fn apply(&self, l: u32, r: u32) -> u32;
+    }
+}
+
+mod method_renamed {
+    use super::*;
+    #[sabi_trait]
+    pub trait Trait {
+        #[sabi(last_prefix_field)]
+        #[sabi(renamed_from = "apply")]
+        fn combine(&self, l: u32, r: u32) -> u32;
+    }
+}
+
+fn check(
+    interface: &'static TypeLayout,
+    implementation: &'static TypeLayout,
+) -> Result<(), Vec<AbiInstability>> {
+    let globals = CheckingGlobals::new();
+    check_layout_compatibility_with_globals(interface, implementation, &globals)
+        .map_err(|e| e.flattened_errors().collect())
+}
+
+fn assert_compatible_both_ways(this: &'static TypeLayout, other: &'static TypeLayout) {
+    check(this, other).unwrap();
+    check(other, this).unwrap();
+}
+
+#[test]
+fn renamed_fields() {
+    let point = <point_a::Point as StableAbi>::LAYOUT;
+    let renamed = <point_renamed::Point as StableAbi>::LAYOUT;
+
+    assert_compatible_both_ways(point, renamed);
+    check(renamed, renamed).unwrap();
+
+    let fields = match renamed.data() {
+        TLData::Struct { fields } => fields,
+        x => panic!("expected a struct, found {:?}", x),
+    };
+    let field_aliases = fields
+        .iter()
+        .map(|f| (f.name(), f.renamed_from().collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        field_aliases,
+        vec![("x", vec![]), ("height", vec!["why", "y"])]
+    );
+
+    // Renaming doesn't allow changing the type of the field.
+    let changed_type = <point_renamed_changed_type::Point as StableAbi>::LAYOUT;
+    assert!(check(point, changed_type).is_err());
+    assert!(check(renamed, changed_type).is_err());
+
+    let errs = check(point, <point_unmarked_rename::Point as StableAbi>::LAYOUT).unwrap_err();
+    assert!(
+        errs.iter()
+            .any(|e| matches!(e, AbiInstability::UnexpectedField { .. })),
+        "{:#?}",
+        errs,
+    );
+}
+
+#[test]
+fn renamed_variants() {
+    let enum_ = <enum_a::Enum as StableAbi>::LAYOUT;
+    let renamed = <enum_renamed::Enum as StableAbi>::LAYOUT;
+
+    assert_compatible_both_ways(enum_, renamed);
+
+    let tl_enum = match renamed.data() {
+        TLData::Enum(x) => x,
+        x => panic!("expected an enum, found {:?}", x),
+    };
+    assert_eq!(
+        tl_enum.variant_names_iter().collect::<Vec<_>>(),
+        vec!["A", "Bee", "Cee"]
+    );
+    assert_eq!(tl_enum.variant_names.as_str(), "A;Bee;Cee;");
+    assert_eq!(renamed.item_info().variant_renamed_from("A").count(), 0);
+    assert_eq!(
+        renamed
+            .item_info()
+            .variant_renamed_from("Bee")
+            .collect::<Vec<_>>(),
+        vec!["B"]
+    );
+    assert_eq!(
+        renamed
+            .item_info()
+            .variant_renamed_from("Cee")
+            .collect::<Vec<_>>(),
+        vec!["Sea", "C"]
+    );
+
+    let errs = check(enum_, <enum_unmarked_rename::Enum as StableAbi>::LAYOUT).unwrap_err();
+    assert!(
+        errs.iter()
+            .any(|e| matches!(e, AbiInstability::UnexpectedVariant { .. })),
+        "{:#?}",
+        errs,
+    );
+}
+
+#[test]
+fn renamed_variants_encoding() {
+    let renamed = <enum_renamed_variants::Enum as StableAbi>::LAYOUT;
+    let unrenamed = <enum_without_renames::Enum as StableAbi>::LAYOUT;
+
+    // The variant names are encoded the same as before `renamed_from` existed,
+    // so loaders that read every `;` separated name only see the names of the variants.
+    assert_eq!(
+        renamed.shared_vars().strings(),
+        unrenamed.shared_vars().strings()
+    );
+    for layout in [renamed, unrenamed] {
+        let tl_enum = match layout.data() {
+            TLData::Enum(x) => x,
+            x => panic!("expected an enum, found {:?}", x),
+        };
+        assert_eq!(tl_enum.variant_names.as_str(), "A;Bee;C2;");
+        assert_eq!(
+            tl_enum
+                .variant_names
+                .as_str()
+                .split(';')
+                .collect::<Vec<_>>(),
+            ["A", "Bee", "C2", ""]
+        );
+    }
+
+    let item_info = renamed.item_info();
+    assert_eq!(
+        item_info.variant_renamed_from("Bee").collect::<Vec<_>>(),
+        vec!["B"]
+    );
+    assert_eq!(
+        item_info.variant_renamed_from("C2").collect::<Vec<_>>(),
+        vec!["C"]
+    );
+    assert_eq!(item_info.variant_renamed_from("A").count(), 0);
+    assert_eq!(
+        renamed.item_info().package(),
+        unrenamed.item_info().package()
+    );
+    assert_eq!(
+        renamed.item_info().version(),
+        unrenamed.item_info().version()
+    );
+    assert_eq!(renamed.item_info().moved_from().count(), 0);
+
+    assert_compatible_both_ways(<enum_a::Enum as StableAbi>::LAYOUT, renamed);
+}
+
+#[test]
+fn nonexhaustive_enum_info() {
+    let info = <nonexhaustive_a::Enum as GetEnumInfo>::ENUM_INFO;
+    assert_eq!(info.variant_names(), "Old;New;");
+    assert_eq!(
+        info.variant_names_iter().collect::<Vec<_>>(),
+        vec!["Old", "New"]
+    );
+
+    let layout = <nonexhaustive_a::Enum_NE as StableAbi>::LAYOUT;
+    check(layout, layout).unwrap();
+}
+
+#[test]
+fn renamed_prefix_fields() {
+    let module = <module_a::Module_Ref as StableAbi>::LAYOUT;
+    let renamed = <module_renamed::Module_Ref as StableAbi>::LAYOUT;
+
+    check(module, renamed).unwrap();
+
+    let report = classify_changes(module, renamed);
+    let kinds = report
+        .changes
+        .iter()
+        .map(|change| &change.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            &ChangeKind::PrefixFieldsAppended {
+                fields: vec!["third".to_string()],
+            },
+            &ChangeKind::Renamed {
+                old_name: "second".to_string(),
+                new_name: "next".to_string(),
+            },
+        ],
+        "{}",
+        report,
+    );
+    assert_eq!(report.recommended_bump(), VersionBump::Minor);
+}
+
+#[test]
+fn renamed_sabi_trait_methods() {
+    let method = <method_a::Trait_TO<'_, RBox<()>> as StableAbi>::LAYOUT;
+    let renamed = <method_renamed::Trait_TO<'_, RBox<()>> as StableAbi>::LAYOUT;
+
+    assert_compatible_both_ways(method, renamed);
+
+    let report = classify_changes(method, renamed);
+    assert!(
+        report.changes.iter().any(|change| change.kind
+            == ChangeKind::Renamed {
+                old_name: "apply".to_string(),
+                new_name: "combine".to_string(),
+            }),
+        "{}",
+        report,
+    );
+    assert_eq!(report.recommended_bump(), VersionBump::Minor, "{}", report);
+}
+
+#[test]
+fn semver_renames() {
+    let report = classify_changes(
+        <enum_a::Enum as StableAbi>::LAYOUT,
+        <enum_renamed::Enum as StableAbi>::LAYOUT,
+    );
+
+    let renames = report
+        .changes
+        .iter()
+        .map(|change| (change.path.as_str(), change.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        renames,
+        vec![
+            (
+                "Enum",
+                "Enum: `B` renamed to `Bee`: minor-compatible".to_string()
+            ),
+            (
+                "Enum",
+                "Enum: `C` renamed to `Cee`: minor-compatible".to_string()
+            ),
+            (
+                "Enum",
+                "Enum: `x` renamed to `ex`: minor-compatible".to_string()
+            ),
+        ],
+        "{}",
+        report,
+    );
+
+    let report = classify_changes(
+        <point_a::Point as StableAbi>::LAYOUT,
+        <point_renamed_changed_type::Point as StableAbi>::LAYOUT,
+    );
+    assert_eq!(report.recommended_bump(), VersionBump::Major, "{}", report);
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn renamed_snapshots() {
+    use abi_stable::abi_stability::snapshot::{check_layout_against_snapshot, AbiSnapshot};
+
+    let point = <point_a::Point as StableAbi>::LAYOUT;
+    let renamed = <point_renamed::Point as StableAbi>::LAYOUT;
+    let unmarked = <point_unmarked_rename::Point as StableAbi>::LAYOUT;
+    let enum_ = <enum_a::Enum as StableAbi>::LAYOUT;
+    let renamed_enum = <enum_renamed::Enum as StableAbi>::LAYOUT;
+
+    for (interface, implementation) in [
+        (point, renamed),
+        (renamed, point),
+        (enum_, renamed_enum),
+        (renamed_enum, enum_),
+    ] {
+        let snapshot = AbiSnapshot::from_json(&AbiSnapshot::new(interface).to_json()).unwrap();
+        check_layout_against_snapshot(&snapshot, implementation).unwrap();
+    }
+
+    let snapshot = AbiSnapshot::new(point);
+    assert!(check_layout_against_snapshot(&snapshot, unmarked).is_err());
+}
@@ -0,0 +1,336 @@
+#![allow(dead_code)]
+
+use std::{cell::Cell, rc::Rc};
+
+use abi_stable::{
+    abi_stability::abi_checking::{check_layout_compatibility_with_globals, CheckingGlobals},
+    sabi_trait,
+    sabi_trait::prelude::{TD_CanDowncast, TD_Opaque},
+    std_types::{RBox, RString},
+    type_layout::TypeLayout,
+    StableAbi,
+};
+
+mod widgets {
+    use super::*;
+
+    #[sabi_trait]
+    pub trait Widget: Debug {
+        fn name(&self) -> RString;
+
+        fn resize(&mut self, size: u32);
+
+        #[sabi(last_prefix_field)]
+        fn into_size(self) -> u32;
+
+        fn is_visible(&self) -> bool {
+            true
+        }
+    }
+
+    #[sabi_trait]
+    pub trait Renderer: Widget + Debug {
+        #[sabi(last_prefix_field)]
+        fn render(&self) -> RString;
+    }
+
+    #[derive(Debug)]
+    pub struct Label {
+        pub text: RString,
+        pub size: u32,
+        pub drops: Rc<Cell<u32>>,
+    }
+
+    impl Drop for Label {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    impl Widget for Label {
+        fn name(&self) -> RString {
+            "label".into()
+        }
+
+        fn resize(&mut self, size: u32) {
+            self.size = size;
+        }
+
+        fn into_size(self) -> u32 {
+            self.size
+        }
+
+        fn is_visible(&self) -> bool {
+            !self.text.is_empty()
+        }
+    }
+
+    impl Renderer for Label {
+        fn render(&self) -> RString {
+            format!("{}@{}", self.text, self.size).into()
+        }
+    }
+}
+
+use self::widgets::{Label, Renderer_TO, Widget, Widget_TO};
+
+fn new_label(text: &str, drops: &Rc<Cell<u32>>) -> Label {
+    Label {
+        text: text.into(),
+        size: 10,
+        drops: drops.clone(),
+    }
+}
+
+#[test]
+fn calling_supertrait_methods() {
+    let drops = Rc::new(Cell::new(0));
+
+    let mut object = Renderer_TO::from_value(new_label("hello", &drops), TD_Opaque);
+
+    assert_eq!(object.name(), "label");
+    assert!(object.is_visible());
+    object.resize(20);
+    assert_eq!(object.render(), "hello@20");
+    assert_eq!(format!("{:?}", object.obj), format!("{:?}", object.obj));
+
+    assert_eq!(object.into_size(), 20);
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn upcasting() {
+    let drops = Rc::new(Cell::new(0));
+
+    {
+        let object = Renderer_TO::from_value(new_label("hello", &drops), TD_CanDowncast);
+
+        let mut widget: Widget_TO<'_, RBox<()>> = object.into();
+        assert_eq!(widget.name(), "label");
+        widget.resize(3);
+        assert!(format!("{:?}", widget).contains("hello"));
+
+        assert_eq!(widget.obj.downcast_as::<Label>().unwrap().size, 3);
+        assert_eq!(drops.get(), 0);
+    }
+    assert_eq!(drops.get(), 1);
+
+    {
+        let object = Renderer_TO::from_value(new_label("", &drops), TD_Opaque);
+        let widget = Widget_TO::from(object);
+        assert!(!widget.is_visible());
+        assert_eq!(widget.into_size(), 10);
+    }
+    assert_eq!(drops.get(), 2);
+}
+
+#[test]
+fn upcasting_reborrows() {
+    let drops = Rc::new(Cell::new(0));
+
+    let mut object = Renderer_TO::from_value(new_label("hello", &drops), TD_Opaque);
+
+    {
+        let widget: Widget_TO<'_, _> = object.sabi_reborrow().into();
+        assert_eq!(widget.name(), "label");
+    }
+    {
+        let mut widget: Widget_TO<'_, _> = object.sabi_reborrow_mut().into();
+        widget.resize(30);
+    }
+    assert_eq!(drops.get(), 0);
+    assert_eq!(object.render(), "hello@30");
+
+    drop(object);
+    assert_eq!(drops.get(), 1);
+}
+
+#[test]
+fn generic_functions() {
+    fn widget_name<W: Widget>(widget: &W) -> RString {
+        widget.name()
+    }
+
+    let drops = Rc::new(Cell::new(0));
+
+    let renderer = Renderer_TO::from_value(new_label("hello", &drops), TD_Opaque);
+    assert_eq!(widget_name(&renderer), "label");
+
+    let widget = Widget_TO::from_value(new_label("hello", &drops), TD_Opaque);
+    assert_eq!(widget_name(&widget), "label");
+}
+
+mod static_traits {
+    use super::*;
+
+    #[sabi_trait]
+    pub trait Base: 'static {
+        fn base(&self) -> u32;
+    }
+
+    #[sabi_trait]
+    pub trait Derived<T>: Base + 'static {
+        fn derived(&self, value: T) -> T;
+    }
+
+    impl Base for u32 {
+        fn base(&self) -> u32 {
+            *self
+        }
+    }
+
+    impl Derived<u64> for u32 {
+        fn derived(&self, value: u64) -> u64 {
+            value + u64::from(*self)
+        }
+    }
+
+    #[test]
+    fn static_supertraits() {
+        let object = Derived_TO::from_value(3u32, TD_Opaque);
+        assert_eq!(object.base(), 3);
+        assert_eq!(object.derived(5u64), 8);
+
+        let base: Base_TO<RBox<()>> = object.into();
+        assert_eq!(base.base(), 3);
+    }
+
+    const CONST_OBJECT: Derived_CTO<'static, u64> = Derived_CTO::from_const(&7u32, TD_Opaque);
+
+    #[test]
+    fn const_constructed() {
+        assert_eq!(CONST_OBJECT.base(), 7);
+        assert_eq!(CONST_OBJECT.derived(1), 8);
+    }
+}
+
+mod generic_traits {
+    use super::*;
+
+    #[sabi_trait]
+    pub trait Getter<'a, T: 'a> {
+        fn get(&self) -> &'a T;
+    }
+
+    #[sabi_trait]
+    pub trait Named<'a, T: 'a>: Getter<'a, T> {
+        fn named(&self) -> RString;
+    }
+
+    impl<'a> Getter<'a, u8> for &'a u8 {
+        fn get(&self) -> &'a u8 {
+            self
+        }
+    }
+
+    impl<'a> Named<'a, u8> for &'a u8 {
+        fn named(&self) -> RString {
+            "byte".into()
+        }
+    }
+
+    #[test]
+    fn generic_supertraits() {
+        let value = 13u8;
+        let object = Named_TO::from_value(&value, TD_Opaque);
+        assert_eq!(*object.get(), 13);
+        assert_eq!(object.named(), "byte");
+
+        let getter: Getter_TO<'_, '_, RBox<()>, u8> = object.into();
+        assert_eq!(*getter.get(), 13);
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+
+mod v1 {
+    use super::*;
+
+    #[sabi_trait]
+    pub trait Widget {
+        #[sabi(last_prefix_field)]
+        fn name(&self) -> RString;
+    }
+
+    #[sabi_trait]
+    pub trait Renderer: Widget {
+        #[sabi(last_prefix_field)]
+        fn render(&self) -> RString;
+    }
+}
+
+mod v2 {
+    use super::*;
+
+    #[sabi_trait]
+    pub trait Widget {
+        #[sabi(last_prefix_field)]
+        fn name(&self) -> RString;
+
+        fn is_visible(&self) -> bool {
+            true
+        }
+    }
+
+    #[sabi_trait]
+    pub trait Renderer: Widget {
+        #[sabi(last_prefix_field)]
+        fn render(&self) -> RString;
+    }
+}
+
+mod v2_incompatible {
+    use super::*;
+
+    #[sabi_trait]
+    pub trait Widget {
+        #[sabi(last_prefix_field)]
+        fn name(&self) -> u32;
+    }
+
+    #[sabi_trait]
+    pub trait Renderer: Widget {
+        #[sabi(last_prefix_field)]
+        fn render(&self) -> RString;
+    }
+}
+
+mod without_supertrait {
+    use super::*;
+
+    #[sabi_trait]
+    pub trait Renderer {
+        #[sabi(last_prefix_field)]
+        fn render(&self) -> RString;
+    }
+}
+
+fn check(
+    interface: &'static TypeLayout,
+    implementation: &'static TypeLayout,
+) -> Result<(), String> {
+    let globals = CheckingGlobals::new();
+    check_layout_compatibility_with_globals(interface, implementation, &globals)
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn extending_supertrait_vtable() {
+    let v1 = <v1::Renderer_TO<'_, RBox<()>> as StableAbi>::LAYOUT;
+    let v2 = <v2::Renderer_TO<'_, RBox<()>> as StableAbi>::LAYOUT;
+
+    check(v1, v1).unwrap();
+    check(v1, v2).unwrap();
+    check(v2, v1).unwrap_err();
+}
+
+#[test]
+fn incompatible_supertrait_vtable() {
+    let v1 = <v1::Renderer_TO<'_, RBox<()>> as StableAbi>::LAYOUT;
+    let incompatible = <v2_incompatible::Renderer_TO<'_, RBox<()>> as StableAbi>::LAYOUT;
+    let without = <without_supertrait::Renderer_TO<'_, RBox<()>> as StableAbi>::LAYOUT;
+
+    check(v1, incompatible).unwrap_err();
+    check(v1, without).unwrap_err();
+    check(without, v1).unwrap_err();
+}
@@ -0,0 +1,228 @@
+#![allow(dead_code)]
+
+use abi_stable::{
+    abi_stability::semver::{classify_changes, ChangeKind, SemverReport, VersionBump},
+    nonexhaustive_enum::{
+        examples::{command_a, command_b},
+        NonExhaustiveFor,
+    },
+    sabi_trait,
+    std_types::RBox,
+    StableAbi,
+};
+
+mod module_v1 {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    #[sabi(kind(Prefix))]
+    pub struct Module {
+        pub first: u32,
+        #[sabi(last_prefix_field)]
+        pub second: u32,
+        pub third: u32,
+    }
+}
+
+mod module_v2 {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    #[sabi(kind(Prefix))]
+    pub struct Module {
+        pub first: u32,
+        #[sabi(last_prefix_field)]
+        pub second: u32,
+        pub third: u32,
+        pub fourth: u64,
+        pub fifth: u8,
+    }
+}
+
+mod module_changed_field {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    #[sabi(kind(Prefix))]
+    pub struct Module {
+        pub first: u64,
+        #[sabi(last_prefix_field)]
+        pub second: u32,
+        pub third: u32,
+    }
+}
+
+mod one_method {
+    use super::*;
+    #[sabi_trait]
+    pub trait Trait {
+        #[sabi(last_prefix_field)]
+        fn apply(&self, l: u32, r: u32) -> u32;
+    }
+}
+
+mod two_methods {
+    use super::*;
+    #[sabi_trait]
+    pub trait Trait {
+        #[sabi(last_prefix_field)]
+        fn apply(&self, l: u32, r: u32) -> u32;
+        fn apply2(&self, l: u32, r: u32) -> u32;
+    }
+}
+
+mod handlers_v1 {
+    use super::*;
+
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Handlers {
+        pub callback: extern "C" fn(NonExhaustiveFor<command_a::Foo>) -> u32,
+    }
+}
+
+mod handlers_v2 {
+    use super::*;
+
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Handlers {
+        pub callback: extern "C" fn(NonExhaustiveFor<command_b::Foo>) -> u32,
+    }
+}
+
+fn bumps(report: &SemverReport) -> Vec<VersionBump> {
+    report.changes.iter().map(|change| change.bump).collect()
+}
+
+#[test]
+fn identical_layouts() {
+    let layout = <module_v1::Module_Ref as StableAbi>::LAYOUT;
+    let report = classify_changes(layout, layout);
+
+    assert_eq!(report.changes, Vec::new());
+    assert_eq!(report.recommended_bump(), VersionBump::Patch);
+}
+
+#[test]
+fn prefix_fields() {
+    let v1 = <module_v1::Module_Ref as StableAbi>::LAYOUT;
+    let v2 = <module_v2::Module_Ref as StableAbi>::LAYOUT;
+
+    let report = classify_changes(v1, v2);
+    assert_eq!(bumps(&report), vec![VersionBump::Minor], "{}", report);
+    assert_eq!(
+        report.changes[0].kind,
+        ChangeKind::PrefixFieldsAppended {
+            fields: vec!["fourth".to_string(), "fifth".to_string()],
+        },
+    );
+    assert!(
+        report.changes[0]
+            .to_string()
+            .ends_with("field(s) appended to prefix type: `fourth`, `fifth`: minor-compatible"),
+        "{}",
+        report,
+    );
+    assert_eq!(report.recommended_bump(), VersionBump::Minor);
+
+    let report = classify_changes(v2, v1);
+    assert!(
+        report.changes.iter().any(|change| matches!(
+            &change.kind,
+            ChangeKind::Incompatible(mismatch) if mismatch.kind == "FieldCountMismatch"
+        )),
+        "{}",
+        report,
+    );
+    assert_eq!(report.recommended_bump(), VersionBump::Major);
+}
+
+#[test]
+fn incompatible_changes() {
+    let report = classify_changes(
+        <module_v1::Module_Ref as StableAbi>::LAYOUT,
+        <module_changed_field::Module_Ref as StableAbi>::LAYOUT,
+    );
+
+    assert!(!report.changes.is_empty());
+    assert!(
+        report.changes.iter().all(|change| matches!(
+            &change.kind,
+            ChangeKind::Incompatible(mismatch) if change.bump == VersionBump::Major
+                && change.path == mismatch.path
+        )),
+        "{}",
+        report,
+    );
+    assert!(
+        report.changes.iter().any(|change| matches!(
+            &change.kind,
+            ChangeKind::Incompatible(mismatch) if mismatch.kind == "Name"
+                && mismatch.path.ends_with(".first")
+        )),
+        "{}",
+        report,
+    );
+    assert_eq!(report.recommended_bump(), VersionBump::Major);
+}
+
+#[test]
+fn vtable_methods() {
+    let report = classify_changes(
+        <one_method::Trait_TO<'_, RBox<()>> as StableAbi>::LAYOUT,
+        <two_methods::Trait_TO<'_, RBox<()>> as StableAbi>::LAYOUT,
+    );
+
+    assert_eq!(bumps(&report), vec![VersionBump::Minor], "{}", report);
+    assert_eq!(
+        report.changes[0].kind,
+        ChangeKind::VTableMethodsAppended {
+            methods: vec!["apply2".to_string()],
+        },
+    );
+
+    let report = classify_changes(
+        <two_methods::Trait_TO<'_, RBox<()>> as StableAbi>::LAYOUT,
+        <one_method::Trait_TO<'_, RBox<()>> as StableAbi>::LAYOUT,
+    );
+    assert_eq!(report.recommended_bump(), VersionBump::Major, "{}", report);
+}
+
+#[test]
+fn nonexhaustive_variants() {
+    let report = classify_changes(
+        <handlers_v1::Handlers as StableAbi>::LAYOUT,
+        <handlers_v2::Handlers as StableAbi>::LAYOUT,
+    );
+
+    assert_eq!(bumps(&report), vec![VersionBump::Minor], "{}", report);
+    assert!(
+        report.changes[0]
+            .path
+            .starts_with("Handlers.callback.param[0]."),
+        "{}",
+        report,
+    );
+    assert_eq!(
+        report.changes[0].kind,
+        ChangeKind::VariantsAdded {
+            variants: vec!["C".to_string()],
+        },
+    );
+
+    let report = classify_changes(
+        <handlers_v2::Handlers as StableAbi>::LAYOUT,
+        <handlers_v1::Handlers as StableAbi>::LAYOUT,
+    );
+    assert_eq!(report.recommended_bump(), VersionBump::Major, "{}", report);
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn json_round_trip() {
+    let report = classify_changes(
+        <module_v1::Module_Ref as StableAbi>::LAYOUT,
+        <module_changed_field::Module_Ref as StableAbi>::LAYOUT,
+    );
+
+    let deserialized: SemverReport = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(deserialized, report);
+}
@@ -0,0 +1,314 @@
+use abi_stable::{
+    abi_stability::{
+        abi_checking::{check_layout_compatibility_with_globals, AbiInstability, CheckingGlobals},
+        snapshot::{check_layout_against_snapshot, AbiSnapshot},
+    },
+    nonexhaustive_enum::{
+        examples::{command_a, command_b, command_c},
+        NonExhaustiveFor,
+    },
+    std_types::{RArc, RBox, RHashMap, ROption, RResult, RSlice, RStr, RString, RVec, Tuple2},
+    tag,
+    type_layout::TypeLayout,
+    StableAbi,
+};
+
+mod point_a {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Point {
+        pub x: u32,
+        pub y: u32,
+    }
+}
+
+mod point_more_fields {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Point {
+        pub x: u32,
+        pub y: u32,
+        pub z: u32,
+    }
+}
+
+mod point_renamed_field {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Point {
+        pub x: u32,
+        pub w: u32,
+    }
+}
+
+mod point_changed_field {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Point {
+        pub x: u32,
+        pub y: u64,
+    }
+}
+
+mod callback_a {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Callback<'a> {
+        pub func: extern "C" fn(&'a u32, u8) -> u16,
+    }
+}
+
+mod callback_unsafe {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Callback<'a> {
+        pub func: unsafe extern "C" fn(&'a u32, u8) -> u16,
+    }
+}
+
+mod callback_param {
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    pub struct Callback<'a> {
+        pub func: extern "C" fn(&'a u32, u16) -> u16,
+    }
+}
+
+mod enum_a {
+    #[repr(u8)]
+    #[derive(abi_stable::StableAbi)]
+    #[allow(dead_code)]
+    pub enum Enum {
+        A,
+        B(u32),
+    }
+}
+
+mod enum_discriminant {
+    #[repr(u8)]
+    #[derive(abi_stable::StableAbi)]
+    #[allow(dead_code)]
+    pub enum Enum {
+        A = 10,
+        B(u32),
+    }
+}
+
+mod enum_variant {
+    #[repr(u8)]
+    #[derive(abi_stable::StableAbi)]
+    #[allow(dead_code)]
+    pub enum Enum {
+        A,
+        B(u32),
+        C,
+    }
+}
+
+mod tagged_a {
+    use super::tag;
+
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    #[sabi(tag = tag!{{ "traits" => tag![[ "Debug", "Clone" ]] }})]
+    pub struct Tagged;
+}
+
+mod tagged_b {
+    use super::tag;
+
+    #[repr(C)]
+    #[derive(abi_stable::StableAbi)]
+    #[sabi(tag = tag!{{ "traits" => tag![[ "Debug" ]] }})]
+    pub struct Tagged;
+}
+
+fn round_trip(layout: &'static TypeLayout) -> AbiSnapshot {
+    let snapshot = AbiSnapshot::new(layout);
+    let deserialized = AbiSnapshot::from_json(&snapshot.to_json()).unwrap();
+    assert_eq!(snapshot, deserialized);
+    deserialized
+}
+
+/// Checks that checking against a snapshot gives the same result as
+/// checking against the layout the snapshot was taken of.
+fn check_same_results(list: &[&'static TypeLayout]) {
+    for &interface in list {
+        let snapshot = round_trip(interface);
+        for &implementation in list {
+            let globals = CheckingGlobals::new();
+            let expected =
+                check_layout_compatibility_with_globals(interface, implementation, &globals);
+            let found = check_layout_against_snapshot(&snapshot, implementation);
+            assert_eq!(
+                expected.is_ok(),
+                found.is_ok(),
+                "\ninterface:{}\nimplementation:{}\n{:?}\n{:?}\n",
+                interface.full_type(),
+                implementation.full_type(),
+                expected,
+                found,
+            );
+        }
+    }
+}
+
+fn snapshot_errors(
+    interface: &'static TypeLayout,
+    implementation: &'static TypeLayout,
+) -> Vec<AbiInstability<AbiSnapshot>> {
+    let errors = check_layout_against_snapshot(&round_trip(interface), implementation).unwrap_err();
+    errors.flattened_errors().collect()
+}
+
+#[test]
+fn same_results_as_layout_checking() {
+    check_same_results(&[
+        <point_a::Point as StableAbi>::LAYOUT,
+        <point_more_fields::Point as StableAbi>::LAYOUT,
+        <point_renamed_field::Point as StableAbi>::LAYOUT,
+        <point_changed_field::Point as StableAbi>::LAYOUT,
+        <callback_a::Callback<'_> as StableAbi>::LAYOUT,
+        <callback_unsafe::Callback<'_> as StableAbi>::LAYOUT,
+        <callback_param::Callback<'_> as StableAbi>::LAYOUT,
+        <enum_a::Enum as StableAbi>::LAYOUT,
+        <enum_discriminant::Enum as StableAbi>::LAYOUT,
+        <enum_variant::Enum as StableAbi>::LAYOUT,
+        <tagged_a::Tagged as StableAbi>::LAYOUT,
+        <tagged_b::Tagged as StableAbi>::LAYOUT,
+        <u32 as StableAbi>::LAYOUT,
+        <RStr<'_> as StableAbi>::LAYOUT,
+        <RSlice<'_, u8> as StableAbi>::LAYOUT,
+        <RString as StableAbi>::LAYOUT,
+        <RVec<u8> as StableAbi>::LAYOUT,
+        <RVec<u16> as StableAbi>::LAYOUT,
+        <RVec<RString> as StableAbi>::LAYOUT,
+        <RBox<u8> as StableAbi>::LAYOUT,
+        <RArc<u8> as StableAbi>::LAYOUT,
+        <ROption<u32> as StableAbi>::LAYOUT,
+        <RResult<u32, u8> as StableAbi>::LAYOUT,
+        <Tuple2<u8, u16> as StableAbi>::LAYOUT,
+        <RHashMap<RString, u32> as StableAbi>::LAYOUT,
+        <NonExhaustiveFor<command_a::Foo> as StableAbi>::LAYOUT,
+        <NonExhaustiveFor<command_b::Foo> as StableAbi>::LAYOUT,
+        <NonExhaustiveFor<command_c::Foo> as StableAbi>::LAYOUT,
+    ]);
+}
+
+#[test]
+fn field_errors() {
+    let point = <point_a::Point as StableAbi>::LAYOUT;
+
+    let errs = snapshot_errors(point, <point_more_fields::Point as StableAbi>::LAYOUT);
+    assert!(errs.iter().any(
+        |e| matches!(e, AbiInstability::FieldCountMismatch(x) if x.expected == 2 && x.found == 3)
+    ));
+
+    let errs = snapshot_errors(point, <point_renamed_field::Point as StableAbi>::LAYOUT);
+    assert!(errs
+        .iter()
+        .any(|e| matches!(e, AbiInstability::UnexpectedField(_))));
+
+    let errs = snapshot_errors(point, <point_changed_field::Point as StableAbi>::LAYOUT);
+    assert!(errs
+        .iter()
+        .any(|e| matches!(e, AbiInstability::Name(x) if x.expected == "u32" && x.found == "u64")));
+}
+
+#[test]
+fn function_errors() {
+    let callback = <callback_a::Callback<'_> as StableAbi>::LAYOUT;
+
+    let errs = snapshot_errors(
+        callback,
+        <callback_unsafe::Callback<'_> as StableAbi>::LAYOUT,
+    );
+    assert!(errs
+        .iter()
+        .any(|e| matches!(e, AbiInstability::FnQualifierMismatch(_))));
+
+    let errs = snapshot_errors(
+        callback,
+        <callback_param::Callback<'_> as StableAbi>::LAYOUT,
+    );
+    assert!(errs
+        .iter()
+        .any(|e| matches!(e, AbiInstability::Name(x) if x.expected == "u8" && x.found == "u16")));
+}
+
+#[test]
+fn enum_and_tag_errors() {
+    let enum_ = <enum_a::Enum as StableAbi>::LAYOUT;
+
+    let errs = snapshot_errors(enum_, <enum_discriminant::Enum as StableAbi>::LAYOUT);
+    assert!(errs
+        .iter()
+        .any(|e| matches!(e, AbiInstability::EnumDiscriminant(_))));
+
+    let errs = snapshot_errors(enum_, <enum_variant::Enum as StableAbi>::LAYOUT);
+    assert!(errs
+        .iter()
+        .any(|e| matches!(e, AbiInstability::TooManyVariants(_))));
+
+    let errs = snapshot_errors(
+        <tagged_a::Tagged as StableAbi>::LAYOUT,
+        <tagged_b::Tagged as StableAbi>::LAYOUT,
+    );
+    assert!(errs
+        .iter()
+        .any(|e| matches!(e, AbiInstability::TagError { .. })));
+}
+
+#[test]
+fn error_message() {
+    let point = <point_a::Point as StableAbi>::LAYOUT;
+    let errors = check_layout_against_snapshot(
+        &AbiSnapshot::new(point),
+        <point_changed_field::Point as StableAbi>::LAYOUT,
+    )
+    .unwrap_err()
+    .to_string();
+
+    assert!(errors.contains("mismatched type"), "{}", errors);
+    assert!(errors.contains("y: u64"), "{}", errors);
+}
+
+#[test]
+fn package_version() {
+    let layout = <point_a::Point as StableAbi>::LAYOUT;
+    let mut json: serde_json::Value =
+        serde_json::from_str(&AbiSnapshot::new(layout).to_json()).unwrap();
+
+    json["types"][0]["version"] = "0.1.0".into();
+    let snapshot = AbiSnapshot::from_json(&json.to_string()).unwrap();
+    let errors = check_layout_against_snapshot(&snapshot, layout).unwrap_err();
+    assert!(errors
+        .flattened_errors()
+        .any(|e| matches!(e, AbiInstability::PackageVersion(_))));
+
+    json["types"][0]["version"] = "foo".into();
+    let snapshot = AbiSnapshot::from_json(&json.to_string()).unwrap();
+    let errors = check_layout_against_snapshot(&snapshot, layout).unwrap_err();
+    assert!(errors
+        .flattened_errors()
+        .any(|e| matches!(e, AbiInstability::PackageVersionParseError(_))));
+}
+
+#[test]
+fn invalid_snapshots() {
+    let layout = <point_a::Point as StableAbi>::LAYOUT;
+    let json: serde_json::Value =
+        serde_json::from_str(&AbiSnapshot::new(layout).to_json()).unwrap();
+
+    let mut other_format = json.clone();
+    other_format["format_version"] = 1000.into();
+    assert!(AbiSnapshot::from_json(&other_format.to_string()).is_err());
+
+    let mut out_of_bounds = json.clone();
+    out_of_bounds["types"][0]["data"]["Struct"]["fields"][0]["layout"] = 1000.into();
+    assert!(AbiSnapshot::from_json(&out_of_bounds.to_string()).is_err());
+
+    let mut empty = json;
+    empty["types"] = serde_json::Value::Array(Vec::new());
+    assert!(AbiSnapshot::from_json(&empty.to_string()).is_err());
+}
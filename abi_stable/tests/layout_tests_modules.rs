@@ -42,4 +42,22 @@ mod layout_tests {
 
     #[cfg(all(test, not(feature = "only_new_tests")))]
     mod shared_types;
+
+    #[cfg(all(test, feature = "serde_json", not(feature = "only_new_tests")))]
+    mod snapshots;
+
+    #[cfg(all(test, not(feature = "only_new_tests")))]
+    mod mismatch_reports;
+
+    #[cfg(all(test, not(feature = "only_new_tests")))]
+    mod semver_changes;
+
+    #[cfg(all(test, not(feature = "only_new_tests")))]
+    mod renamed_from;
+
+    #[cfg(all(test, not(feature = "only_new_tests")))]
+    mod moved_from;
+
+    #[cfg(all(test, not(feature = "only_new_tests")))]
+    mod sabi_supertraits;
 }
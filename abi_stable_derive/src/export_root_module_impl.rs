@@ -2,13 +2,20 @@
 
 use super::*;
 
-use as_derive_utils::return_spanned_err;
+use as_derive_utils::{return_spanned_err, spanned_err};
 
-use syn::Ident;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Expr, Ident, LitStr, Token, Type,
+};
 
 use proc_macro2::Span;
 
-use abi_stable_shared::mangled_root_module_loader_name;
+use abi_stable_shared::{
+    mangled_build_metadata_name, mangled_host_module_slots_name, mangled_module_dependencies_name,
+    mangled_root_module_loader_name,
+};
 
 #[doc(hidden)]
 pub fn export_root_module_attr(_attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
@@ -26,12 +33,38 @@ fn export_root_module_inner(mut input: ItemFn) -> Result<TokenStream2, syn::Erro
     let unsafe_no_layout_constant_path =
         syn::parse_str::<syn::Path>("unsafe_no_layout_constant").expect("BUG");
 
+    let build_metadata_path = syn::parse_str::<syn::Path>("build_metadata").expect("BUG");
+
+    let host_modules_path = syn::parse_str::<syn::Path>("host_modules").expect("BUG");
+
     let mut found_unsafe_no_layout_constant = false;
+    let mut build_metadata_attrs = Vec::new();
+    let mut host_modules_attrs = Vec::new();
     input.attrs.retain(|attr| {
+        if attr.path == build_metadata_path {
+            build_metadata_attrs.push(attr.clone());
+            return false;
+        }
+        if attr.path == host_modules_path {
+            host_modules_attrs.push(attr.clone());
+            return false;
+        }
         let is_it = attr.path == unsafe_no_layout_constant_path;
         found_unsafe_no_layout_constant = found_unsafe_no_layout_constant || is_it;
         !is_it
     });
+
+    let mut build_metadata = BuildMetadataArgs::default();
+    for attr in build_metadata_attrs {
+        build_metadata.merge(attr.parse_args::<BuildMetadataArgs>()?)?;
+    }
+
+    let mut host_modules = Vec::<Type>::new();
+    for attr in host_modules_attrs {
+        let types = attr.parse_args_with(Punctuated::<Type, Token![,]>::parse_terminated)?;
+        host_modules.extend(types);
+    }
+
     let check_ty_layout_variant = Ident::new(
         if found_unsafe_no_layout_constant {
             "No"
@@ -52,6 +85,20 @@ fn export_root_module_inner(mut input: ItemFn) -> Result<TokenStream2, syn::Erro
 
     let export_name = Ident::new(&mangled_root_module_loader_name(), Span::call_site());
 
+    let metadata_name = Ident::new(&mangled_build_metadata_name(), Span::call_site());
+
+    let host_slots_name = Ident::new(&mangled_host_module_slots_name(), Span::call_site());
+
+    let dependencies_name = Ident::new(&mangled_module_dependencies_name(), Span::call_site());
+
+    let git_hash = match &build_metadata.git_hash {
+        Some((_, expr)) => quote!(::abi_stable::library::BuildMetadata::__git_hash(#expr)),
+        None => quote!(::abi_stable::std_types::RNone),
+    };
+
+    let custom_keys = build_metadata.custom.iter().map(|(key, _)| key);
+    let custom_values = build_metadata.custom.iter().map(|(_, value)| value);
+
     Ok(quote!(
         #input
 
@@ -70,9 +117,132 @@ fn export_root_module_inner(mut input: ItemFn) -> Result<TokenStream2, syn::Erro
                 )
             }
         };
+
+        #[no_mangle]
+        #vis static #metadata_name: ::abi_stable::library::BuildMetadata_Ref = {
+            const __SABI_CUSTOM: &[::abi_stable::library::MetadataEntry] = &[
+                #( ::abi_stable::library::MetadataEntry::new(#custom_keys, #custom_values), )*
+            ];
+
+            const __SABI_METADATA: &::abi_stable::prefix_type::WithMetadata<
+                ::abi_stable::library::BuildMetadata,
+            > = &::abi_stable::prefix_type::WithMetadata::new(
+                ::abi_stable::library::BuildMetadata::__new(
+                    ::std::env!("CARGO_PKG_NAME"),
+                    ::std::env!("CARGO_PKG_VERSION"),
+                    ::std::cfg!(debug_assertions),
+                    #git_hash,
+                    __SABI_CUSTOM,
+                ),
+            );
+
+            ::abi_stable::library::BuildMetadata_Ref(__SABI_METADATA.static_as_prefix())
+        };
+
+        #[no_mangle]
+        #vis static #host_slots_name: ::abi_stable::std_types::RSlice<
+            'static,
+            ::abi_stable::library::HostModuleSlot,
+        > = {
+            const __SABI_SLOTS: &[::abi_stable::library::HostModuleSlot] = &[
+                #( ::abi_stable::library::HostModuleSlot::new::<#host_modules>(), )*
+            ];
+
+            ::abi_stable::std_types::RSlice::from_slice(__SABI_SLOTS)
+        };
+
+        #[no_mangle]
+        #vis static #dependencies_name: ::abi_stable::std_types::RSlice<
+            'static,
+            ::abi_stable::library::ModuleDependency,
+        > = {
+            type __SABI_Module = <#ret_ty as ::abi_stable::library::IntoRootModuleResult>::Module;
+            ::abi_stable::std_types::RSlice::from_slice(
+                <__SABI_Module as ::abi_stable::library::RootModule>::DEPENDENCIES
+            )
+        };
     ))
 }
 
+/// The arguments of the `#[build_metadata(...)]` helper attribute.
+#[derive(Default)]
+struct BuildMetadataArgs {
+    git_hash: Option<(Ident, Expr)>,
+    custom: Vec<(LitStr, Expr)>,
+}
+
+impl BuildMetadataArgs {
+    fn merge(&mut self, other: Self) -> Result<(), syn::Error> {
+        if let Some((ident, expr)) = other.git_hash {
+            if self.git_hash.is_some() {
+                return_spanned_err!(ident, "Cannot specify `git_hash` more than once.")
+            }
+            self.git_hash = Some((ident, expr));
+        }
+        for (key, value) in other.custom {
+            if let Some((prev, _)) = self.custom.iter().find(|(k, _)| k.value() == key.value()) {
+                let mut err = spanned_err!(key, "Duplicate custom metadata key.");
+                err.combine(spanned_err!(prev, "Previously specified here."));
+                return Err(err);
+            }
+            self.custom.push((key, value));
+        }
+        Ok(())
+    }
+}
+
+/// A `"key" = value` pair of the `custom(...)` argument of `#[build_metadata(...)]`.
+struct CustomEntry {
+    key: LitStr,
+    value: Expr,
+}
+
+impl Parse for CustomEntry {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let key = input.parse::<LitStr>()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse::<Expr>()?;
+        Ok(Self { key, value })
+    }
+}
+
+impl Parse for BuildMetadataArgs {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let mut this = BuildMetadataArgs::default();
+
+        while !input.is_empty() {
+            let ident = input.parse::<Ident>()?;
+            if ident == "git_hash" {
+                input.parse::<Token![=]>()?;
+                let expr = input.parse::<Expr>()?;
+                this.merge(BuildMetadataArgs {
+                    git_hash: Some((ident, expr)),
+                    custom: Vec::new(),
+                })?;
+            } else if ident == "custom" {
+                let content;
+                syn::parenthesized!(content in input);
+                let entries = Punctuated::<CustomEntry, Token![,]>::parse_terminated(&content)?;
+                this.merge(BuildMetadataArgs {
+                    git_hash: None,
+                    custom: entries.into_iter().map(|e| (e.key, e.value)).collect(),
+                })?;
+            } else {
+                return_spanned_err!(
+                    ident,
+                    "Unrecognized build_metadata argument,expected `git_hash` or `custom`.",
+                )
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(this)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -130,4 +300,129 @@ mod tests {
             assert!(str_out.contains(expected_const));
         }
     }
+
+    #[test]
+    fn test_build_metadata() {
+        let list = vec![
+            (
+                r##"
+                    pub fn hello()->RString{}
+                "##,
+                vec![
+                    "__new(::std::env!(\"CARGO_PKG_NAME\"),\
+                     ::std::env!(\"CARGO_PKG_VERSION\"),\
+                     ::std::cfg!(debug_assertions),\
+                     ::abi_stable::std_types::RNone,\
+                     __SABI_CUSTOM,)",
+                ],
+            ),
+            (
+                r##"
+                    #[build_metadata(git_hash = env!("GIT_HASH"))]
+                    pub fn hello()->RString{}
+                "##,
+                vec!["__git_hash(env!(\"GIT_HASH\"))"],
+            ),
+            (
+                r##"
+                    #[build_metadata(custom("foo" = "bar", "baz" = QUX))]
+                    #[build_metadata(git_hash = "0123abc")]
+                    pub fn hello()->RString{}
+                "##,
+                vec![
+                    "MetadataEntry::new(\"foo\",\"bar\")",
+                    "MetadataEntry::new(\"baz\",QUX)",
+                    "__git_hash(\"0123abc\")",
+                ],
+            ),
+        ];
+
+        for (item, expected) in list {
+            let str_out = export_root_module_str(item)
+                .unwrap()
+                .to_string()
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>();
+            assert!(!str_out.contains("build_metadata"), "{}", str_out);
+            for expected in expected {
+                assert!(str_out.contains(expected), "{}\n{}", expected, str_out);
+            }
+        }
+    }
+
+    #[test]
+    fn test_host_modules() {
+        let list = vec![
+            (
+                r##"
+                    pub fn hello()->RString{}
+                "##,
+                vec![],
+            ),
+            (
+                r##"
+                    #[host_modules(Foo_Ref)]
+                    pub fn hello()->RString{}
+                "##,
+                vec!["HostModuleSlot::new::<Foo_Ref>()"],
+            ),
+            (
+                r##"
+                    #[host_modules(Foo_Ref, bar::Bar_Ref,)]
+                    #[host_modules(Baz_Ref)]
+                    pub fn hello()->RString{}
+                "##,
+                vec![
+                    "HostModuleSlot::new::<Foo_Ref>()",
+                    "HostModuleSlot::new::<bar::Bar_Ref>()",
+                    "HostModuleSlot::new::<Baz_Ref>()",
+                ],
+            ),
+        ];
+
+        for (item, expected) in list {
+            let str_out = export_root_module_str(item)
+                .unwrap()
+                .to_string()
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>();
+            assert!(!str_out.contains("host_modules"), "{}", str_out);
+            assert_eq!(
+                str_out.matches("HostModuleSlot::new").count(),
+                expected.len(),
+                "{}",
+                str_out
+            );
+            for expected in expected {
+                assert!(str_out.contains(expected), "{}\n{}", expected, str_out);
+            }
+        }
+
+        assert!(export_root_module_str("#[host_modules(1)] pub fn hello()->RString{}").is_err());
+    }
+
+    #[test]
+    fn test_build_metadata_errors() {
+        let list = vec![
+            r##"
+                #[build_metadata(foo = "bar")]
+                pub fn hello()->RString{}
+            "##,
+            r##"
+                #[build_metadata(git_hash = "bar", git_hash = "baz")]
+                pub fn hello()->RString{}
+            "##,
+            r##"
+                #[build_metadata(custom("foo" = "bar"))]
+                #[build_metadata(custom("foo" = "baz"))]
+                pub fn hello()->RString{}
+            "##,
+        ];
+
+        for item in list {
+            assert!(export_root_module_str(item).is_err(), "{}", item);
+        }
+    }
 }
@@ -80,9 +80,27 @@ pub fn __const_mangled_root_module_loader_name(_: TokenStream1) -> TokenStream1
     let name = abi_stable_shared::mangled_root_module_loader_name();
     let name_nulled = format!("{}\0", name);
 
+    let metadata_name = abi_stable_shared::mangled_build_metadata_name();
+    let metadata_name_nulled = format!("{}\0", metadata_name);
+
+    let host_slots_name = abi_stable_shared::mangled_host_module_slots_name();
+    let host_slots_name_nulled = format!("{}\0", host_slots_name);
+
+    let dependencies_name = abi_stable_shared::mangled_module_dependencies_name();
+    let dependencies_name_nulled = format!("{}\0", dependencies_name);
+
     quote!(
         const PRIV_MANGLED_ROOT_MODULE_LOADER_NAME: &str = #name;
         const PRIV_MANGLED_ROOT_MODULE_LOADER_NAME_NUL: &str = #name_nulled;
+
+        const PRIV_MANGLED_BUILD_METADATA_NAME: &str = #metadata_name;
+        const PRIV_MANGLED_BUILD_METADATA_NAME_NUL: &str = #metadata_name_nulled;
+
+        const PRIV_MANGLED_HOST_MODULE_SLOTS_NAME: &str = #host_slots_name;
+        const PRIV_MANGLED_HOST_MODULE_SLOTS_NAME_NUL: &str = #host_slots_name_nulled;
+
+        const PRIV_MANGLED_MODULE_DEPENDENCIES_NAME: &str = #dependencies_name;
+        const PRIV_MANGLED_MODULE_DEPENDENCIES_NAME_NUL: &str = #dependencies_name_nulled;
     )
     .into()
 }
@@ -5,11 +5,11 @@ use std::mem;
 use as_derive_utils::return_spanned_err;
 
 use proc_macro::TokenStream as TokenStream1;
-use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree};
+use proc_macro2::{Span, TokenStream as TokenStream2};
 
 use quote::{quote, ToTokens};
 
-use syn::{Expr, ItemFn};
+use syn::{parse::Parser, punctuated::Punctuated, Expr, Ident, ItemFn, ReturnType, Token};
 
 use crate::parse_or_compile_err;
 
@@ -33,11 +33,22 @@ pub enum WithEarlyReturn {
     Yes,
 }
 
-/// Converts a function into an `extern "C" fn` which aborts on panic.
-pub(crate) fn convert_to_sabi_extern_fn(with_early_return: WithEarlyReturn, item: &mut ItemFn) {
-    let no_early_return = match with_early_return {
-        WithEarlyReturn::No => Some(quote!( no_early_return; )),
-        WithEarlyReturn::Yes => None,
+/// What the function does when the code inside panics.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum OnPanic {
+    /// Aborts the process.
+    Abort(WithEarlyReturn),
+    /// Catches the panic, returning it in an `RResult<_, RPanicPayload>`.
+    CatchUnwind,
+}
+
+/// Converts a function into an `extern "C" fn` which aborts on panic,
+/// or which returns the panic if `on_panic` is `OnPanic::CatchUnwind`.
+pub(crate) fn convert_to_sabi_extern_fn(on_panic: OnPanic, item: &mut ItemFn) {
+    let mode = match on_panic {
+        OnPanic::Abort(WithEarlyReturn::No) => Some(quote!( no_early_return; )),
+        OnPanic::Abort(WithEarlyReturn::Yes) => None,
+        OnPanic::CatchUnwind => Some(quote!( catch_unwind; )),
     };
 
     item.sig.abi = Some(syn::Abi {
@@ -45,11 +56,21 @@ pub(crate) fn convert_to_sabi_extern_fn(with_early_return: WithEarlyReturn, item
         name: Some(syn::LitStr::new("C", Span::call_site())),
     });
 
+    if on_panic == OnPanic::CatchUnwind {
+        let ret_ty = match &item.sig.output {
+            ReturnType::Default => quote!(()),
+            ReturnType::Type(_, ty) => ty.to_token_stream(),
+        };
+        item.sig.output = syn::parse_quote!(
+            -> ::abi_stable::std_types::RResult<#ret_ty, ::abi_stable::std_types::RPanicPayload>
+        );
+    }
+
     let statements = mem::take(&mut item.block.stmts);
 
     let x = quote! {
         ::abi_stable::extern_fn_panic_handling!(
-            #no_early_return
+            #mode
 
             #(#statements)*
         )
@@ -61,13 +82,30 @@ pub(crate) fn convert_to_sabi_extern_fn(with_early_return: WithEarlyReturn, item
 }
 
 fn sabi_extern_fn_inner(attr: TokenStream2, mut item: ItemFn) -> Result<TokenStream2, syn::Error> {
-    let with_early_return = match attr.into_iter().next() {
-        Some(TokenTree::Ident(ref ident)) if ident == "no_early_return" => WithEarlyReturn::No,
-        Some(tt) => return_spanned_err!(tt, "Unrecognized `#[sabi_extern_fn]` parameter",),
-        None => WithEarlyReturn::Yes,
+    let params = Punctuated::<Ident, Token![,]>::parse_terminated.parse2(attr)?;
+
+    let mut with_early_return = WithEarlyReturn::Yes;
+    let mut catch_unwind = None::<Ident>;
+    for param in params {
+        if param == "no_early_return" {
+            with_early_return = WithEarlyReturn::No;
+        } else if param == "catch_unwind" {
+            catch_unwind = Some(param);
+        } else {
+            return_spanned_err!(param, "Unrecognized `#[sabi_extern_fn]` parameter",)
+        }
+    }
+
+    let on_panic = match (catch_unwind, with_early_return) {
+        (Some(param), WithEarlyReturn::No) => return_spanned_err!(
+            param,
+            "`catch_unwind` can't be combined with `no_early_return`",
+        ),
+        (Some(_), WithEarlyReturn::Yes) => OnPanic::CatchUnwind,
+        (None, with_early_return) => OnPanic::Abort(with_early_return),
     };
 
-    convert_to_sabi_extern_fn(with_early_return, &mut item);
+    convert_to_sabi_extern_fn(on_panic, &mut item);
 
     Ok(item.into_token_stream())
 }
@@ -120,6 +158,48 @@ mod tests {
                     }
                 ),
             ),
+            (
+                "catch_unwind",
+                r##"
+                    pub fn hello() -> u32 {
+                        if HELLO {
+                            return 0;
+                        }
+                        1
+                    }
+                "##,
+                quote!(
+                    pub extern "C" fn hello(
+                    ) -> ::abi_stable::std_types::RResult<u32, ::abi_stable::std_types::RPanicPayload>
+                    {
+                        ::abi_stable::extern_fn_panic_handling!(
+                            catch_unwind;
+                            if HELLO {
+                                return 0;
+                            }
+                            1
+                        )
+                    }
+                ),
+            ),
+            (
+                "catch_unwind",
+                r##"
+                    fn hello() {
+                        println!("{}",HELLO);
+                    }
+                "##,
+                quote!(
+                    extern "C" fn hello(
+                    ) -> ::abi_stable::std_types::RResult<(), ::abi_stable::std_types::RPanicPayload>
+                    {
+                        ::abi_stable::extern_fn_panic_handling!(
+                            catch_unwind;
+                            println!("{}",HELLO);
+                        )
+                    }
+                ),
+            ),
         ];
 
         for (attr, item, expected) in list {
@@ -129,4 +209,16 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_errors() {
+        let item = "fn hello() {}";
+        for attr in &[
+            "foo",
+            "catch_unwind, no_early_return",
+            "no_early_return, catch_unwind",
+        ] {
+            assert!(sabi_extern_fn_str(attr, item).is_err(), "{}", attr);
+        }
+    }
 }
@@ -124,6 +124,10 @@ pub fn derive_sabi_trait(item: ItemTrait) -> Result<TokenStream2, syn::Error> {
 
     vtable_impl(tokenizer_params, &mut mod_contents);
 
+    supertrait_items(tokenizer_params, &mut mod_contents)?;
+
+    subtrait_items(tokenizer_params, &mut mod_contents)?;
+
     impl_delegations::delegated_impls(tokenizer_params, &mut mod_contents);
 
     let doc_hidden_attr = config.doc_hidden_attr;
@@ -626,6 +630,22 @@ fn constructor_items(params: TokenizerParams<'_>, mod_: &mut TokenStream2) {
 
     let reborrow_methods = reborrow_methods_tokenizer(params);
 
+    let sabi_supertraits_ptr = sabi_supertrait_vtable_bounds(
+        params,
+        &quote!(_ErasedPtr,),
+        &quote!(Downcasting, _OrigPtr::PtrTarget, _OrigPtr),
+    );
+    let sabi_supertraits_value = sabi_supertrait_vtable_bounds(
+        params,
+        &quote!(__sabi_re::RBox<()>,),
+        &quote!(Downcasting, _Self, __sabi_re::RBox<_Self>),
+    );
+    let sabi_supertraits_const = sabi_supertrait_vtable_bounds(
+        params,
+        &quote!(__sabi_re::RRef<'_sub, ()>,),
+        &quote!(Downcasting, _Self, &'_sub _Self),
+    );
+
     let plus_lt = &lt_tokens.plus_lt;
 
     let constructing_backend = match totrait_def.which_object {
@@ -668,6 +688,7 @@ fn constructor_items(params: TokenizerParams<'_>, mod_: &mut TokenStream2) {
                         Downcasting,_OrigPtr::PtrTarget,_ErasedPtr,_OrigPtr
                     >,
                 #extra_constraints_ptr
+                #sabi_supertraits_ptr
             {
                 let _can_it_downcast=can_it_downcast;
                 unsafe{
@@ -712,6 +733,7 @@ fn constructor_items(params: TokenizerParams<'_>, mod_: &mut TokenStream2) {
                         Downcasting,_Self,__sabi_re::RBox<()>,__sabi_re::RBox<_Self>
                     >,
                 #extra_constraints_value
+                #sabi_supertraits_value
             {
                 Self::from_ptr::<
                     __sabi_re::RBox<_Self>,
@@ -737,6 +759,7 @@ fn constructor_items(params: TokenizerParams<'_>, mod_: &mut TokenStream2) {
                         Downcasting, _Self, __sabi_re::RRef<'_sub, ()>, &'_sub _Self
                     >,
                 #extra_constraints_const
+                #sabi_supertraits_const
             {
                 unsafe{
                     Self{
@@ -836,8 +859,10 @@ fn trait_and_impl(
     let methods_tokenizer_impl = trait_def.methods_tokenizer(WhichItem::TraitImpl);
     let lifetime_bounds_a = trait_def.lifetime_bounds.iter();
     let lifetime_bounds_c = trait_def.lifetime_bounds.iter();
-    let super_traits_a = trait_def.impld_traits.iter().map(|t| &t.bound);
+    let super_traits_a = trait_def.supertrait_bounds();
     let super_traits_b = super_traits_a.clone();
+    let sabi_super_traits_a = trait_def.sabi_supertraits.iter().map(|t| t.bound);
+    let sabi_super_traits_b = sabi_super_traits_a.clone();
 
     let assoc_tys_a = trait_def.assoc_tys.values().map(|x| &x.assoc_ty);
 
@@ -850,7 +875,7 @@ fn trait_and_impl(
         #( #other_attrs )*
         #submod_vis #unsafety trait #trait_ident<
             #gen_params_trait
-        >: #( #super_traits_a + )* #( #lifetime_bounds_a + )*
+        >: #( #super_traits_a + )* #( #sabi_super_traits_a + )* #( #lifetime_bounds_a + )*
         where
             #(#where_preds,)*
         {
@@ -888,7 +913,10 @@ fn trait_and_impl(
             impl<#gen_params_header> #trait_ident<#gen_params_use_trait>
             for #trait_to<#gen_params_use_to>
             where
-                Self:#( #super_traits_b + )* #(#lifetime_bounds_c+)*  ,
+                Self:
+                    #( #super_traits_b + )*
+                    #( #sabi_super_traits_b + )*
+                    #(#lifetime_bounds_c+)*,
                 #erased_ptr_bounds
                 #(#where_preds_b,)*
             {
@@ -914,7 +942,7 @@ fn methods_impls(param: TokenizerParams, mod_: &mut TokenStream2) -> Result<(),
 
     let impl_where_preds = totrait_def.trait_impl_where_preds()?;
 
-    let super_traits_a = totrait_def.impld_traits.iter().map(|t| &t.bound);
+    let super_traits_a = totrait_def.supertrait_bounds();
 
     let gen_params_header = totrait_def.generics_tokenizer(
         InWhat::ImplHeader,
@@ -967,6 +995,7 @@ fn declare_vtable(
         vtable_trait_decl,
         submod_vis,
         trait_interface,
+        lt_tokens,
         ..
     }: TokenizerParams,
     mod_: &mut TokenStream2,
@@ -1017,6 +1046,22 @@ fn declare_vtable(
         >
     );
 
+    let sabi_super_fields = vtable_trait_decl.sabi_supertraits.iter().map(|x| &x.field);
+    // Using the full path to `SupertraitVTable` because
+    // `StableAbi` uses its own `__sabi_re` module.
+    let sabi_super_vtables = vtable_trait_decl
+        .sabi_supertraits
+        .iter()
+        .map(|x| {
+            let trait_object = x.trait_object(&lt_tokens.staticlt_erasedptr);
+            quote!(
+                ::abi_stable::sabi_trait::reexports::__sabi_re::SupertraitVTable<#trait_object>
+            )
+        })
+        .collect::<Vec<_>>();
+    let sabi_super_vtables_a = sabi_super_vtables.iter();
+    let sabi_super_vtables_b = sabi_super_vtables.iter();
+
     quote!(
 
         #[repr(C)]
@@ -1025,6 +1070,7 @@ fn declare_vtable(
         #[sabi(missing_field(panic))]
         #( #[sabi(prefix_bound(#lifetime_bounds))] )*
         #[sabi(bound(#robject_vtable: ::abi_stable::StableAbi))]
+        #( #[sabi(bound(#sabi_super_vtables_a: ::abi_stable::StableAbi))] )*
         #(#derive_attrs)*
         #[doc(hidden)]
         #submod_vis struct VTable<#generics_decl>
@@ -1035,6 +1081,8 @@ fn declare_vtable(
 
             _sabi_vtable:#robject_vtable,
 
+            #( #sabi_super_fields: #sabi_super_vtables_b, )*
+
             #methods_tokenizer
         }
     )
@@ -1047,8 +1095,8 @@ fn declare_vtable(
 ///
 /// - The methods that the vtable is constructed with.
 ///
-fn vtable_impl(
-    TokenizerParams {
+fn vtable_impl(params: TokenizerParams, mod_: &mut TokenStream2) {
+    let TokenizerParams {
         ctokens,
         vtable_trait_impl,
         trait_interface,
@@ -1056,9 +1104,8 @@ fn vtable_impl(
         make_vtable_ident,
         lt_tokens,
         ..
-    }: TokenizerParams,
-    mod_: &mut TokenStream2,
-) {
+    } = params;
+
     let struct_decl_generics = vtable_trait_impl.generics_tokenizer(
         InWhat::ItemDecl,
         WithAssocTys::No,
@@ -1131,6 +1178,15 @@ fn vtable_impl(
         WhichObject::RObject => quote!(),
     };
 
+    let sabi_supertrait_bounds =
+        sabi_supertrait_vtable_bounds(params, &ctokens.ts_erasedptr, &quote!(IA, _Self, _OrigPtr));
+
+    let sabi_super_fields = vtable_trait_impl.sabi_supertraits.iter().map(|x| &x.field);
+    let sabi_super_vtables = vtable_trait_impl.sabi_supertraits.iter().map(|x| {
+        let trait_object = x.trait_object(&lt_tokens.staticlt_erasedptr);
+        quote!(<#trait_object as __sabi_re::MakeSabiTraitVTable<IA, _Self, _OrigPtr>>::VTABLE)
+    });
+
     quote!(
         struct #make_vtable_ident<#struct_decl_generics>(#dummy_struct_tys);
 
@@ -1144,6 +1200,7 @@ fn vtable_impl(
             #trait_interface<#trait_interface_use>:
                 __sabi_re::GetRObjectVTable<IA,_Self,_ErasedPtr,_OrigPtr>,
             #extra_constraints
+            #sabi_supertrait_bounds
         {
             const TMP0: __sabi_re::WithMetadata<
                 VTable<#withmetadata_generics>
@@ -1152,6 +1209,7 @@ fn vtable_impl(
                     VTable{
                         _sabi_tys: __sabi_re::NonOwningPhantom::NEW,
                         _sabi_vtable:__sabi_re::GetRObjectVTable::ROBJECT_VTABLE,
+                        #( #sabi_super_fields: #sabi_super_vtables, )*
                         #(
                             #method_names_a:Self::#method_names_b,
                         )*
@@ -1170,6 +1228,311 @@ fn vtable_impl(
     .to_tokens(mod_);
 }
 
+/// Outputs the `MakeSabiTraitVTable` bounds required to construct the vtables of
+/// the `#[sabi_trait]` supertraits,which are stored in the vtable of this trait.
+///
+/// `erased_ptr` is the type-erased pointer of the trait objects,
+/// and `make_vtable_args` are the type arguments to `MakeSabiTraitVTable`.
+fn sabi_supertrait_vtable_bounds(
+    TokenizerParams {
+        trait_def,
+        lt_tokens,
+        ..
+    }: TokenizerParams<'_>,
+    erased_ptr: &TokenStream2,
+    make_vtable_args: &TokenStream2,
+) -> TokenStream2 {
+    let staticlt = &lt_tokens.staticlt;
+    let trait_objects = trait_def
+        .sabi_supertraits
+        .iter()
+        .map(|x| x.trait_object(quote!(#staticlt #erased_ptr)));
+
+    quote!(
+        #(
+            #trait_objects: __sabi_re::MakeSabiTraitVTable<#make_vtable_args>,
+        )*
+    )
+}
+
+/// Outputs the items that allow using this trait as a supertrait of
+/// other `#[sabi_trait]` traits:
+///
+/// - An impl of `SabiTraitObject` for the trait object.
+///
+/// - An impl of `MakeSabiTraitVTable` for the trait object,
+///     used to construct the vtable embedded in the vtable of subtraits.
+///
+/// - A blanket impl of the trait for the trait objects of subtraits,
+///     which get the vtable of this trait with `GetSupertraitVTable`.
+///
+fn supertrait_items(params: TokenizerParams, mod_: &mut TokenStream2) -> Result<(), syn::Error> {
+    let TokenizerParams {
+        ctokens,
+        trait_def,
+        totrait_def,
+        vtable_trait_impl,
+        trait_ident,
+        trait_to,
+        trait_interface,
+        make_vtable_ident,
+        lt_tokens,
+        ..
+    } = params;
+
+    if trait_def.which_object == WhichObject::DynTrait {
+        return Ok(());
+    }
+
+    let impl_where_preds = totrait_def.trait_impl_where_preds()?;
+
+    let gen_params_header = totrait_def.generics_tokenizer(
+        InWhat::ImplHeader,
+        WithAssocTys::Yes(WhichSelf::NoSelf),
+        &lt_tokens.lt_erasedptr,
+    );
+    let gen_params_use_to = totrait_def.generics_tokenizer(
+        InWhat::ItemUse,
+        WithAssocTys::Yes(WhichSelf::NoSelf),
+        &lt_tokens.lt_erasedptr,
+    );
+
+    let vtable_generics = totrait_def.generics_tokenizer(
+        InWhat::ItemUse,
+        WithAssocTys::Yes(WhichSelf::NoSelf),
+        &ctokens.ts_unit_erasedptr,
+    );
+
+    quote!(
+        unsafe impl<#gen_params_header> __sabi_re::SabiTraitObject
+        for #trait_to<#gen_params_use_to>
+        where
+            _ErasedPtr: __GetPointerKind,
+            #impl_where_preds
+        {
+            type ErasedPtr = _ErasedPtr;
+
+            type VTable = VTable_Ref<#vtable_generics>;
+
+            #[inline]
+            fn sabi_as_rref(&self) -> __sabi_re::RRef<'_, ()>
+            where
+                _ErasedPtr: __sabi_re::AsPtr<PtrTarget = ()>,
+            {
+                self.obj.sabi_as_rref()
+            }
+
+            #[inline]
+            fn sabi_as_rmut(&mut self) -> __sabi_re::RMut<'_, ()>
+            where
+                _ErasedPtr: __sabi_re::AsMutPtr<PtrTarget = ()>,
+            {
+                self.obj.sabi_as_rmut()
+            }
+
+            #[inline]
+            fn sabi_with_value<F, R>(self, f: F) -> R
+            where
+                _ErasedPtr: __sabi_re::OwnedPointer<PtrTarget = ()>,
+                F: FnOnce(__sabi_re::MovePtr<'_, ()>) -> R,
+            {
+                self.obj.sabi_with_value(f)
+            }
+        }
+    )
+    .to_tokens(mod_);
+
+    // The vtables of traits with associated types can't be embedded in
+    // the vtables of subtraits.
+    if !trait_def.assoc_tys.is_empty() {
+        return Ok(());
+    }
+
+    let makevtable_header = vtable_trait_impl.generics_tokenizer(
+        InWhat::ImplHeader,
+        WithAssocTys::No,
+        &ctokens.ts_makevtable_params,
+    );
+
+    let to_static_use = vtable_trait_impl.generics_tokenizer(
+        InWhat::ItemUse,
+        WithAssocTys::No,
+        &lt_tokens.staticlt_erasedptr,
+    );
+
+    let makevtable_generics = vtable_trait_impl.generics_tokenizer(
+        InWhat::ItemUse,
+        WithAssocTys::No,
+        &ctokens.ts_getvtable_static_params,
+    );
+
+    let trait_generics =
+        vtable_trait_impl.generics_tokenizer(InWhat::ItemUse, WithAssocTys::No, &ctokens.empty_ts);
+
+    let trait_interface_use =
+        vtable_trait_impl.generics_tokenizer(InWhat::ItemUse, WithAssocTys::No, &ctokens.ts_empty);
+
+    let sabi_supertrait_bounds =
+        sabi_supertrait_vtable_bounds(params, &ctokens.ts_erasedptr, &quote!(IA, _Self, _OrigPtr));
+
+    quote!(
+        unsafe impl<#makevtable_header>
+            __sabi_re::MakeSabiTraitVTable<IA, _Self, _OrigPtr>
+        for #trait_to<#to_static_use>
+        where
+            _Self: #trait_ident<#trait_generics>,
+            _OrigPtr:
+                __sabi_re::CanTransmuteElement<(), PtrTarget = _Self, TransmutedPtr = _ErasedPtr>,
+            _ErasedPtr:__sabi_re::AsPtr<PtrTarget=()>,
+            #trait_interface<#trait_interface_use>:
+                __sabi_re::GetRObjectVTable<IA,_Self,_ErasedPtr,_OrigPtr>,
+            #sabi_supertrait_bounds
+            #impl_where_preds
+        {
+            const VTABLE: __sabi_re::PrefixRef<VTable_Prefix<#vtable_generics>> =
+                #make_vtable_ident::<#makevtable_generics>::VTABLE_INNER;
+        }
+    )
+    .to_tokens(mod_);
+
+    if trait_def.disable_trait_impl {
+        return Ok(());
+    }
+
+    let gen_params_header = trait_def.generics_tokenizer(
+        InWhat::ImplHeader,
+        WithAssocTys::No,
+        &ctokens.ts_sub_erasedptr,
+    );
+    let gen_params_use_trait =
+        trait_def.generics_tokenizer(InWhat::ItemUse, WithAssocTys::No, &ctokens.empty_ts);
+
+    let where_preds = (&trait_def.where_preds).into_iter();
+    let super_traits = trait_def.supertrait_bounds();
+    let sabi_super_traits = trait_def.sabi_supertraits.iter().map(|t| t.bound);
+    let lifetime_bounds = trait_def.lifetime_bounds.iter();
+    let erased_ptr_bounds = trait_def.erased_ptr_preds();
+
+    let methods_tokenizer = trait_def.methods_tokenizer(WhichItem::SupertraitImpl);
+
+    quote!(
+        #[deny(unsafe_op_in_unsafe_fn)]
+        #[allow(
+            clippy::needless_lifetimes,
+            clippy::new_ret_no_self,
+        )]
+        impl<#gen_params_header> #trait_ident<#gen_params_use_trait> for __Sub
+        where
+            __Sub: __sabi_re::SabiTraitObject<ErasedPtr = _ErasedPtr> +
+                __sabi_re::GetSupertraitVTable<#trait_to<#to_static_use>>,
+            Self:
+                #( #super_traits + )*
+                #( #sabi_super_traits + )*
+                #( #lifetime_bounds + )*,
+            #erased_ptr_bounds
+            #(#where_preds,)*
+        {
+            #methods_tokenizer
+        }
+    )
+    .to_tokens(mod_);
+
+    Ok(())
+}
+
+/// Outputs the items for each `#[sabi_trait]` supertrait of this trait:
+///
+/// - An impl of `GetSupertraitVTable` for the trait object,
+///     which gets the vtable of the supertrait out of the vtable of this trait.
+///
+/// - A conversion from the trait object to the trait object of the supertrait.
+///
+fn subtrait_items(params: TokenizerParams, mod_: &mut TokenStream2) -> Result<(), syn::Error> {
+    let TokenizerParams {
+        totrait_def,
+        trait_to,
+        lt_tokens,
+        ..
+    } = params;
+
+    // Used to require that the supertraits of the `#[sabi_trait]` supertraits
+    // (ie: `Send`/`Sync`/`Debug`) are also declared for this trait,
+    // regardless of the pointer type.
+    let gen_params_use_to_rbox = totrait_def.generics_tokenizer(
+        InWhat::ItemUse,
+        WithAssocTys::Yes(WhichSelf::NoSelf),
+        &lt_tokens.staticlt_rbox,
+    );
+
+    let impl_where_preds = totrait_def.trait_impl_where_preds()?;
+
+    let gen_params_header = totrait_def.generics_tokenizer(
+        InWhat::ImplHeader,
+        WithAssocTys::Yes(WhichSelf::NoSelf),
+        &lt_tokens.lt_erasedptr,
+    );
+    let gen_params_use_to = totrait_def.generics_tokenizer(
+        InWhat::ItemUse,
+        WithAssocTys::Yes(WhichSelf::NoSelf),
+        &lt_tokens.lt_erasedptr,
+    );
+
+    for supertrait in &totrait_def.sabi_supertraits {
+        let field = &supertrait.field;
+        let bound = supertrait.bound;
+        let super_static = supertrait.trait_object(&lt_tokens.staticlt_erasedptr);
+        let super_to = supertrait.trait_object(&lt_tokens.lt_erasedptr);
+
+        let upcast_docs = format!(
+            "Converts the trait object into one for the `{}` supertrait.",
+            supertrait.bound.path.segments.last().expect("BUG").ident,
+        );
+
+        quote!(
+            unsafe impl<#gen_params_header> __sabi_re::GetSupertraitVTable<#super_static>
+            for #trait_to<#gen_params_use_to>
+            where
+                _ErasedPtr: __GetPointerKind,
+                #impl_where_preds
+            {
+                #[inline]
+                fn sabi_supertrait_vtable(
+                    &self
+                ) -> <#super_static as __sabi_re::SabiTraitObject>::VTable {
+                    __sabi_re::PrefixRefTrait::from_prefix_ref(
+                        VTable_Ref(self.obj.sabi_et_vtable()).#field()
+                    )
+                }
+            }
+
+            impl<#gen_params_header> ::std::convert::From<#trait_to<#gen_params_use_to>>
+            for #super_to
+            where
+                _ErasedPtr: __sabi_re::AsPtr<PtrTarget = ()>,
+                #trait_to<#gen_params_use_to_rbox>: #bound,
+                #impl_where_preds
+            {
+                #[doc = #upcast_docs]
+                fn from(this: #trait_to<#gen_params_use_to>) -> Self {
+                    let vtable = <
+                        #trait_to<#gen_params_use_to> as
+                        __sabi_re::GetSupertraitVTable<#super_static>
+                    >::sabi_supertrait_vtable(&this);
+
+                    unsafe{
+                        Self::from_sabi(this.obj.sabi_upcast_with_vtable(
+                            __sabi_re::PrefixRefTrait::to_prefix_ref(vtable)
+                        ))
+                    }
+                }
+            }
+        )
+        .to_tokens(mod_);
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum SelfParam<'a> {
     ByRef {
@@ -1188,6 +1551,8 @@ pub(crate) enum WhichItem {
     TraitImpl,
     /// the methods in the inherent implemetation of the generated trait object.
     TraitObjectImpl,
+    /// the methods in the trait implementation for the trait objects of its subtraits.
+    SupertraitImpl,
     /// the fields of the trait object vtable.
     VtableDecl,
     /// the methods used to construct the vtable.
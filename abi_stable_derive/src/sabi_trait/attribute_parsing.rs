@@ -42,6 +42,7 @@ impl<'a> SabiTraitOptions<'a> {
 
 mod kw {
     syn::custom_keyword! {no_default_fallback}
+    syn::custom_keyword! {catch_unwind}
     syn::custom_keyword! {debug_print_trait}
     syn::custom_keyword! {debug_output_tokens}
     syn::custom_keyword! {use_dyntrait}
@@ -102,6 +103,9 @@ pub(super) struct SabiTraitAttrs<'a> {
     /// If true,doesn't use the default implementation of methods when
     /// the vtable entry is absent.
     pub(super) disable_inherent_default: Vec<bool>,
+    /// If true,the method returns panics to the caller of the vtable entry,
+    /// which resumes them,instead of aborting.
+    pub(super) catch_unwind: Vec<bool>,
 
     pub(super) is_hidden: bool,
     pub(super) debug_output_tokens: bool,
@@ -136,6 +140,7 @@ pub(crate) fn parse_attrs_for_sabi_trait<'a>(
     this.methods_with_attrs.reserve(assoc_fns.len());
 
     this.disable_inherent_default.resize(assoc_fns.len(), false);
+    this.catch_unwind.resize(assoc_fns.len(), false);
 
     parse_inner(&mut this, &*trait_.attrs, ParseContext::TraitAttr, arenas)?;
 
@@ -230,6 +235,17 @@ fn parse_sabi_trait_attr<'a>(
                 this.disable_inherent_default[index] = true;
             }
         }
+    } else if input.check_parse(kw::catch_unwind)? {
+        match pctx {
+            ParseContext::TraitAttr => {
+                for catches in &mut this.catch_unwind {
+                    *catches = true;
+                }
+            }
+            ParseContext::Method { index } => {
+                this.catch_unwind[index] = true;
+            }
+        }
     } else if input.check_parse(kw::debug_print_trait)? {
         this.debug_print_trait = true;
     } else if input.check_parse(kw::debug_output_tokens)? {
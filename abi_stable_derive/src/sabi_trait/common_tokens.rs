@@ -107,12 +107,16 @@ declare_common_tokens! {
 
         ts_make_vtable_args="Downcasting,_OrigPtr::PtrTarget,_OrigPtr::TransmutedPtr,_OrigPtr,",
         ts_make_vtable_args_const="Downcasting,_Self,__sabi_re::RRef<'_sub, ()>, &'_sub _Self,",
+        ts_erasedptr="_ErasedPtr,",
         ts_erasedptr_and2="_ErasedPtr,_ErasedPtr2,",
         ts_self_erasedptr="_Self,_ErasedPtr,",
         ts_unit_erasedptr="(),_ErasedPtr,",
 
         ts_getvtable_params="'lt,'_sub,IA,_Self,_ErasedPtr,_OrigPtr,",
         ts_getvtable_dummy_struct_fields="&'lt(),&'_sub(),IA,_Self,_ErasedPtr,_OrigPtr,",
+        ts_getvtable_static_params="'static,'static,IA,_Self,_ErasedPtr,_OrigPtr,",
+        ts_makevtable_params="IA,_Self,_ErasedPtr,_OrigPtr,",
+        ts_sub_erasedptr="__Sub,_ErasedPtr,",
         missing_field_option="#[sabi(missing_field(option))]",
     ]
 
@@ -204,6 +208,7 @@ declare_lifetime_tokens! {
     lifetime_tokens=[
         lt="",
         lt_erasedptr="_ErasedPtr,",
+        lt_de_erasedptr="'_de,_ErasedPtr,",
         lt_rbox="__sabi_re::RBox<()>,",
         lt_rref="__sabi_re::RRef<'_sub,()>,",
         lt_rmut="__sabi_re::RMut<'_sub,()>,",
@@ -213,6 +218,8 @@ declare_lifetime_tokens! {
         one_lt="",
     ]
     static_lifetime_tokens=[
+        staticlt="",
+        staticlt_rbox="__sabi_re::RBox<()>,",
         staticlt_erasedptr2="_ErasedPtr2,",
         staticlt_erasedptr="_ErasedPtr,",
     ]
@@ -1,6 +1,6 @@
 use proc_macro2::TokenStream as TokenStream2;
 
-use quote::{quote_spanned, ToTokens};
+use quote::{quote, quote_spanned, ToTokens};
 
 use as_derive_utils::gen_params_in::InWhat;
 
@@ -26,13 +26,6 @@ pub(super) fn delegated_impls(
     let impls = totrait_def.trait_flags;
     let spans = &totrait_def.trait_spans;
 
-    // let gen_params_deser_header=
-    //     totrait_def.generics_tokenizer(
-    //         InWhat::ImplHeader,
-    //         WithAssocTys::Yes(WhichSelf::NoSelf),
-    //         &lt_tokens.lt_de_erasedptr,
-    //     );
-
     let gen_params_header = totrait_def.generics_tokenizer(
         InWhat::ImplHeader,
         WithAssocTys::Yes(WhichSelf::NoSelf),
@@ -375,66 +368,63 @@ pub(super) fn delegated_impls(
         .to_tokens(mod_);
     }
 
-    // if let Some(deserialize_bound)=&totrait_def.deserialize_bound {
-    //     let deserialize_path=&deserialize_bound.bound.path;
-
-    //     let lifetimes=deserialize_bound.bound.lifetimes.as_ref()
-    //         .map(|x|ToTokenFnMut::new(move|ts|{
-    //             for lt in &x.lifetimes {
-    //                 lt.to_tokens(ts);
-    //                 Comma::default().to_tokens(ts);
-    //             }
-    //         }));
-
-    //     let suffix=&lt_tokens.lt_erasedptr;
-
-    //     let header_generics=arenas.alloc(quote!( #lifetimes #suffix ));
-
-    //     let gen_params_header=
-    //         totrait_def.generics_tokenizer(
-    //             InWhat::ImplHeader,
-    //             WithAssocTys::Yes(WhichSelf::NoSelf),
-    //             header_generics,
-    //         );
-
-    //     let lifetime_param=deserialize_bound.lifetime;
-
-    //     quote!(
-    //         impl<#gen_params_header> #deserialize_path for #trait_to<#gen_params_use_to>
-    //         where
-    //             #trait_backend<#gen_params_use_to>: #deserialize_path,
-    //             _ErasedPtr:__sabi_re::AsPtr<PtrTarget=()>,
-    //             #(#where_preds,)*
-    //         {
-    //             fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    //             where
-    //                 D: ::serde::Deserializer<#lifetime_param>,
-    //             {
-    //                 #trait_backend::<#gen_params_use_to>::deserialize(deserializer)
-    //                     .map(Self::from_sabi)
-    //             }
-    //         }
-    //     ).to_tokens(mod_);
-    // }
-
-    // if impls.serialize{
-
-    //     quote!(
-    //         impl<#gen_params_header> ::serde::Serialize for #trait_to<#gen_params_use_to>
-    //         where
-    //             #trait_backend<#gen_params_use_to>: ::serde::Serialize,
-    //             _ErasedPtr:__sabi_re::AsPtr<PtrTarget=()>,
-    //             #(#where_preds,)*
-    //         {
-    //             fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    //             where
-    //                 S: ::serde::Serializer,
-    //             {
-    //                 self.obj.serialize(serializer)
-    //             }
-    //         }
-    //     ).to_tokens(mod_);
-    // }
+    if impls.serialize {
+        let where_preds = where_preds.into_iter();
+
+        quote_spanned!(spans.serialize=>
+            impl<#gen_params_header> __sabi_re::Serialize for #trait_to<#gen_params_use_to>
+            where
+                #trait_backend<#gen_params_use_to>: __sabi_re::Serialize,
+                _ErasedPtr:__sabi_re::AsPtr<PtrTarget=()>,
+                #(#where_preds,)*
+            {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: __sabi_re::Serializer,
+                {
+                    __sabi_re::Serialize::serialize(&self.obj,serializer)
+                }
+            }
+        )
+        .to_tokens(mod_);
+    }
+    if impls.deserialize {
+        let where_preds = where_preds.into_iter();
+
+        let gen_params_de_header = totrait_def.generics_tokenizer(
+            InWhat::ImplHeader,
+            WithAssocTys::Yes(WhichSelf::NoSelf),
+            &lt_tokens.lt_de_erasedptr,
+        );
+
+        let interface = quote!(#trait_interface<#trait_interface_use>);
+
+        quote_spanned!(spans.deserialize=>
+            impl<#gen_params_de_header> __sabi_re::Deserialize<'_de>
+            for #trait_to<#gen_params_use_to>
+            where
+                #interface: __sabi_re::DeserializeDyn<'_de,Self>,
+                <#interface as __sabi_re::DeserializeDyn<'_de,Self>>::Proxy:
+                    __sabi_re::Deserialize<'_de>,
+                _ErasedPtr:__sabi_re::AsPtr<PtrTarget=()>,
+                #(#where_preds,)*
+            {
+                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: __sabi_re::Deserializer<'_de>,
+                {
+                    let proxy=
+                        <<#interface as __sabi_re::DeserializeDyn<'_de,Self>>::Proxy as
+                            __sabi_re::Deserialize<'_de>
+                        >::deserialize(deserializer)?;
+
+                    <#interface as __sabi_re::DeserializeDyn<'_de,Self>>::deserialize_dyn(proxy)
+                        .map_err(<D::Error as __sabi_re::DeError>::custom)
+                }
+            }
+        )
+        .to_tokens(mod_);
+    }
 
     if let Some(iter_item) = &totrait_def.iterator_item {
         let one_lt = &lt_tokens.one_lt;
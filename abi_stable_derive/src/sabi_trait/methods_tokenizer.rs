@@ -12,6 +12,10 @@
 //! - `WhichItem::TraitObjectImpl`:
 //!     outputs the methods in the inherent implemetation of the generated trait object.
 //!
+//! - `WhichItem::SupertraitImpl`:
+//!     outputs the methods in the implementation of the trait for
+//!     the trait objects of its `#[sabi_trait]` subtraits.
+//!
 //! - `WhichItem::VtableDecl`:
 //!     outputs the fields of the trait object vtable.
 //!
@@ -63,7 +67,7 @@ impl ToTokens for MethodTokenizer<'_> {
         // vis: the visibility of the generated method,
         //      None if it's implicit,Some(_) if it's explicit.
         let (is_method, vis) = match which_item {
-            WhichItem::Trait | WhichItem::TraitImpl => (true, None),
+            WhichItem::Trait | WhichItem::TraitImpl | WhichItem::SupertraitImpl => (true, None),
             WhichItem::TraitObjectImpl => (true, Some(trait_def.submod_vis)),
             WhichItem::VtableDecl | WhichItem::VtableImpl => (false, Some(trait_def.submod_vis)),
         };
@@ -163,7 +167,22 @@ impl ToTokens for MethodTokenizer<'_> {
         let param_names_c = param_names_a.clone();
         let param_names_d = param_names_a.clone();
         let param_names_e = method.params.iter().map(|x| x.pattern);
-        let return_ty = method.output.iter();
+
+        // The vtable entries of `#[sabi(catch_unwind)]` methods return the panic
+        // to the trait object,which resumes it.
+        let return_ty = match (which_item, method.catch_unwind) {
+            (WhichItem::VtableDecl | WhichItem::VtableImpl, true) => {
+                let ret = match &method.output {
+                    Some(ty) => ty.to_token_stream(),
+                    None => quote!(()),
+                };
+                Some(quote_spanned!(method_span=>
+                    __sabi_re::RResult<#ret, __sabi_re::RPanicPayload>
+                ))
+            }
+            (_, _) => method.output.as_ref().map(ToTokens::to_token_stream),
+        };
+        let return_ty = return_ty.iter();
 
         let self_is_sized_bound = Some(&ctokens.self_sized)
             .filter(|_| is_method && method.self_param == SelfParam::ByVal);
@@ -236,14 +255,32 @@ impl ToTokens for MethodTokenizer<'_> {
         }
         .to_tokens(ts);
 
-        let ptr_constraint = match &method.self_param {
-            SelfParam::ByRef {
-                is_mutable: false, ..
-            } => &ctokens.ptr_ref_bound,
-            SelfParam::ByRef {
-                is_mutable: true, ..
-            } => &ctokens.ptr_mut_bound,
-            SelfParam::ByVal => &ctokens.ptr_val_bound,
+        // The bounds on the erased pointer are in the where clause of
+        // the impl block for `WhichItem::SupertraitImpl`.
+        let ptr_constraint = match (which_item, &method.self_param) {
+            (WhichItem::SupertraitImpl, _) => &ctokens.empty_ts,
+            (
+                _,
+                SelfParam::ByRef {
+                    is_mutable: false, ..
+                },
+            ) => &ctokens.ptr_ref_bound,
+            (
+                _,
+                SelfParam::ByRef {
+                    is_mutable: true, ..
+                },
+            ) => &ctokens.ptr_mut_bound,
+            (_, SelfParam::ByVal) => &ctokens.ptr_val_bound,
+        };
+
+        // The default implementation of an `async fn` is the body of the `RFuture` it returns.
+        let default_block = |block: &syn::Block| -> TokenStream2 {
+            if method.is_async {
+                quote_spanned!(method_span=> { __sabi_re::RFuture::new(async move #block) })
+            } else {
+                block.to_token_stream()
+            }
         };
 
         let output_safety = |output: &mut TokenStream2, input: TokenStream2| {
@@ -256,7 +293,11 @@ impl ToTokens for MethodTokenizer<'_> {
 
         match (which_item, &method.self_param) {
             (WhichItem::Trait, _) => {
-                method.default.as_ref().map(|x| x.block).to_tokens(ts);
+                method
+                    .default
+                    .as_ref()
+                    .map(|x| default_block(x.block))
+                    .to_tokens(ts);
                 method.semicolon.to_tokens(ts);
             }
             (WhichItem::TraitImpl, _) => {
@@ -267,25 +308,45 @@ impl ToTokens for MethodTokenizer<'_> {
                     ),
                 );
             }
-            (WhichItem::TraitObjectImpl, _) => {
+            (WhichItem::TraitObjectImpl | WhichItem::SupertraitImpl, _) => {
+                // In `WhichItem::SupertraitImpl` the erased object and the vtable
+                // are accessed through the traits implemented by subtrait objects.
+                let (object, vtable) = if which_item == WhichItem::SupertraitImpl {
+                    (
+                        quote_spanned!(method_span=> __sabi_re::SabiTraitObject::),
+                        trait_def.ts_supertrait_vtable.clone(),
+                    )
+                } else {
+                    (
+                        quote_spanned!(method_span=> self.obj.),
+                        quote_spanned!(method_span=> self.sabi_vtable()),
+                    )
+                };
+                let self_arg = if which_item == WhichItem::SupertraitImpl {
+                    Some(quote_spanned!(method_span=> self,))
+                } else {
+                    None
+                };
+
                 let method_call = match &method.self_param {
                     SelfParam::ByRef {
                         is_mutable: false, ..
                     } => {
                         quote_spanned!(method_span=>
-                            __method(self.obj.sabi_as_rref(),#(#param_names_c,)*)
+                            __method(#object sabi_as_rref(#self_arg),#(#param_names_c,)*)
                         )
                     }
                     SelfParam::ByRef {
                         is_mutable: true, ..
                     } => {
                         quote_spanned!(method_span=>
-                            __method(self.obj.sabi_as_rmut(),#(#param_names_c,)*)
+                            __method(#object sabi_as_rmut(#self_arg),#(#param_names_c,)*)
                         )
                     }
                     SelfParam::ByVal => {
                         quote_spanned!(method_span=>
-                            self.obj.sabi_with_value(
+                            #object sabi_with_value(
+                                #self_arg
                                 #[allow(clippy::unnecessary_cast)]
                                 move|_self|__method(
                                     __sabi_re::MovePtr::into_raw(_self) as *mut (),
@@ -296,13 +357,24 @@ impl ToTokens for MethodTokenizer<'_> {
                     }
                 };
 
+                let method_call = if method.catch_unwind {
+                    quote_spanned!(method_span=>
+                        __sabi_re::RResult::unwrap_or_else(
+                            #method_call,
+                            |payload| __sabi_re::RPanicPayload::resume_unwind(payload),
+                        )
+                    )
+                } else {
+                    method_call
+                };
+
                 match default_ {
                     Some(default_) => {
-                        let block = &default_.block;
+                        let block = default_block(default_.block);
                         ts.append_all(quote_spanned!(method_span=>
                                 #ptr_constraint
                             {
-                                match self.sabi_vtable().#method_name() {
+                                match #vtable.#method_name() {
                                     Some(__method)=>{
                                         unsafe{
                                             #method_call
@@ -322,7 +394,7 @@ impl ToTokens for MethodTokenizer<'_> {
                         ts.append_all(quote_spanned!(method_span=>
                                 #ptr_constraint
                             {
-                                let __method=self.sabi_vtable().#method_name();
+                                let __method=#vtable.#method_name();
                                 unsafe{
                                     #method_call
                                 }
@@ -361,22 +433,42 @@ impl ToTokens for MethodTokenizer<'_> {
                     None => quote_spanned!(method_span=> #ret ),
                 };
 
-                ts.append_all(quote_spanned!(method_span=>{
-                    unsafe{
-                        let #ret = ::abi_stable::extern_fn_panic_handling!{no_early_return;
-                            __Trait::#method_name(
-                                &#mut_token *_self.transmute_into_raw::<#self_ty>(),
-                                #(#param_names_c,)*
-                            )
-                        };
+                if method.catch_unwind {
+                    ts.append_all(quote_spanned!(method_span=>{
+                        unsafe{
+                            let res = ::abi_stable::extern_fn_panic_handling!{catch_unwind;
+                                __Trait::#method_name(
+                                    &#mut_token *_self.transmute_into_raw::<#self_ty>(),
+                                    #(#param_names_c,)*
+                                )
+                            };
 
-                        #transmute_ret
-                    }
-                }));
+                            res.map(|#ret| #transmute_ret )
+                        }
+                    }));
+                } else {
+                    ts.append_all(quote_spanned!(method_span=>{
+                        unsafe{
+                            let #ret = ::abi_stable::extern_fn_panic_handling!{no_early_return;
+                                __Trait::#method_name(
+                                    &#mut_token *_self.transmute_into_raw::<#self_ty>(),
+                                    #(#param_names_c,)*
+                                )
+                            };
+
+                            #transmute_ret
+                        }
+                    }));
+                }
             }
             (WhichItem::VtableImpl, SelfParam::ByVal) => {
+                let mode = if method.catch_unwind {
+                    quote_spanned!(method_span=> catch_unwind; )
+                } else {
+                    quote_spanned!(method_span=> no_early_return; )
+                };
                 ts.append_all(quote_spanned!(method_span=>{
-                    ::abi_stable::extern_fn_panic_handling!{no_early_return; unsafe{
+                    ::abi_stable::extern_fn_panic_handling!{#mode unsafe{
                         __Trait::#method_name(
                             (_self as *mut #self_ty).read(),#(#param_names_c,)*
                         )
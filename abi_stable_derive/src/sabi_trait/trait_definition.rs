@@ -68,6 +68,8 @@ pub(crate) struct TraitDefinition<'a> {
     pub(crate) impld_traits: Vec<TraitImplness<'a>>,
     /// The traits this doesn't have as supertraits.
     pub(crate) unimpld_traits: Vec<&'a Ident>,
+    /// The supertraits that were declared with `#[sabi_trait]`.
+    pub(crate) sabi_supertraits: Vec<SabiSupertrait<'a>>,
     /// A struct describing the traits this does and doesn't have as supertraits
     /// (true means implemented,false means unimplemented)
     pub(crate) trait_flags: TraitStruct<bool>,
@@ -95,6 +97,9 @@ pub(crate) struct TraitDefinition<'a> {
     pub(crate) is_static: IsStaticTrait,
     /// A TokenStream with the equivalent of `<Pointer::PtrTarget as Trait>::`
     pub(crate) ts_fq_self: &'a TokenStream2,
+    /// A TokenStream that gets the vtable of this trait from the trait object of a subtrait,
+    /// used in the implementation of this trait for the trait objects of subtraits.
+    pub(crate) ts_supertrait_vtable: &'a TokenStream2,
     pub(crate) ctokens: &'a CommonTokens,
 }
 
@@ -109,6 +114,7 @@ impl<'a> TraitDefinition<'a> {
             which_object,
             disable_trait_impl,
             disable_inherent_default,
+            catch_unwind,
             ..
         }: SabiTraitAttrs<'a>,
         arenas: &'a Arenas,
@@ -124,8 +130,9 @@ impl<'a> TraitDefinition<'a> {
         methods_with_attrs
             .into_iter()
             .zip(disable_inherent_default)
-            .filter_map(|(func, disable_inh_def)| {
-                match TraitMethod::new(func, disable_inh_def, ctokens, arenas) {
+            .zip(catch_unwind)
+            .filter_map(|((func, disable_inh_def), catch_unwind)| {
+                match TraitMethod::new(func, disable_inh_def, catch_unwind, ctokens, arenas) {
                     Ok(x) => x,
                     Err(e) => {
                         errors.push_err(e);
@@ -150,6 +157,7 @@ impl<'a> TraitDefinition<'a> {
         let GetSupertraits {
             impld_traits,
             unimpld_traits,
+            sabi_supertraits,
             mut lifetime_bounds,
             iterator_item,
             deserialize_bound,
@@ -229,7 +237,7 @@ impl<'a> TraitDefinition<'a> {
 
         errors.into_result()?;
 
-        Ok(TraitDefinition {
+        let mut this = TraitDefinition {
             item: trait_,
             name: &trait_.ident,
             which_object,
@@ -247,6 +255,7 @@ impl<'a> TraitDefinition<'a> {
             deserialize_bound,
             impld_traits,
             unimpld_traits,
+            sabi_supertraits,
             trait_flags,
             trait_spans,
             vis,
@@ -257,9 +266,26 @@ impl<'a> TraitDefinition<'a> {
             has_val_methods,
             disable_trait_impl,
             ts_fq_self: arenas.alloc(ts_fq_self),
+            ts_supertrait_vtable: &ctokens.empty_ts,
             is_static,
             ctokens,
-        })
+        };
+
+        this.ts_supertrait_vtable = {
+            let staticlt_erasedptr = arenas.alloc(match is_static {
+                IsStaticTrait::Yes => quote!(_ErasedPtr,),
+                IsStaticTrait::No => quote!('static,_ErasedPtr,),
+            });
+            let trait_to = parse_str_as_ident(&format!("{}_TO", trait_.ident));
+            let generics =
+                this.generics_tokenizer(InWhat::ItemUse, WithAssocTys::No, staticlt_erasedptr);
+            arenas.alloc(quote!(
+                <Self as __sabi_re::GetSupertraitVTable<#trait_to<#generics>>>
+                    ::sabi_supertrait_vtable(&self)
+            ))
+        };
+
+        Ok(this)
     }
 
     /// Returns a clone of `self`,
@@ -272,7 +298,7 @@ impl<'a> TraitDefinition<'a> {
         let mut errors = LinearResult::ok(());
 
         let replace_with = match which_item {
-            WhichItem::Trait | WhichItem::TraitImpl => {
+            WhichItem::Trait | WhichItem::TraitImpl | WhichItem::SupertraitImpl => {
                 return Ok(this);
             }
             WhichItem::TraitObjectImpl => ReplaceWith::Remove,
@@ -367,6 +393,15 @@ impl<'a> TraitDefinition<'a> {
         }
     }
 
+    /// Returns the bounds for the supertraits that implementors of the trait
+    /// are required to implement.
+    pub fn supertrait_bounds(&self) -> impl Iterator<Item = &syn::TraitBound> + Clone {
+        self.impld_traits
+            .iter()
+            .filter(|t| t.is_supertrait_bound)
+            .map(|t| &t.bound)
+    }
+
     /// Returns the where predicates of the inherent implementation of
     /// the ffi-safe trait object.
     pub fn trait_impl_where_preds(&self) -> Result<Punctuated<WherePredicate, Comma>, syn::Error> {
@@ -396,6 +431,8 @@ impl<'a> TraitDefinition<'a> {
 #[derive(Debug, Clone)]
 pub(crate) struct TraitMethod<'a> {
     pub(crate) disable_inherent_default: bool,
+    /// Whether panics are caught in the vtable entry,and resumed by the caller.
+    pub(crate) catch_unwind: bool,
     pub(crate) unsafety: Option<&'a Unsafe>,
     pub(crate) abi: Option<&'a Abi>,
     /// Attributes applied to the method in the vtable.
@@ -409,8 +446,14 @@ pub(crate) struct TraitMethod<'a> {
     pub(crate) lifetimes: Vec<&'a LifetimeDef>,
     pub(crate) params: Vec<MethodParam<'a>>,
     /// The return type of this method,if None this returns `()`.
+    ///
+    /// For `async fn`s this is the `RFuture` that the method returns.
     pub(crate) output: Option<syn::Type>,
 
+    /// Whether this is an `async fn`,
+    /// whose default implementation is wrapped in an `RFuture`.
+    pub(crate) is_async: bool,
+
     /// Whether the return type borrows from self
     pub(crate) return_borrow_kind: Option<BorrowKind>,
 
@@ -443,6 +486,7 @@ impl<'a> TraitMethod<'a> {
     pub fn new(
         mwa: MethodWithAttrs<'a>,
         disable_inherent_default: bool,
+        catch_unwind: bool,
         ctokens: &'a CommonTokens,
         arena: &'a Arenas,
     ) -> Result<Option<Self>, syn::Error> {
@@ -483,26 +527,44 @@ impl<'a> TraitMethod<'a> {
             }
         };
 
+        let is_async = decl.asyncness.is_some();
+
+        if let (Some(asyncness), SelfParam::ByVal) = (&decl.asyncness, &self_param) {
+            errors.push_err(spanned_err!(
+                asyncness,
+                "async methods in #[sabi_trait] traits must take a `&self`/`&mut self` receiver."
+            ));
+        }
+
         let mut lifetimes: Vec<&'a syn::LifetimeDef> = decl.generics.lifetimes().collect();
 
         let mut return_borrow_kind = None::<BorrowKind>;
 
         let output = match &decl.output {
             syn::ReturnType::Default => None,
-            syn::ReturnType::Type(_, ty) => {
-                let mut ty: syn::Type = (**ty).clone();
-                if let SelfParam::ByRef { lifetime, .. } = &mut self_param {
-                    let visit_data = LifetimeUnelider::new(lifetime).visit_type(&mut ty);
+            syn::ReturnType::Type(_, ty) => Some((**ty).clone()),
+        };
 
-                    return_borrow_kind = visit_data.found_borrow_kind;
+        // `async fn`s return an `RFuture` that borrows from `self`.
+        let output = if is_async {
+            let ret = output.unwrap_or_else(|| syn::parse_quote!(()));
+            Some(syn::parse_quote!(__sabi_re::RFuture<'_, #ret>))
+        } else {
+            output
+        };
 
-                    if let Some(lt) = visit_data.additional_lifetime_def {
-                        lifetimes.push(lt);
-                    }
+        let output = output.map(|mut ty: syn::Type| {
+            if let SelfParam::ByRef { lifetime, .. } = &mut self_param {
+                let visit_data = LifetimeUnelider::new(lifetime).visit_type(&mut ty);
+
+                return_borrow_kind = visit_data.found_borrow_kind;
+
+                if let Some(lt) = visit_data.additional_lifetime_def {
+                    lifetimes.push(lt);
                 }
-                Some(ty)
             }
-        };
+            ty
+        });
 
         let default = mwa
             .item
@@ -549,6 +611,7 @@ impl<'a> TraitMethod<'a> {
 
         Ok(Some(Self {
             disable_inherent_default,
+            catch_unwind,
             unsafety: method_signature.unsafety.as_ref(),
             abi: method_signature.abi.as_ref(),
             derive_attrs: arena.alloc(mwa.attrs.derive_attrs),
@@ -558,6 +621,7 @@ impl<'a> TraitMethod<'a> {
             self_param,
             params,
             output,
+            is_async,
             return_borrow_kind,
             where_clause,
             default,
@@ -689,6 +753,7 @@ pub(crate) struct DeserializeBound;
 struct GetSupertraits<'a> {
     impld_traits: Vec<TraitImplness<'a>>,
     unimpld_traits: Vec<&'a Ident>,
+    sabi_supertraits: Vec<SabiSupertrait<'a>>,
     lifetime_bounds: Punctuated<&'a Lifetime, Comma>,
     iterator_item: Option<&'a syn::Type>,
     deserialize_bound: Option<DeserializeBound>,
@@ -703,9 +768,159 @@ pub(crate) struct TraitImplness<'a> {
     pub(crate) ident: Ident,
     pub(crate) bound: syn::TraitBound,
     pub(crate) is_implemented: bool,
+    /// Whether implementors of the trait are required to implement this trait,
+    /// false for the traits that are only implemented by the trait object
+    /// (`Serialize` and `Deserialize`).
+    pub(crate) is_supertrait_bound: bool,
     pub(crate) _marker: PhantomData<&'a ()>,
 }
 
+/// A supertrait that was declared with `#[sabi_trait]`,
+/// whose vtable is stored in the vtable of the subtrait.
+#[derive(Debug, Clone)]
+pub(crate) struct SabiSupertrait<'a> {
+    /// The supertrait bound,as written in the trait definition.
+    pub(crate) bound: &'a syn::TraitBound,
+    /// The name of the vtable field that stores the vtable of the supertrait,
+    /// from the position of the supertrait among the `#[sabi_trait]` supertraits.
+    pub(crate) field: Ident,
+    /// The path to the trait object of the supertrait,without generic arguments.
+    pub(crate) trait_to: syn::Path,
+    /// The lifetime arguments of the supertrait.
+    pub(crate) lifetime_args: Vec<&'a Lifetime>,
+    /// The type and const arguments of the supertrait.
+    pub(crate) other_args: Vec<&'a syn::GenericArgument>,
+}
+
+impl<'a> SabiSupertrait<'a> {
+    fn new(trait_bound: &'a syn::TraitBound, index: usize) -> Result<Self, syn::Error> {
+        use syn::{GenericArgument, PathArguments, TraitBoundModifier};
+
+        if !can_be_sabi_trait(&trait_bound.path) {
+            return Err(unexpected_supertrait_err(&trait_bound.path));
+        }
+
+        if let TraitBoundModifier::Maybe(_) = trait_bound.modifier {
+            return_spanned_err!(trait_bound, "Unexpected `?` in supertrait bound.");
+        }
+        if let Some(lifetimes) = &trait_bound.lifetimes {
+            return_spanned_err!(
+                lifetimes,
+                "Higher ranked lifetimes are not supported in `#[sabi_trait]` supertraits.",
+            );
+        }
+
+        let last_segment = trait_bound.path.segments.last().expect("BUG");
+
+        let mut lifetime_args = Vec::new();
+        let mut other_args = Vec::new();
+
+        match &last_segment.arguments {
+            PathArguments::None => {}
+            PathArguments::AngleBracketed(args) => {
+                for arg in &args.args {
+                    match arg {
+                        GenericArgument::Lifetime(lt) => lifetime_args.push(lt),
+                        GenericArgument::Type(_) | GenericArgument::Const(_) => {
+                            other_args.push(arg)
+                        }
+                        GenericArgument::Binding(_) | GenericArgument::Constraint(_) => {
+                            return_spanned_err!(
+                                arg,
+                                "Associated types are not supported in \
+                                 `#[sabi_trait]` supertraits.",
+                            )
+                        }
+                    }
+                }
+            }
+            PathArguments::Parenthesized(_) => {
+                return Err(unexpected_supertrait_err(last_segment));
+            }
+        }
+
+        let trait_ident = &last_segment.ident;
+
+        let mut trait_to = trait_bound.path.clone();
+        let last_to_segment = trait_to.segments.last_mut().expect("BUG");
+        last_to_segment.ident = Ident::new(&format!("{}_TO", trait_ident), trait_ident.span());
+        last_to_segment.arguments = PathArguments::None;
+
+        Ok(Self {
+            bound: trait_bound,
+            field: Ident::new(&format!("_sabi_super_{}", index), trait_ident.span()),
+            trait_to,
+            lifetime_args,
+            other_args,
+        })
+    }
+
+    /// Outputs the type of the trait object for the supertrait,
+    /// with `after_lifetimes` between the lifetime arguments and the type arguments.
+    pub(crate) fn trait_object<T>(&self, after_lifetimes: T) -> TokenStream2
+    where
+        T: ToTokens,
+    {
+        let trait_to = &self.trait_to;
+        let lifetime_args = &self.lifetime_args;
+        let other_args = &self.other_args;
+        quote!( #trait_to< #(#lifetime_args,)* #after_lifetimes #(#other_args,)* > )
+    }
+}
+
+/// Traits from the standard library that can be used without importing them,
+/// which aren't usable as supertraits.
+const PRELUDE_TRAITS: &[&str] = &[
+    "AsMut",
+    "AsRef",
+    "Copy",
+    "Drop",
+    "ExactSizeIterator",
+    "Extend",
+    "Fn",
+    "FnMut",
+    "FnOnce",
+    "From",
+    "FromIterator",
+    "Into",
+    "IntoIterator",
+    "Sized",
+    "ToOwned",
+    "ToString",
+    "TryFrom",
+    "TryInto",
+];
+
+/// Whether `path` can refer to a trait declared with `#[sabi_trait]`,
+/// which is false for paths into the standard library,
+/// and for the traits in the standard library prelude
+/// (the ones in `TRAIT_LIST` are handled before this is called).
+fn can_be_sabi_trait(path: &syn::Path) -> bool {
+    let first = &path.segments.first().expect("BUG").ident;
+    let is_std_path = first == "std" || first == "core" || first == "alloc";
+    let is_prelude_trait = path.leading_colon.is_none()
+        && path.segments.len() == 1
+        && PRELUDE_TRAITS.iter().any(|name| first == name);
+
+    !is_std_path && !is_prelude_trait
+}
+
+fn unexpected_supertrait_err<T>(tokens: &T) -> syn::Error
+where
+    T: ToTokens,
+{
+    spanned_err!(
+        tokens,
+        "Unexpected supertrait bound.\n\
+         Expected either a trait declared with `#[sabi_trait]`,or one of:\n{}",
+        TRAIT_LIST
+            .iter()
+            .map(|t| t.name)
+            .collect::<Vec<&str>>()
+            .join("/"),
+    )
+}
+
 /// Processes the supertrait bounds of a trait definition.
 fn get_supertraits<'a, I>(
     supertraits: I,
@@ -728,10 +943,15 @@ where
         ident: parse_str_as_ident(t.name),
         bound: parse_str_as_trait_bound(t.full_path).expect("BUG"),
         is_implemented: false,
+        is_supertrait_bound: !matches!(
+            t.which_trait,
+            WhichTrait::Serialize | WhichTrait::Deserialize
+        ),
         _marker: PhantomData,
     });
 
     let mut lifetime_bounds = Punctuated::<&'a Lifetime, Comma>::new();
+    let mut sabi_supertraits = Vec::<SabiSupertrait<'a>>::new();
     let mut iterator_item = None;
     let mut errors = LinearResult::ok(());
     let deserialize_bound = None;
@@ -786,18 +1006,6 @@ where
                                 let iter_item = extract_iterator_item(last_path_component, arenas);
                                 iterator_item = iterator_item.or(iter_item);
                             }
-                            WhichTrait::Deserialize => {
-                                errors.push_err(spanned_err!(
-                                    trait_bound.path,
-                                    "Deserialize is not currently supported."
-                                ));
-                            }
-                            WhichTrait::Serialize => {
-                                errors.push_err(spanned_err!(
-                                    trait_bound.path,
-                                    "Serialize is not currently supported."
-                                ));
-                            }
                             WhichTrait::Eq | WhichTrait::PartialOrd => {
                                 set_impld(&mut trait_struct.partial_eq, span);
                             }
@@ -816,19 +1024,26 @@ where
                             _ => {}
                         }
                     }
-                    None => {
-                        let list = trait_map
-                            .keys()
-                            .map(|x| x.to_string())
-                            .collect::<Vec<String>>();
-
+                    None if which_object == WhichObject::DynTrait => {
                         errors.push_err(spanned_err!(
-                            supertrait_bound,
-                            "Unexpected supertrait bound.\nExpected one of:\n{}",
-                            list.join("/"),
+                            trait_bound.path,
+                            "`#[sabi_trait]` traits can't be used as supertraits \
+                             with #[sabi(use_dyntrait)]",
                         ));
-                        break;
                     }
+                    None => match SabiSupertrait::new(trait_bound, sabi_supertraits.len()) {
+                        Ok(supertrait) => {
+                            if sabi_supertraits.iter().any(|x| x.bound == trait_bound) {
+                                errors.push_err(spanned_err!(
+                                    trait_bound.path,
+                                    "Cannot have the same `#[sabi_trait]` supertrait twice",
+                                ));
+                            } else {
+                                sabi_supertraits.push(supertrait);
+                            }
+                        }
+                        Err(e) => errors.push_err(e),
+                    },
                 }
             }
             TypeParamBound::Lifetime(lt) => {
@@ -885,6 +1100,7 @@ where
     GetSupertraits {
         impld_traits,
         unimpld_traits,
+        sabi_supertraits,
         lifetime_bounds,
         iterator_item,
         deserialize_bound,
@@ -430,6 +430,28 @@ pub(crate) fn derive(mut data: DeriveInput) -> Result<TokenStream2, syn::Error>
 
     let shared_vars_tokenizer = shared_vars.shared_vars_tokenizer(mono_type_layout);
 
+    // The `;old_package:OldName` entries for every place that the type was moved from.
+    let moved_from = config
+        .moved_from
+        .iter()
+        .map(|x| format!(";{}:{}", x.package, x.name))
+        .collect::<String>();
+
+    // The `;VariantName=OldName,OlderName,` entries for every variant that was renamed,
+    // stored outside of the variant names so that their encoding doesn't change.
+    let renamed_variants = if is_enum {
+        let renamed_from = ds.variants.iter().zip(&config.renamed_from_variants);
+        renamed_from
+            .filter(|(_, old_names)| !old_names.is_empty())
+            .map(|(variant, old_names)| {
+                let old_names = old_names.iter().map(|x| format!("{},", x));
+                format!(";{}={}", variant.name, old_names.collect::<String>())
+            })
+            .collect::<String>()
+    } else {
+        String::new()
+    };
+
     // drop(_measure_time0);
 
     let shared_where_preds = quote!(
@@ -501,7 +523,10 @@ pub(crate) fn derive(mut data: DeriveInput) -> Result<TokenStream2, syn::Error>
             #prefixref_impls
 
             const #item_info_const: abi_stable::type_layout::ItemInfo=
-                abi_stable::make_item_info!();
+                abi_stable::make_item_info!(
+                    moved_from = #moved_from,
+                    renamed_variants = #renamed_variants,
+                );
 
             const #strings_const: ::abi_stable::std_types::RStr<'static>=#strings;
 
@@ -67,11 +67,14 @@ mod kw {
     syn::custom_keyword! {last_prefix_field}
     syn::custom_keyword! {missing_field}
     syn::custom_keyword! {module_reflection}
+    syn::custom_keyword! {moved_from}
     syn::custom_keyword! {Module}
+    syn::custom_keyword! {name}
     syn::custom_keyword! {not_stableabi}
     syn::custom_keyword! {Opaque}
     syn::custom_keyword! {option}
     syn::custom_keyword! {Ord}
+    syn::custom_keyword! {package}
     syn::custom_keyword! {packed}
     syn::custom_keyword! {panic}
     syn::custom_keyword! {PartialEq}
@@ -88,6 +91,7 @@ mod kw {
     syn::custom_keyword! {pub_getter}
     syn::custom_keyword! {refl}
     syn::custom_keyword! {rename}
+    syn::custom_keyword! {renamed_from}
     syn::custom_keyword! {size}
     syn::custom_keyword! {Send}
     syn::custom_keyword! {Serialize}
@@ -131,6 +135,14 @@ pub(crate) struct StableAbiOptions<'a> {
     pub(crate) renamed_fields: FieldMap<Option<&'a Ident>>,
     pub(crate) changed_types: FieldMap<Option<&'a Type>>,
 
+    /// The names that each field was renamed from.
+    pub(crate) renamed_from_fields: FieldMap<Vec<&'a Ident>>,
+    /// The names that each variant was renamed from.
+    pub(crate) renamed_from_variants: Vec<Vec<&'a Ident>>,
+
+    /// The packages and names that the type was moved from.
+    pub(crate) moved_from: Vec<MovedFrom>,
+
     pub(crate) doc_hidden_attr: Option<&'a TokenStream2>,
 
     pub(crate) mod_refl_mode: ModReflMode<usize>,
@@ -149,6 +161,15 @@ pub(crate) struct StableAbiOptions<'a> {
 
 //////////////////////
 
+/// A package and type name that the type was moved from,
+/// declared with the `#[sabi(moved_from(package = "...", name = "..."))]` attribute.
+pub(crate) struct MovedFrom {
+    pub(crate) package: String,
+    pub(crate) name: String,
+}
+
+//////////////////////
+
 /// Identifiers of generated top-level constants.
 pub struct ConstIdents {
     /// The identifier of a constant where the string in MonoSharedVars will be stored.
@@ -350,6 +371,15 @@ impl<'a> StableAbiOptions<'a> {
             strings: parse_str_as_ident(&format!("_SHARED_VARS_STRINGS_{}", ds.name)),
         };
 
+        let moved_from = this
+            .moved_from
+            .into_iter()
+            .map(|x| MovedFrom {
+                package: x.package,
+                name: x.name.unwrap_or_else(|| ds.name.to_string()),
+            })
+            .collect();
+
         errors.into_result()?;
 
         Ok(StableAbiOptions {
@@ -361,6 +391,9 @@ impl<'a> StableAbiOptions<'a> {
             layout_ctor: this.layout_ctor,
             renamed_fields: this.renamed_fields,
             changed_types: this.changed_types,
+            renamed_from_fields: this.renamed_from_fields,
+            renamed_from_variants: this.renamed_from_variants,
+            moved_from,
             override_field_accessor: this.override_field_accessor,
             tags: this.tags,
             extra_checks: this.extra_checks,
@@ -408,6 +441,11 @@ struct StableAbiAttrs<'a> {
     renamed_fields: FieldMap<Option<&'a Ident>>,
     changed_types: FieldMap<Option<&'a Type>>,
 
+    renamed_from_fields: FieldMap<Vec<&'a Ident>>,
+    renamed_from_variants: Vec<Vec<&'a Ident>>,
+
+    moved_from: Vec<UncheckedMovedFrom>,
+
     accessor_bounds: FieldMap<Vec<TypeParamBound>>,
 
     extra_phantom_fields: Vec<(&'a Ident, &'a Type)>,
@@ -425,6 +463,12 @@ struct StableAbiAttrs<'a> {
     errors: LinearResult<()>,
 }
 
+/// A `MovedFrom` where the name defaults to the name of the type.
+struct UncheckedMovedFrom {
+    package: String,
+    name: Option<String>,
+}
+
 #[derive(Clone)]
 enum UncheckedStabilityKind<'a> {
     Value { impl_prefix_stable_abi: bool },
@@ -476,6 +520,8 @@ where
     this.override_field_accessor = FieldMap::defaulted(ds);
     this.accessor_bounds = FieldMap::defaulted(ds);
     this.changed_types = FieldMap::defaulted(ds);
+    this.renamed_from_fields = FieldMap::defaulted(ds);
+    this.renamed_from_variants = vec![Vec::new(); ds.variants.len()];
     this.ne_variants.resize(
         ds.variants.len(),
         UncheckedNEVariant {
@@ -635,6 +681,9 @@ fn parse_sabi_attr<'a>(
                 this.extra_phantom_fields.push((fname, ty));
                 Ok(())
             })?;
+        } else if input.check_parse(kw::moved_from)? {
+            let moved_from = input.parse_paren_with(parse_moved_from)?;
+            this.moved_from.push(moved_from);
         } else if input.check_parse(kw::phantom_type_param)? {
             input.parse::<Token!(=)>()?;
             let ty = arenas.alloc(input.parse_type()?);
@@ -825,6 +874,9 @@ fn parse_sabi_attr<'a>(
                 Some(UncheckedVariantConstructor::Regular);
         } else if input.check_parse(kw::with_boxed_constructor)? {
             this.ne_variants[variant_index].constructor = Some(UncheckedVariantConstructor::Boxed);
+        } else if input.check_parse(kw::renamed_from)? {
+            let old_name = parse_renamed_from(input, arenas)?;
+            this.renamed_from_variants[variant_index].push(old_name);
         } else {
             return Err(make_err(input));
         }
@@ -840,6 +892,9 @@ fn parse_sabi_attr<'a>(
             input.parse::<Token!(=)>()?;
             let renamed = input.parse::<Ident>()?.piped(|x| arenas.alloc(x));
             this.renamed_fields.insert(field, Some(renamed));
+        } else if input.check_parse(kw::renamed_from)? {
+            let old_name = parse_renamed_from(input, arenas)?;
+            this.renamed_from_fields[field].push(old_name);
         } else if input.check_parse(kw::unsafe_change_type)? {
             input.parse::<Token!(=)>()?;
             let changed_type = input.parse_type()?.piped(|x| arenas.alloc(x));
@@ -888,6 +943,46 @@ fn parse_sabi_attr<'a>(
     Ok(())
 }
 
+/// Parses the `= "old_name"` part of the `#[sabi(renamed_from = "old_name")]` attribute.
+fn parse_renamed_from<'a>(
+    input: &ParseBuffer,
+    arenas: &'a Arenas,
+) -> Result<&'a Ident, syn::Error> {
+    input.parse::<Token!(=)>()?;
+    let old_name = input.parse::<syn::LitStr>()?.parse::<Ident>()?;
+    Ok(arenas.alloc(old_name))
+}
+
+/// Parses the contents of the `#[sabi(moved_from( .. ))]` attribute.
+fn parse_moved_from(input: &ParseBuffer) -> Result<UncheckedMovedFrom, syn::Error> {
+    let mut package = None::<String>;
+    let mut name = None::<String>;
+
+    input.for_each_separated(Token!(,), |input| {
+        if input.check_parse(kw::package)? {
+            input.parse::<Token!(=)>()?;
+            let lit = input.parse::<syn::LitStr>()?;
+            let value = lit.value();
+            let is_valid = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+            if value.is_empty() || !value.chars().all(is_valid) {
+                return_syn_err!(lit.span(), "`{}` is not a valid package name", value);
+            }
+            package = Some(value);
+        } else if input.check_parse(kw::name)? {
+            input.parse::<Token!(=)>()?;
+            name = Some(input.parse::<syn::LitStr>()?.parse::<Ident>()?.to_string());
+        } else {
+            return Err(input.error("expected either `package = \"...\"` or `name = \"...\"`"));
+        }
+        Ok(())
+    })?;
+
+    match package {
+        Some(package) => Ok(UncheckedMovedFrom { package, name }),
+        None => Err(input.error("expected a `package = \"...\"` argument")),
+    }
+}
+
 /// Parses the `#[sabi(refl = ...)` attribute.
 fn parse_refl_field<'a>(
     this: &mut StableAbiAttrs<'a>,
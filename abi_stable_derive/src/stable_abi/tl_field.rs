@@ -19,6 +19,7 @@ abi_stable_shared::declare_comp_tl_field! {
 impl CompTLField {
     pub(crate) fn from_expanded<'a, I>(
         name: &syn::Ident,
+        renamed_from: &[&'a syn::Ident],
         lifetime_indices: I,
         field_accessor: FieldAccessor<'a>,
         layout: TypeLayoutIndex,
@@ -31,13 +32,21 @@ impl CompTLField {
         let (name_range, comp_field_accessor) =
             Self::push_name_field_accessor(name, field_accessor, shared_vars);
 
-        Self::new(
+        let this = Self::new(
             name_range,
             shared_vars.extend_with_lifetime_indices(lifetime_indices),
             comp_field_accessor,
             layout,
             is_function,
-        )
+        );
+
+        if renamed_from.is_empty() {
+            this
+        } else {
+            shared_vars.extend_with_idents(",", renamed_from.iter().cloned());
+            shared_vars.push_str(";", None);
+            this.with_renamed_from()
+        }
     }
 
     pub(crate) fn from_expanded_std_field<I>(
@@ -51,6 +60,7 @@ impl CompTLField {
     {
         Self::from_expanded(
             name,
+            &[],
             lifetime_indices,
             FieldAccessor::Direct,
             layout,
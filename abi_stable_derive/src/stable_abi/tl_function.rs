@@ -64,6 +64,7 @@ impl<'a> VisitedFieldMap<'a> {
 
                 let comp_field = CompTLField::from_expanded(
                     name,
+                    &config.renamed_from_fields[field],
                     visit_info.referenced_lifetimes.iter().cloned(),
                     field_accessor,
                     shared_vars.push_type(layout_ctor, mutated_ty),
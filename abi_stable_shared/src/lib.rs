@@ -81,3 +81,28 @@ where
 pub fn mangled_root_module_loader_name() -> String {
     mangle_ident("lib_header", "root module loader")
 }
+
+/// Gets the name of the static that contains the BuildMetadata of an abi_stable library.
+///
+/// This does not have a trailing `'\0'`,
+/// you need to append it to pass the name to C APIs.
+pub fn mangled_build_metadata_name() -> String {
+    mangle_ident("lib_header", "build metadata")
+}
+
+/// Gets the name of the static that contains the host module slots of an abi_stable library.
+///
+/// This does not have a trailing `'\0'`,
+/// you need to append it to pass the name to C APIs.
+pub fn mangled_host_module_slots_name() -> String {
+    mangle_ident("lib_header", "host module slots")
+}
+
+/// Gets the name of the static that contains the root module dependencies
+/// of an abi_stable library.
+///
+/// This does not have a trailing `'\0'`,
+/// you need to append it to pass the name to C APIs.
+pub fn mangled_module_dependencies_name() -> String {
+    mangle_ident("lib_header", "module dependencies")
+}
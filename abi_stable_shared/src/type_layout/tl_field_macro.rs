@@ -29,8 +29,11 @@ macro_rules! declare_comp_tl_field {(
         const IS_FUNCTION_OFFSET:u32=Self::TYPE_LAYOUT_OFFSET+TypeLayoutIndex::BIT_SIZE;
         const IS_FUNCTION_BIT_SIZE:u32=1;
 
+        const HAS_RENAMED_FROM_OFFSET:u32=Self::IS_FUNCTION_OFFSET+Self::IS_FUNCTION_BIT_SIZE;
+        const HAS_RENAMED_FROM_BIT_SIZE:u32=1;
+
         /// The amount of bits necessary to represent a CompTLField.
-        pub const BIT_SIZE:u32=Self::IS_FUNCTION_OFFSET+Self::IS_FUNCTION_BIT_SIZE;
+        pub const BIT_SIZE:u32=Self::HAS_RENAMED_FROM_OFFSET+Self::HAS_RENAMED_FROM_BIT_SIZE;
 
         /// Constructs a CompTLField.
         #[inline]
@@ -83,6 +86,21 @@ macro_rules! declare_comp_tl_field {(
             (self.bits0 & (1<<Self::IS_FUNCTION_OFFSET))!=0
         }
 
+        /// Returns this CompTLField,with a flag stating that
+        /// the `;` terminated payload of the field is followed by
+        /// a `,` separated list of the names the field was renamed from.
+        #[inline]
+        pub const fn with_renamed_from(self)->Self{
+            CompTLField{bits0: self.bits0 | (1<<Self::HAS_RENAMED_FROM_OFFSET)}
+        }
+
+        /// Whether the field was renamed from other names,
+        /// stored in `SharedVars`'s string slice after the name of the field.
+        #[inline]
+        pub const fn has_renamed_from(&self)->bool{
+            (self.bits0 & (1<<Self::HAS_RENAMED_FROM_OFFSET))!=0
+        }
+
         #[inline]
         pub(crate) const fn std_field(
             name:StartLen,
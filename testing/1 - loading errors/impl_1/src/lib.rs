@@ -1,8 +1,10 @@
 //! This crate is where extra tests which don't belong in examples go.
 
-use testing_interface_1::{get_env_vars, ReturnWhat, TestingMod, TestingMod_Ref};
+use testing_interface_1::{get_env_vars, ReturnWhat, TestingHost_Ref, TestingMod, TestingMod_Ref};
 
-use abi_stable::{export_root_module, prefix_type::PrefixTypeTrait, std_types::RBoxError};
+use abi_stable::{
+    export_root_module, library::HostModule, prefix_type::PrefixTypeTrait, std_types::RBoxError,
+};
 
 ///////////////////////////////////////////////////////////////////////////////////
 
@@ -11,12 +13,21 @@ use abi_stable::{export_root_module, prefix_type::PrefixTypeTrait, std_types::RB
 /// LibHeader is used to check that the layout of `TextOpsMod` in this dynamic library
 /// is compatible with the layout of it in the binary that loads this library.
 #[export_root_module]
+#[build_metadata(custom("crate" = env!("CARGO_PKG_NAME")))]
+#[host_modules(TestingHost_Ref)]
 pub fn get_library() -> Result<TestingMod_Ref, RBoxError> {
     let envars = get_env_vars();
 
     match envars.return_what {
         ReturnWhat::Ok => {
-            let ret = TestingMod { a: 5, b: 8, c: 13 }.leak_into_prefix();
+            let d = TestingHost_Ref::get_host_module().map_or(0, |host| host.value());
+            let ret = TestingMod {
+                a: 5,
+                b: 8,
+                c: 13,
+                d,
+            }
+            .leak_into_prefix();
 
             Ok(ret)
         }
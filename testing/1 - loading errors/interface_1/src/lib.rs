@@ -4,17 +4,64 @@
 //! These crate test a few of the errors that are returned when loading dynamic libraries
 
 use abi_stable::{
-    library::RootModule, package_version_strings, sabi_types::VersionStrings, StableAbi,
+    library::{HostModule, RootModule},
+    package_version_strings,
+    sabi_types::VersionStrings,
+    std_types::RBoxError,
+    StableAbi,
 };
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 impl RootModule for TestingMod_Ref {
     abi_stable::declare_root_module_statics! {TestingMod_Ref}
 
     const BASE_NAME: &'static str = "testing_1_loading_errors";
     const NAME: &'static str = "testing_1_loading_errors";
     const VERSION_STRINGS: VersionStrings = package_version_strings!();
+
+    fn on_load(self) -> Result<(), RBoxError> {
+        HOOK_CALLS.on_load.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn on_shutdown(self) -> Result<(), RBoxError> {
+        HOOK_CALLS.on_shutdown.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn on_unload(self) -> Result<(), RBoxError> {
+        HOOK_CALLS.on_unload.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+/// How many times the lifecycle hooks of `TestingMod_Ref` were called by the
+/// crate that loads the library.
+pub struct HookCalls {
+    pub on_load: AtomicUsize,
+    pub on_shutdown: AtomicUsize,
+    pub on_unload: AtomicUsize,
+}
+
+impl HookCalls {
+    /// Returns the amount of times that `on_load`, `on_shutdown`, and `on_unload`
+    /// were called, in that order.
+    pub fn get(&self) -> [usize; 3] {
+        [
+            self.on_load.load(Ordering::SeqCst),
+            self.on_shutdown.load(Ordering::SeqCst),
+            self.on_unload.load(Ordering::SeqCst),
+        ]
+    }
 }
 
+pub static HOOK_CALLS: HookCalls = HookCalls {
+    on_load: AtomicUsize::new(0),
+    on_shutdown: AtomicUsize::new(0),
+    on_unload: AtomicUsize::new(0),
+};
+
 #[repr(C)]
 #[derive(StableAbi)]
 #[sabi(kind(Prefix(prefix_ref = TestingMod_Ref)))]
@@ -24,6 +71,26 @@ pub struct TestingMod {
     pub a: u32,
     pub b: u32,
     pub c: u32,
+    /// The `value` of the `TestingHost` passed to the library,
+    /// 0 if it wasn't passed.
+    pub d: u32,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A host module that the library uses.
+#[repr(C)]
+#[derive(StableAbi)]
+#[sabi(kind(Prefix(prefix_ref = TestingHost_Ref)))]
+pub struct TestingHost {
+    #[sabi(last_prefix_field)]
+    pub value: u32,
+}
+
+impl HostModule for TestingHost_Ref {
+    abi_stable::declare_host_module_statics! {TestingHost_Ref}
+
+    const NAME: &'static str = "testing_1_host";
 }
 
 ////////////////////////////////////////////////////////////////////////////////
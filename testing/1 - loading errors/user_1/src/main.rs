@@ -1,14 +1,33 @@
-use abi_stable::library::{
-    development_utils::compute_library_path, LibraryError, RootModule, RootModuleError,
+use abi_stable::{
+    library::{
+        build_metadata_from_path, dependencies_from_path, development_utils::compute_library_path,
+        lib_header_from_raw_library, provide_host_module, remove_layout_cache,
+        remove_load_observer, run_preflight_child, scan_directory, set_layout_cache,
+        set_load_observer, shutdown_all, CheckOutcome, ExtendedLibraryError, LayoutCache,
+        LibraryError, LibraryStatus, LoadCheck, LoadEvent, LoadObserver, LoadOptions, LoadStage,
+        ModuleRegistry, ModuleSet, PreflightCheck, RawLibrary, ReloadableModule, RootModule,
+        RootModuleError,
+    },
+    prefix_type::PrefixTypeTrait,
+    std_types::ROption,
 };
 
 use testing_interface_1::{
-    get_env_vars, NonAbiStableLib_Ref, ReturnWhat, TestingMod_Ref, WithIncompatibleLayout_Ref,
+    get_env_vars, NonAbiStableLib_Ref, ReturnWhat, TestingHost, TestingMod_Ref,
+    WithIncompatibleLayout_Ref, HOOK_CALLS,
 };
 
-use std::fmt;
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 fn main() {
+    run_preflight_child::<TestingMod_Ref>();
+    run_preflight_child::<NonAbiStableLib_Ref>();
+    run_preflight_child::<WithIncompatibleLayout_Ref>();
+
     let target: &std::path::Path = "../../../target/".as_ref();
 
     let envars = get_env_vars();
@@ -34,6 +53,172 @@ fn main() {
         );
     }
 
+    {
+        let library_path = compute_library_path::<TestingMod_Ref>(target).unwrap();
+        let catalog = scan_directory::<TestingMod_Ref>(&library_path).unwrap();
+
+        let find_status = |path: &std::path::Path| {
+            &catalog
+                .entries()
+                .iter()
+                .find(|entry| entry.path == path)
+                .unwrap_or_else(|| panic!("{} not found in {:#?}", path.display(), catalog))
+                .status
+        };
+
+        let testing_path = TestingMod_Ref::get_library_path(&library_path);
+        let status = find_status(&testing_path);
+        assert!(
+            matches!(status, LibraryStatus::Compatible(_)),
+            "{:?}",
+            status
+        );
+
+        let non_abi_stable_path = NonAbiStableLib_Ref::get_library_path(&library_path);
+        let status = find_status(&non_abi_stable_path);
+        assert!(
+            matches!(status, LibraryStatus::NotAbiStable(_)),
+            "{:?}",
+            status
+        );
+        assert!(
+            matches!(status.error(), Some(LibraryError::GetSymbolError { .. })),
+            "{:?}",
+            status,
+        );
+    }
+
+    {
+        let library_path = compute_library_path::<TestingMod_Ref>(target).unwrap();
+
+        let report =
+            TestingMod_Ref::validate_library(&TestingMod_Ref::get_library_path(&library_path));
+        assert!(report.is_loadable(), "{}", report);
+        assert!(TestingMod_Ref::get_module().is_none());
+
+        let report = NonAbiStableLib_Ref::validate_library(&NonAbiStableLib_Ref::get_library_path(
+            &library_path,
+        ));
+        assert!(!report.is_loadable());
+        assert!(report.outcome(LoadCheck::OpenLibrary).is_passed());
+        assert!(
+            matches!(
+                report.outcome(LoadCheck::AbiHeader).error(),
+                Some(LibraryError::GetSymbolError { .. })
+            ),
+            "{}",
+            report,
+        );
+        assert!(matches!(
+            report.outcome(LoadCheck::Layout),
+            CheckOutcome::Skipped
+        ));
+
+        let report = WithIncompatibleLayout_Ref::validate_library(
+            &WithIncompatibleLayout_Ref::get_library_path(&library_path),
+        );
+        let failures = report.failures().map(|(c, _)| c).collect::<Vec<_>>();
+        assert_eq!(failures, [LoadCheck::Layout], "{}", report);
+        print_error_sum(line!(), report.into_result().unwrap_err());
+    }
+
+    {
+        let library_path = compute_library_path::<TestingMod_Ref>(target).unwrap();
+
+        let report =
+            TestingMod_Ref::preflight_check(&TestingMod_Ref::get_library_path(&library_path))
+                .unwrap();
+        assert!(report.is_loadable(), "{}", report);
+        assert_eq!(
+            report.library_version.as_deref(),
+            Some(TestingMod_Ref::VERSION_STRINGS.version.as_str()),
+        );
+
+        let report = PreflightCheck::new()
+            .timeout(Duration::from_secs(60))
+            .run::<NonAbiStableLib_Ref>(&NonAbiStableLib_Ref::get_library_path(&library_path))
+            .unwrap();
+        assert!(report.outcome(LoadCheck::OpenLibrary).is_passed());
+        assert!(
+            matches!(
+                report.outcome(LoadCheck::AbiHeader).error(),
+                Some(LibraryError::Extended(
+                    ExtendedLibraryError::PreflightCheckFailed { .. }
+                ))
+            ),
+            "{}",
+            report,
+        );
+
+        let report = PreflightCheck::new()
+            .run::<WithIncompatibleLayout_Ref>(&WithIncompatibleLayout_Ref::get_library_path(
+                &library_path,
+            ))
+            .unwrap();
+        let failures = report.failures().map(|(c, _)| c).collect::<Vec<_>>();
+        assert_eq!(failures, [LoadCheck::Layout], "{}", report);
+
+        let err = PreflightCheck::new()
+            .program("this program does not exist")
+            .run::<TestingMod_Ref>(&TestingMod_Ref::get_library_path(&library_path))
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                LibraryError::Extended(ExtendedLibraryError::PreflightError { .. })
+            ),
+            "{:?}",
+            err
+        );
+    }
+
+    {
+        let library_path = compute_library_path::<TestingMod_Ref>(target).unwrap();
+
+        let metadata = build_metadata_from_path(&TestingMod_Ref::get_library_path(&library_path))
+            .unwrap()
+            .expect("the library was built with the current abi_stable");
+        assert_eq!(metadata.get_custom("crate"), Some("testing_impl_1"));
+        assert_eq!(metadata.git_hash(), ROption::RNone);
+        assert!(!metadata.rustc_version().is_empty(), "{}", metadata);
+        assert_eq!(metadata.package_name().as_str(), "testing_impl_1");
+
+        let err = build_metadata_from_path(&NonAbiStableLib_Ref::get_library_path(&library_path))
+            .unwrap_err();
+        assert!(
+            matches!(err, LibraryError::GetSymbolError { .. }),
+            "{:?}",
+            err
+        );
+    }
+
+    {
+        let library_path = compute_library_path::<TestingMod_Ref>(target).unwrap();
+
+        // The library uses the `TestingHost` host module, which wasn't provided yet.
+        let err = ModuleRegistry::<TestingMod_Ref>::new()
+            .load_from_directory(&library_path)
+            .err()
+            .unwrap();
+        match &err {
+            LibraryError::Extended(ExtendedLibraryError::MissingHostModule {
+                module_name, ..
+            }) => {
+                assert_eq!(module_name, "testing_1_host");
+            }
+            _ => panic!(
+                "Expected an ExtendedLibraryError::MissingHostModule, found:\n{:#?}",
+                err
+            ),
+        }
+        print_error_sum(line!(), err);
+
+        provide_host_module(TestingHost { value: 21 }.leak_into_prefix());
+    }
+
+    let load_events = Arc::new(Mutex::new(Vec::new()));
+    set_load_observer(RecordingObserver(load_events.clone()));
+
     {
         let library_path = compute_library_path::<WithIncompatibleLayout_Ref>(target).unwrap();
 
@@ -42,6 +227,15 @@ fn main() {
             .unwrap();
 
         assert!(matches!(err, LibraryError::AbiInstability(_)), "{:#}", err,);
+        assert_eq!(
+            *load_events.lock().unwrap(),
+            [
+                (LoadStage::OpenLibrary, true),
+                (LoadStage::AbiHeader, true),
+                (LoadStage::Layout, false),
+            ],
+        );
+        load_events.lock().unwrap().clear();
 
         // Doing this to make sure that the error formatting is not optimized out.
         let formatted = format!("{0} {0:?}", err);
@@ -55,12 +249,42 @@ fn main() {
         let library_path = compute_library_path::<TestingMod_Ref>(target).unwrap();
         let res = TestingMod_Ref::load_from_directory(&library_path);
 
+        remove_load_observer().unwrap();
+        assert_eq!(
+            *load_events.lock().unwrap(),
+            [
+                (LoadStage::OpenLibrary, true),
+                (LoadStage::AbiHeader, true),
+                (LoadStage::Layout, true),
+                (LoadStage::Version, true),
+                (LoadStage::HostModules, true),
+                (LoadStage::RootModule, res.is_ok()),
+            ],
+        );
+
         match envars.return_what {
             ReturnWhat::Ok => {
                 let module = res.unwrap();
                 assert_eq!(module.a(), 5);
                 assert_eq!(module.b(), 8);
                 assert_eq!(module.c(), 13);
+                assert_eq!(module.d(), 21);
+                assert_eq!(HOOK_CALLS.get(), [1, 0, 0]);
+
+                test_module_registry(&library_path, module);
+                test_reloadable_module(&library_path);
+                test_layout_cache(target, &library_path);
+                test_load_from_bytes(&library_path);
+
+                let with_deps =
+                    TestingMod_Ref::load_with_dependencies(&library_path, &ModuleSet::new())
+                        .unwrap();
+                assert_eq!(with_deps.0.to_raw_ptr(), module.0.to_raw_ptr());
+
+                let lib_path = TestingMod_Ref::get_library_path(&library_path);
+                assert_eq!(dependencies_from_path(&lib_path).unwrap(), &[]);
+
+                test_shutdown_all();
             }
             ReturnWhat::Error | ReturnWhat::Panic => {
                 let err = res.err().expect("Expected the library to return an error");
@@ -96,6 +320,158 @@ fn main() {
     }
 }
 
+/// Records the stage and success of every `LoadEvent`.
+struct RecordingObserver(Arc<Mutex<Vec<(LoadStage, bool)>>>);
+
+impl LoadObserver for RecordingObserver {
+    fn stage_completed(&self, event: &LoadEvent<'_>) {
+        assert!(event.stage_duration <= event.total_duration);
+        self.0.lock().unwrap().push((event.stage, true));
+    }
+
+    fn load_failed(&self, event: &LoadEvent<'_>, _: &LibraryError) {
+        self.0.lock().unwrap().push((event.stage, false));
+    }
+}
+
+fn test_module_registry(library_path: &std::path::Path, module: TestingMod_Ref) {
+    let registry = ModuleRegistry::<TestingMod_Ref>::new();
+    assert!(registry.is_empty());
+
+    let path = TestingMod_Ref::get_library_path(library_path);
+    let first = registry.load_from_file(&path).unwrap();
+    let second = registry.load_from_directory(library_path).unwrap();
+    assert_eq!(registry.len(), 1);
+    assert_eq!(first.0.to_raw_ptr(), second.0.to_raw_ptr());
+
+    // The library was already loaded with `RootModule::load_from_directory`,
+    // so both of these are the same module.
+    assert_eq!(first.0.to_raw_ptr(), module.0.to_raw_ptr());
+    assert_eq!(registry.get(&path).unwrap().c(), 13);
+
+    let err = registry
+        .load_from_directory("foo/bar/bar".as_ref())
+        .err()
+        .unwrap();
+    assert!(matches!(err, LibraryError::OpenError { .. }), "{:?}", err);
+    assert_eq!(registry.len(), 1);
+}
+
+fn test_load_from_bytes(library_path: &std::path::Path) {
+    if !cfg!(target_os = "linux") {
+        return;
+    }
+
+    let bytes = std::fs::read(TestingMod_Ref::get_library_path(library_path)).unwrap();
+    let raw_library = RawLibrary::load_from_bytes("testing_impl_1", &bytes).unwrap();
+    assert_eq!(
+        raw_library.path(),
+        RawLibrary::in_memory_path("testing_impl_1")
+    );
+
+    let module = unsafe { lib_header_from_raw_library(&raw_library) }
+        .unwrap()
+        .init_root_module::<TestingMod_Ref>()
+        .unwrap();
+    assert_eq!(module.c(), 13);
+    // Host modules are only passed by the `RootModule::load*` functions.
+    assert_eq!(module.d(), 0);
+
+    // The module borrows from the library.
+    std::mem::forget(raw_library);
+
+    let err = RawLibrary::load_from_bytes("not_a_library", b"hello")
+        .err()
+        .unwrap();
+    match &err {
+        LibraryError::OpenError { path, .. } => {
+            assert_eq!(*path, RawLibrary::in_memory_path("not_a_library"));
+        }
+        _ => panic!("Expected a LibraryError::OpenError, found:\n{:#?}", err),
+    }
+    print_error_sum(line!(), err);
+}
+
+/// Tests that `shutdown_all` calls the `on_shutdown` hook of the root modules
+/// loaded by `RootModule::load_from_directory` and `test_module_registry`.
+fn test_shutdown_all() {
+    let [on_load, on_shutdown, on_unload] = HOOK_CALLS.get();
+
+    shutdown_all().unwrap();
+    assert_eq!(HOOK_CALLS.get(), [on_load, on_shutdown + 2, on_unload]);
+
+    // The hooks are only called once for every loaded root module.
+    shutdown_all().unwrap();
+    assert_eq!(HOOK_CALLS.get(), [on_load, on_shutdown + 2, on_unload]);
+}
+
+fn test_reloadable_module(library_path: &std::path::Path) {
+    let [on_load, on_shutdown, on_unload] = HOOK_CALLS.get();
+
+    let reloadable = ReloadableModule::<TestingMod_Ref>::from_directory(library_path)
+        .with_load_options(LoadOptions::new().now().deep_bind(true));
+    assert!(!reloadable.is_loaded());
+
+    let lease = reloadable.load().unwrap();
+    assert_eq!(lease.a(), 5);
+    assert_eq!(HOOK_CALLS.get(), [on_load + 1, on_shutdown, on_unload]);
+    let other_lease = lease.clone();
+    drop(lease);
+    assert_eq!(reloadable.lease_count(), 1);
+
+    let err = unsafe { reloadable.unload() }.err().unwrap();
+    match &err {
+        LibraryError::Extended(ExtendedLibraryError::UnloadBlocked { leases, .. }) => {
+            assert_eq!(*leases, 1);
+        }
+        _ => panic!(
+            "Expected an ExtendedLibraryError::UnloadBlocked, found:\n{:#?}",
+            err
+        ),
+    }
+    print_error_sum(line!(), err);
+
+    assert_eq!(other_lease.b(), 8);
+    drop(other_lease);
+
+    let lease = unsafe { reloadable.reload() }.unwrap();
+    assert_eq!(lease.c(), 13);
+    assert_eq!(lease.d(), 21);
+    assert_eq!(
+        HOOK_CALLS.get(),
+        [on_load + 2, on_shutdown + 1, on_unload + 1]
+    );
+    drop(lease);
+
+    unsafe { reloadable.unload() }.unwrap();
+    assert!(!reloadable.is_loaded());
+    assert_eq!(
+        HOOK_CALLS.get(),
+        [on_load + 2, on_shutdown + 2, on_unload + 2]
+    );
+}
+
+/// Tests that libraries which passed layout checking are stored in the `LayoutCache`,
+/// reopening the cache every time to simulate running the program again.
+fn test_layout_cache(target: &std::path::Path, library_path: &std::path::Path) {
+    let cache_path = target.join("testing_user_1-layout_cache.txt");
+    let _ = std::fs::remove_file(&cache_path);
+
+    let reloadable = ReloadableModule::<TestingMod_Ref>::from_directory(library_path);
+    for _ in 0..2 {
+        set_layout_cache(LayoutCache::open(&cache_path).unwrap());
+
+        assert_eq!(reloadable.load().unwrap().c(), 13);
+        unsafe { reloadable.unload() }.unwrap();
+
+        let cache = remove_layout_cache().unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(LayoutCache::open(&cache_path).unwrap().len(), 1);
+    }
+
+    std::fs::remove_file(&cache_path).unwrap();
+}
+
 fn print_error_sum<E: fmt::Debug + fmt::Display>(line: u32, e: E) {
     let formatted = format!("{0} {0:?}", e);
     let sum = formatted.bytes().map(|x| x as u64).sum::<u64>();
@@ -1,9 +1,14 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use abi_stable::{
-    library::lib_header_from_path,
+    abi_stability::semver::classify_changes,
+    library::{build_metadata_from_path, lib_header_from_path, LibHeader},
     //abi_stability::check_layout_compatibility,
     reflection::export_module::MRItem,
+    type_layout::TypeLayout,
 };
 
 use core_extensions::SelfOps;
@@ -36,6 +41,38 @@ enum Command {
         #[structopt(long = "--compact")]
         compact_json: bool,
     },
+    /// Prints the build metadata of an abi_stable library,
+    /// with its package name and version, and the rustc version and target it was compiled with.
+    #[structopt(name = "metadata")]
+    #[structopt(author = "_")]
+    Metadata {
+        /// The path to the library.
+        library_path: PathBuf,
+    },
+    /// Prints the fingerprint of the layout of the root module of an abi_stable library,
+    /// which is the same for libraries whose root modules have identical layouts.
+    #[structopt(name = "fingerprint")]
+    #[structopt(author = "_")]
+    Fingerprint {
+        /// The path to the library.
+        library_path: PathBuf,
+    },
+    /// Classifies the differences between the root modules of two versions of
+    /// an abi_stable library by whether they're compatible in a minor release,
+    /// and recommends the minimum version bump for the new version.
+    #[structopt(name = "semver")]
+    #[structopt(author = "_")]
+    Semver {
+        /// The path to the old version of the library.
+        old_library_path: PathBuf,
+
+        /// The path to the new version of the library.
+        new_library_path: PathBuf,
+
+        /// Whether to output the changes as json
+        #[structopt(long = "--json")]
+        json: bool,
+    },
 }
 
 fn main() {
@@ -107,5 +144,67 @@ version of abi_stable to be loaded successfully.
                 println!("{}", json);
             }
         }
+        Command::Fingerprint { library_path } => {
+            let lib_header = lib_header_from_path(library_path.as_ref()).unwrap();
+
+            match lib_header.layout() {
+                Some(layout) => println!("{:016x}", layout.fingerprint()),
+                None => {
+                    println!(
+                        "The dynamic library does not include the layout of its root module:\n    {}",
+                        library_path.display(),
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Semver {
+            old_library_path,
+            new_library_path,
+            json,
+        } => {
+            let old_header = lib_header_from_path(old_library_path.as_ref()).unwrap();
+            let new_header = lib_header_from_path(new_library_path.as_ref()).unwrap();
+
+            let report = classify_changes(
+                root_mod_layout(old_header, &old_library_path),
+                root_mod_layout(new_header, &new_library_path),
+            );
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report).unwrap());
+            } else {
+                println!("{}", report);
+                if let Ok(old_version) = old_header.version_strings().parsed() {
+                    println!(
+                        "minimum next version: {}",
+                        report.recommended_bump().next_version(old_version),
+                    );
+                }
+            }
+        }
+        Command::Metadata { library_path } => {
+            match build_metadata_from_path(library_path.as_ref()).unwrap() {
+                Some(metadata) => print!("{}", metadata),
+                None => {
+                    println!(
+                        "The dynamic library was built with a version of abi_stable \
+                         that doesn't export build metadata:\n    {}",
+                        library_path.display(),
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
     }
 }
+
+fn root_mod_layout(lib_header: &'static LibHeader, library_path: &Path) -> &'static TypeLayout {
+    lib_header.layout().unwrap_or_else(|| {
+        println!(
+            "The dynamic library does not include the layout of its root module:\n    {}",
+            library_path.display(),
+        );
+        std::process::exit(1);
+    })
+}